@@ -66,6 +66,15 @@ pub enum AnkerError {
 
     /// The most recent price sample is too recent, we can’t call `SellRewards` yet.
     SellRewardsTooEarly = 4017,
+
+    /// The signer is not the manager of this Anker instance.
+    InvalidManager = 4018,
+
+    /// There is no pending manager, or the signer is not the pending manager.
+    InvalidPendingManager = 4019,
+
+    /// The Anker state is not at the version that the instruction expects.
+    InvalidAnkerVersion = 4020,
 }
 
 // Just reuse the generated Debug impl for Display. It shows the variant names.
@@ -70,6 +70,18 @@ pub enum AnkerInstruction {
         #[allow(dead_code)] // It is not dead code when compiled for BPF.
         sell_rewards_min_out_bps: u64,
     },
+
+    /// Propose a new manager, that takes over once it signs `AcceptManager`.
+    ProposeNewManager,
+
+    /// Make the pending manager the manager.
+    AcceptManager,
+
+    /// Convert a version 0 instance to the current version.
+    ///
+    /// Version 0 instances used the Solido manager, which signs this
+    /// instruction, and becomes the Anker manager.
+    MigrateState,
 }
 
 impl AnkerInstruction {
@@ -596,3 +608,89 @@ pub fn send_rewards(
         data: data.to_vec(),
     }
 }
+
+accounts_struct! {
+    ProposeNewManagerAccountsMeta, ProposeNewManagerAccountsInfo {
+        // Needs to be writable in order to save the pending manager.
+        pub anker {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub solido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub new_manager {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn propose_new_manager(
+    program_id: &Pubkey,
+    accounts: &ProposeNewManagerAccountsMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: AnkerInstruction::ProposeNewManager.to_vec(),
+    }
+}
+
+accounts_struct! {
+    AcceptManagerAccountsMeta, AcceptManagerAccountsInfo {
+        // Needs to be writable in order to save the new manager.
+        pub anker {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub solido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub new_manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn accept_manager(program_id: &Pubkey, accounts: &AcceptManagerAccountsMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: AnkerInstruction::AcceptManager.to_vec(),
+    }
+}
+
+accounts_struct! {
+    MigrateStateAccountsMeta, MigrateStateAccountsInfo {
+        // Needs to be writable in order to save the migrated state.
+        pub anker {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub solido {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The Solido manager.
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn migrate_state(program_id: &Pubkey, accounts: &MigrateStateAccountsMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: AnkerInstruction::MigrateState.to_vec(),
+    }
+}
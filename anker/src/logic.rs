@@ -26,7 +26,7 @@ use std::convert::TryFrom;
 
 use crate::{
     instruction::{DepositAccountsInfo, InitializeAccountsInfo, SellRewardsAccountsInfo},
-    state::Anker,
+    state::{Anker, ANKER_VERSION},
 };
 
 /// Deserialize the Solido and Anker state.
@@ -49,10 +49,35 @@ use crate::{
 /// the Solido instance that this Anker instance belongs to. This ensures that
 /// for a given deployment of the Anker program, there exists a unique Anker
 /// instance address per Solido instance.
+///
+/// The Anker state must be at the current version, see `MigrateState`.
 pub fn deserialize_anker(
     anker_program_id: &Pubkey,
     anker_account: &AccountInfo,
     solido_account: &AccountInfo,
+) -> Result<(Lido, Anker), ProgramError> {
+    let (solido, anker) =
+        deserialize_anker_any_version(anker_program_id, anker_account, solido_account)?;
+
+    if anker.version != ANKER_VERSION {
+        msg!(
+            "Anker state is at version {}, but the program expects version {}. \
+            Migrate it with MigrateState first.",
+            anker.version,
+            ANKER_VERSION
+        );
+        return Err(AnkerError::InvalidAnkerVersion.into());
+    }
+
+    Ok((solido, anker))
+}
+
+/// Deserialize the Solido and Anker state like `deserialize_anker`, but accept
+/// any version of the Anker state.
+pub fn deserialize_anker_any_version(
+    anker_program_id: &Pubkey,
+    anker_account: &AccountInfo,
+    solido_account: &AccountInfo,
 ) -> Result<(Lido, Anker), ProgramError> {
     if anker_account.owner != anker_program_id {
         msg!(
@@ -24,14 +24,14 @@ use crate::{
     find_instance_address, find_mint_authority, find_reserve_authority,
     find_st_sol_reserve_account,
     instruction::{
-        AnkerInstruction, ChangeTerraRewardsDestinationAccountsInfo,
+        AcceptManagerAccountsInfo, AnkerInstruction, ChangeTerraRewardsDestinationAccountsInfo,
         ChangeTokenSwapPoolAccountsInfo, DepositAccountsInfo, FetchPoolPriceAccountsInfo,
-        InitializeAccountsInfo, SellRewardsAccountsInfo, SendRewardsAccountsInfo,
-        WithdrawAccountsInfo,
+        InitializeAccountsInfo, MigrateStateAccountsInfo, ProposeNewManagerAccountsInfo,
+        SellRewardsAccountsInfo, SendRewardsAccountsInfo, WithdrawAccountsInfo,
     },
-    logic::{burn_b_sol, deserialize_anker, mint_b_sol_to},
+    logic::{burn_b_sol, deserialize_anker, deserialize_anker_any_version, mint_b_sol_to},
     metrics::Metrics,
-    state::{Anker, WormholeParameters, ANKER_VERSION},
+    state::{Anker, WormholeParameters, ANKER_HEADROOM, ANKER_VERSION, ANKER_VERSION_V0},
     token::{BLamports, MicroUst},
     wormhole::{get_wormhole_transfer_instruction, TerraAddress},
 };
@@ -95,7 +95,7 @@ fn process_initialize(
         // need to store more data in the future, we need to create the headroom
         // for it now (or switch to a different account later). So add 128 bytes
         // of headroom for future expansion, in case we need it.
-        ANKER_LEN + 128,
+        ANKER_LEN + ANKER_HEADROOM,
         &anker_seeds,
     )?;
 
@@ -150,6 +150,8 @@ fn process_initialize(
         b_sol_mint: *accounts.b_sol_mint.key,
         solido_program_id: *accounts.solido_program.key,
         solido: *accounts.solido.key,
+        manager: solido.manager,
+        pending_manager: Pubkey::default(),
         token_swap_pool: *accounts.token_swap_pool.key,
         terra_rewards_destination,
         wormhole_parameters: WormholeParameters {
@@ -491,7 +493,7 @@ fn process_withdraw(
 }
 
 /// Change the Terra rewards destination.
-/// Solido's manager needs to sign the transaction.
+/// Anker's manager needs to sign the transaction.
 #[inline(never)]
 fn process_change_terra_rewards_destination(
     program_id: &Pubkey,
@@ -499,15 +501,15 @@ fn process_change_terra_rewards_destination(
     terra_rewards_destination: TerraAddress,
 ) -> ProgramResult {
     let accounts = ChangeTerraRewardsDestinationAccountsInfo::try_from_slice(accounts_raw)?;
-    let (solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
-    solido.check_manager(accounts.manager)?;
+    let (_solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
+    anker.check_manager(accounts.manager)?;
 
     anker.terra_rewards_destination = terra_rewards_destination;
     anker.save(accounts.anker)
 }
 
 /// Change the Token Pool instance.
-/// Solido's manager needs to sign the transaction.
+/// Anker's manager needs to sign the transaction.
 #[inline(never)]
 fn process_change_token_swap_pool(
     program_id: &Pubkey,
@@ -515,7 +517,7 @@ fn process_change_token_swap_pool(
 ) -> ProgramResult {
     let accounts = ChangeTokenSwapPoolAccountsInfo::try_from_slice(accounts_raw)?;
     let (solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
-    solido.check_manager(accounts.manager)?;
+    anker.check_manager(accounts.manager)?;
 
     let current_token_swap_program_id = accounts.current_token_swap_pool.owner;
     let current_token_swap = anker.get_token_swap_instance(
@@ -536,7 +538,7 @@ fn process_change_token_swap_pool(
 }
 
 /// Change Anker's `sell_rewards_min_out_bps`.
-/// Solido's manager needs to sign the transaction.
+/// Anker's manager needs to sign the transaction.
 #[inline(never)]
 fn process_change_sell_rewards_min_out_bps(
     program_id: &Pubkey,
@@ -544,8 +546,8 @@ fn process_change_sell_rewards_min_out_bps(
     sell_rewards_min_out_bps: u64,
 ) -> ProgramResult {
     let accounts = ChangeSellRewardsMinOutBpsAccountsInfo::try_from_slice(accounts_raw)?;
-    let (solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
-    solido.check_manager(accounts.manager)?;
+    let (_solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
+    anker.check_manager(accounts.manager)?;
 
     // Cannot be greater than 100%.
     if sell_rewards_min_out_bps > 10_000 {
@@ -556,6 +558,81 @@ fn process_change_sell_rewards_min_out_bps(
     anker.save(accounts.anker)
 }
 
+/// Propose a new manager, which takes over once it accepts with `AcceptManager`.
+/// Anker's manager needs to sign the transaction.
+#[inline(never)]
+fn process_propose_new_manager(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = ProposeNewManagerAccountsInfo::try_from_slice(accounts_raw)?;
+    let (_solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
+    anker.check_manager(accounts.manager)?;
+
+    anker.pending_manager = *accounts.new_manager.key;
+    msg!(
+        "Anker: Proposed {} as the new manager.",
+        anker.pending_manager
+    );
+    anker.save(accounts.anker)
+}
+
+/// Make the pending manager the manager.
+/// The pending manager needs to sign the transaction.
+#[inline(never)]
+fn process_accept_manager(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = AcceptManagerAccountsInfo::try_from_slice(accounts_raw)?;
+    let (_solido, mut anker) = deserialize_anker(program_id, accounts.anker, accounts.solido)?;
+    anker.check_pending_manager(accounts.new_manager)?;
+
+    msg!(
+        "Anker: Manager changed from {} to {}.",
+        anker.manager,
+        anker.pending_manager
+    );
+    anker.manager = anker.pending_manager;
+    anker.pending_manager = Pubkey::default();
+    anker.save(accounts.anker)
+}
+
+/// Convert a version 0 instance, which used the Solido manager, to the current
+/// version, which stores its own manager.
+/// Solido's manager needs to sign the transaction, and becomes Anker's manager.
+#[inline(never)]
+fn process_migrate_state(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = MigrateStateAccountsInfo::try_from_slice(accounts_raw)?;
+    let (solido, mut anker) =
+        deserialize_anker_any_version(program_id, accounts.anker, accounts.solido)?;
+
+    if anker.version != ANKER_VERSION_V0 {
+        msg!(
+            "Anker state is at version {}, only version {} can be migrated.",
+            anker.version,
+            ANKER_VERSION_V0
+        );
+        return Err(AnkerError::InvalidAnkerVersion.into());
+    }
+    solido.check_manager(accounts.manager)?;
+
+    // Version 0 instances were created with headroom, so the appended fields fit.
+    if accounts.anker.data_len() < ANKER_LEN {
+        msg!(
+            "Anker account is {} bytes, but the current layout needs {} bytes.",
+            accounts.anker.data_len(),
+            ANKER_LEN
+        );
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+
+    anker.version = ANKER_VERSION;
+    anker.manager = solido.manager;
+    anker.pending_manager = Pubkey::default();
+    msg!(
+        "Anker: Migrated state from version {} to {}, the manager is {}.",
+        ANKER_VERSION_V0,
+        ANKER_VERSION,
+        anker.manager
+    );
+    anker.save(accounts.anker)
+}
+
 /// Send rewards via Wormhole from the UST reserve address to Terra.
 #[inline(never)]
 fn process_send_rewards(
@@ -692,5 +769,8 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
         } => {
             process_change_sell_rewards_min_out_bps(program_id, accounts, sell_rewards_min_out_bps)
         }
+        AnkerInstruction::ProposeNewManager => process_propose_new_manager(program_id, accounts),
+        AnkerInstruction::AcceptManager => process_accept_manager(program_id, accounts),
+        AnkerInstruction::MigrateState => process_migrate_state(program_id, accounts),
     }
 }
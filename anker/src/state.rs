@@ -25,8 +25,21 @@ use spl_token_swap::state::SwapV1;
 use crate::token::{self, BLamports, MicroUst};
 
 /// Size of the serialized [`Anker`] struct, in bytes.
-pub const ANKER_LEN: usize = 370;
-pub const ANKER_VERSION: u8 = 0;
+pub const ANKER_LEN: usize = 434;
+pub const ANKER_VERSION: u8 = 1;
+
+/// Version of the original layout, that did not store a manager.
+///
+/// Version 1 only appended fields, so a version 0 instance deserializes as the
+/// current layout with the new fields zeroed, because they fall in the headroom
+/// that `Initialize` reserved.
+pub const ANKER_VERSION_V0: u8 = 0;
+
+/// Size of the serialized version 0 layout, in bytes.
+pub const ANKER_V0_LEN: usize = 370;
+
+/// Space that `Initialize` reserves beyond the serialized [`Anker`] struct.
+pub const ANKER_HEADROOM: usize = 128;
 
 // Next are three constants related to stored stSOL/UST prices. Because Anker is
 // permissionless, everybody can call `SellRewards` if there are rewards to sell.
@@ -227,6 +240,20 @@ pub struct Anker {
 
     /// Bump seed for the UST reserve account.
     pub ust_reserve_account_bump_seed: u8,
+
+    /// Manager of the Anker instance, able to change its parameters.
+    ///
+    /// Set to the Solido manager at initialization, and by `MigrateState` for
+    /// instances that were created at version 0.
+    #[serde(serialize_with = "serialize_b58")]
+    pub manager: Pubkey,
+
+    /// Manager proposed with `ProposeNewManager`, that can become the manager
+    /// by signing `AcceptManager`.
+    ///
+    /// Set to `Pubkey::default()` when there is no pending manager.
+    #[serde(serialize_with = "serialize_b58")]
+    pub pending_manager: Pubkey,
 }
 
 impl Anker {
@@ -239,6 +266,36 @@ impl Anker {
         Ok(())
     }
 
+    /// Confirm that the passed account is the manager of this instance.
+    pub fn check_manager(&self, manager: &AccountInfo) -> ProgramResult {
+        if &self.manager != manager.key {
+            msg!(
+                "Invalid manager, expected {} but got {}.",
+                self.manager,
+                manager.key
+            );
+            return Err(AnkerError::InvalidManager.into());
+        }
+        Ok(())
+    }
+
+    /// Confirm that the passed account is the pending manager proposed by the current manager.
+    pub fn check_pending_manager(&self, pending_manager: &AccountInfo) -> ProgramResult {
+        if self.pending_manager == Pubkey::default() {
+            msg!("There is no pending manager, propose one with ProposeNewManager first.");
+            return Err(AnkerError::InvalidPendingManager.into());
+        }
+        if &self.pending_manager != pending_manager.key {
+            msg!(
+                "Invalid pending manager, expected {} but got {}.",
+                self.pending_manager,
+                pending_manager.key
+            );
+            return Err(AnkerError::InvalidPendingManager.into());
+        }
+        Ok(())
+    }
+
     /// Confirm that the account address is the derived address where the Anker instance should live.
     pub fn check_self_address(
         &self,
@@ -895,6 +952,35 @@ mod test {
         assert_eq!(writer.len(), ANKER_LEN);
     }
 
+    #[test]
+    fn test_v0_layout_is_prefix() {
+        let anker = Anker {
+            version: ANKER_VERSION_V0,
+            sell_rewards_min_out_bps: 42,
+            ust_reserve_account_bump_seed: 7,
+            manager: Pubkey::new_unique(),
+            pending_manager: Pubkey::new_unique(),
+            ..Anker::default()
+        };
+        let mut data = Vec::new();
+        BorshSerialize::serialize(&anker, &mut data).unwrap();
+
+        // A version 0 account holds the old fields, followed by zeroed headroom.
+        data.truncate(ANKER_V0_LEN);
+        data.resize(ANKER_V0_LEN + ANKER_HEADROOM, 0);
+        assert!(ANKER_LEN <= data.len());
+
+        let anker_v0: Anker = solana_sdk::borsh::try_from_slice_unchecked(&data).unwrap();
+        assert_eq!(
+            anker_v0,
+            Anker {
+                manager: Pubkey::default(),
+                pending_manager: Pubkey::default(),
+                ..anker
+            }
+        );
+    }
+
     #[test]
     fn test_version_serialise() {
         use solana_sdk::borsh::try_from_slice_unchecked;
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use anker::{error::AnkerError, wormhole::TerraAddress};
-use lido::{error::LidoError, token::Lamports};
+use lido::token::Lamports;
 use solana_program::pubkey::Pubkey;
 use solana_program_test::tokio;
 use solana_sdk::{signature::Keypair, signer::Signer};
@@ -83,7 +83,7 @@ async fn test_change_token_swap_pool_different_manager() {
     context.solido_context.manager = context.solido_context.deterministic_keypair.new_keypair();
     let anker = context.get_anker().await;
     let result = context.try_change_token_swap_pool(new_token_swap).await;
-    assert_solido_error!(result, LidoError::InvalidManager);
+    assert_solido_error!(result, AnkerError::InvalidManager);
     let new_anker = context.get_anker().await;
     assert_eq!(anker.token_swap_pool, new_anker.token_swap_pool);
 }
@@ -112,7 +112,7 @@ async fn test_change_terra_rewards_destination_different_manager() {
     let result = context
         .try_change_terra_rewards_destination(&wrong_manager, new_terra_rewards_address)
         .await;
-    assert_solido_error!(result, LidoError::InvalidManager);
+    assert_solido_error!(result, AnkerError::InvalidManager);
     let new_anker = context.get_anker().await;
     assert_eq!(
         anker.terra_rewards_destination,
@@ -152,5 +152,73 @@ async fn test_change_sell_rewards_fake_manager() {
     let result = context
         .try_change_sell_rewards_min_out_bps(&manager, sell_rewards_min_out_bps)
         .await;
-    assert_solido_error!(result, LidoError::InvalidManager);
+    assert_solido_error!(result, AnkerError::InvalidManager);
+}
+
+#[tokio::test]
+async fn test_successful_manager_handover() {
+    let mut context = Context::new().await;
+    let manager = Keypair::from_bytes(&context.solido_context.manager.to_bytes()).unwrap();
+    let new_manager = context.solido_context.deterministic_keypair.new_keypair();
+
+    let anker = context.get_anker().await;
+    assert_eq!(anker.manager, manager.pubkey());
+
+    context
+        .try_propose_new_manager(&manager, new_manager.pubkey())
+        .await
+        .expect("The current manager can propose a new manager.");
+    let anker = context.get_anker().await;
+    assert_eq!(anker.manager, manager.pubkey());
+    assert_eq!(anker.pending_manager, new_manager.pubkey());
+
+    context
+        .try_accept_manager(&new_manager)
+        .await
+        .expect("The pending manager can accept.");
+    let anker = context.get_anker().await;
+    assert_eq!(anker.manager, new_manager.pubkey());
+    assert_eq!(anker.pending_manager, Pubkey::default());
+
+    // The old manager no longer has any rights, the new one does.
+    let result = context
+        .try_change_sell_rewards_min_out_bps(&manager, 10)
+        .await;
+    assert_solido_error!(result, AnkerError::InvalidManager);
+    let result = context
+        .try_change_sell_rewards_min_out_bps(&new_manager, 10)
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_propose_new_manager_different_manager() {
+    let mut context = Context::new().await;
+    let wrong_manager = context.solido_context.deterministic_keypair.new_keypair();
+    let result = context
+        .try_propose_new_manager(&wrong_manager, wrong_manager.pubkey())
+        .await;
+    assert_solido_error!(result, AnkerError::InvalidManager);
+}
+
+#[tokio::test]
+async fn test_accept_manager_without_proposal() {
+    let mut context = Context::new().await;
+    let new_manager = context.solido_context.deterministic_keypair.new_keypair();
+    let result = context.try_accept_manager(&new_manager).await;
+    assert_solido_error!(result, AnkerError::InvalidPendingManager);
+}
+
+#[tokio::test]
+async fn test_accept_manager_wrong_signer() {
+    let mut context = Context::new().await;
+    let manager = Keypair::from_bytes(&context.solido_context.manager.to_bytes()).unwrap();
+    let new_manager = context.solido_context.deterministic_keypair.new_keypair();
+    let impostor = context.solido_context.deterministic_keypair.new_keypair();
+    context
+        .try_propose_new_manager(&manager, new_manager.pubkey())
+        .await
+        .unwrap();
+    let result = context.try_accept_manager(&impostor).await;
+    assert_solido_error!(result, AnkerError::InvalidPendingManager);
 }
@@ -0,0 +1,65 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use anker::error::AnkerError;
+use anker::state::{ANKER_LEN, ANKER_V0_LEN, ANKER_VERSION, ANKER_VERSION_V0};
+use lido::token::Lamports;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use testlib::anker_context::Context;
+use testlib::assert_solido_error;
+
+/// Overwrite the Anker instance with the version 0 layout, which ends before
+/// the manager fields.
+async fn downgrade_to_v0(context: &mut Context) {
+    let account = context.solido_context.get_account(context.anker).await;
+    let mut data = account.data.clone();
+    data[0] = ANKER_VERSION_V0;
+    for byte in &mut data[ANKER_V0_LEN..ANKER_LEN] {
+        *byte = 0;
+    }
+
+    let mut account_shared =
+        AccountSharedData::new(account.lamports, account.data.len(), &account.owner);
+    account_shared.set_rent_epoch(account.rent_epoch);
+    account_shared.set_data(data);
+    context
+        .solido_context
+        .context
+        .set_account(&context.anker, &account_shared);
+}
+
+#[tokio::test]
+async fn test_migrate_state_from_v0() {
+    let mut context = Context::new().await;
+    let manager = Keypair::from_bytes(&context.solido_context.manager.to_bytes()).unwrap();
+    downgrade_to_v0(&mut context).await;
+
+    let anker = context.get_anker().await;
+    assert_eq!(anker.version, ANKER_VERSION_V0);
+    assert_eq!(anker.manager, Pubkey::default());
+
+    // The program refuses to operate on the old layout.
+    let result = context.try_deposit(Lamports(1_000_000_000)).await;
+    assert_solido_error!(result, AnkerError::InvalidAnkerVersion);
+
+    // Only the Solido manager can migrate.
+    let impostor = context.solido_context.deterministic_keypair.new_keypair();
+    let result = context.try_migrate_state(&impostor).await;
+    assert!(result.is_err());
+
+    context.try_migrate_state(&manager).await.unwrap();
+    let anker = context.get_anker().await;
+    assert_eq!(anker.version, ANKER_VERSION);
+    assert_eq!(anker.manager, manager.pubkey());
+    assert_eq!(anker.pending_manager, Pubkey::default());
+
+    context.deposit(Lamports(1_000_000_000)).await;
+
+    // Migrating a second time is not possible.
+    let result = context.try_migrate_state(&manager).await;
+    assert_solido_error!(result, AnkerError::InvalidAnkerVersion);
+}
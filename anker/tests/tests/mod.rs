@@ -7,6 +7,7 @@ pub mod amm;
 pub mod deposit;
 pub mod fetch_pool_price;
 pub mod manager;
+pub mod migrate_state;
 pub mod sell_rewards;
 pub mod send_rewards;
 pub mod withdraw;
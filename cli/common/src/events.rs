@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Utilities for decoding Solido events from transaction logs.
+
+use std::str::FromStr;
+
+use lido::events::SolidoEvent;
+use solana_sdk::pubkey::Pubkey;
+
+/// Extract the events that the Solido program logged, from the log messages of a transaction.
+///
+/// Only program data that was logged while `solido_program_id` was executing
+/// is considered, data logged by other programs (including programs that
+/// Solido calls into) is ignored, as is data that does not decode as an event.
+pub fn parse_solido_events(
+    solido_program_id: &Pubkey,
+    log_messages: &[String],
+) -> Vec<SolidoEvent> {
+    let mut events = Vec::new();
+
+    // The programs that are currently executing, the innermost one last.
+    let mut call_stack: Vec<Pubkey> = Vec::new();
+
+    for message in log_messages {
+        if let Some(data) = message.strip_prefix("Program data: ") {
+            if call_stack.last() != Some(solido_program_id) {
+                continue;
+            }
+            // `sol_log_data` logs every field separated by a space, we log a single one.
+            if let Some(event) = base64::decode(data.trim())
+                .ok()
+                .and_then(|bytes| SolidoEvent::decode(&bytes))
+            {
+                events.push(event);
+            }
+        } else if let Some(rest) = message.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let program_id = words.next().and_then(|id| Pubkey::from_str(id).ok());
+            match (program_id, words.next()) {
+                (Some(program_id), Some("invoke")) => call_stack.push(program_id),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    call_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::BorshSerialize;
+    use lido::token::Lamports;
+
+    fn data_line(event: &SolidoEvent) -> String {
+        format!(
+            "Program data: {}",
+            base64::encode(event.try_to_vec().unwrap())
+        )
+    }
+
+    #[test]
+    fn test_parse_solido_events_ignores_other_programs() {
+        let solido = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let event = SolidoEvent::StakeDeposit {
+            validator_vote_account: Pubkey::new_unique(),
+            amount: Lamports(1),
+        };
+        let other_event = SolidoEvent::PausedOperationsChanged {
+            paused_operations: 1,
+        };
+
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            data_line(&other_event),
+            format!("Program {} success", other),
+            format!("Program {} invoke [1]", solido),
+            "Program log: Staked 0.000000001 SOL out of the reserve.".to_string(),
+            format!("Program {} invoke [2]", other),
+            data_line(&other_event),
+            format!("Program {} success", other),
+            data_line(&event),
+            "Program data: not-base64".to_string(),
+            format!("Program {} consumed 1234 of 200000 compute units", solido),
+            format!("Program {} success", solido),
+        ];
+
+        assert_eq!(parse_solido_events(&solido, &logs), vec![event]);
+    }
+}
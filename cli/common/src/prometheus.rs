@@ -211,23 +211,39 @@ pub fn write_solido_metrics_as_prometheus<W: io::Write>(
     write_metric(
         out,
         &MetricFamily {
-            name: "solido_fee_treasury_sol_total",
-            help: "Total fees paid to the treasury, in SOL value before conversion to stSOL. \
+            name: "solido_fee_recipient_sol_total",
+            help: "Total fees paid to a fee recipient, in SOL value before conversion to stSOL. \
                    On-chain this value can only increase, but decreases in the observed value can \
                    happen due to reorgs.",
             type_: "gauge",
-            metrics: vec![Metric::new_sol(metrics.fee_treasury_sol_total).at(at)],
+            metrics: metrics
+                .fee_recipients
+                .iter()
+                .map(|recipient| {
+                    Metric::new_sol(recipient.fee_sol_total)
+                        .at(at)
+                        .with_label("st_sol_account", recipient.st_sol_account.to_string())
+                })
+                .collect(),
         },
     )?;
     write_metric(
         out,
         &MetricFamily {
-            name: "solido_fee_treasury_st_sol_total",
-            help: "Total fees paid to the treasury. \
+            name: "solido_fee_recipient_st_sol_total",
+            help: "Total fees paid to a fee recipient. \
                    On-chain this value can only increase, but decreases in the observed value can \
                    happen due to reorgs.",
             type_: "gauge",
-            metrics: vec![Metric::new_st_sol(metrics.fee_treasury_st_sol_total).at(at)],
+            metrics: metrics
+                .fee_recipients
+                .iter()
+                .map(|recipient| {
+                    Metric::new_st_sol(recipient.fee_st_sol_total)
+                        .at(at)
+                        .with_label("st_sol_account", recipient.st_sol_account.to_string())
+                })
+                .collect(),
         },
     )?;
     write_metric(
@@ -252,28 +268,6 @@ pub fn write_solido_metrics_as_prometheus<W: io::Write>(
             metrics: vec![Metric::new_st_sol(metrics.fee_validation_st_sol_total).at(at)],
         },
     )?;
-    write_metric(
-        out,
-        &MetricFamily {
-            name: "solido_fee_developer_sol_total",
-            help: "Total fees paid to the developer, in SOL value before conversion to stSOL. \
-                   On-chain this value can only increase, but decreases in the observed value can \
-                   happen due to reorgs.",
-            type_: "gauge",
-            metrics: vec![Metric::new_sol(metrics.fee_developer_sol_total).at(at)],
-        },
-    )?;
-    write_metric(
-        out,
-        &MetricFamily {
-            name: "solido_fee_developer_st_sol_total",
-            help: "Total fees paid to the developer. \
-                   On-chain this value can only increase, but decreases in the observed value can \
-                   happen due to reorgs.",
-            type_: "gauge",
-            metrics: vec![Metric::new_st_sol(metrics.fee_developer_st_sol_total).at(at)],
-        },
-    )?;
     write_metric(
         out,
         &MetricFamily {
@@ -322,6 +316,53 @@ pub fn write_solido_metrics_as_prometheus<W: io::Write>(
             metrics: vec![Metric::new_st_sol(metrics.withdraw_amount.total_st_sol_amount).at(at)],
         },
     )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_instant_unstake_count_total",
+            help: "Total number of instant unstakes made by users.",
+            type_: "counter",
+            metrics: vec![Metric::new(metrics.instant_unstake_amount.count).at(at)],
+        },
+    )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_instant_unstake_amount_sol_total",
+            help: "Total amount of SOL that we paid out of the reserve for instant unstakes.",
+            type_: "counter",
+            metrics: vec![Metric::new_sol(metrics.instant_unstake_amount.total_sol_amount).at(at)],
+        },
+    )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_instant_unstake_amount_st_sol_total",
+            help: "Total amount of stSOL that users burned for instant unstakes, excluding fees.",
+            type_: "counter",
+            metrics: vec![
+                Metric::new_st_sol(metrics.instant_unstake_amount.total_st_sol_amount).at(at),
+            ],
+        },
+    )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_fee_instant_unstake_st_sol_total",
+            help: "Total fees paid to the treasury for instant unstakes.",
+            type_: "counter",
+            metrics: vec![Metric::new_st_sol(metrics.fee_instant_unstake_st_sol_total).at(at)],
+        },
+    )?;
+    write_metric(
+        out,
+        &MetricFamily {
+            name: "solido_fee_withdrawal_st_sol_total",
+            help: "Total fees paid to the treasury for withdrawals.",
+            type_: "counter",
+            metrics: vec![Metric::new_st_sol(metrics.fee_withdrawal_st_sol_total).at(at)],
+        },
+    )?;
 
     Ok(())
 }
@@ -47,7 +47,8 @@ use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
 use solana_vote_program::vote_state::VoteState;
 
 use anker::state::Anker;
-use lido::state::Lido;
+use lido::migration::VersionedLido;
+use lido::state::{Lido, Validators, WithdrawalTicket};
 use lido::token::Lamports;
 use spl_token::solana_program::hash::Hash;
 
@@ -288,6 +289,23 @@ impl<'a> Snapshot<'a> {
         }
     }
 
+    /// Return the log messages of a confirmed transaction.
+    ///
+    /// Returns an empty list if the transaction failed, because then none of
+    /// its effects, including the events it logged, took place.
+    pub fn get_transaction_log_messages(
+        &mut self,
+        signature: &Signature,
+    ) -> crate::Result<Vec<String>> {
+        let transaction = self
+            .rpc_client
+            .get_transaction(signature, UiTransactionEncoding::Json)?;
+        Ok(match transaction.transaction.meta {
+            Some(meta) if meta.err.is_none() => meta.log_messages.unwrap_or_default(),
+            _ => Vec::new(),
+        })
+    }
+
     /// Read and parse the vote account at the given address.
     pub fn get_vote_account(&mut self, address: &Pubkey) -> crate::Result<VoteState> {
         let vote_account = self.get_account(address)?;
@@ -358,6 +376,72 @@ impl<'a> Snapshot<'a> {
         }
     }
 
+    /// Read the account and deserialize the Solido struct in any supported layout.
+    ///
+    /// Unlike `get_solido`, this also reads instances that still need to be
+    /// migrated with `MigrateState`.
+    pub fn get_versioned_solido(
+        &mut self,
+        solido_address: &Pubkey,
+    ) -> crate::Result<VersionedLido> {
+        let account = self.get_account(solido_address)?;
+        match VersionedLido::deserialize(&account.data) {
+            Ok(solido) => Ok(solido),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *solido_address,
+                    context: format!(
+                        "Failed to deserialize versioned Lido struct, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Read the validator list account and deserialize the `Validators` struct.
+    pub fn get_validators(&mut self, validator_list_address: &Pubkey) -> crate::Result<Validators> {
+        let account = self.get_account(validator_list_address)?;
+        match try_from_slice_unchecked::<Validators>(&account.data) {
+            Ok(validators) => Ok(validators),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *validator_list_address,
+                    context: format!(
+                        "Failed to deserialize Validators struct, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Read the account and deserialize the `WithdrawalTicket` struct.
+    pub fn get_withdrawal_ticket(
+        &mut self,
+        ticket_address: &Pubkey,
+    ) -> crate::Result<WithdrawalTicket> {
+        let account = self.get_account(ticket_address)?;
+        match try_from_slice_unchecked::<WithdrawalTicket>(&account.data) {
+            Ok(ticket) => Ok(ticket),
+            Err(err) => {
+                let error: Error = Box::new(SerializationError {
+                    cause: Some(err.into()),
+                    address: *ticket_address,
+                    context: format!(
+                        "Failed to deserialize WithdrawalTicket struct, data length is {} bytes.",
+                        account.data.len()
+                    ),
+                });
+                Err(error.into())
+            }
+        }
+    }
+
     /// Read the account and deserialize the Anker struct.
     pub fn get_anker(&mut self, anker_address: &Pubkey) -> crate::Result<Anker> {
         let account = self.get_account(anker_address)?;
@@ -12,6 +12,7 @@ use std::{
 
 use chrono::TimeZone;
 use clap::Parser;
+use lido::state::ExchangeRateHistory;
 use lido::token::Rational;
 use rand::{rngs::ThreadRng, Rng};
 use rusqlite::{params, Connection, Row};
@@ -322,6 +323,7 @@ impl<'a, 'b> Daemon<'a, 'b> {
             polls: 0,
             errors: 0,
             solido_average_30d_interval_price: None,
+            solido_exchange_rate_history: None,
         };
         let current_clock = config
             .with_snapshot(|config| config.client.get_clock())
@@ -358,6 +360,7 @@ impl<'a, 'b> Daemon<'a, 'b> {
                 }
                 ListenerResult::OkListener(
                     exchange_rate,
+                    exchange_rate_history,
                     interval_prices_option,
                     current_clock,
                 ) => {
@@ -370,6 +373,7 @@ impl<'a, 'b> Daemon<'a, 'b> {
                         exchange_rate.slot,
                         exchange_rate.epoch,
                     );
+                    self.metrics.solido_exchange_rate_history = Some(exchange_rate_history);
 
                     match interval_prices_option {
                         None => println!(
@@ -434,6 +438,9 @@ pub struct Metrics {
 
     /// Solido's maximum price interval.
     pub solido_average_30d_interval_price: Option<IntervalPrices>,
+
+    /// Exchange rates of the most recent epochs, as stored on-chain.
+    pub solido_exchange_rate_history: Option<ExchangeRateHistory>,
 }
 
 impl Metrics {
@@ -464,6 +471,25 @@ impl Metrics {
                 },
             )?;
         }
+        if let Some(history) = &self.solido_exchange_rate_history {
+            write_metric(
+                out,
+                &MetricFamily {
+                    name: "solido_pricedb_exchange_rate_history_sol_per_st_sol",
+                    help:
+                        "Price of stSOL in SOL at the epochs in the on-chain exchange rate history.",
+                    type_: "gauge",
+                    metrics: history
+                        .iter()
+                        .filter(|rate| rate.st_sol_supply.0 > 0)
+                        .map(|rate| {
+                            Metric::new(rate.sol_balance.0 as f64 / rate.st_sol_supply.0 as f64)
+                                .with_label("epoch", rate.computed_in_epoch.to_string())
+                        })
+                        .collect(),
+                },
+            )?;
+        }
 
         Ok(())
     }
@@ -844,8 +870,13 @@ enum ListenerResult {
     /// We failed to obtain a snapshot of the on-chain state at all, possibly a connectivity problem.
     ErrSnapshot(Error),
 
-    /// We have a snapshot, and we got the price.
-    OkListener(ExchangeRate, Option<IntervalPrices>, Clock),
+    /// We have a snapshot, and we got the price, and the on-chain price history.
+    OkListener(
+        ExchangeRate,
+        ExchangeRateHistory,
+        Option<IntervalPrices>,
+        Clock,
+    ),
 
     /// We have a snapshot, but failed in-between, e.g. when inserting in database.
     ErrListener(Error, Clock),
@@ -871,17 +902,21 @@ fn get_and_save_exchange_rate(
                 price_lamports_numerator: solido.exchange_rate.sol_balance.0,
                 price_lamports_denominator: solido.exchange_rate.st_sol_supply.0,
             },
+            solido.exchange_rate_history,
             clock,
         ))
     });
 
     match result {
         Err(err) => ListenerResult::ErrSnapshot(err),
-        Ok((exchange_rate, clock)) => {
+        Ok((exchange_rate, exchange_rate_history, clock)) => {
             match insert_price_and_query_30d_price_interval(db_connection, &exchange_rate) {
-                Ok(interval_prices) => {
-                    ListenerResult::OkListener(exchange_rate, interval_prices, clock)
-                }
+                Ok(interval_prices) => ListenerResult::OkListener(
+                    exchange_rate,
+                    exchange_rate_history,
+                    interval_prices,
+                    clock,
+                ),
                 Err(error) => ListenerResult::ErrListener(Box::new(error), clock),
             }
         }
@@ -21,11 +21,14 @@ use solido_cli_common::error::Abort;
 use solido_cli_common::snapshot::{SnapshotClientConfig, SnapshotConfig};
 
 use crate::anker_state::AnkerState;
-use crate::commands_multisig::{propose_instruction, ProposeInstructionOutput};
+use crate::commands_multisig::{
+    get_multisig_program_address, propose_instruction, ProposeInstructionOutput,
+};
 use crate::config::{
-    AnkerChangeSellRewardsMinOutBpsOpts, AnkerChangeTerraRewardsDestinationOpts,
-    AnkerChangeTokenSwapPoolOpts, AnkerDepositOpts, AnkerWithdrawOpts, ConfigFile, CreateAnkerOpts,
-    CreateTokenPoolOpts, ShowAnkerAuthoritiesOpts, ShowAnkerOpts,
+    AnkerAcceptManagerOpts, AnkerChangeSellRewardsMinOutBpsOpts,
+    AnkerChangeTerraRewardsDestinationOpts, AnkerChangeTokenSwapPoolOpts, AnkerDepositOpts,
+    AnkerMigrateStateOpts, AnkerProposeNewManagerOpts, AnkerWithdrawOpts, ConfigFile,
+    CreateAnkerOpts, CreateTokenPoolOpts, ShowAnkerAuthoritiesOpts, ShowAnkerOpts,
 };
 use crate::print_output;
 use crate::serialization_utils::serialize_bech32;
@@ -59,6 +62,16 @@ enum SubCommand {
 
     /// Change Anker's `sell_rewards_min_out_bps`.
     ChangeSellRewardsMinOutBps(AnkerChangeSellRewardsMinOutBpsOpts),
+
+    /// Propose a new manager for Anker, that takes over once it accepts.
+    ProposeNewManager(AnkerProposeNewManagerOpts),
+
+    /// Accept the manager role of Anker, after it was proposed.
+    AcceptManager(AnkerAcceptManagerOpts),
+
+    /// Migrate an Anker instance created at version 0, so it stores its own
+    /// manager. Requires the Solido manager to sign, it becomes the Anker manager.
+    MigrateState(AnkerMigrateStateOpts),
 }
 
 #[derive(Parser, Debug)]
@@ -89,6 +102,11 @@ impl AnkerOpts {
             SubCommand::ChangeSellRewardsMinOutBps(opts) => {
                 opts.merge_with_config_and_environment(config_file)
             }
+            SubCommand::ProposeNewManager(opts) => {
+                opts.merge_with_config_and_environment(config_file)
+            }
+            SubCommand::AcceptManager(opts) => opts.merge_with_config_and_environment(config_file),
+            SubCommand::MigrateState(opts) => opts.merge_with_config_and_environment(config_file),
         }
     }
 }
@@ -144,6 +162,21 @@ pub fn main(config: &mut SnapshotClientConfig, anker_opts: &AnkerOpts) {
             let output = result.ok_or_abort_with("Failed to change Anker sell_rewards_min_bps.");
             print_output(config.output_mode, &output);
         }
+        SubCommand::ProposeNewManager(opts) => {
+            let result = config.with_snapshot(|config| command_propose_new_manager(config, opts));
+            let output = result.ok_or_abort_with("Failed to propose new Anker manager.");
+            print_output(config.output_mode, &output);
+        }
+        SubCommand::AcceptManager(opts) => {
+            let result = config.with_snapshot(|config| command_accept_manager(config, opts));
+            let output = result.ok_or_abort_with("Failed to accept Anker manager.");
+            print_output(config.output_mode, &output);
+        }
+        SubCommand::MigrateState(opts) => {
+            let result = config.with_snapshot(|config| command_migrate_state(config, opts));
+            let output = result.ok_or_abort_with("Failed to migrate Anker state.");
+            print_output(config.output_mode, &output);
+        }
     }
 }
 
@@ -243,6 +276,12 @@ struct ShowAnkerOutput {
     #[serde(serialize_with = "serialize_b58")]
     solido_program_id: Pubkey,
 
+    #[serde(serialize_with = "serialize_b58")]
+    manager: Pubkey,
+
+    #[serde(serialize_with = "serialize_b58")]
+    pending_manager: Pubkey,
+
     #[serde(serialize_with = "serialize_b58")]
     b_sol_mint: Pubkey,
 
@@ -293,6 +332,10 @@ impl fmt::Display for ShowAnkerOutput {
         writeln!(f, "Anker program id:       {}", self.anker_program_id)?;
         writeln!(f, "Solido address:         {}", self.solido_address)?;
         writeln!(f, "Solido program id:      {}", self.solido_program_id)?;
+        writeln!(f, "Manager:                {}", self.manager)?;
+        if self.pending_manager != Pubkey::default() {
+            writeln!(f, "Pending manager:        {}", self.pending_manager)?;
+        }
         writeln!(
             f,
             "Rewards destination:    {}",
@@ -383,6 +426,9 @@ fn command_show_anker(
         solido_address: anker.solido,
         solido_program_id: anker.solido_program_id,
 
+        manager: anker.manager,
+        pending_manager: anker.pending_manager,
+
         token_swap_pool: anker.token_swap_pool,
         token_swap_pool_st_sol_account: anker_state.pool_st_sol_account,
         token_swap_pool_ust_account: anker_state.pool_ust_account,
@@ -796,14 +842,13 @@ pub fn command_change_terra_rewards_destination(
     let anker_account = client.get_account(opts.anker_address())?;
     let anker_program_id = anker_account.owner;
     let anker = client.get_anker(opts.anker_address())?;
-    let solido = config.client.get_solido(&anker.solido)?;
 
     let instruction = anker::instruction::change_terra_rewards_destination(
         &anker_program_id,
         &anker::instruction::ChangeTerraRewardsDestinationAccountsMeta {
             anker: *opts.anker_address(),
             solido: anker.solido,
-            manager: solido.manager,
+            manager: anker.manager,
         },
         opts.terra_rewards_destination().clone(),
     );
@@ -823,14 +868,13 @@ pub fn command_change_token_swap_pool(
     let anker_account = client.get_account(opts.anker_address())?;
     let anker_program_id = anker_account.owner;
     let anker = client.get_anker(opts.anker_address())?;
-    let solido = config.client.get_solido(&anker.solido)?;
 
     let instruction = anker::instruction::change_token_swap_pool(
         &anker_program_id,
         &anker::instruction::ChangeTokenSwapPoolAccountsMeta {
             anker: *opts.anker_address(),
             solido: anker.solido,
-            manager: solido.manager,
+            manager: anker.manager,
             current_token_swap_pool: anker.token_swap_pool,
             new_token_swap_pool: *opts.token_swap_pool(),
         },
@@ -851,14 +895,13 @@ pub fn command_change_sell_rewards_min_out_bps(
     let anker_account = client.get_account(opts.anker_address())?;
     let anker_program_id = anker_account.owner;
     let anker = client.get_anker(opts.anker_address())?;
-    let solido = config.client.get_solido(&anker.solido)?;
 
     let instruction = anker::instruction::change_sell_rewards_min_out_bps(
         &anker_program_id,
         &anker::instruction::ChangeSellRewardsMinOutBpsAccountsMeta {
             anker: *opts.anker_address(),
             solido: anker.solido,
-            manager: solido.manager,
+            manager: anker.manager,
         },
         *opts.sell_rewards_min_out_bps(),
     );
@@ -869,3 +912,85 @@ pub fn command_change_sell_rewards_min_out_bps(
         instruction,
     )
 }
+
+pub fn command_propose_new_manager(
+    config: &mut SnapshotConfig,
+    opts: &AnkerProposeNewManagerOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let client = &mut config.client;
+    let anker_account = client.get_account(opts.anker_address())?;
+    let anker_program_id = anker_account.owner;
+    let anker = client.get_anker(opts.anker_address())?;
+
+    let instruction = anker::instruction::propose_new_manager(
+        &anker_program_id,
+        &anker::instruction::ProposeNewManagerAccountsMeta {
+            anker: *opts.anker_address(),
+            solido: anker.solido,
+            manager: anker.manager,
+            new_manager: *opts.new_manager(),
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Propose to accept the manager role, from the multisig that was proposed as the new manager.
+pub fn command_accept_manager(
+    config: &mut SnapshotConfig,
+    opts: &AnkerAcceptManagerOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let client = &mut config.client;
+    let anker_account = client.get_account(opts.anker_address())?;
+    let anker_program_id = anker_account.owner;
+    let anker = client.get_anker(opts.anker_address())?;
+    let (new_manager, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = anker::instruction::accept_manager(
+        &anker_program_id,
+        &anker::instruction::AcceptManagerAccountsMeta {
+            anker: *opts.anker_address(),
+            solido: anker.solido,
+            new_manager,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Propose to migrate a version 0 Anker instance, from the multisig of the Solido manager.
+pub fn command_migrate_state(
+    config: &mut SnapshotConfig,
+    opts: &AnkerMigrateStateOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let client = &mut config.client;
+    let anker_account = client.get_account(opts.anker_address())?;
+    let anker_program_id = anker_account.owner;
+    let anker = client.get_anker(opts.anker_address())?;
+    let (manager, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = anker::instruction::migrate_state(
+        &anker_program_id,
+        &anker::instruction::MigrateStateAccountsMeta {
+            anker: *opts.anker_address(),
+            solido: anker.solido,
+            manager,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
@@ -8,8 +8,9 @@ use std::str::FromStr;
 use anchor_lang::prelude::{AccountMeta, ToAccountMetas};
 use anchor_lang::{Discriminator, InstructionData};
 use anker::instruction::{
-    ChangeSellRewardsMinOutBpsAccountsMeta, ChangeTerraRewardsDestinationAccountsMeta,
-    ChangeTokenSwapPoolAccountsMeta,
+    AcceptManagerAccountsMeta, ChangeSellRewardsMinOutBpsAccountsMeta,
+    ChangeTerraRewardsDestinationAccountsMeta, ChangeTokenSwapPoolAccountsMeta,
+    MigrateStateAccountsMeta, ProposeNewManagerAccountsMeta,
 };
 use anker::wormhole::TerraAddress;
 use borsh::de::BorshDeserialize;
@@ -28,11 +29,16 @@ use solana_sdk::sysvar;
 
 use lido::{
     instruction::{
-        AddMaintainerMeta, AddValidatorMetaV2, ChangeRewardDistributionMeta,
-        DeactivateValidatorMeta, LidoInstruction, RemoveMaintainerMeta,
-        SetMaxValidationCommissionMeta,
+        AcceptManagerMeta, AddMaintainerMeta, AddValidatorMetaV2, ChangeFeeRecipientsMeta,
+        ChangeRewardDistributionMeta, DeactivateValidatorMeta, EmergencyUnstakeValidatorMeta,
+        LidoInstruction, MigrateStateMeta, ProposeNewManagerMeta, RemoveMaintainerMeta,
+        ResizeValidatorListMeta, SetDepositLimitsMeta, SetInstantUnstakeFeeMeta,
+        SetMaintainerPermissionsMeta, SetMaxDirectedStakeMeta, SetMaxValidationCommissionMeta,
+        SetMinVoteCreditsMeta, SetPausedOperationsMeta, SetReserveBufferMeta, SetRoleMeta,
+        SetValidatorWeightMeta, SetWithdrawalFeeMeta,
     },
-    state::{FeeRecipients, Lido, RewardDistribution},
+    state::{Lido, ReserveBuffer, RewardDistribution, Role, LIDO_VERSION},
+    token::Lamports,
     util::{serialize_b58, serialize_b58_slice},
 };
 use solido_cli_common::error::Abort;
@@ -42,6 +48,10 @@ use solido_cli_common::{
     Result,
 };
 
+use crate::commands_solido::{
+    format_maintainer_permissions, format_max_total_sol, format_paused_operations,
+    format_reserve_buffer,
+};
 use crate::config::{
     ApproveBatchOpts, ApproveOpts, ConfigFile, CreateMultisigOpts, ExecuteTransactionOpts,
     ProposeChangeMultisigOpts, ProposeUpgradeOpts, ShowMultisigOpts, ShowTransactionOpts,
@@ -413,6 +423,18 @@ enum SolidoInstruction {
         #[serde(serialize_with = "serialize_b58")]
         validator_vote_account: Pubkey,
     },
+    EmergencyUnstakeValidator {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+
+        num_stake_accounts: usize,
+    },
     AddMaintainer {
         #[serde(serialize_with = "serialize_b58")]
         solido_instance: Pubkey,
@@ -442,8 +464,6 @@ enum SolidoInstruction {
 
         #[serde(serialize_with = "serialize_b58")]
         manager: Pubkey,
-
-        fee_recipients: FeeRecipients,
     },
     SetMaxValidationCommission {
         #[serde(serialize_with = "serialize_b58")]
@@ -454,6 +474,177 @@ enum SolidoInstruction {
         #[serde(serialize_with = "serialize_b58")]
         manager: Pubkey,
     },
+    SetInstantUnstakeFee {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        instant_unstake_fee_bps: u64,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    SetValidatorWeight {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+
+        weight: u32,
+    },
+    SetMinVoteCredits {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        min_vote_credits: u64,
+        vote_credits_epochs: u64,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+    },
+    ProposeNewManager {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_manager: Pubkey,
+    },
+    AcceptManager {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        old_manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_manager: Pubkey,
+    },
+    ChangeFeeRecipients {
+        current_solido: Box<Lido>,
+
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58_slice")]
+        fee_recipients: Vec<Pubkey>,
+    },
+    SetPausedOperations {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_paused_operations: u8,
+        new_paused_operations: u8,
+    },
+    ResizeValidatorList {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        funder: Pubkey,
+
+        old_max_validators: u32,
+        new_max_validators: u32,
+    },
+    MigrateState {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        funder: Pubkey,
+
+        old_version: u8,
+        new_version: u8,
+    },
+    SetDepositLimits {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_max_total_sol: Lamports,
+        new_max_total_sol: Lamports,
+        old_min_deposit: Lamports,
+        new_min_deposit: Lamports,
+    },
+    SetMaxDirectedStake {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_max_directed_stake_bps: u64,
+        new_max_directed_stake_bps: u64,
+    },
+    SetReserveBuffer {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_reserve_buffer: ReserveBuffer,
+        new_reserve_buffer: ReserveBuffer,
+    },
+    SetWithdrawalFee {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_withdrawal_fee_bps: u64,
+        new_withdrawal_fee_bps: u64,
+    },
+    SetRole {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        role: Role,
+
+        #[serde(serialize_with = "serialize_b58")]
+        old_authority: Pubkey,
+        #[serde(serialize_with = "serialize_b58")]
+        new_authority: Pubkey,
+    },
+    SetMaintainerPermissions {
+        #[serde(serialize_with = "serialize_b58")]
+        solido_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        maintainer: Pubkey,
+
+        old_permissions: u8,
+        new_permissions: u8,
+    },
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -494,6 +685,35 @@ enum AnkerInstruction {
 
         new_sell_rewards_min_out_bps: u64,
     },
+    ProposeNewManager {
+        #[serde(serialize_with = "serialize_b58")]
+        anker_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_manager: Pubkey,
+    },
+    AcceptManager {
+        #[serde(serialize_with = "serialize_b58")]
+        anker_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        old_manager: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        new_manager: Pubkey,
+    },
+    MigrateState {
+        #[serde(serialize_with = "serialize_b58")]
+        anker_instance: Pubkey,
+
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+
+        old_version: u8,
+    },
 }
 
 #[derive(Serialize)]
@@ -622,6 +842,21 @@ impl fmt::Display for ShowTransactionOutput {
                         writeln!(f, "    Manager:                {}", manager)?;
                         writeln!(f, "    Validator vote account: {}", validator_vote_account)?;
                     }
+                    SolidoInstruction::EmergencyUnstakeValidator {
+                        solido_instance,
+                        manager,
+                        validator_vote_account,
+                        num_stake_accounts,
+                    } => {
+                        writeln!(
+                            f,
+                            "It deactivates a validator and unstakes all of its stake."
+                        )?;
+                        writeln!(f, "    Solido instance:        {}", solido_instance)?;
+                        writeln!(f, "    Manager:                {}", manager)?;
+                        writeln!(f, "    Validator vote account: {}", validator_vote_account)?;
+                        writeln!(f, "    Stake accounts:         {}", num_stake_accounts)?;
+                    }
                     SolidoInstruction::AddMaintainer {
                         solido_instance,
                         manager,
@@ -647,14 +882,12 @@ impl fmt::Display for ShowTransactionOutput {
                         reward_distribution,
                         solido_instance,
                         manager,
-                        fee_recipients,
                     } => {
                         writeln!(f, "It changes the reward distribution")?;
                         writeln!(f, "    Solido instance:       {}", solido_instance)?;
                         writeln!(f, "    Manager:               {}", manager)?;
                         writeln!(f)?;
                         print_changed_reward_distribution(f, current_solido, reward_distribution)?;
-                        print_changed_recipients(f, current_solido, fee_recipients)?;
                     }
                     SolidoInstruction::SetMaxValidationCommission {
                         solido_instance,
@@ -670,6 +903,228 @@ impl fmt::Display for ShowTransactionOutput {
                             max_commission_percentage
                         )?;
                     }
+                    SolidoInstruction::SetInstantUnstakeFee {
+                        solido_instance,
+                        instant_unstake_fee_bps,
+                        manager,
+                    } => {
+                        writeln!(f, "It sets the instant unstake fee")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Instant unstake fee: {} basis points",
+                            instant_unstake_fee_bps
+                        )?;
+                    }
+                    SolidoInstruction::SetValidatorWeight {
+                        solido_instance,
+                        manager,
+                        validator_vote_account,
+                        weight,
+                    } => {
+                        writeln!(f, "It sets the weight of a validator")?;
+                        writeln!(f, "    Solido instance:        {}", solido_instance)?;
+                        writeln!(f, "    Manager:                {}", manager)?;
+                        writeln!(f, "    Validator vote account: {}", validator_vote_account)?;
+                        writeln!(f, "    Weight:                 {}", weight)?;
+                    }
+                    SolidoInstruction::SetMinVoteCredits {
+                        solido_instance,
+                        min_vote_credits,
+                        vote_credits_epochs,
+                        manager,
+                    } => {
+                        writeln!(f, "It sets the minimum vote credits")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    Min vote credits:   {}", min_vote_credits)?;
+                        writeln!(f, "    Over epochs:        {}", vote_credits_epochs)?;
+                    }
+                    SolidoInstruction::ProposeNewManager {
+                        solido_instance,
+                        manager,
+                        new_manager,
+                    } => {
+                        writeln!(f, "It proposes a new manager for Solido")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    New manager:        {}", new_manager)?;
+                    }
+                    SolidoInstruction::AcceptManager {
+                        solido_instance,
+                        old_manager,
+                        new_manager,
+                    } => {
+                        writeln!(f, "It accepts the manager role of Solido")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Old manager:        {}", old_manager)?;
+                        writeln!(f, "    New manager:        {}", new_manager)?;
+                    }
+                    SolidoInstruction::ChangeFeeRecipients {
+                        current_solido,
+                        solido_instance,
+                        manager,
+                        fee_recipients,
+                    } => {
+                        writeln!(f, "It changes the fee recipients")?;
+                        writeln!(f, "    Solido instance:       {}", solido_instance)?;
+                        writeln!(f, "    Manager:               {}", manager)?;
+                        writeln!(f)?;
+                        print_changed_recipients(f, current_solido, fee_recipients)?;
+                    }
+                    SolidoInstruction::SetPausedOperations {
+                        solido_instance,
+                        manager,
+                        old_paused_operations,
+                        new_paused_operations,
+                    } => {
+                        writeln!(f, "It sets the paused operations")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Paused operations:  {} -> {}",
+                            format_paused_operations(*old_paused_operations),
+                            format_paused_operations(*new_paused_operations)
+                        )?;
+                    }
+                    SolidoInstruction::ResizeValidatorList {
+                        solido_instance,
+                        manager,
+                        funder,
+                        old_max_validators,
+                        new_max_validators,
+                    } => {
+                        writeln!(f, "It resizes the validator list")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    Funder:             {}", funder)?;
+                        writeln!(
+                            f,
+                            "    Max validators:     {} -> {}",
+                            old_max_validators, new_max_validators
+                        )?;
+                    }
+                    SolidoInstruction::MigrateState {
+                        solido_instance,
+                        manager,
+                        validator_list,
+                        funder,
+                        old_version,
+                        new_version,
+                    } => {
+                        writeln!(f, "It migrates the Solido state")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(f, "    Validator list:     {}", validator_list)?;
+                        writeln!(f, "    Funder:             {}", funder)?;
+                        writeln!(
+                            f,
+                            "    Version:            {} -> {}",
+                            old_version, new_version
+                        )?;
+                    }
+                    SolidoInstruction::SetDepositLimits {
+                        solido_instance,
+                        manager,
+                        old_max_total_sol,
+                        new_max_total_sol,
+                        old_min_deposit,
+                        new_min_deposit,
+                    } => {
+                        writeln!(f, "It sets the deposit limits")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Max total SOL:      {} -> {}",
+                            format_max_total_sol(*old_max_total_sol),
+                            format_max_total_sol(*new_max_total_sol)
+                        )?;
+                        writeln!(
+                            f,
+                            "    Min deposit:        {} -> {}",
+                            old_min_deposit, new_min_deposit
+                        )?;
+                    }
+                    SolidoInstruction::SetMaxDirectedStake {
+                        solido_instance,
+                        manager,
+                        old_max_directed_stake_bps,
+                        new_max_directed_stake_bps,
+                    } => {
+                        writeln!(f, "It sets the maximum directed stake per validator")?;
+                        writeln!(f, "    Solido instance:    {}", solido_instance)?;
+                        writeln!(f, "    Manager:            {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Max directed stake: {} -> {} basis points",
+                            old_max_directed_stake_bps, new_max_directed_stake_bps
+                        )?;
+                    }
+                    SolidoInstruction::SetReserveBuffer {
+                        solido_instance,
+                        manager,
+                        old_reserve_buffer,
+                        new_reserve_buffer,
+                    } => {
+                        writeln!(f, "It sets the reserve buffer")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Reserve buffer:  {} -> {}",
+                            format_reserve_buffer(*old_reserve_buffer),
+                            format_reserve_buffer(*new_reserve_buffer)
+                        )?;
+                    }
+                    SolidoInstruction::SetWithdrawalFee {
+                        solido_instance,
+                        manager,
+                        old_withdrawal_fee_bps,
+                        new_withdrawal_fee_bps,
+                    } => {
+                        writeln!(f, "It sets the withdrawal fee")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(
+                            f,
+                            "    Withdrawal fee:  {} -> {} basis points",
+                            old_withdrawal_fee_bps, new_withdrawal_fee_bps
+                        )?;
+                    }
+                    SolidoInstruction::SetRole {
+                        solido_instance,
+                        manager,
+                        role,
+                        old_authority,
+                        new_authority,
+                    } => {
+                        writeln!(f, "It assigns the {} role", role)?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(f, "    Old authority:   {}", old_authority)?;
+                        writeln!(f, "    New authority:   {}", new_authority)?;
+                    }
+                    SolidoInstruction::SetMaintainerPermissions {
+                        solido_instance,
+                        manager,
+                        maintainer,
+                        old_permissions,
+                        new_permissions,
+                    } => {
+                        writeln!(f, "It sets the permissions of a maintainer")?;
+                        writeln!(f, "    Solido instance: {}", solido_instance)?;
+                        writeln!(f, "    Manager:         {}", manager)?;
+                        writeln!(f, "    Maintainer:      {}", maintainer)?;
+                        writeln!(
+                            f,
+                            "    Permitted:       {} -> {}",
+                            format_maintainer_permissions(*old_permissions),
+                            format_maintainer_permissions(*new_permissions)
+                        )?;
+                    }
                 }
             }
             ParsedInstruction::Unrecognized => {
@@ -771,6 +1226,41 @@ impl fmt::Display for ShowTransactionOutput {
                         new_sell_rewards_min_out_bps
                     )?;
                 }
+                AnkerInstruction::ProposeNewManager {
+                    anker_instance,
+                    manager,
+                    new_manager,
+                } => {
+                    writeln!(f, "It proposes a new manager for Anker")?;
+                    writeln!(f, "    Anker instance: {}", anker_instance)?;
+                    writeln!(f, "    Manager:        {}", manager)?;
+                    writeln!(f, "    New manager:    {}", new_manager)?;
+                }
+                AnkerInstruction::AcceptManager {
+                    anker_instance,
+                    old_manager,
+                    new_manager,
+                } => {
+                    writeln!(f, "It accepts the manager role of Anker")?;
+                    writeln!(f, "    Anker instance: {}", anker_instance)?;
+                    writeln!(f, "    Old manager:    {}", old_manager)?;
+                    writeln!(f, "    New manager:    {}", new_manager)?;
+                }
+                AnkerInstruction::MigrateState {
+                    anker_instance,
+                    manager,
+                    old_version,
+                } => {
+                    writeln!(f, "It migrates the Anker state to the current version")?;
+                    writeln!(f, "    Anker instance: {}", anker_instance)?;
+                    writeln!(f, "    Manager:        {}", manager)?;
+                    writeln!(
+                        f,
+                        "    Version:        {} -> {}",
+                        old_version,
+                        anker::state::ANKER_VERSION
+                    )?;
+                }
             },
             ParsedInstruction::InvalidAnkerInstruction => {
                 writeln!(
@@ -807,27 +1297,48 @@ fn print_changed_reward_distribution(
     current_solido: &Lido,
     reward_distribution: &RewardDistribution,
 ) -> fmt::Result {
-    let current_sum = current_solido.reward_distribution.sum();
+    let current = &current_solido.reward_distribution;
+    let current_sum = current.sum();
     let new_sum = reward_distribution.sum();
+
+    // Recipients that remain, or that are new, in the order of the new list.
+    for (i, recipient) in reward_distribution.fee_recipients.iter().enumerate() {
+        let name = if i == 0 {
+            format!("{} (treasury)", recipient.st_sol_account)
+        } else {
+            recipient.st_sol_account.to_string()
+        };
+        match current
+            .fee_recipients
+            .iter()
+            .find(|r| r.st_sol_account == recipient.st_sol_account)
+        {
+            Some(current_recipient) => changed_fee(
+                f,
+                current_recipient.parts,
+                recipient.parts,
+                current_sum,
+                new_sum,
+                &name,
+            )?,
+            None => writeln!(f, "   {}: added with {}/{}", name, recipient.parts, new_sum)?,
+        }
+    }
+    for current_recipient in current.fee_recipients.iter().filter(|r| {
+        !reward_distribution
+            .fee_recipients
+            .iter()
+            .any(|new| new.st_sol_account == r.st_sol_account)
+    }) {
+        writeln!(
+            f,
+            "   {}: removed, was {}/{}",
+            current_recipient.st_sol_account, current_recipient.parts, current_sum
+        )?;
+    }
     changed_fee(
         f,
-        current_solido.reward_distribution.treasury_fee,
-        reward_distribution.treasury_fee,
-        current_sum,
-        new_sum,
-        "treasury",
-    )?;
-    changed_fee(
-        f,
-        current_solido.reward_distribution.developer_fee,
-        reward_distribution.developer_fee,
-        current_sum,
-        new_sum,
-        "developer",
-    )?;
-    changed_fee(
-        f,
-        current_solido.reward_distribution.st_sol_appreciation,
+        current.st_sol_appreciation,
         reward_distribution.st_sol_appreciation,
         current_sum,
         new_sum,
@@ -839,20 +1350,25 @@ fn print_changed_reward_distribution(
 fn print_changed_recipients(
     f: &mut fmt::Formatter,
     current_solido: &Lido,
-    fee_recipients: &FeeRecipients,
+    fee_recipients: &[Pubkey],
 ) -> fmt::Result {
-    changed_addr(
-        f,
-        &current_solido.fee_recipients.treasury_account,
-        &fee_recipients.treasury_account,
-        "treasury",
-    )?;
-    changed_addr(
-        f,
-        &current_solido.fee_recipients.developer_account,
-        &fee_recipients.developer_account,
-        "developer",
-    )?;
+    let current = &current_solido.reward_distribution.fee_recipients;
+    if current.len() != fee_recipients.len() {
+        writeln!(
+            f,
+            "   WARNING: Solido has {} fee recipients, but {} accounts are provided.",
+            current.len(),
+            fee_recipients.len()
+        )?;
+    }
+    for (i, (current_recipient, new_addr)) in current.iter().zip(fee_recipients).enumerate() {
+        let name = if i == 0 {
+            "recipient 0 (treasury)".to_string()
+        } else {
+            format!("recipient {}", i)
+        };
+        changed_addr(f, &current_recipient.st_sol_account, new_addr, &name)?;
+    }
     Ok(())
 }
 
@@ -865,7 +1381,7 @@ fn changed_addr(
     if current_addr == new_addr {
         writeln!(f, "   {}: {}", param_name, new_addr)?;
     } else {
-        writeln!(f, "   {}: {} -> {}", param_name, new_addr, current_addr,)?;
+        writeln!(f, "   {}: {} -> {}", param_name, current_addr, new_addr)?;
     }
     Ok(())
 }
@@ -1061,10 +1577,6 @@ fn try_parse_solido_instruction(
                 reward_distribution: new_reward_distribution,
                 solido_instance: accounts.lido,
                 manager: accounts.manager,
-                fee_recipients: FeeRecipients {
-                    treasury_account: accounts.treasury_account,
-                    developer_account: accounts.developer_account,
-                },
             })
         }
         LidoInstruction::AddValidatorV2 => {
@@ -1083,6 +1595,15 @@ fn try_parse_solido_instruction(
                 validator_vote_account: accounts.validator_vote_account_to_deactivate,
             })
         }
+        LidoInstruction::EmergencyUnstakeValidator => {
+            let accounts = EmergencyUnstakeValidatorMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::EmergencyUnstakeValidator {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_vote_account: accounts.validator_vote_account,
+                num_stake_accounts: accounts.stake_accounts.len(),
+            })
+        }
         LidoInstruction::AddMaintainer => {
             let accounts = AddMaintainerMeta::try_from_slice(&instr.accounts)?;
             ParsedInstruction::SolidoInstruction(SolidoInstruction::AddMaintainer {
@@ -1109,6 +1630,174 @@ fn try_parse_solido_instruction(
                 manager: accounts.manager,
             })
         }
+        LidoInstruction::SetInstantUnstakeFee {
+            instant_unstake_fee_bps,
+        } => {
+            let accounts = SetInstantUnstakeFeeMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetInstantUnstakeFee {
+                solido_instance: accounts.lido,
+                instant_unstake_fee_bps,
+                manager: accounts.manager,
+            })
+        }
+        LidoInstruction::SetValidatorWeight { weight } => {
+            let accounts = SetValidatorWeightMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetValidatorWeight {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_vote_account: accounts.validator_vote_account,
+                weight,
+            })
+        }
+        LidoInstruction::SetMinVoteCredits {
+            min_vote_credits,
+            vote_credits_epochs,
+        } => {
+            let accounts = SetMinVoteCreditsMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetMinVoteCredits {
+                solido_instance: accounts.lido,
+                min_vote_credits,
+                vote_credits_epochs,
+                manager: accounts.manager,
+            })
+        }
+        LidoInstruction::ProposeNewManager => {
+            let accounts = ProposeNewManagerMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ProposeNewManager {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                new_manager: accounts.new_manager,
+            })
+        }
+        LidoInstruction::AcceptManager => {
+            let accounts = AcceptManagerMeta::try_from_slice(&instr.accounts)?;
+            let solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::AcceptManager {
+                solido_instance: accounts.lido,
+                old_manager: solido.manager,
+                new_manager: accounts.new_manager,
+            })
+        }
+        LidoInstruction::ChangeFeeRecipients => {
+            let accounts = ChangeFeeRecipientsMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ChangeFeeRecipients {
+                current_solido: Box::new(current_solido),
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                fee_recipients: accounts.fee_recipients,
+            })
+        }
+
+        LidoInstruction::SetPausedOperations { paused_operations } => {
+            let accounts = SetPausedOperationsMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetPausedOperations {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                old_paused_operations: current_solido.paused_operations,
+                new_paused_operations: paused_operations,
+            })
+        }
+        LidoInstruction::ResizeValidatorList { max_validators } => {
+            let accounts = ResizeValidatorListMeta::try_from_slice(&instr.accounts)?;
+            let current_validators = config.client.get_validators(&accounts.validator_list)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::ResizeValidatorList {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                funder: accounts.funder,
+                old_max_validators: current_validators.maximum_entries,
+                new_max_validators: max_validators,
+            })
+        }
+        LidoInstruction::MigrateState => {
+            let accounts = MigrateStateMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_versioned_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::MigrateState {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                validator_list: accounts.validator_list,
+                funder: accounts.funder,
+                old_version: current_solido.version(),
+                new_version: LIDO_VERSION,
+            })
+        }
+        LidoInstruction::SetDepositLimits {
+            max_total_sol,
+            min_deposit,
+        } => {
+            let accounts = SetDepositLimitsMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetDepositLimits {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                old_max_total_sol: current_solido.max_total_sol,
+                new_max_total_sol: max_total_sol,
+                old_min_deposit: current_solido.min_deposit,
+                new_min_deposit: min_deposit,
+            })
+        }
+        LidoInstruction::SetMaxDirectedStake {
+            max_directed_stake_bps,
+        } => {
+            let accounts = SetMaxDirectedStakeMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetMaxDirectedStake {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                old_max_directed_stake_bps: current_solido.max_directed_stake_bps,
+                new_max_directed_stake_bps: max_directed_stake_bps,
+            })
+        }
+        LidoInstruction::SetReserveBuffer { reserve_buffer } => {
+            let accounts = SetReserveBufferMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetReserveBuffer {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                old_reserve_buffer: current_solido.reserve_buffer,
+                new_reserve_buffer: reserve_buffer,
+            })
+        }
+        LidoInstruction::SetWithdrawalFee { withdrawal_fee_bps } => {
+            let accounts = SetWithdrawalFeeMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetWithdrawalFee {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                old_withdrawal_fee_bps: current_solido.withdrawal_fee_bps,
+                new_withdrawal_fee_bps: withdrawal_fee_bps,
+            })
+        }
+        LidoInstruction::SetRole { role } => {
+            let accounts = SetRoleMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetRole {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                role,
+                old_authority: *current_solido.roles.get(role),
+                new_authority: accounts.new_authority,
+            })
+        }
+        LidoInstruction::SetMaintainerPermissions { permissions } => {
+            let accounts = SetMaintainerPermissionsMeta::try_from_slice(&instr.accounts)?;
+            let current_solido = config.client.get_solido(&accounts.lido)?;
+            // If the account is not a maintainer, the instruction will fail,
+            // show it as having no permissions.
+            let old_permissions = current_solido
+                .maintainers
+                .get(&accounts.maintainer)
+                .map(|pe| pe.entry)
+                .unwrap_or(0);
+            ParsedInstruction::SolidoInstruction(SolidoInstruction::SetMaintainerPermissions {
+                solido_instance: accounts.lido,
+                manager: accounts.manager,
+                maintainer: accounts.maintainer,
+                old_permissions,
+                new_permissions: permissions,
+            })
+        }
 
         _ => ParsedInstruction::InvalidSolidoInstruction,
     })
@@ -1181,6 +1870,32 @@ fn try_parse_anker_instruction(
                 new_sell_rewards_min_out_bps: sell_rewards_min_out_bps,
             })
         }
+        anker::instruction::AnkerInstruction::ProposeNewManager => {
+            let accounts = ProposeNewManagerAccountsMeta::try_from_slice(&instr.accounts)?;
+            ParsedInstruction::AnkerInstruction(AnkerInstruction::ProposeNewManager {
+                anker_instance: accounts.anker,
+                manager: accounts.manager,
+                new_manager: accounts.new_manager,
+            })
+        }
+        anker::instruction::AnkerInstruction::AcceptManager => {
+            let accounts = AcceptManagerAccountsMeta::try_from_slice(&instr.accounts)?;
+            let current_anker = config.client.get_anker(&accounts.anker)?;
+            ParsedInstruction::AnkerInstruction(AnkerInstruction::AcceptManager {
+                anker_instance: accounts.anker,
+                old_manager: current_anker.manager,
+                new_manager: accounts.new_manager,
+            })
+        }
+        anker::instruction::AnkerInstruction::MigrateState => {
+            let accounts = MigrateStateAccountsMeta::try_from_slice(&instr.accounts)?;
+            let current_anker = config.client.get_anker(&accounts.anker)?;
+            ParsedInstruction::AnkerInstruction(AnkerInstruction::MigrateState {
+                anker_instance: accounts.anker,
+                manager: accounts.manager,
+                old_version: current_anker.version,
+            })
+        }
         _ => ParsedInstruction::InvalidAnkerInstruction,
     })
 }
@@ -12,10 +12,17 @@ use solana_sdk::{
 
 use lido::{
     balance::get_validator_to_withdraw,
+    events::SolidoEvent,
     find_authority_program_address,
     metrics::LamportsHistogram,
+    migration::VersionedLido,
     processor::StakeType,
-    state::{Lido, RewardDistribution},
+    stake_account::{deserialize_stake_account, StakeAccount},
+    state::{
+        FeeRecipient, Lido, ReserveBuffer, RewardDistribution, Roles, Validators, LIDO_VERSION,
+        MAINTAINER_STAKE_DEPOSIT, MAINTAINER_UNSTAKE, PAUSE_DEPOSIT, PAUSE_STAKE_DEPOSIT,
+        PAUSE_UNSTAKE, PAUSE_WITHDRAW,
+    },
     token::{Lamports, StLamports},
     util::serialize_b58,
     vote_state::get_vote_account_commission,
@@ -23,6 +30,7 @@ use lido::{
 };
 use solido_cli_common::{
     error::{CliError, Error},
+    events::parse_solido_events,
     snapshot::{SnapshotClientConfig, SnapshotConfig},
     validator_info_utils::ValidatorInfo,
 };
@@ -35,9 +43,16 @@ use crate::{
 };
 use crate::{
     config::{
-        AddRemoveMaintainerOpts, AddValidatorOpts, CreateSolidoOpts,
+        AcceptManagerOpts, AddRemoveMaintainerOpts, AddValidatorOpts, ChangeFeeRecipientsOpts,
+        ChangeRewardDistributionOpts, ClaimWithdrawalOpts, CreateSolidoOpts,
         DeactivateValidatorIfCommissionExceedsMaxOpts, DeactivateValidatorOpts, DepositOpts,
-        SetMaxValidationCommissionOpts, ShowSolidoAuthoritiesOpts, ShowSolidoOpts, WithdrawOpts,
+        DepositStakeOpts, InstantUnstakeOpts, MigrateStateOpts, PreviewMigrateStateOpts,
+        ProposeNewManagerOpts, RequestWithdrawalOpts, ResizeValidatorListOpts,
+        SetDepositLimitsOpts, SetInstantUnstakeFeeOpts, SetMaintainerPermissionsOpts,
+        SetMaxDirectedStakeOpts, SetMaxValidationCommissionOpts, SetMinVoteCreditsOpts,
+        SetPausedOperationsOpts, SetReserveBufferOpts, SetRoleOpts, SetValidatorWeightOpts,
+        SetWithdrawalFeeOpts, ShowEventsOpts, ShowSolidoAuthoritiesOpts, ShowSolidoOpts,
+        WithdrawOpts,
     },
     get_signer_from_path,
 };
@@ -48,6 +63,10 @@ pub struct CreateSolidoOutput {
     #[serde(serialize_with = "serialize_b58")]
     pub solido_address: Pubkey,
 
+    /// Account that stores the validators of this Solido instance.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_list_address: Pubkey,
+
     /// Manages the deposited sol.
     #[serde(serialize_with = "serialize_b58")]
     pub reserve_account: Pubkey,
@@ -77,6 +96,11 @@ impl fmt::Display for CreateSolidoOutput {
             "  Solido address:                {}",
             self.solido_address
         )?;
+        writeln!(
+            f,
+            "  Validator list address:        {}",
+            self.validator_list_address
+        )?;
         writeln!(
             f,
             "  Reserve account:               {}",
@@ -135,11 +159,17 @@ pub fn command_create_solido(
     let (manager, _nonce) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
 
-    let lido_size = Lido::calculate_size(*opts.max_validators(), *opts.max_maintainers());
+    let lido_size = Lido::calculate_size(*opts.max_maintainers());
     let lido_account_balance = config
         .client
         .get_minimum_balance_for_rent_exemption(lido_size)?;
 
+    let validator_list_signer = Keypair::new();
+    let validator_list_size = Validators::required_bytes(*opts.max_validators() as usize);
+    let validator_list_account_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+
     let mut instructions = Vec::new();
 
     // We need to fund Lido's reserve account so it is rent-exempt, otherwise it
@@ -200,11 +230,28 @@ pub fn command_create_solido(
         opts.solido_program_id(),
     ));
 
+    // Create the account that holds the validators of the instance.
+    instructions.push(system_instruction::create_account(
+        &config.signer.pubkey(),
+        &validator_list_signer.pubkey(),
+        validator_list_account_balance.0,
+        validator_list_size as u64,
+        opts.solido_program_id(),
+    ));
+
     instructions.push(lido::instruction::initialize(
         opts.solido_program_id(),
         RewardDistribution {
-            treasury_fee: *opts.treasury_fee_share(),
-            developer_fee: *opts.developer_fee_share(),
+            fee_recipients: vec![
+                FeeRecipient {
+                    st_sol_account: treasury_keypair.pubkey(),
+                    parts: *opts.treasury_fee_share(),
+                },
+                FeeRecipient {
+                    st_sol_account: developer_keypair.pubkey(),
+                    parts: *opts.developer_fee_share(),
+                },
+            ],
             st_sol_appreciation: *opts.st_sol_appreciation_share(),
         },
         *opts.max_validators(),
@@ -212,19 +259,23 @@ pub fn command_create_solido(
         *opts.max_commission_percentage(),
         &lido::instruction::InitializeAccountsMeta {
             lido: lido_signer.pubkey(),
+            validator_list: validator_list_signer.pubkey(),
             st_sol_mint: st_sol_mint_pubkey,
             manager,
-            treasury_account: treasury_keypair.pubkey(),
-            developer_account: developer_keypair.pubkey(),
             reserve_account,
+            fee_recipients: vec![treasury_keypair.pubkey(), developer_keypair.pubkey()],
         },
     ));
 
-    config.sign_and_send_transaction(&instructions[..], &[config.signer, &*lido_signer])?;
+    config.sign_and_send_transaction(
+        &instructions[..],
+        &[config.signer, &*lido_signer, &validator_list_signer],
+    )?;
     eprintln!("Did send Lido init.");
 
     let result = CreateSolidoOutput {
         solido_address: lido_signer.pubkey(),
+        validator_list_address: validator_list_signer.pubkey(),
         reserve_account,
         mint_authority,
         st_sol_mint_address: st_sol_mint_pubkey,
@@ -241,11 +292,13 @@ pub fn command_add_validator(
 ) -> solido_cli_common::Result<ProposeInstructionOutput> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
 
     let instruction = lido::instruction::add_validator(
         opts.solido_program_id(),
         &lido::instruction::AddValidatorMetaV2 {
             lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
             manager: multisig_address,
             validator_vote_account: *opts.validator_vote_account(),
         },
@@ -265,11 +318,13 @@ pub fn command_deactivate_validator(
 ) -> solido_cli_common::Result<ProposeInstructionOutput> {
     let (multisig_address, _) =
         get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
 
     let instruction = lido::instruction::deactivate_validator(
         opts.solido_program_id(),
         &lido::instruction::DeactivateValidatorMeta {
             lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
             manager: multisig_address,
             validator_vote_account_to_deactivate: *opts.validator_vote_account(),
         },
@@ -332,6 +387,8 @@ pub fn command_remove_maintainer(
 pub struct ShowSolidoOutput {
     pub solido: Lido,
 
+    pub validators: Validators,
+
     #[serde(serialize_with = "serialize_b58")]
     pub solido_program_id: Pubkey,
 
@@ -347,19 +404,26 @@ pub struct ShowSolidoOutput {
     #[serde(serialize_with = "serialize_b58")]
     pub mint_authority: Pubkey,
 
-    /// Identity account address for all validators in the same order as `solido.validators`.
+    /// Identity account address for all validators in the same order as `validators`.
     pub validator_identities: Vec<Pubkey>,
 
-    /// Contains validator info in the same order as `solido.validators`.
+    /// Contains validator info in the same order as `validators`.
     pub validator_infos: Vec<ValidatorInfo>,
 
-    /// Contains validator fees in the same order as `solido.validators`.
+    /// Contains validator fees in the same order as `validators`.
     pub validator_commission_percentages: Vec<u8>,
 }
 
 impl fmt::Display for ShowSolidoOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Manager:                     {}", self.solido.manager)?;
+        if self.solido.pending_manager != Pubkey::default() {
+            writeln!(
+                f,
+                "Pending manager:             {}",
+                self.solido.pending_manager
+            )?;
+        }
         writeln!(
             f,
             "stSOL mint:                  {}",
@@ -383,6 +447,21 @@ impl fmt::Display for ShowSolidoOutput {
             self.solido.exchange_rate.st_sol_supply
         )?;
 
+        writeln!(f, "\nExchange rate history:")?;
+        if self.solido.exchange_rate_history.iter().next().is_none() {
+            writeln!(f, "  No exchange rates recorded yet.")?;
+        }
+        for rate in self.solido.exchange_rate_history.iter() {
+            writeln!(
+                f,
+                "  Epoch {:>6}: {:.9} SOL per stSOL ({} / {})",
+                rate.computed_in_epoch,
+                rate.sol_balance.0 as f64 / rate.st_sol_supply.0 as f64,
+                rate.sol_balance,
+                rate.st_sol_supply,
+            )?;
+        }
+
         writeln!(f, "\nAuthorities (public key, bump seed):")?;
         writeln!(
             f,
@@ -399,51 +478,80 @@ impl fmt::Display for ShowSolidoOutput {
             "Reserve:                    {}, {}",
             self.reserve_account, self.solido.sol_reserve_account_bump_seed
         )?;
+        let reward_distribution = &self.solido.reward_distribution;
         writeln!(f, "\nReward distribution:")?;
-        let mut print_reward = |name, get: fn(&RewardDistribution) -> u32| {
+        writeln!(
+            f,
+            "  {:4}/{:4} => stSOL appreciation",
+            reward_distribution.st_sol_appreciation,
+            reward_distribution.sum(),
+        )?;
+        for (i, recipient) in reward_distribution.fee_recipients.iter().enumerate() {
             writeln!(
                 f,
-                "  {:4}/{:4} => {}",
-                get(&self.solido.reward_distribution),
-                self.solido.reward_distribution.sum(),
-                name,
-            )
-        };
-        print_reward("stSOL appreciation", |d| d.st_sol_appreciation)?;
-        print_reward("Treasury", |d| d.treasury_fee)?;
-        print_reward("Developer fee", |d| d.developer_fee)?;
+                "  {:4}/{:4} => {}{}",
+                recipient.parts,
+                reward_distribution.sum(),
+                recipient.st_sol_account,
+                if i == 0 { " (treasury)" } else { "" },
+            )?;
+        }
 
-        writeln!(f, "\nFee recipients:")?;
         writeln!(
             f,
-            "Treasury SPL token account:      {}",
-            self.solido.fee_recipients.treasury_account
+            "Max validation commission: {}%",
+            self.solido.max_commission_percentage
+        )?;
+        writeln!(
+            f,
+            "Instant unstake fee:       {} basis points",
+            self.solido.instant_unstake_fee_bps
         )?;
         writeln!(
             f,
-            "Developer fee SPL token account: {}",
-            self.solido.fee_recipients.developer_account
+            "Withdrawal fee:            {} basis points",
+            self.solido.withdrawal_fee_bps
         )?;
-
         writeln!(
             f,
-            "Max validation commission: {}%",
-            self.solido.max_commission_percentage
+            "Owed to withdrawal tickets: {}",
+            self.solido.withdrawal_tickets_owed
         )?;
-
-        writeln!(f, "\nMetrics:")?;
         writeln!(
             f,
-            "  Total treasury fee:       {}, valued at {} when it was paid",
-            self.solido.metrics.fee_treasury_st_sol_total,
-            self.solido.metrics.fee_treasury_sol_total,
+            "Min vote credits:          {} over the last {} epochs",
+            self.solido.min_vote_credits, self.solido.vote_credits_epochs
         )?;
         writeln!(
             f,
-            "  Total developer fee:      {}, valued at {} when it was paid",
-            self.solido.metrics.fee_developer_st_sol_total,
-            self.solido.metrics.fee_developer_sol_total,
+            "Paused operations:         {}",
+            format_paused_operations(self.solido.paused_operations)
         )?;
+        writeln!(
+            f,
+            "Max total SOL:             {}",
+            format_max_total_sol(self.solido.max_total_sol)
+        )?;
+        writeln!(f, "Min deposit:               {}", self.solido.min_deposit)?;
+        writeln!(
+            f,
+            "Max directed stake:        {} basis points of the SOL under management",
+            self.solido.max_directed_stake_bps
+        )?;
+        writeln!(
+            f,
+            "Reserve buffer:            {}",
+            format_reserve_buffer(self.solido.reserve_buffer)
+        )?;
+
+        writeln!(f, "\nMetrics:")?;
+        for recipient in &self.solido.metrics.fee_recipients {
+            writeln!(
+                f,
+                "  Total fee to {}: {}, valued at {} when it was paid",
+                recipient.st_sol_account, recipient.fee_st_sol_total, recipient.fee_sol_total,
+            )?;
+        }
         writeln!(
             f,
             "  Total stSOL appreciation: {}",
@@ -460,6 +568,30 @@ impl fmt::Display for ShowSolidoOutput {
             "  Number of withdrawals:    {}",
             self.solido.metrics.withdraw_amount.count,
         )?;
+        writeln!(
+            f,
+            "  Total instant unstaked:   {}, valued at {} when it was unstaked",
+            self.solido
+                .metrics
+                .instant_unstake_amount
+                .total_st_sol_amount,
+            self.solido.metrics.instant_unstake_amount.total_sol_amount,
+        )?;
+        writeln!(
+            f,
+            "  Instant unstake count:    {}",
+            self.solido.metrics.instant_unstake_amount.count,
+        )?;
+        writeln!(
+            f,
+            "  Instant unstake fees:     {}",
+            self.solido.metrics.fee_instant_unstake_st_sol_total,
+        )?;
+        writeln!(
+            f,
+            "  Withdrawal fees:          {}",
+            self.solido.metrics.fee_withdrawal_st_sol_total,
+        )?;
         writeln!(
             f,
             "  Total deposited:          {}",
@@ -484,11 +616,11 @@ impl fmt::Display for ShowSolidoOutput {
         writeln!(
             f,
             "\nValidators: {} in use out of {} that the instance can support",
-            self.solido.validators.len(),
-            self.solido.validators.maximum_entries
+            self.validators.len(),
+            self.validators.maximum_entries
         )?;
+        writeln!(f, "Validator list: {}", self.solido.validator_list)?;
         for (((pe, identity), info), commission) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -505,6 +637,8 @@ impl fmt::Display for ShowSolidoOutput {
                 Identity account:          {}\n    \
                 Commission:                {}%\n   \
                 Active:                    {}\n    \
+                Weight:                    {}\n    \
+                Directed stake:            {}\n    \
                 Stake in all accounts:     {}\n    \
                 Stake in stake accounts:   {}\n    \
                 Stake in unstake accounts: {}",
@@ -517,6 +651,8 @@ impl fmt::Display for ShowSolidoOutput {
                 identity,
                 commission,
                 pe.entry.active,
+                pe.entry.weight,
+                pe.entry.directed_stake,
                 pe.entry.stake_accounts_balance,
                 pe.entry.effective_stake_balance(),
                 pe.entry.unstake_accounts_balance,
@@ -567,7 +703,12 @@ impl fmt::Display for ShowSolidoOutput {
             self.solido.maintainers.maximum_entries
         )?;
         for pe in &self.solido.maintainers.entries {
-            writeln!(f, "  - {}", pe.pubkey)?;
+            writeln!(
+                f,
+                "  - {}, permitted: {}",
+                pe.pubkey,
+                format_maintainer_permissions(pe.entry)
+            )?;
         }
         Ok(())
     }
@@ -578,6 +719,7 @@ pub fn command_show_solido(
     opts: &ShowSolidoOpts,
 ) -> solido_cli_common::Result<ShowSolidoOutput> {
     let lido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&lido.validator_list)?;
     let reserve_account =
         lido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
     let stake_authority =
@@ -588,7 +730,7 @@ pub fn command_show_solido(
     let mut validator_identities = Vec::new();
     let mut validator_infos = Vec::new();
     let mut validator_commission_percentages = Vec::new();
-    for validator in lido.validators.entries.iter() {
+    for validator in validators.entries.iter() {
         let vote_state = config.client.get_vote_account(&validator.pubkey)?;
         validator_identities.push(vote_state.node_pubkey);
         let info = config.client.get_validator_info(&vote_state.node_pubkey)?;
@@ -603,6 +745,7 @@ pub fn command_show_solido(
         solido_program_id: *opts.solido_program_id(),
         solido_address: *opts.solido_address(),
         solido: lido,
+        validators,
         validator_identities,
         validator_infos,
         validator_commission_percentages,
@@ -628,6 +771,17 @@ pub struct ShowSolidoAuthoritiesOutput {
 
     #[serde(serialize_with = "serialize_b58")]
     pub mint_authority: Pubkey,
+
+    /// The manager and the role holders, if the instance exists.
+    pub administrators: Option<SolidoAdministrators>,
+}
+
+#[derive(Serialize)]
+pub struct SolidoAdministrators {
+    #[serde(serialize_with = "serialize_b58")]
+    pub manager: Pubkey,
+
+    pub roles: Roles,
 }
 
 impl fmt::Display for ShowSolidoAuthoritiesOutput {
@@ -635,11 +789,24 @@ impl fmt::Display for ShowSolidoAuthoritiesOutput {
         writeln!(f, "Stake authority:            {}", self.stake_authority,)?;
         writeln!(f, "Mint authority:             {}", self.mint_authority)?;
         writeln!(f, "Reserve account:            {}", self.reserve_account)?;
+        if let Some(administrators) = &self.administrators {
+            let roles = &administrators.roles;
+            writeln!(f, "Manager:                    {}", administrators.manager)?;
+            writeln!(f, "Validator admin:            {}", roles.validator_admin)?;
+            writeln!(f, "Fee admin:                  {}", roles.fee_admin)?;
+            writeln!(f, "Maintainer admin:           {}", roles.maintainer_admin)?;
+            writeln!(
+                f,
+                "Emergency guardian:         {}",
+                roles.emergency_guardian
+            )?;
+        }
         Ok(())
     }
 }
 
 pub fn command_show_solido_authorities(
+    config: &mut SnapshotConfig,
     opts: &ShowSolidoAuthoritiesOpts,
 ) -> solido_cli_common::Result<ShowSolidoAuthoritiesOutput> {
     let (reserve_account, _) = find_authority_program_address(
@@ -657,12 +824,54 @@ pub fn command_show_solido_authorities(
         opts.solido_address(),
         STAKE_AUTHORITY,
     );
+    // The program-derived authorities are known before the instance exists,
+    // the manager and roles only once it has been created.
+    let administrators = if config.client.account_exists(opts.solido_address())? {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        Some(SolidoAdministrators {
+            manager: solido.manager,
+            roles: solido.roles,
+        })
+    } else {
+        None
+    };
     Ok(ShowSolidoAuthoritiesOutput {
         solido_program_id: *opts.solido_program_id(),
         solido_address: *opts.solido_address(),
         reserve_account,
         stake_authority,
         mint_authority,
+        administrators,
+    })
+}
+
+#[derive(Serialize)]
+pub struct ShowEventsOutput {
+    pub events: Vec<SolidoEvent>,
+}
+
+impl fmt::Display for ShowEventsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.events.is_empty() {
+            writeln!(f, "The transaction did not log any Solido events.")?;
+        }
+        for event in &self.events {
+            writeln!(f, "{:?}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI entry point to decode the events that Solido logged in a transaction.
+pub fn command_show_events(
+    config: &mut SnapshotConfig,
+    opts: &ShowEventsOpts,
+) -> solido_cli_common::Result<ShowEventsOutput> {
+    let log_messages = config
+        .client
+        .get_transaction_log_messages(opts.transaction_signature())?;
+    Ok(ShowEventsOutput {
+        events: parse_solido_events(opts.solido_program_id(), &log_messages),
     })
 }
 
@@ -710,12 +919,15 @@ impl fmt::Display for DepositOutput {
     }
 }
 
-pub fn command_deposit(
+/// Return the associated stSOL account of the signer, create it if it does not exist.
+///
+/// Also returns whether the account had to be created.
+fn get_or_create_associated_st_sol_account(
     config: &mut SnapshotClientConfig,
-    opts: &DepositOpts,
-) -> std::result::Result<DepositOutput, Error> {
-    let (recipient, created_recipient) = config.with_snapshot(|config| {
-        let solido = config.client.get_solido(opts.solido_address())?;
+    solido_address: &Pubkey,
+) -> std::result::Result<(Pubkey, bool), Error> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(solido_address)?;
 
         let recipient = spl_associated_token_account::get_associated_token_address(
             &config.signer.pubkey(),
@@ -735,7 +947,15 @@ pub fn command_deposit(
         } else {
             Ok((recipient, false))
         }
-    })?;
+    })
+}
+
+pub fn command_deposit(
+    config: &mut SnapshotClientConfig,
+    opts: &DepositOpts,
+) -> std::result::Result<DepositOutput, Error> {
+    let (recipient, created_recipient) =
+        get_or_create_associated_st_sol_account(config, opts.solido_address())?;
 
     let (balance_before, exchange_rate) = config.with_snapshot(|config| {
         let balance_before = config
@@ -748,18 +968,35 @@ pub fn command_deposit(
         let mint_authority =
             solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
 
-        let instr = lido::instruction::deposit(
-            opts.solido_program_id(),
-            &lido::instruction::DepositAccountsMeta {
-                lido: *opts.solido_address(),
-                user: config.signer.pubkey(),
-                recipient,
-                st_sol_mint: solido.st_sol_mint,
-                mint_authority,
-                reserve_account: reserve,
-            },
-            *opts.amount_sol(),
-        );
+        let instr = if *opts.preferred_validator_vote_account() == Pubkey::default() {
+            lido::instruction::deposit(
+                opts.solido_program_id(),
+                &lido::instruction::DepositAccountsMeta {
+                    lido: *opts.solido_address(),
+                    user: config.signer.pubkey(),
+                    recipient,
+                    st_sol_mint: solido.st_sol_mint,
+                    mint_authority,
+                    reserve_account: reserve,
+                },
+                *opts.amount_sol(),
+            )
+        } else {
+            lido::instruction::deposit_directed(
+                opts.solido_program_id(),
+                &lido::instruction::DepositDirectedAccountsMeta {
+                    lido: *opts.solido_address(),
+                    user: config.signer.pubkey(),
+                    recipient,
+                    st_sol_mint: solido.st_sol_mint,
+                    mint_authority,
+                    reserve_account: reserve,
+                    validator_list: solido.validator_list,
+                    validator_vote_account: *opts.preferred_validator_vote_account(),
+                },
+                *opts.amount_sol(),
+            )
+        };
 
         config.sign_and_send_transaction(&[instr], &[config.signer])?;
 
@@ -790,6 +1027,143 @@ pub fn command_deposit(
     Ok(result)
 }
 
+#[derive(Serialize)]
+pub struct DepositStakeOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub recipient: Pubkey,
+
+    /// Amount of stSOL we expected to receive based on the exchange rate at the
+    /// time of the deposit, for the full balance of the stake account.
+    #[serde(rename = "expected_st_lamports")]
+    pub expected_st_sol: StLamports,
+
+    /// Whether the stake was merged into an existing Solido stake account, or
+    /// became a new stake account.
+    pub merged: bool,
+
+    /// Whether we had to create the associated stSOL account. False if one existed already.
+    pub created_associated_st_sol_account: bool,
+}
+
+impl fmt::Display for DepositStakeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.created_associated_st_sol_account {
+            writeln!(f, "Created recipient stSOL account, it did not yet exist.")?;
+        } else {
+            writeln!(f, "Recipient stSOL account existed already before deposit.")?;
+        }
+        writeln!(f, "Recipient stSOL account: {}", self.recipient)?;
+        writeln!(f, "Expected stSOL amount:   {}", self.expected_st_sol)?;
+        if self.merged {
+            writeln!(f, "Stake was merged into an existing Solido stake account.")?;
+        } else {
+            writeln!(f, "Stake became a new Solido stake account.")?;
+        }
+        Ok(())
+    }
+}
+
+/// Deposit a fully active stake account, that the signer is the staker and
+/// withdrawer of, and receive stSOL in return.
+pub fn command_deposit_stake(
+    config: &mut SnapshotClientConfig,
+    opts: &DepositStakeOpts,
+) -> std::result::Result<DepositStakeOutput, Error> {
+    let (recipient, created_recipient) =
+        get_or_create_associated_st_sol_account(config, opts.solido_address())?;
+
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let validators = config.client.get_validators(&solido.validator_list)?;
+        let clock = config.client.get_clock()?;
+        let stake_history = config.client.get_stake_history()?;
+
+        let user_stake_account = config.client.get_account(opts.stake_account())?;
+        let user_stake_balance = Lamports(user_stake_account.lamports);
+        let user_stake = deserialize_stake_account(&user_stake_account.data)?;
+        let vote_account = user_stake.delegation.voter_pubkey;
+        let user_stake = StakeAccount::from_delegated_account(
+            user_stake_balance,
+            &user_stake,
+            &clock,
+            &stake_history,
+            0,
+        );
+        let validator =
+            match validators.get(&vote_account) {
+                Ok(validator) => validator,
+                Err(_) => return Err(CliError::new(
+                    "The stake account is not delegated to a validator of this Solido instance.",
+                )
+                .into()),
+            };
+
+        let (stake_account_end, _) = validator.find_stake_account_address(
+            opts.solido_program_id(),
+            opts.solido_address(),
+            validator.entry.stake_seeds.end,
+            StakeType::Stake,
+        );
+
+        // Merge into the validator's last stake account if the stake program
+        // allows it, otherwise the deposited stake becomes a new stake account.
+        let mut stake_account_merge_into = stake_account_end;
+        if validator.entry.stake_seeds.end > validator.entry.stake_seeds.begin {
+            let last_seed = validator.entry.stake_seeds.end - 1;
+            let (last_address, _) = validator.find_stake_account_address(
+                opts.solido_program_id(),
+                opts.solido_address(),
+                last_seed,
+                StakeType::Stake,
+            );
+            let last_account = config.client.get_account(&last_address)?;
+            let last_stake = StakeAccount::from_delegated_account(
+                Lamports(last_account.lamports),
+                &deserialize_stake_account(&last_account.data)?,
+                &clock,
+                &stake_history,
+                last_seed,
+            );
+            if last_stake.can_merge(&user_stake) {
+                stake_account_merge_into = last_address;
+            }
+        }
+
+        let stake_authority =
+            solido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
+        let mint_authority =
+            solido.get_mint_authority(opts.solido_program_id(), opts.solido_address())?;
+
+        let instr = lido::instruction::deposit_stake(
+            opts.solido_program_id(),
+            &lido::instruction::DepositStakeAccountsMeta {
+                lido: *opts.solido_address(),
+                validator_list: solido.validator_list,
+                validator_vote_account: vote_account,
+                user_stake_account: *opts.stake_account(),
+                user_stake_authority: config.signer.pubkey(),
+                recipient,
+                st_sol_mint: solido.st_sol_mint,
+                stake_account_merge_into,
+                stake_account_end,
+                stake_authority,
+                mint_authority,
+            },
+        );
+        config.sign_and_send_transaction(&[instr], &[config.signer])?;
+
+        Ok(DepositStakeOutput {
+            recipient,
+            expected_st_sol: solido
+                .exchange_rate
+                .exchange_sol(user_stake_balance)
+                .unwrap_or(StLamports(0)),
+            merged: stake_account_merge_into != stake_account_end,
+            created_associated_st_sol_account: created_recipient,
+        })
+    })
+}
+
 #[derive(Serialize)]
 pub struct WithdrawOutput {
     #[serde(serialize_with = "serialize_b58")]
@@ -828,37 +1202,78 @@ pub fn command_withdraw(
         let stake_authority =
             solido.get_stake_authority(opts.solido_program_id(), opts.solido_address())?;
 
-        // Get heaviest validator.
-        let heaviest_validator = get_validator_to_withdraw(&solido.validators).map_err(|err| {
-            CliError::with_cause(
-                "The instance has no active validators to withdraw from.",
-                err,
-            )
-        })?;
+        // Get heaviest validator, unless the user picked one.
+        let validators = config.client.get_validators(&solido.validator_list)?;
+        let any_validator = *opts.validator_vote_account() != Pubkey::default();
+        let validator = if any_validator {
+            match validators.get(opts.validator_vote_account()) {
+                Ok(validator) => validator,
+                Err(_) => {
+                    return Err(
+                        CliError::new("The validator is not part of this Solido instance.").into(),
+                    )
+                }
+            }
+        } else {
+            get_validator_to_withdraw(&validators).map_err(|err| {
+                CliError::with_cause(
+                    "The instance has no active validators to withdraw from.",
+                    err,
+                )
+            })?
+        };
 
-        let (stake_address, _bump_seed) = heaviest_validator.find_stake_account_address(
+        let (stake_address, _bump_seed) = validator.find_stake_account_address(
             opts.solido_program_id(),
             opts.solido_address(),
-            heaviest_validator.entry.stake_seeds.begin,
+            validator.entry.stake_seeds.begin,
             StakeType::Stake,
         );
 
         let destination_stake_account = Keypair::new();
-
-        let instr = lido::instruction::withdraw(
-            opts.solido_program_id(),
-            &lido::instruction::WithdrawAccountsMeta {
-                lido: *opts.solido_address(),
-                st_sol_mint: solido.st_sol_mint,
-                st_sol_account_owner: config.signer.pubkey(),
-                st_sol_account: st_sol_address,
-                validator_vote_account: heaviest_validator.pubkey,
-                source_stake_account: stake_address,
-                destination_stake_account: destination_stake_account.pubkey(),
-                stake_authority,
-            },
-            *opts.amount_st_sol(),
-        );
+        let treasury_st_sol_account = *solido
+            .reward_distribution
+            .treasury_account()
+            .expect("Solido always has at least one fee recipient.");
+
+        let instr = if any_validator {
+            let reserve =
+                solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+            lido::instruction::withdraw_from_any_validator(
+                opts.solido_program_id(),
+                &lido::instruction::WithdrawFromAnyValidatorAccountsMeta {
+                    lido: *opts.solido_address(),
+                    validator_list: solido.validator_list,
+                    st_sol_mint: solido.st_sol_mint,
+                    st_sol_account_owner: config.signer.pubkey(),
+                    st_sol_account: st_sol_address,
+                    treasury_st_sol_account,
+                    validator_vote_account: validator.pubkey,
+                    source_stake_account: stake_address,
+                    destination_stake_account: destination_stake_account.pubkey(),
+                    stake_authority,
+                    reserve,
+                },
+                *opts.amount_st_sol(),
+            )
+        } else {
+            lido::instruction::withdraw(
+                opts.solido_program_id(),
+                &lido::instruction::WithdrawAccountsMeta {
+                    lido: *opts.solido_address(),
+                    validator_list: solido.validator_list,
+                    st_sol_mint: solido.st_sol_mint,
+                    st_sol_account_owner: config.signer.pubkey(),
+                    st_sol_account: st_sol_address,
+                    treasury_st_sol_account,
+                    validator_vote_account: validator.pubkey,
+                    source_stake_account: stake_address,
+                    destination_stake_account: destination_stake_account.pubkey(),
+                    stake_authority,
+                },
+                *opts.amount_st_sol(),
+            )
+        };
         config.sign_and_send_transaction(&[instr], &[config.signer, &destination_stake_account])?;
 
         Ok((st_sol_address, destination_stake_account))
@@ -877,53 +1292,239 @@ pub fn command_withdraw(
 }
 
 #[derive(Serialize)]
-pub struct DeactivateValidatorIfCommissionExceedsMaxOutput {
-    // List of validators that exceeded max commission
-    entries: Vec<ValidatorViolationInfo>,
-    max_commission_percentage: u8,
-}
-
-#[derive(Serialize)]
-struct ValidatorViolationInfo {
+pub struct InstantUnstakeOutput {
     #[serde(serialize_with = "serialize_b58")]
-    pub validator_vote_account: Pubkey,
-    pub commission: u8,
+    pub from_token_address: Pubkey,
+
+    /// Amount of stSOL that was paid to the treasury as fee.
+    pub fee_st_sol: StLamports,
+
+    /// Amount of SOL that we expected to receive from the reserve.
+    pub expected_sol: Lamports,
 }
 
-impl fmt::Display for DeactivateValidatorIfCommissionExceedsMaxOutput {
+impl fmt::Display for InstantUnstakeOutput {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(
-            f,
-            "Maximum validation commission: {}",
-            self.max_commission_percentage
-        )?;
-
-        for entry in &self.entries {
-            writeln!(
-                f,
-                "Validator vote account: {}, validation commission: {}",
-                entry.validator_vote_account, entry.commission
-            )?;
-        }
+        writeln!(f, "Unstaked from:           {}", self.from_token_address)?;
+        writeln!(f, "Fee paid:                {}", self.fee_st_sol)?;
+        writeln!(f, "Expected SOL received:   {}", self.expected_sol)?;
         Ok(())
     }
 }
 
-/// CLI entry point to punish validator for commission violation.
-pub fn command_deactivate_validator_if_commission_exceeds_max(
-    config: &mut SnapshotConfig,
-    opts: &DeactivateValidatorIfCommissionExceedsMaxOpts,
-) -> solido_cli_common::Result<DeactivateValidatorIfCommissionExceedsMaxOutput> {
-    let solido = config.client.get_solido(opts.solido_address())?;
+pub fn command_instant_unstake(
+    config: &mut SnapshotClientConfig,
+    opts: &InstantUnstakeOpts,
+) -> std::result::Result<InstantUnstakeOutput, Error> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
 
-    let mut violations = vec![];
-    let mut instructions = vec![];
-    for pubkey_entry in solido.validators.entries {
-        let validator = pubkey_entry.entry;
-        let vote_pubkey = pubkey_entry.pubkey;
-        let validator_account = config.client.get_account(&vote_pubkey)?;
-        let commission = get_vote_account_commission(&validator_account.data)
-            .ok_or_else(|| CliError::new("Validator account data too small"))?;
+        let st_sol_address = spl_associated_token_account::get_associated_token_address(
+            &config.signer.pubkey(),
+            &solido.st_sol_mint,
+        );
+        let reserve_account =
+            solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+
+        let instr = lido::instruction::instant_unstake(
+            opts.solido_program_id(),
+            &lido::instruction::InstantUnstakeAccountsMeta {
+                lido: *opts.solido_address(),
+                st_sol_account_owner: config.signer.pubkey(),
+                st_sol_account: st_sol_address,
+                st_sol_mint: solido.st_sol_mint,
+                treasury_st_sol_account: *solido
+                    .reward_distribution
+                    .treasury_account()
+                    .expect("Solido always has at least one fee recipient."),
+                reserve_account,
+                recipient: config.signer.pubkey(),
+            },
+            *opts.amount_st_sol(),
+        );
+        config.sign_and_send_transaction(&[instr], &[config.signer])?;
+
+        // If these are not `Ok`, the transaction should have failed, but if
+        // the transaction did not fail, then we do want to show the output.
+        let fee_st_sol = solido
+            .get_instant_unstake_fee(*opts.amount_st_sol())
+            .unwrap_or(StLamports(0));
+        let expected_sol = (*opts.amount_st_sol() - fee_st_sol)
+            .ok()
+            .and_then(|amount| solido.exchange_rate.exchange_st_sol(amount).ok())
+            .unwrap_or(Lamports(0));
+
+        Ok(InstantUnstakeOutput {
+            from_token_address: st_sol_address,
+            fee_st_sol,
+            expected_sol,
+        })
+    })
+}
+
+#[derive(Serialize)]
+pub struct RequestWithdrawalOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub from_token_address: Pubkey,
+
+    /// Newly created withdrawal ticket, to be claimed with 'claim-withdrawal'.
+    #[serde(serialize_with = "serialize_b58")]
+    pub withdrawal_ticket: Pubkey,
+
+    /// Amount of SOL that the ticket can be claimed for.
+    pub owed_sol: Lamports,
+}
+
+impl fmt::Display for RequestWithdrawalOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Withdrawn from:          {}", self.from_token_address)?;
+        writeln!(f, "Withdrawal ticket:       {}", self.withdrawal_ticket)?;
+        writeln!(f, "SOL owed:                {}", self.owed_sol)?;
+        Ok(())
+    }
+}
+
+pub fn command_request_withdrawal(
+    config: &mut SnapshotClientConfig,
+    opts: &RequestWithdrawalOpts,
+) -> std::result::Result<RequestWithdrawalOutput, Error> {
+    let (st_sol_address, withdrawal_ticket) = config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+
+        let st_sol_address = spl_associated_token_account::get_associated_token_address(
+            &config.signer.pubkey(),
+            &solido.st_sol_mint,
+        );
+
+        let withdrawal_ticket = Keypair::new();
+
+        let instr = lido::instruction::request_withdrawal(
+            opts.solido_program_id(),
+            &lido::instruction::RequestWithdrawalAccountsMeta {
+                lido: *opts.solido_address(),
+                st_sol_account_owner: config.signer.pubkey(),
+                st_sol_account: st_sol_address,
+                st_sol_mint: solido.st_sol_mint,
+                withdrawal_ticket: withdrawal_ticket.pubkey(),
+            },
+            *opts.amount_st_sol(),
+        );
+        config.sign_and_send_transaction(&[instr], &[config.signer, &withdrawal_ticket])?;
+
+        Ok((st_sol_address, withdrawal_ticket))
+    })?;
+
+    let ticket = config.with_snapshot(|config| {
+        config
+            .client
+            .get_withdrawal_ticket(&withdrawal_ticket.pubkey())
+    })?;
+    let result = RequestWithdrawalOutput {
+        from_token_address: st_sol_address,
+        withdrawal_ticket: withdrawal_ticket.pubkey(),
+        owed_sol: ticket.amount,
+    };
+    Ok(result)
+}
+
+#[derive(Serialize)]
+pub struct ClaimWithdrawalOutput {
+    #[serde(serialize_with = "serialize_b58")]
+    pub withdrawal_ticket: Pubkey,
+
+    /// Amount of SOL that was paid out of the reserve.
+    pub claimed_sol: Lamports,
+}
+
+impl fmt::Display for ClaimWithdrawalOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Withdrawal ticket:       {}", self.withdrawal_ticket)?;
+        writeln!(f, "Total SOL claimed:       {}", self.claimed_sol)?;
+        Ok(())
+    }
+}
+
+pub fn command_claim_withdrawal(
+    config: &mut SnapshotClientConfig,
+    opts: &ClaimWithdrawalOpts,
+) -> std::result::Result<ClaimWithdrawalOutput, Error> {
+    config.with_snapshot(|config| {
+        let solido = config.client.get_solido(opts.solido_address())?;
+        let ticket = config
+            .client
+            .get_withdrawal_ticket(opts.withdrawal_ticket())?;
+
+        let reserve_account =
+            solido.get_reserve_account(opts.solido_program_id(), opts.solido_address())?;
+
+        let instr = lido::instruction::claim_withdrawal(
+            opts.solido_program_id(),
+            &lido::instruction::ClaimWithdrawalAccountsMeta {
+                lido: *opts.solido_address(),
+                ticket_owner: config.signer.pubkey(),
+                withdrawal_ticket: *opts.withdrawal_ticket(),
+                reserve_account,
+                recipient: config.signer.pubkey(),
+            },
+        );
+        config.sign_and_send_transaction(&[instr], &[config.signer])?;
+
+        Ok(ClaimWithdrawalOutput {
+            withdrawal_ticket: *opts.withdrawal_ticket(),
+            claimed_sol: ticket.amount,
+        })
+    })
+}
+
+#[derive(Serialize)]
+pub struct DeactivateValidatorIfCommissionExceedsMaxOutput {
+    // List of validators that exceeded max commission
+    entries: Vec<ValidatorViolationInfo>,
+    max_commission_percentage: u8,
+}
+
+#[derive(Serialize)]
+struct ValidatorViolationInfo {
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_vote_account: Pubkey,
+    pub commission: u8,
+}
+
+impl fmt::Display for DeactivateValidatorIfCommissionExceedsMaxOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Maximum validation commission: {}",
+            self.max_commission_percentage
+        )?;
+
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "Validator vote account: {}, validation commission: {}",
+                entry.validator_vote_account, entry.commission
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI entry point to punish validator for commission violation.
+pub fn command_deactivate_validator_if_commission_exceeds_max(
+    config: &mut SnapshotConfig,
+    opts: &DeactivateValidatorIfCommissionExceedsMaxOpts,
+) -> solido_cli_common::Result<DeactivateValidatorIfCommissionExceedsMaxOutput> {
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&solido.validator_list)?;
+
+    let mut violations = vec![];
+    let mut instructions = vec![];
+    for pubkey_entry in validators.entries {
+        let validator = pubkey_entry.entry;
+        let vote_pubkey = pubkey_entry.pubkey;
+        let validator_account = config.client.get_account(&vote_pubkey)?;
+        let commission = get_vote_account_commission(&validator_account.data)
+            .ok_or_else(|| CliError::new("Validator account data too small"))?;
 
         if !validator.active || commission <= solido.max_commission_percentage {
             continue;
@@ -933,6 +1534,7 @@ pub fn command_deactivate_validator_if_commission_exceeds_max(
             opts.solido_program_id(),
             &lido::instruction::DeactivateValidatorIfCommissionExceedsMaxMeta {
                 lido: *opts.solido_address(),
+                validator_list: solido.validator_list,
                 validator_vote_account_to_deactivate: vote_pubkey,
             },
         );
@@ -978,3 +1580,634 @@ pub fn command_set_max_commission_percentage(
         instruction,
     )
 }
+
+pub fn command_set_instant_unstake_fee(
+    config: &mut SnapshotConfig,
+    opts: &SetInstantUnstakeFeeOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_instant_unstake_fee(
+        opts.solido_program_id(),
+        &lido::instruction::SetInstantUnstakeFeeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        *opts.instant_unstake_fee_bps(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_min_vote_credits(
+    config: &mut SnapshotConfig,
+    opts: &SetMinVoteCreditsOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_min_vote_credits(
+        opts.solido_program_id(),
+        &lido::instruction::SetMinVoteCreditsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        *opts.min_vote_credits(),
+        *opts.vote_credits_epochs(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_validator_weight(
+    config: &mut SnapshotConfig,
+    opts: &SetValidatorWeightOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
+
+    let instruction = lido::instruction::set_validator_weight(
+        opts.solido_program_id(),
+        &lido::instruction::SetValidatorWeightMeta {
+            lido: *opts.solido_address(),
+            validator_list: solido.validator_list,
+            manager: multisig_address,
+            validator_vote_account: *opts.validator_vote_account(),
+        },
+        *opts.weight(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_propose_new_manager(
+    config: &mut SnapshotConfig,
+    opts: &ProposeNewManagerOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::propose_new_manager(
+        opts.solido_program_id(),
+        &lido::instruction::ProposeNewManagerMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            new_manager: *opts.new_manager(),
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Propose to accept the manager role, from the multisig that was proposed as the new manager.
+pub fn command_accept_manager(
+    config: &mut SnapshotConfig,
+    opts: &AcceptManagerOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::accept_manager(
+        opts.solido_program_id(),
+        &lido::instruction::AcceptManagerMeta {
+            lido: *opts.solido_address(),
+            new_manager: multisig_address,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_change_fee_recipients(
+    config: &mut SnapshotConfig,
+    opts: &ChangeFeeRecipientsOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::change_fee_recipients(
+        opts.solido_program_id(),
+        &lido::instruction::ChangeFeeRecipientsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            fee_recipients: opts.fee_recipient_accounts().0.clone(),
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to change the fee recipients and the shares of the rewards.
+pub fn command_change_reward_distribution(
+    config: &mut SnapshotConfig,
+    opts: &ChangeRewardDistributionOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let reward_distribution = RewardDistribution {
+        fee_recipients: opts.fee_recipients().0.clone(),
+        st_sol_appreciation: *opts.st_sol_appreciation_share(),
+    };
+    let instruction = lido::instruction::change_reward_distribution(
+        opts.solido_program_id(),
+        reward_distribution.clone(),
+        &lido::instruction::ChangeRewardDistributionMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            fee_recipients: reward_distribution
+                .fee_recipients
+                .iter()
+                .map(|recipient| recipient.st_sol_account)
+                .collect(),
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Format the `PAUSE_*` bits as a human-readable list of operation names.
+pub fn format_paused_operations(paused_operations: u8) -> String {
+    let names: Vec<&str> = [
+        (PAUSE_DEPOSIT, "deposit"),
+        (PAUSE_WITHDRAW, "withdraw"),
+        (PAUSE_STAKE_DEPOSIT, "stake deposit"),
+        (PAUSE_UNSTAKE, "unstake"),
+    ]
+    .iter()
+    .filter(|(bit, _)| paused_operations & bit != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+pub fn command_set_paused_operations(
+    config: &mut SnapshotConfig,
+    opts: &SetPausedOperationsOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let mut paused_operations = 0;
+    for (pause, bit) in [
+        (opts.pause_deposit(), PAUSE_DEPOSIT),
+        (opts.pause_withdraw(), PAUSE_WITHDRAW),
+        (opts.pause_stake_deposit(), PAUSE_STAKE_DEPOSIT),
+        (opts.pause_unstake(), PAUSE_UNSTAKE),
+    ] {
+        if *pause {
+            paused_operations |= bit;
+        }
+    }
+
+    let instruction = lido::instruction::set_paused_operations(
+        opts.solido_program_id(),
+        &lido::instruction::SetPausedOperationsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        paused_operations,
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Format `Lido::max_total_sol`, where 0 means there is no maximum.
+pub fn format_max_total_sol(max_total_sol: Lamports) -> String {
+    if max_total_sol == Lamports(0) {
+        "no maximum".to_string()
+    } else {
+        max_total_sol.to_string()
+    }
+}
+
+pub fn format_reserve_buffer(reserve_buffer: ReserveBuffer) -> String {
+    match reserve_buffer {
+        ReserveBuffer::Amount(amount) => amount.to_string(),
+        ReserveBuffer::Bps(bps) => format!("{} basis points of the SOL under management", bps),
+    }
+}
+
+pub fn command_resize_validator_list(
+    config: &mut SnapshotConfig,
+    opts: &ResizeValidatorListOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
+
+    // The multisig signs as the manager, and it also pays for the additional
+    // rent, so its program-derived address needs to hold enough SOL.
+    let instruction = lido::instruction::resize_validator_list(
+        opts.solido_program_id(),
+        &lido::instruction::ResizeValidatorListMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            validator_list: solido.validator_list,
+            funder: multisig_address,
+        },
+        *opts.max_validators(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+#[derive(Serialize)]
+pub struct PreviewMigrateStateOutput {
+    /// Version of the layout that the instance uses now.
+    pub version: u8,
+
+    /// The Solido state after migrating to the current version.
+    pub solido: Lido,
+
+    /// The validators, that the migration moves to the validator list account.
+    pub validators: Validators,
+}
+
+impl fmt::Display for PreviewMigrateStateOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Migrating from version {} to version {} results in:",
+            self.version, LIDO_VERSION
+        )?;
+        let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+        writeln!(f, "{}", json)
+    }
+}
+
+/// Show the state that `MigrateState` would produce, without changing anything.
+pub fn command_preview_migrate_state(
+    config: &mut SnapshotConfig,
+    opts: &PreviewMigrateStateOpts,
+) -> solido_cli_common::Result<PreviewMigrateStateOutput> {
+    let solido = config.client.get_versioned_solido(opts.solido_address())?;
+    let version = solido.version();
+    match solido {
+        VersionedLido::V1(solido_v1) => {
+            let (solido, validators) = solido_v1.migrate(*opts.validator_list_address());
+            Ok(PreviewMigrateStateOutput {
+                version,
+                solido,
+                validators,
+            })
+        }
+        VersionedLido::Current(_) => Err(CliError::new(
+            "The Solido instance uses the current version already, there is nothing to migrate.",
+        )
+        .into()),
+    }
+}
+
+#[derive(Serialize)]
+pub struct MigrateStateOutput {
+    /// Account that will hold the validators after the migration.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_list_address: Pubkey,
+
+    /// The multisig transaction that performs the migration.
+    pub proposal: ProposeInstructionOutput,
+}
+
+impl fmt::Display for MigrateStateOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Validator list:      {}", self.validator_list_address)?;
+        write!(f, "{}", self.proposal)
+    }
+}
+
+/// Create the validator list account, and propose `MigrateState` to the multisig.
+pub fn command_migrate_state(
+    config: &mut SnapshotConfig,
+    opts: &MigrateStateOpts,
+) -> solido_cli_common::Result<MigrateStateOutput> {
+    let solido_v1 = match config.client.get_versioned_solido(opts.solido_address())? {
+        VersionedLido::V1(solido_v1) => solido_v1,
+        VersionedLido::Current(_) => return Err(CliError::new(
+            "The Solido instance uses the current version already, there is nothing to migrate.",
+        )
+        .into()),
+    };
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    // The validators move out of the Solido account, into a new account that
+    // is owned by the Solido program.
+    let validator_list_signer = Keypair::new();
+    let validator_list_size =
+        Validators::required_bytes(solido_v1.validators.maximum_entries as usize);
+    let validator_list_account_balance = config
+        .client
+        .get_minimum_balance_for_rent_exemption(validator_list_size)?;
+    config.sign_and_send_transaction(
+        &[system_instruction::create_account(
+            &config.signer.pubkey(),
+            &validator_list_signer.pubkey(),
+            validator_list_account_balance.0,
+            validator_list_size as u64,
+            opts.solido_program_id(),
+        )],
+        &[config.signer, &validator_list_signer],
+    )?;
+    eprintln!("Did send validator list creation.");
+
+    // The multisig signs as the manager, and it also pays for the additional
+    // rent if the Solido account needs to grow.
+    let instruction = lido::instruction::migrate_state(
+        opts.solido_program_id(),
+        &lido::instruction::MigrateStateMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            validator_list: validator_list_signer.pubkey(),
+            funder: multisig_address,
+        },
+    );
+    let proposal = propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )?;
+    Ok(MigrateStateOutput {
+        validator_list_address: validator_list_signer.pubkey(),
+        proposal,
+    })
+}
+
+pub fn command_set_deposit_limits(
+    config: &mut SnapshotConfig,
+    opts: &SetDepositLimitsOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_deposit_limits(
+        opts.solido_program_id(),
+        &lido::instruction::SetDepositLimitsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        *opts.max_total_sol(),
+        *opts.min_deposit_sol(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_max_directed_stake(
+    config: &mut SnapshotConfig,
+    opts: &SetMaxDirectedStakeOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_max_directed_stake(
+        opts.solido_program_id(),
+        &lido::instruction::SetMaxDirectedStakeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        *opts.max_directed_stake_bps(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// CLI entry point to deactivate a validator and unstake all of its stake at once.
+///
+/// The stake accounts are determined when the instruction is proposed, so the
+/// proposal fails if the validator's stake accounts change before it executes.
+pub fn command_emergency_unstake_validator(
+    config: &mut SnapshotConfig,
+    opts: &DeactivateValidatorOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+    let solido = config.client.get_solido(opts.solido_address())?;
+    let validators = config.client.get_validators(&solido.validator_list)?;
+    let validator = match validators.get(opts.validator_vote_account()) {
+        Ok(validator) => validator,
+        Err(_) => {
+            return Err(CliError::new("The validator is not part of this Solido instance.").into())
+        }
+    };
+
+    let stake_accounts: Vec<Pubkey> = validator
+        .entry
+        .stake_seeds
+        .into_iter()
+        .map(|seed| {
+            validator
+                .find_stake_account_address(
+                    opts.solido_program_id(),
+                    opts.solido_address(),
+                    seed,
+                    StakeType::Stake,
+                )
+                .0
+        })
+        .collect();
+
+    let instruction = lido::instruction::emergency_unstake_validator(
+        opts.solido_program_id(),
+        &lido::instruction::EmergencyUnstakeValidatorMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            validator_list: solido.validator_list,
+            validator_vote_account: *opts.validator_vote_account(),
+            stake_authority: solido
+                .get_stake_authority(opts.solido_program_id(), opts.solido_address())?,
+            stake_accounts,
+        },
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_reserve_buffer(
+    config: &mut SnapshotConfig,
+    opts: &SetReserveBufferOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let reserve_buffer = match (*opts.reserve_buffer_sol(), *opts.reserve_buffer_bps()) {
+        (amount, 0) => ReserveBuffer::Amount(amount),
+        (Lamports(0), bps) => ReserveBuffer::Bps(bps),
+        _ => {
+            return Err(CliError::new(
+                "Expected either --reserve-buffer-sol or --reserve-buffer-bps, not both.",
+            )
+            .into())
+        }
+    };
+
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_reserve_buffer(
+        opts.solido_program_id(),
+        &lido::instruction::SetReserveBufferMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        reserve_buffer,
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_withdrawal_fee(
+    config: &mut SnapshotConfig,
+    opts: &SetWithdrawalFeeOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_withdrawal_fee(
+        opts.solido_program_id(),
+        &lido::instruction::SetWithdrawalFeeMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+        },
+        *opts.withdrawal_fee_bps(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+pub fn command_set_role(
+    config: &mut SnapshotConfig,
+    opts: &SetRoleOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let instruction = lido::instruction::set_role(
+        opts.solido_program_id(),
+        &lido::instruction::SetRoleMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            new_authority: *opts.new_authority(),
+        },
+        *opts.role(),
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
+
+/// Format the `MAINTAINER_*` bits as a human-readable list of operation names.
+pub fn format_maintainer_permissions(permissions: u8) -> String {
+    let names: Vec<&str> = [
+        (MAINTAINER_STAKE_DEPOSIT, "stake deposit"),
+        (MAINTAINER_UNSTAKE, "unstake"),
+    ]
+    .iter()
+    .filter(|(bit, _)| permissions & bit != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+pub fn command_set_maintainer_permissions(
+    config: &mut SnapshotConfig,
+    opts: &SetMaintainerPermissionsOpts,
+) -> solido_cli_common::Result<ProposeInstructionOutput> {
+    let (multisig_address, _) =
+        get_multisig_program_address(opts.multisig_program_id(), opts.multisig_address());
+
+    let mut permissions = 0;
+    for (allow, bit) in [
+        (opts.allow_stake_deposit(), MAINTAINER_STAKE_DEPOSIT),
+        (opts.allow_unstake(), MAINTAINER_UNSTAKE),
+    ] {
+        if *allow {
+            permissions |= bit;
+        }
+    }
+
+    let instruction = lido::instruction::set_maintainer_permissions(
+        opts.solido_program_id(),
+        &lido::instruction::SetMaintainerPermissionsMeta {
+            lido: *opts.solido_address(),
+            manager: multisig_address,
+            maintainer: *opts.maintainer_address(),
+        },
+        permissions,
+    );
+    propose_instruction(
+        config,
+        opts.multisig_program_id(),
+        *opts.multisig_address(),
+        instruction,
+    )
+}
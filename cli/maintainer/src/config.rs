@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use std::{
+    num::ParseIntError,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -10,9 +11,11 @@ use clap::Parser;
 use serde::Deserialize;
 use serde_json::Value;
 use solana_sdk::pubkey::{ParsePubkeyError, Pubkey};
+use solana_sdk::signature::Signature;
 
 use anker::token::BLamports;
 use anker::wormhole::TerraAddress;
+use lido::state::{FeeRecipient, Role};
 use lido::token::Lamports;
 use lido::token::StLamports;
 use solido_cli_common::snapshot::OutputMode;
@@ -183,6 +186,34 @@ impl FromStr for PubkeyVec {
     }
 }
 
+/// Type to represent a list of fee recipients and their shares of the rewards.
+#[derive(Debug, Clone)]
+pub struct FeeRecipientVec(pub Vec<FeeRecipient>);
+/// Constructs a `FeeRecipientVec` from a string of the form
+/// `account:parts,account:parts,...`, where `account` is the stSOL account of
+/// the recipient, and `parts` its share of the rewards.
+impl FromStr for FeeRecipientVec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let recipients = s
+            .split(',')
+            .map(|recipient| {
+                let (account, parts) = recipient.split_once(':').ok_or_else(|| {
+                    format!("Expected <account>:<parts>, but got \"{}\".", recipient)
+                })?;
+                Ok(FeeRecipient {
+                    st_sol_account: Pubkey::from_str(account).map_err(|err| err.to_string())?,
+                    parts: parts
+                        .parse()
+                        .map_err(|err: ParseIntError| err.to_string())?,
+                })
+            })
+            .collect::<Result<Vec<FeeRecipient>, Self::Err>>()?;
+        Ok(FeeRecipientVec(recipients))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ConfigFile {
     pub values: Value,
@@ -307,6 +338,27 @@ cli_opt_struct! {
         /// Amount to deposit, in SOL, using . as decimal separator.
         #[clap(long, value_name = "sol")]
         amount_sol: Lamports,
+
+        /// Vote account of a validator to direct the deposit to. If not set,
+        /// the deposit is not directed to any validator.
+        #[clap(long, value_name = "address")]
+        preferred_validator_vote_account: Pubkey => Pubkey::default(),
+    }
+}
+
+cli_opt_struct! {
+    DepositStakeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Fully active stake account to deposit, the signer must be its staker and withdrawer.
+        #[clap(long, value_name = "address")]
+        stake_account: Pubkey,
     }
 }
 
@@ -323,6 +375,60 @@ cli_opt_struct! {
          /// Amount to withdraw in stSOL, using . as decimal separator.
          #[clap(long, value_name = "st_sol")]
          amount_st_sol: StLamports,
+
+         /// Vote account of the validator to withdraw from. If not set, we
+         /// withdraw from the validator with the most stake. If set, the
+         /// withdrawal may be charged an imbalance penalty.
+         #[clap(long, value_name = "address")]
+         validator_vote_account: Pubkey => Pubkey::default(),
+    }
+}
+
+cli_opt_struct! {
+    InstantUnstakeOpts {
+         /// Address of the Solido program.
+         #[clap(long, value_name = "address")]
+         solido_program_id: Pubkey,
+
+         /// Account that stores the data for this Solido instance.
+         #[clap(long, value_name = "address")]
+         solido_address: Pubkey,
+
+         /// Amount to unstake in stSOL, including the fee, using . as decimal separator.
+         #[clap(long, value_name = "st_sol")]
+         amount_st_sol: StLamports,
+    }
+}
+
+cli_opt_struct! {
+    RequestWithdrawalOpts {
+         /// Address of the Solido program.
+         #[clap(long, value_name = "address")]
+         solido_program_id: Pubkey,
+
+         /// Account that stores the data for this Solido instance.
+         #[clap(long, value_name = "address")]
+         solido_address: Pubkey,
+
+         /// Amount to withdraw in stSOL, using . as decimal separator.
+         #[clap(long, value_name = "st_sol")]
+         amount_st_sol: StLamports,
+    }
+}
+
+cli_opt_struct! {
+    ClaimWithdrawalOpts {
+         /// Address of the Solido program.
+         #[clap(long, value_name = "address")]
+         solido_program_id: Pubkey,
+
+         /// Account that stores the data for this Solido instance.
+         #[clap(long, value_name = "address")]
+         solido_address: Pubkey,
+
+         /// Withdrawal ticket created by 'request-withdrawal'.
+         #[clap(long, value_name = "address")]
+         withdrawal_ticket: Pubkey,
     }
 }
 
@@ -419,6 +525,18 @@ cli_opt_struct! {
    }
 }
 
+cli_opt_struct! {
+    ShowEventsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Signature of the transaction to decode the events of.
+        #[clap(long, value_name = "signature")]
+        transaction_signature: Signature,
+    }
+}
+
 cli_opt_struct! {
     ShowAnkerAuthoritiesOpts {
         /// The Solido instance, used to derive the Anker instance.
@@ -490,6 +608,454 @@ cli_opt_struct! {
     }
 }
 
+cli_opt_struct! {
+    SetInstantUnstakeFeeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Fee charged on instant unstakes, in basis points, in range [0, 10000]
+        #[clap(long, value_name = "bps")]
+        instant_unstake_fee_bps: u64,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetMinVoteCreditsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Minimum number of vote credits that validators must earn, 0 disables the check.
+        #[clap(long, value_name = "credits")]
+        min_vote_credits: u64,
+
+        /// Number of completed epochs to count vote credits over, in range [1, 64].
+        #[clap(long, value_name = "epochs")]
+        vote_credits_epochs: u64,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetValidatorWeightOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Address of the validator vote account.
+        #[clap(long, value_name = "address")]
+        validator_vote_account: Pubkey,
+
+        /// Relative share of the stake that the validator should receive.
+        #[clap(long, value_name = "weight")]
+        weight: u32,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ProposeNewManagerOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Address that becomes the manager once it accepts, usually a multisig program-derived address.
+        #[clap(long, value_name = "address")]
+        new_manager: Pubkey,
+
+        /// Multisig instance of the current manager.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    AcceptManagerOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance of the pending manager.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ChangeFeeRecipientsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// New stSOL accounts of the fee recipients, separated by commas.
+        ///
+        /// There must be one account for every fee recipient, in the order of
+        /// the recipients in the current reward distribution. The first
+        /// recipient is the treasury. The shares of the recipients are not
+        /// affected, use `change-reward-distribution` to change those.
+        #[clap(long, value_name = "address,address,...")]
+        fee_recipient_accounts: PubkeyVec,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ChangeRewardDistributionOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// The fee recipients and their shares of the rewards, separated by commas.
+        ///
+        /// Every recipient is an stSOL account and its share, separated by a
+        /// colon. The first recipient is the treasury, which also receives the
+        /// instant unstake fees.
+        #[clap(long, value_name = "address:int,address:int,...")]
+        fee_recipients: FeeRecipientVec,
+
+        /// Share of the rewards that goes to stSOL appreciation (the non-fee part).
+        #[clap(long, value_name = "int")]
+        st_sol_appreciation_share: u32,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetPausedOperationsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Whether to pause deposits.
+        #[clap(long, value_name = "bool")]
+        pause_deposit: bool => false,
+
+        /// Whether to pause withdrawals, instant unstakes and withdrawal tickets.
+        #[clap(long, value_name = "bool")]
+        pause_withdraw: bool => false,
+
+        /// Whether to pause staking from the reserve.
+        #[clap(long, value_name = "bool")]
+        pause_stake_deposit: bool => false,
+
+        /// Whether to pause unstaking.
+        #[clap(long, value_name = "bool")]
+        pause_unstake: bool => false,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    ResizeValidatorListOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// The new maximum number of validators that this Solido instance will support.
+        #[clap(long, value_name = "int")]
+        max_validators: u32,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    PreviewMigrateStateOpts {
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Account that will hold the validators after the migration, if it
+        /// exists already. Otherwise the preview shows the default address.
+        #[clap(long, value_name = "address")]
+        validator_list_address: Pubkey => Pubkey::default(),
+    }
+}
+
+cli_opt_struct! {
+    MigrateStateOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetDepositLimitsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Maximum amount of SOL under management, in SOL, using . as decimal
+        /// separator. 0 means no maximum.
+        #[clap(long, value_name = "sol")]
+        max_total_sol: Lamports,
+
+        /// Minimum amount per deposit, in SOL, using . as decimal separator.
+        /// 0 means no minimum.
+        #[clap(long, value_name = "sol")]
+        min_deposit_sol: Lamports,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetMaxDirectedStakeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Maximum directed stake per validator, in basis points of the SOL
+        /// under management. 0 disables directed stake.
+        #[clap(long, value_name = "bps")]
+        max_directed_stake_bps: u64,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetReserveBufferOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Amount of SOL to keep in the reserve, using . as decimal separator.
+        /// Cannot be combined with --reserve-buffer-bps.
+        #[clap(long, value_name = "sol")]
+        reserve_buffer_sol: Lamports => Lamports(0),
+
+        /// Fraction of the SOL under management to keep in the reserve, in
+        /// basis points. Cannot be combined with --reserve-buffer-sol.
+        #[clap(long, value_name = "bps")]
+        reserve_buffer_bps: u64 => 0,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetWithdrawalFeeOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Fee charged on withdrawals, in basis points, in range [0, 10000]
+        #[clap(long, value_name = "bps")]
+        withdrawal_fee_bps: u64,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetRoleOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Role to assign, one of 'validator-admin', 'fee-admin',
+        /// 'maintainer-admin' or 'emergency-guardian'.
+        #[clap(long, value_name = "role")]
+        role: Role,
+
+        /// Address that will hold the role, usually a multisig.
+        #[clap(long, value_name = "address")]
+        new_authority: Pubkey,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    SetMaintainerPermissionsOpts {
+        /// Address of the Solido program.
+        #[clap(long, value_name = "address")]
+        solido_program_id: Pubkey,
+
+        /// Account that stores the data for this Solido instance.
+        #[clap(long, value_name = "address")]
+        solido_address: Pubkey,
+
+        /// Maintainer whose permissions to set.
+        #[clap(long, value_name = "address")]
+        maintainer_address: Pubkey,
+
+        /// Whether the maintainer may stake from the reserve.
+        #[clap(long, value_name = "bool")]
+        allow_stake_deposit: bool => false,
+
+        /// Whether the maintainer may unstake.
+        #[clap(long, value_name = "bool")]
+        allow_unstake: bool => false,
+
+        /// Multisig instance.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long, value_name = "address")]
+        multisig_program_id: Pubkey,
+    }
+}
+
 // Multisig opts
 
 cli_opt_struct! {
@@ -946,3 +1512,55 @@ cli_opt_struct! {
         multisig_program_id: Pubkey,
     }
 }
+
+cli_opt_struct! {
+    AnkerProposeNewManagerOpts {
+        /// Address of the Anker instance.
+        #[clap(long, value_name = "address")]
+        anker_address: Pubkey,
+
+        /// Address that becomes the manager once it accepts, usually a multisig program-derived address.
+        #[clap(long, value_name = "address")]
+        new_manager: Pubkey,
+
+        /// Multisig instance of the current manager.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long)]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    AnkerAcceptManagerOpts {
+        /// Address of the Anker instance.
+        #[clap(long, value_name = "address")]
+        anker_address: Pubkey,
+
+        /// Multisig instance of the pending manager.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long)]
+        multisig_program_id: Pubkey,
+    }
+}
+
+cli_opt_struct! {
+    AnkerMigrateStateOpts {
+        /// Address of the Anker instance.
+        #[clap(long, value_name = "address")]
+        anker_address: Pubkey,
+
+        /// Multisig instance of the Solido manager.
+        #[clap(long, value_name = "address")]
+        multisig_address: Pubkey,
+
+        /// Address of the Multisig program.
+        #[clap(long)]
+        multisig_program_id: Pubkey,
+    }
+}
@@ -61,9 +61,15 @@ struct MaintenanceMetrics {
     /// Number of times we performed `DeactivateValidatorIfCommissionExceedsMax`.
     transactions_deactivate_validator_if_commission_exceeds_max: u64,
 
+    /// Number of times we performed `DeactivateValidatorIfDelinquent`.
+    transactions_deactivate_validator_if_delinquent: u64,
+
     /// Number of times we performed `Unstake` on an active validator for balancing purposes.
     transactions_unstake_from_active_validator: u64,
 
+    /// Number of times we performed `Unstake` to cover withdrawal tickets.
+    transactions_unstake_for_withdrawal_tickets: u64,
+
     /// Number of times we performed `SellRewards` on the Anker instance.
     transactions_sell_rewards: u64,
 
@@ -111,6 +117,8 @@ impl MaintenanceMetrics {
                         .with_label("operation", "RemoveValidator".to_string()),
                     Metric::new(self.transactions_unstake_from_active_validator)
                         .with_label("operation", "UnstakeFromActiveValidator".to_string()),
+                    Metric::new(self.transactions_unstake_for_withdrawal_tickets)
+                        .with_label("operation", "UnstakeForWithdrawalTickets".to_string()),
                     Metric::new(self.transactions_sell_rewards)
                         .with_label("operation", "SellRewards".to_string()),
                     Metric::new(self.transactions_fetch_pool_price)
@@ -120,6 +128,8 @@ impl MaintenanceMetrics {
                             "operation",
                             "DeactivateValidatorIfCommissionExceedsMax".to_string(),
                         ),
+                    Metric::new(self.transactions_deactivate_validator_if_delinquent)
+                        .with_label("operation", "DeactivateValidatorIfDelinquent".to_string()),
                 ],
             },
         )?;
@@ -146,9 +156,15 @@ impl MaintenanceMetrics {
             MaintenanceOutput::DeactivateValidatorIfCommissionExceedsMax { .. } => {
                 self.transactions_deactivate_validator_if_commission_exceeds_max += 1
             }
+            MaintenanceOutput::DeactivateValidatorIfDelinquent { .. } => {
+                self.transactions_deactivate_validator_if_delinquent += 1
+            }
             MaintenanceOutput::UnstakeFromActiveValidator { .. } => {
                 self.transactions_unstake_from_active_validator += 1
             }
+            MaintenanceOutput::UnstakeForWithdrawalTickets { .. } => {
+                self.transactions_unstake_for_withdrawal_tickets += 1
+            }
             MaintenanceOutput::SellRewards { .. } => self.transactions_sell_rewards += 1,
             MaintenanceOutput::FetchPoolPrice { .. } => self.transactions_fetch_pool_price += 1,
         }
@@ -311,7 +327,9 @@ impl<'a, 'b> Daemon<'a, 'b> {
             transactions_unstake_from_inactive_validator: 0,
             transactions_remove_validator: 0,
             transactions_deactivate_validator_if_commission_exceeds_max: 0,
+            transactions_deactivate_validator_if_delinquent: 0,
             transactions_unstake_from_active_validator: 0,
+            transactions_unstake_for_withdrawal_tickets: 0,
             transactions_sell_rewards: 0,
             transactions_fetch_pool_price: 0,
         };
@@ -20,10 +20,18 @@ use solido_cli_common::snapshot::{Config, OutputMode, SnapshotClient};
 use crate::commands_anker::AnkerOpts;
 use crate::commands_multisig::MultisigOpts;
 use crate::commands_solido::{
-    command_add_maintainer, command_add_validator, command_create_solido,
-    command_deactivate_validator, command_deactivate_validator_if_commission_exceeds_max,
-    command_deposit, command_remove_maintainer, command_set_max_commission_percentage,
-    command_show_solido, command_show_solido_authorities, command_withdraw,
+    command_accept_manager, command_add_maintainer, command_add_validator,
+    command_change_fee_recipients, command_change_reward_distribution, command_claim_withdrawal,
+    command_create_solido, command_deactivate_validator,
+    command_deactivate_validator_if_commission_exceeds_max, command_deposit, command_deposit_stake,
+    command_emergency_unstake_validator, command_instant_unstake, command_migrate_state,
+    command_preview_migrate_state, command_propose_new_manager, command_remove_maintainer,
+    command_request_withdrawal, command_resize_validator_list, command_set_deposit_limits,
+    command_set_instant_unstake_fee, command_set_maintainer_permissions,
+    command_set_max_commission_percentage, command_set_max_directed_stake,
+    command_set_min_vote_credits, command_set_paused_operations, command_set_reserve_buffer,
+    command_set_role, command_set_validator_weight, command_set_withdrawal_fee,
+    command_show_events, command_show_solido, command_show_solido_authorities, command_withdraw,
 };
 use crate::config::*;
 
@@ -154,6 +162,9 @@ REWARDS
     of the rewards (after validators take their commission) go to stSOL value
     appreciation, and 8% go to fees. Of those fees, 62.5% go to the treasury,
     and 37.5% goes to the developer.
+
+    The treasury and developer are the initial fee recipients. Use
+    `change-reward-distribution` to add or remove recipients later.
     ")]
     CreateSolido(CreateSolidoOpts),
 
@@ -163,6 +174,12 @@ REWARDS
     /// Deactivates a validator and initiates the removal process.
     DeactivateValidator(DeactivateValidatorOpts),
 
+    /// Deactivates a validator and unstakes all of its stake at once.
+    ///
+    /// Meant for a compromised validator. Once the stake is inactive,
+    /// updating the validator's balance withdraws it into the reserve.
+    EmergencyUnstakeValidator(DeactivateValidatorOpts),
+
     /// Deactivates a validator and initiates the removal process if
     /// validator exceeds maximum validation commission. Requires no permission.
     DeactivateValidatorIfCommissionExceedsMax(DeactivateValidatorIfCommissionExceedsMaxOpts),
@@ -179,20 +196,46 @@ REWARDS
     /// If the associated token account does not yet exist, it will be created.
     Deposit(DepositOpts),
 
+    /// Deposit an active stake account, receive stSOL in return.
+    ///
+    /// The stake account must be delegated to one of the validators of the
+    /// Solido instance. The recipient will be set to the associated token
+    /// account for the signer.
+    DepositStake(DepositStakeOpts),
+
     /// Withdraw stSOL, receive a delegated stake account in return.
     ///
     /// The amount of SOL is calculated and stored in the returned stake.
     Withdraw(WithdrawOpts),
 
+    /// Burn stSOL, receive SOL from the reserve in return, minus a fee.
+    ///
+    /// The fee is paid in stSOL to the treasury. The amount is limited by the
+    /// SOL available in the reserve.
+    InstantUnstake(InstantUnstakeOpts),
+
+    /// Burn stSOL, receive a withdrawal ticket in return.
+    ///
+    /// The ticket can be claimed for SOL with 'claim-withdrawal' in a later
+    /// epoch, once the maintainers have unstaked enough SOL.
+    RequestWithdrawal(RequestWithdrawalOpts),
+
+    /// Claim the SOL owed by a withdrawal ticket, and close the ticket.
+    ClaimWithdrawal(ClaimWithdrawalOpts),
+
     /// Show an instance of Solido in detail
     ShowSolido(ShowSolidoOpts),
 
     /// Show Solido authorities, even if the instance is not initialized.
     ///
     /// This is useful for testing, and when setting up a token mint ahead of
-    /// time, to be used later when initializing the Solido instance.
+    /// time, to be used later when initializing the Solido instance. Once the
+    /// instance exists, this also shows the manager and the holder of every role.
     ShowAuthorities(ShowSolidoAuthoritiesOpts),
 
+    /// Decode the structured events that Solido logged in a transaction.
+    ShowEvents(ShowEventsOpts),
+
     /// Execute one iteration of periodic maintenance logic.
     ///
     /// This is mainly useful for testing. To perform maintenance continuously,
@@ -212,8 +255,102 @@ REWARDS
     /// If validators exeed the threshold they will be deactivated by
     /// a maintainer.
     ///
-    /// Requires the manager to sign.
+    /// Requires the validator admin to sign.
     SetMaxValidationCommission(SetMaxValidationCommissionOpts),
+
+    /// Set the fee charged on instant unstakes, in basis points.
+    ///
+    /// Requires the fee admin to sign.
+    SetInstantUnstakeFee(SetInstantUnstakeFeeOpts),
+
+    /// Set the weight of a validator, which determines its share of the stake.
+    ///
+    /// Requires the validator admin to sign.
+    SetValidatorWeight(SetValidatorWeightOpts),
+
+    /// Set the minimum number of vote credits that validators must earn over
+    /// the last epochs. Validators that earn fewer will be deactivated by a
+    /// maintainer.
+    ///
+    /// Requires the validator admin to sign.
+    SetMinVoteCredits(SetMinVoteCreditsOpts),
+
+    /// Propose a new manager, that takes over once it accepts.
+    ///
+    /// Requires the manager to sign.
+    ProposeNewManager(ProposeNewManagerOpts),
+
+    /// Accept the manager role, after it was proposed.
+    ///
+    /// Requires the proposed new manager to sign.
+    AcceptManager(AcceptManagerOpts),
+
+    /// Change the stSOL accounts of the fee recipients, keeping their shares.
+    ///
+    /// Requires the fee admin to sign.
+    ChangeFeeRecipients(ChangeFeeRecipientsOpts),
+
+    /// Replace the list of fee recipients, their shares of the rewards, and
+    /// the share that goes to stSOL appreciation.
+    ///
+    /// Requires the fee admin to sign.
+    ChangeRewardDistribution(ChangeRewardDistributionOpts),
+
+    /// Set which operations are paused. Operations that are not passed as
+    /// `true` are unpaused.
+    ///
+    /// Requires the emergency guardian to sign.
+    SetPausedOperations(SetPausedOperationsOpts),
+
+    /// Grow the validator list so the instance can hold more validators. The
+    /// multisig pays for the additional rent.
+    ///
+    /// Requires the validator admin to sign.
+    ResizeValidatorList(ResizeValidatorListOpts),
+
+    /// Show the state of a Solido instance after migrating it to the current
+    /// version, as JSON, without changing anything.
+    PreviewMigrateState(PreviewMigrateStateOpts),
+
+    /// Create a validator list account, and propose migrating the Solido
+    /// state to the current version.
+    ///
+    /// The multisig pays for additional rent if the Solido account needs to
+    /// grow. Requires the manager to sign.
+    MigrateState(MigrateStateOpts),
+
+    /// Set the maximum amount of SOL under management, and the minimum amount
+    /// per deposit.
+    ///
+    /// Requires the fee admin to sign.
+    SetDepositLimits(SetDepositLimitsOpts),
+
+    /// Set the maximum directed stake per validator.
+    ///
+    /// Requires the validator admin to sign.
+    SetMaxDirectedStake(SetMaxDirectedStakeOpts),
+
+    /// Set the amount of SOL that is kept in the reserve rather than staked,
+    /// either in SOL or in basis points of the SOL under management.
+    ///
+    /// Requires the fee admin to sign.
+    SetReserveBuffer(SetReserveBufferOpts),
+
+    /// Set the fee charged on withdrawals, in basis points.
+    ///
+    /// Requires the fee admin to sign.
+    SetWithdrawalFee(SetWithdrawalFeeOpts),
+
+    /// Assign an administrative role to a new authority.
+    ///
+    /// Requires the manager to sign.
+    SetRole(SetRoleOpts),
+
+    /// Set which maintenance operations a maintainer is permitted to perform.
+    /// Operations that are not passed as `true` are not permitted.
+    ///
+    /// Requires the maintainer admin to sign.
+    SetMaintainerPermissions(SetMaintainerPermissionsOpts),
 }
 
 fn print_output<Output: fmt::Display + Serialize>(mode: OutputMode, output: &Output) {
@@ -300,6 +437,12 @@ fn main() {
             let output = result.ok_or_abort_with("Failed to deactivate validator.");
             print_output(output_mode, &output);
         }
+        SubCommand::EmergencyUnstakeValidator(cmd_opts) => {
+            let result = config
+                .with_snapshot(|config| command_emergency_unstake_validator(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to unstake validator.");
+            print_output(output_mode, &output);
+        }
         SubCommand::DeactivateValidatorIfCommissionExceedsMax(cmd_opts) => {
             let result = config.with_snapshot(|config| {
                 command_deactivate_validator_if_commission_exceeds_max(config, &cmd_opts)
@@ -324,17 +467,27 @@ fn main() {
             print_output(output_mode, &output);
         }
         SubCommand::ShowAuthorities(solido_pubkey) => {
-            let result =
-                config.with_snapshot(|_config| command_show_solido_authorities(&solido_pubkey));
+            let result = config
+                .with_snapshot(|config| command_show_solido_authorities(config, &solido_pubkey));
             let output =
                 result.ok_or_abort_with("Failed to show authorities for Solido public key.");
             print_output(output_mode, &output);
         }
+        SubCommand::ShowEvents(cmd_opts) => {
+            let result = config.with_snapshot(|config| command_show_events(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to show transaction events.");
+            print_output(output_mode, &output);
+        }
         SubCommand::Deposit(cmd_opts) => {
             let result = command_deposit(&mut config, &cmd_opts);
             let output = result.ok_or_abort_with("Failed to deposit.");
             print_output(output_mode, &output);
         }
+        SubCommand::DepositStake(cmd_opts) => {
+            let result = command_deposit_stake(&mut config, &cmd_opts);
+            let output = result.ok_or_abort_with("Failed to deposit stake account.");
+            print_output(output_mode, &output);
+        }
         SubCommand::Withdraw(cmd_opts) => {
             let result = command_withdraw(&mut config, &cmd_opts);
             let output = result.ok_or_abort_with("Failed to withdraw.");
@@ -346,6 +499,120 @@ fn main() {
             let output = result.ok_or_abort_with("Failed to set max validation commission.");
             print_output(output_mode, &output);
         }
+        SubCommand::InstantUnstake(cmd_opts) => {
+            let result = command_instant_unstake(&mut config, &cmd_opts);
+            let output = result.ok_or_abort_with("Failed to instantly unstake.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::RequestWithdrawal(cmd_opts) => {
+            let result = command_request_withdrawal(&mut config, &cmd_opts);
+            let output = result.ok_or_abort_with("Failed to request withdrawal.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ClaimWithdrawal(cmd_opts) => {
+            let result = command_claim_withdrawal(&mut config, &cmd_opts);
+            let output = result.ok_or_abort_with("Failed to claim withdrawal.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetInstantUnstakeFee(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_instant_unstake_fee(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set instant unstake fee.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetValidatorWeight(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_validator_weight(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set validator weight.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetMinVoteCredits(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_min_vote_credits(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set min vote credits.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ProposeNewManager(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_propose_new_manager(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to propose new manager.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::AcceptManager(cmd_opts) => {
+            let result = config.with_snapshot(|config| command_accept_manager(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to accept manager.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ChangeFeeRecipients(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_change_fee_recipients(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to change fee recipients.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ChangeRewardDistribution(cmd_opts) => {
+            let result = config
+                .with_snapshot(|config| command_change_reward_distribution(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to change reward distribution.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetPausedOperations(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_paused_operations(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set paused operations.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::ResizeValidatorList(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_resize_validator_list(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to resize the validator list.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::PreviewMigrateState(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_preview_migrate_state(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to preview the state migration.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::MigrateState(cmd_opts) => {
+            let result = config.with_snapshot(|config| command_migrate_state(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to migrate the state.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetDepositLimits(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_deposit_limits(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set deposit limits.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetMaxDirectedStake(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_max_directed_stake(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set max directed stake.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetReserveBuffer(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_reserve_buffer(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set reserve buffer.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetWithdrawalFee(cmd_opts) => {
+            let result =
+                config.with_snapshot(|config| command_set_withdrawal_fee(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set withdrawal fee.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetRole(cmd_opts) => {
+            let result = config.with_snapshot(|config| command_set_role(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set role.");
+            print_output(output_mode, &output);
+        }
+        SubCommand::SetMaintainerPermissions(cmd_opts) => {
+            let result = config
+                .with_snapshot(|config| command_set_maintainer_permissions(config, &cmd_opts));
+            let output = result.ok_or_abort_with("Failed to set maintainer permissions.");
+            print_output(output_mode, &output);
+        }
     }
 }
 
@@ -357,7 +624,7 @@ fn merge_with_config_and_environment(
         SubCommand::Anker(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::CreateSolido(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::AddValidator(opts) => opts.merge_with_config_and_environment(config_file),
-        SubCommand::DeactivateValidator(opts) => {
+        SubCommand::DeactivateValidator(opts) | SubCommand::EmergencyUnstakeValidator(opts) => {
             opts.merge_with_config_and_environment(config_file)
         }
         SubCommand::DeactivateValidatorIfCommissionExceedsMax(opts) => {
@@ -367,15 +634,53 @@ fn merge_with_config_and_environment(
             opts.merge_with_config_and_environment(config_file)
         }
         SubCommand::Deposit(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::DepositStake(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::Withdraw(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::ShowSolido(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::ShowAuthorities(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ShowEvents(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::PerformMaintenance(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::Multisig(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::RunMaintainer(opts) => opts.merge_with_config_and_environment(config_file),
         SubCommand::SetMaxValidationCommission(opts) => {
             opts.merge_with_config_and_environment(config_file)
         }
+        SubCommand::InstantUnstake(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::RequestWithdrawal(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ClaimWithdrawal(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetInstantUnstakeFee(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::SetValidatorWeight(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetMinVoteCredits(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ProposeNewManager(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::AcceptManager(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::ChangeFeeRecipients(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::ChangeRewardDistribution(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::SetPausedOperations(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::ResizeValidatorList(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::PreviewMigrateState(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::MigrateState(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetDepositLimits(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetMaxDirectedStake(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
+        SubCommand::SetReserveBuffer(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetWithdrawalFee(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetRole(opts) => opts.merge_with_config_and_environment(config_file),
+        SubCommand::SetMaintainerPermissions(opts) => {
+            opts.merge_with_config_and_environment(config_file)
+        }
     }
 }
 
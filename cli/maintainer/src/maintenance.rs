@@ -40,11 +40,15 @@ use lido::{
     processor::StakeType,
     stake_account::StakeAccount,
     stake_account::{deserialize_stake_account, StakeBalance},
-    state::{Lido, Validator},
+    state::{
+        Lido, Validator, Validators, MAINTAINER_STAKE_DEPOSIT, MAINTAINER_UNSTAKE,
+        PAUSE_STAKE_DEPOSIT, PAUSE_UNSTAKE,
+    },
     token::Lamports,
     token::Rational,
     token::StLamports,
     util::serialize_b58,
+    vote_state::{can_judge_vote_credits, get_credits_in_last_epochs},
     MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY, STAKE_AUTHORITY,
 };
 use spl_token_swap::curve::calculator::{CurveCalculator, TradeDirection};
@@ -84,6 +88,11 @@ pub enum MaintenanceOutput {
 
         #[serde(rename = "unstake_withdrawn_to_reserve_lamports")]
         unstake_withdrawn_to_reserve: Lamports,
+
+        /// Stake accounts that were deactivated by `EmergencyUnstakeValidator`
+        /// and are now inactive get withdrawn in full.
+        #[serde(rename = "stake_withdrawn_to_reserve_lamports")]
+        stake_withdrawn_to_reserve: Lamports,
     },
 
     MergeStake {
@@ -106,7 +115,13 @@ pub enum MaintenanceOutput {
         #[serde(serialize_with = "serialize_b58")]
         validator_vote_account: Pubkey,
     },
+    DeactivateValidatorIfDelinquent {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        vote_credits: u64,
+    },
     UnstakeFromActiveValidator(Unstake),
+    UnstakeForWithdrawalTickets(Unstake),
 
     FetchPoolPrice {
         #[serde(rename = "st_sol_price_in_micro_ust")]
@@ -174,6 +189,7 @@ impl fmt::Display for MaintenanceOutput {
                 validator_vote_account,
                 expected_difference_stake,
                 unstake_withdrawn_to_reserve,
+                stake_withdrawn_to_reserve,
             } => {
                 writeln!(f, "Withdrew inactive stake.")?;
                 writeln!(
@@ -191,6 +207,11 @@ impl fmt::Display for MaintenanceOutput {
                     "  Amount withdrawn from unstake: {}",
                     unstake_withdrawn_to_reserve
                 )?;
+                writeln!(
+                    f,
+                    "  Amount withdrawn from stake:   {}",
+                    stake_withdrawn_to_reserve
+                )?;
             }
             MaintenanceOutput::MergeStake {
                 validator_vote_account,
@@ -218,6 +239,9 @@ impl fmt::Display for MaintenanceOutput {
             MaintenanceOutput::UnstakeFromActiveValidator(unstake) => {
                 writeln!(f, "Unstake from active validator\n{}", unstake)?;
             }
+            MaintenanceOutput::UnstakeForWithdrawalTickets(unstake) => {
+                writeln!(f, "Unstake to cover withdrawal tickets\n{}", unstake)?;
+            }
             MaintenanceOutput::RemoveValidator {
                 validator_vote_account,
             } => {
@@ -234,6 +258,18 @@ impl fmt::Display for MaintenanceOutput {
                 )?;
                 writeln!(f, "  Validator vote account: {}", validator_vote_account)?;
             }
+            MaintenanceOutput::DeactivateValidatorIfDelinquent {
+                validator_vote_account,
+                vote_credits,
+            } => {
+                writeln!(f, "Check delinquency.")?;
+                writeln!(
+                    f,
+                    "Deactivate validator that earned fewer vote credits than we require."
+                )?;
+                writeln!(f, "  Validator vote account: {}", validator_vote_account)?;
+                writeln!(f, "  Vote credits:           {}", vote_credits)?;
+            }
             MaintenanceOutput::SellRewards { st_sol_amount } => {
                 writeln!(f, "Sell stSOL rewards")?;
                 writeln!(f, "  Amount:               {}", st_sol_amount)?;
@@ -294,10 +330,13 @@ pub struct SolidoState {
     pub solido_address: Pubkey,
     pub solido: Lido,
 
+    /// The validators, read from the `solido.validator_list` account.
+    pub validators: Validators,
+
     /// Anker parameters
     pub anker_state: Option<AnkerState>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the stake balance of the derived stake accounts from the begin seed until
     /// end seed.
     pub validator_stake_accounts: Vec<Vec<(Pubkey, StakeAccount)>>,
@@ -306,20 +345,20 @@ pub struct SolidoState {
     /// unstake accounts from the begin seed until end seed.
     pub validator_unstake_accounts: Vec<Vec<(Pubkey, StakeAccount)>>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the number of Lamports of the validator's vote account.
     pub validator_vote_account_balances: Vec<Lamports>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the deserialized vote account.
     pub validator_vote_accounts: Vec<VoteState>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the balance of the validator's identity account (which pays for the
     /// votes).
     pub validator_identity_account_balances: Vec<Lamports>,
 
-    /// For each validator, in the same order as in `solido.validators`, holds
+    /// For each validator, in the same order as in `validators`, holds
     /// the validator info (name and Keybase username).
     pub validator_infos: Vec<ValidatorInfo>,
 
@@ -457,6 +496,7 @@ impl SolidoState {
         stake_time: StakeTime,
     ) -> Result<SolidoState> {
         let solido = config.client.get_solido(solido_address)?;
+        let validators = config.client.get_validators(&solido.validator_list)?;
 
         let reserve_address = solido.get_reserve_account(solido_program_id, solido_address)?;
         let reserve_account = config.client.get_account(&reserve_address)?;
@@ -475,7 +515,7 @@ impl SolidoState {
         let mut validator_identity_account_balances = Vec::new();
         let mut validator_vote_accounts = Vec::new();
         let mut validator_infos = Vec::new();
-        for validator in solido.validators.entries.iter() {
+        for validator in validators.entries.iter() {
             let vote_account = config.client.get_account(&validator.pubkey)?;
             let vote_state = config.client.get_vote_account(&validator.pubkey)?;
             let validator_info = config.client.get_validator_info(&vote_state.node_pubkey)?;
@@ -537,6 +577,7 @@ impl SolidoState {
             solido_program_id: *solido_program_id,
             solido_address: *solido_address,
             solido,
+            validators,
             anker_state,
             validator_stake_accounts,
             validator_unstake_accounts,
@@ -567,31 +608,72 @@ impl SolidoState {
         )
     }
 
+    /// Return the amount of SOL in the reserve that is not owed to withdrawal
+    /// tickets, and can therefore be staked.
+    pub fn get_unowed_reserve(&self) -> Lamports {
+        Lamports(
+            self.get_effective_reserve()
+                .0
+                .saturating_sub(self.solido.withdrawal_tickets_owed.0),
+        )
+    }
+
+    /// Return the amount of SOL in the reserve that can be staked: the unowed
+    /// reserve minus the reserve buffer.
+    ///
+    /// Like `Lido::get_reserve_stakeable_balance`, a buffer in basis points is
+    /// a fraction of the SOL under management, based on the balances that we
+    /// track for the validators.
+    pub fn get_stakeable_reserve(&self) -> Lamports {
+        let validator_balance = self
+            .validators
+            .iter_entries()
+            .map(|v| v.stake_accounts_balance)
+            .sum::<lido::token::Result<Lamports>>()
+            .expect("Does not overflow, is at most the total SOL managed.");
+        let sol_under_management = Lamports(
+            (validator_balance + self.get_effective_reserve())
+                .expect("Does not overflow, is at most the total SOL in existence.")
+                .0
+                .saturating_sub(self.solido.withdrawal_tickets_owed.0),
+        );
+        let buffer = self
+            .solido
+            .reserve_buffer
+            .get_amount(sol_under_management)
+            .expect("Buffer is at most the SOL under management, does not overflow.");
+        Lamports(self.get_unowed_reserve().0.saturating_sub(buffer.0))
+    }
+
     /// If there is a deposit that can be staked, return the instructions to do so.
     pub fn try_stake_deposit(&self) -> Option<MaintenanceInstruction> {
+        self.confirm_not_paused(PAUSE_STAKE_DEPOSIT)?;
+        self.confirm_permitted(MAINTAINER_STAKE_DEPOSIT)?;
         self.confirm_should_stake_unstake_in_current_slot()?;
-        // We can only stake if there is an active validator. If there is none,
-        // this will short-circuit and return None.
-        self.solido.validators.iter_active().next()?;
+        // We can only stake if there is an active validator with nonzero weight.
+        // If there is none, this will short-circuit and return None.
+        self.validators
+            .iter_entries()
+            .find(|v| v.effective_weight() > 0)?;
 
-        let reserve_balance = self.get_effective_reserve();
+        let reserve_balance = self.get_stakeable_reserve();
 
         // If there is enough reserve, we can make a deposit. To keep the pool
         // balanced, find the validator furthest below its target balance, and
         // deposit to that validator. If we get here there is at least one active
         // validator, so computing the target balance should not fail.
         let undelegated_lamports = reserve_balance;
-        let targets =
-            lido::balance::get_target_balance(undelegated_lamports, &self.solido.validators)
-                .expect("Failed to compute target balance.");
+        let targets = lido::balance::get_target_balance(
+            undelegated_lamports,
+            &self.validators,
+            self.solido.max_directed_stake_bps,
+        )
+        .expect("Failed to compute target balance.");
 
         let (validator_index, amount_below_target) =
-            lido::balance::get_minimum_stake_validator_index_amount(
-                &self.solido.validators,
-                &targets[..],
-            );
+            lido::balance::get_minimum_stake_validator_index_amount(&self.validators, &targets[..]);
 
-        let validator = &self.solido.validators.entries[validator_index];
+        let validator = &self.validators.entries[validator_index];
 
         let (stake_account_end, _bump_seed_end) = validator.find_stake_account_address(
             &self.solido_program_id,
@@ -633,6 +715,7 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::StakeDepositAccountsMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 maintainer: self.maintainer_address,
                 reserve: self.reserve_address,
                 validator_vote_account: validator.pubkey,
@@ -671,6 +754,7 @@ impl SolidoState {
                 &self.solido_program_id,
                 &lido::instruction::UnstakeAccountsMeta {
                     lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
                     maintainer: self.maintainer_address,
                     validator_vote_account: validator.pubkey,
                     source_stake_account: *stake_account_address,
@@ -684,8 +768,9 @@ impl SolidoState {
 
     /// If there is a validator being deactivated, try to unstake its funds.
     pub fn try_unstake_from_inactive_validator(&self) -> Option<MaintenanceInstruction> {
+        self.confirm_not_paused(PAUSE_UNSTAKE)?;
+        self.confirm_permitted(MAINTAINER_UNSTAKE)?;
         for (validator, stake_accounts) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -707,6 +792,14 @@ impl SolidoState {
                 continue;
             }
             let (stake_account_address, stake_account_balance) = stake_accounts[0];
+            // After `EmergencyUnstakeValidator`, the stake accounts are
+            // deactivating already, and `UpdateStakeAccountBalance` withdraws
+            // them once they are inactive.
+            if stake_account_balance.balance.deactivating > Lamports(0)
+                || stake_account_balance.is_inactive()
+            {
+                continue;
+            }
             let (unstake_account, unstake_instruction) = self.get_unstake_instruction(
                 validator,
                 &stake_accounts[0],
@@ -731,7 +824,6 @@ impl SolidoState {
         &self,
     ) -> Option<MaintenanceInstruction> {
         for (validator, vote_state) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -752,6 +844,52 @@ impl SolidoState {
                 &self.solido_program_id,
                 &lido::instruction::DeactivateValidatorIfCommissionExceedsMaxMeta {
                     lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
+                    validator_vote_account_to_deactivate: validator.pubkey,
+                },
+            );
+            return Some(MaintenanceInstruction::new(instruction, task));
+        }
+        None
+    }
+
+    /// If there is a validator which earned too few vote credits, try to deactivate it.
+    pub fn try_deactivate_validator_if_delinquent(&self) -> Option<MaintenanceInstruction> {
+        for (validator, vote_state) in self
+            .validators
+            .entries
+            .iter()
+            .zip(self.validator_vote_accounts.iter())
+        {
+            // Validators that are too new to judge cannot be deactivated.
+            if !can_judge_vote_credits(
+                vote_state.epoch_credits(),
+                validator.entry.added_in_epoch,
+                self.clock.epoch,
+                self.solido.vote_credits_epochs,
+            ) {
+                continue;
+            }
+            let vote_credits = get_credits_in_last_epochs(
+                vote_state.epoch_credits(),
+                self.clock.epoch,
+                self.solido.vote_credits_epochs,
+            );
+            // We are only interested in active validators that are delinquent.
+            if !validator.entry.active || vote_credits >= self.solido.min_vote_credits {
+                continue;
+            }
+
+            let task = MaintenanceOutput::DeactivateValidatorIfDelinquent {
+                validator_vote_account: validator.pubkey,
+                vote_credits,
+            };
+
+            let instruction = lido::instruction::deactivate_validator_if_delinquent(
+                &self.solido_program_id,
+                &lido::instruction::DeactivateValidatorIfDelinquentMeta {
+                    lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
                     validator_vote_account_to_deactivate: validator.pubkey,
                 },
             );
@@ -762,7 +900,7 @@ impl SolidoState {
 
     /// If there is a validator ready for removal, try to remove it.
     pub fn try_remove_validator(&self) -> Option<MaintenanceInstruction> {
-        for validator in &self.solido.validators.entries {
+        for validator in &self.validators.entries {
             // We are only interested in validators that can be removed.
             if validator.entry.check_can_be_removed().is_err() {
                 continue;
@@ -775,6 +913,7 @@ impl SolidoState {
                 &self.solido_program_id,
                 &lido::instruction::RemoveValidatorMeta {
                     lido: self.solido_address,
+                    validator_list: self.solido.validator_list,
                     validator_vote_account_to_remove: validator.pubkey,
                 },
             );
@@ -917,6 +1056,7 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::MergeStakeMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 validator_vote_account: validator.pubkey,
                 from_stake,
                 to_stake,
@@ -929,7 +1069,6 @@ impl SolidoState {
     // stake accounts.  May return None or one instruction.
     pub fn try_merge_on_all_stakes(&self) -> Option<MaintenanceInstruction> {
         for (validator, stake_accounts) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -967,6 +1106,7 @@ impl SolidoState {
             &self.solido_program_id,
             &lido::instruction::UpdateExchangeRateAccountsMeta {
                 lido: self.solido_address,
+                validator_list: self.solido.validator_list,
                 reserve: self.reserve_address,
                 st_sol_mint: self.solido.st_sol_mint,
             },
@@ -983,7 +1123,7 @@ impl SolidoState {
     /// to claim these rewards back to the reserve account so they can be re-staked.
     pub fn try_update_stake_account_balance(&self) -> Option<MaintenanceInstruction> {
         for (validator, stake_accounts, unstake_accounts) in izip!(
-            self.solido.validators.entries.iter(),
+            self.validators.entries.iter(),
             self.validator_stake_accounts.iter(),
             self.validator_unstake_accounts.iter()
         ) {
@@ -1001,6 +1141,17 @@ impl SolidoState {
                     Lamports(0)
                 };
 
+            // Stake accounts that were deactivated in place can be removed
+            // once they are fully inactive, starting from the first one.
+            let mut removed_stake = Lamports(0);
+            for (_addr, stake_account) in stake_accounts.iter() {
+                if stake_account.balance.inactive != stake_account.balance.total() {
+                    break;
+                }
+                removed_stake = (removed_stake + stake_account.balance.total())
+                    .expect("Summing stake accounts should not overflow.");
+            }
+
             let mut removed_unstake = Lamports(0);
 
             for (_addr, unstake_account) in unstake_accounts.iter() {
@@ -1014,32 +1165,41 @@ impl SolidoState {
             // If the expected difference is less than some defined amount
             // of Lamports, we don't bother withdrawing. We try to do this
             // so we don't pay more for fees than the amount that we'll
-            // withdraw. Or if we have stake to remove from unstake accounts.
+            // withdraw. Or if we have stake to remove from stake or unstake
+            // accounts.
             if expected_difference_stake > SolidoState::MINIMUM_WITHDRAW_AMOUNT
+                || removed_stake > Lamports(0)
                 || removed_unstake > Lamports(0)
             {
                 // The balance of this validator is not up to date, try to update it.
-                let mut stake_account_addrs = Vec::new();
-                stake_account_addrs.extend(stake_accounts.iter().map(|(addr, _)| *addr));
-                stake_account_addrs.extend(unstake_accounts.iter().map(|(addr, _)| *addr));
+                // The fee recipients go first, followed by the stake and unstake accounts.
+                let mut accounts: Vec<Pubkey> = self
+                    .solido
+                    .reward_distribution
+                    .fee_recipients
+                    .iter()
+                    .map(|recipient| recipient.st_sol_account)
+                    .collect();
+                accounts.extend(stake_accounts.iter().map(|(addr, _)| *addr));
+                accounts.extend(unstake_accounts.iter().map(|(addr, _)| *addr));
                 let instruction = lido::instruction::update_stake_account_balance(
                     &self.solido_program_id,
                     &lido::instruction::UpdateStakeAccountBalanceMeta {
                         lido: self.solido_address,
+                        validator_list: self.solido.validator_list,
                         validator_vote_account: validator.pubkey,
-                        stake_accounts: stake_account_addrs,
+                        fee_recipients_and_stake_accounts: accounts,
                         reserve: self.reserve_address,
                         stake_authority: self.get_stake_authority(),
                         mint_authority: self.get_mint_authority(),
                         st_sol_mint: self.solido.st_sol_mint,
-                        treasury_st_sol_account: self.solido.fee_recipients.treasury_account,
-                        developer_st_sol_account: self.solido.fee_recipients.developer_account,
                     },
                 );
                 let task = MaintenanceOutput::WithdrawInactiveStake {
                     validator_vote_account: validator.pubkey,
                     expected_difference_stake,
                     unstake_withdrawn_to_reserve: removed_unstake,
+                    stake_withdrawn_to_reserve: removed_stake,
                 };
                 return Some(MaintenanceInstruction::new(instruction, task));
             }
@@ -1050,24 +1210,30 @@ impl SolidoState {
 
     /// Unstake from active validators in order to rebalance validators.
     pub fn try_unstake_from_active_validators(&self) -> Option<MaintenanceInstruction> {
+        self.confirm_not_paused(PAUSE_UNSTAKE)?;
+        self.confirm_permitted(MAINTAINER_UNSTAKE)?;
         self.confirm_should_stake_unstake_in_current_slot()?;
-        // Return None if there's no active validator to unstake from.
-        self.solido.validators.iter_active().next()?;
+        // Return None if there's no active validator with nonzero weight,
+        // then there are no targets to rebalance towards.
+        self.validators
+            .iter_entries()
+            .find(|v| v.effective_weight() > 0)?;
 
         // Get the target for each validator. Undelegated Lamports can be
         // sent when staking with validators.
         let targets = lido::balance::get_target_balance(
-            self.get_effective_reserve(),
-            &self.solido.validators,
+            self.get_stakeable_reserve(),
+            &self.validators,
+            self.solido.max_directed_stake_bps,
         )
         .expect("Failed to compute target balance.");
 
         let (validator_index, unstake_amount) = lido::balance::get_unstake_validator_index(
-            &self.solido.validators,
+            &self.validators,
             &targets,
             SolidoState::UNBALANCE_THRESHOLD,
         )?;
-        let validator = &self.solido.validators.entries[validator_index];
+        let validator = &self.validators.entries[validator_index];
         let stake_account = &self.validator_stake_accounts[validator_index][0];
 
         let maximum_unstake = (stake_account.1.balance.total() - MINIMUM_STAKE_ACCOUNT_BALANCE)
@@ -1096,6 +1262,66 @@ impl SolidoState {
         Some(MaintenanceInstruction::new(instruction, task))
     }
 
+    /// If the reserve, together with the SOL that is already being unstaked,
+    /// does not cover the open withdrawal tickets, unstake the difference.
+    pub fn try_unstake_for_withdrawal_tickets(&self) -> Option<MaintenanceInstruction> {
+        self.confirm_not_paused(PAUSE_UNSTAKE)?;
+        self.confirm_permitted(MAINTAINER_UNSTAKE)?;
+        self.confirm_should_stake_unstake_in_current_slot()?;
+
+        let being_unstaked = self
+            .validators
+            .iter_entries()
+            .map(|v| v.unstake_accounts_balance)
+            .sum::<lido::token::Result<Lamports>>()
+            .expect("Does not overflow, is at most the total SOL managed.");
+        let covered = (self.get_effective_reserve() + being_unstaked)
+            .expect("Does not overflow, is at most the total SOL in existence.");
+        let shortfall = (self.solido.withdrawal_tickets_owed - covered).ok()?;
+        if shortfall == Lamports(0) {
+            return None;
+        }
+
+        // Unstake from the validator with the most stake, that can still
+        // have another unstake account.
+        let (validator_index, validator) = self
+            .validators
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(i, validator)| {
+                !self.validator_stake_accounts[*i].is_empty()
+                    && validator.entry.unstake_seeds.end - validator.entry.unstake_seeds.begin
+                        < lido::MAXIMUM_UNSTAKE_ACCOUNTS
+            })
+            .max_by_key(|(_i, validator)| validator.entry.effective_stake_balance())?;
+        let stake_account = &self.validator_stake_accounts[validator_index][0];
+
+        let maximum_unstake =
+            (stake_account.1.balance.total() - MINIMUM_STAKE_ACCOUNT_BALANCE).ok()?;
+        // The unstake account needs at least the minimum stake account balance,
+        // so we may unstake a bit more than the shortfall. The excess returns to
+        // the reserve, and will be staked again.
+        let amount = shortfall
+            .max(MINIMUM_STAKE_ACCOUNT_BALANCE)
+            .min(maximum_unstake);
+        if amount < MINIMUM_STAKE_ACCOUNT_BALANCE {
+            return None;
+        }
+
+        let (unstake_account, instruction) =
+            self.get_unstake_instruction(validator, stake_account, amount);
+        let task = MaintenanceOutput::UnstakeForWithdrawalTickets(Unstake {
+            validator_vote_account: validator.pubkey,
+            from_stake_account: stake_account.0,
+            to_unstake_account: unstake_account,
+            from_stake_seed: validator.entry.stake_seeds.begin,
+            to_unstake_seed: validator.entry.unstake_seeds.end,
+            amount,
+        });
+        Some(MaintenanceInstruction::new(instruction, task))
+    }
+
     /// Write metrics about the current Solido instance in Prometheus format.
     pub fn write_prometheus<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
         use solido_cli_common::prometheus::{
@@ -1210,7 +1436,6 @@ impl SolidoState {
             ),
             info,
         ) in self
-            .solido
             .validators
             .entries
             .iter()
@@ -1537,6 +1762,27 @@ impl SolidoState {
         }
     }
 
+    /// Return None if the manager paused the given `PAUSE_*` operation, because
+    /// the transaction would fail anyway.
+    pub fn confirm_not_paused(&self, operation: u8) -> Option<()> {
+        if self.solido.is_paused(operation) {
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    /// Return None if the maintainer admin did not permit us to perform the
+    /// given `MAINTAINER_*` operation, because the transaction would fail
+    /// anyway. If we are not a maintainer at all, we leave it to the on-chain
+    /// program to reject us.
+    pub fn confirm_permitted(&self, operation: u8) -> Option<()> {
+        match self.solido.maintainers.get(&self.maintainer_address) {
+            Ok(maintainer) if maintainer.entry & operation == 0 => None,
+            _ => Some(()),
+        }
+    }
+
     /// Return None if we observe we moved past `1 -
     /// SolidoState::END_OF_EPOCH_THRESHOLD`%. Return Some(()) if the above
     /// condition fails or `self.stake_unstake_any_time` is set to
@@ -1616,6 +1862,10 @@ pub fn try_perform_maintenance(
         // Same for updating the validator balance.
         .or_else(|| state.try_update_stake_account_balance())
         .or_else(|| state.try_deactivate_validator_if_commission_exceeds_max())
+        .or_else(|| state.try_deactivate_validator_if_delinquent())
+        // Covering withdrawal tickets goes before staking, such that we do not
+        // stake SOL that we are about to need.
+        .or_else(|| state.try_unstake_for_withdrawal_tickets())
         .or_else(|| state.try_stake_deposit())
         .or_else(|| state.try_unstake_from_active_validators())
         .or_else(|| state.try_remove_validator())
@@ -1660,6 +1910,7 @@ pub fn run_perform_maintenance(
 mod test {
 
     use super::*;
+    use lido::state::{ReserveBuffer, MAINTAINER_ALL};
 
     /// Produce a new state with `default` Solido instance in it, and random pubkeys.
     fn new_empty_solido() -> SolidoState {
@@ -1668,6 +1919,7 @@ mod test {
             solido_program_id: Pubkey::new_unique(),
             solido_address: Pubkey::new_unique(),
             solido: Lido::default(),
+            validators: Validators::default(),
             anker_state: Some(AnkerState::default()),
             validator_stake_accounts: vec![],
             validator_unstake_accounts: vec![],
@@ -1704,9 +1956,8 @@ mod test {
         let mut state = new_empty_solido();
 
         // Add a validators, without any stake accounts yet.
-        state.solido.validators.maximum_entries = 1;
+        state.validators.maximum_entries = 1;
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new())
             .unwrap();
@@ -1727,6 +1978,99 @@ mod test {
         assert!(state.try_stake_deposit().is_some());
     }
 
+    #[test]
+    fn stake_deposit_does_not_stake_sol_owed_to_withdrawal_tickets() {
+        let mut state = new_empty_solido();
+
+        state.validators.maximum_entries = 1;
+        state
+            .validators
+            .add(Pubkey::new_unique(), Validator::new())
+            .unwrap();
+        state.validator_stake_accounts.push(vec![]);
+        state.reserve_account.lamports += 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
+
+        // All of the reserve is owed to tickets, so there is nothing to stake,
+        // but also nothing to unstake.
+        state.solido.withdrawal_tickets_owed = (MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap();
+        assert_eq!(state.try_stake_deposit(), None);
+        assert_eq!(state.try_unstake_for_withdrawal_tickets(), None);
+
+        // Once the tickets are claimed, we can stake again.
+        state.solido.withdrawal_tickets_owed = Lamports(0);
+        assert!(state.try_stake_deposit().is_some());
+    }
+
+    #[test]
+    fn stake_deposit_leaves_the_reserve_buffer() {
+        let mut state = new_empty_solido();
+
+        state.validators.maximum_entries = 1;
+        state
+            .validators
+            .add(Pubkey::new_unique(), Validator::new())
+            .unwrap();
+        state.validator_stake_accounts.push(vec![]);
+        state.reserve_account.lamports += 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
+
+        // With 60% of the SOL under management as buffer, what is left is not
+        // enough for a stake account.
+        state.solido.reserve_buffer = ReserveBuffer::Bps(6_000);
+        assert_eq!(state.try_stake_deposit(), None);
+
+        let buffer = (MINIMUM_STAKE_ACCOUNT_BALANCE + Lamports(1)).unwrap();
+        state.solido.reserve_buffer = ReserveBuffer::Amount(buffer);
+        assert_eq!(state.try_stake_deposit(), None);
+
+        // Without a buffer, we can stake again.
+        state.solido.reserve_buffer = ReserveBuffer::Amount(Lamports(0));
+        assert!(state.try_stake_deposit().is_some());
+    }
+
+    #[test]
+    fn stake_deposit_is_not_proposed_while_paused() {
+        let mut state = new_empty_solido();
+
+        state.validators.maximum_entries = 1;
+        state
+            .validators
+            .add(Pubkey::new_unique(), Validator::new())
+            .unwrap();
+        state.validator_stake_accounts.push(vec![]);
+        state.reserve_account.lamports += 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
+
+        state.solido.paused_operations = PAUSE_STAKE_DEPOSIT;
+        assert_eq!(state.try_stake_deposit(), None);
+
+        // Pausing other operations does not affect staking.
+        state.solido.paused_operations = PAUSE_UNSTAKE;
+        assert!(state.try_stake_deposit().is_some());
+    }
+
+    #[test]
+    fn stake_deposit_is_not_proposed_without_permission() {
+        let mut state = new_empty_solido();
+
+        state.validators.maximum_entries = 1;
+        state
+            .validators
+            .add(Pubkey::new_unique(), Validator::new())
+            .unwrap();
+        state.validator_stake_accounts.push(vec![]);
+        state.reserve_account.lamports += 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
+
+        state.solido.maintainers.maximum_entries = 1;
+        state
+            .solido
+            .maintainers
+            .add(state.maintainer_address, MAINTAINER_UNSTAKE)
+            .unwrap();
+        assert_eq!(state.try_stake_deposit(), None);
+
+        state.solido.maintainers.entries[0].entry |= MAINTAINER_STAKE_DEPOSIT;
+        assert!(state.try_stake_deposit().is_some());
+    }
+
     #[test]
     fn stake_deposit_splits_evenly_if_possible() {
         use std::ops::Add;
@@ -1734,14 +2078,12 @@ mod test {
         let mut state = new_empty_solido();
 
         // Add two validators, both without any stake account yet.
-        state.solido.validators.maximum_entries = 2;
+        state.validators.maximum_entries = 2;
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new())
             .unwrap();
         state
-            .solido
             .validators
             .add(Pubkey::new_unique(), Validator::new())
             .unwrap();
@@ -1752,7 +2094,7 @@ mod test {
         // balance.
         state.reserve_account.lamports += 4 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
 
-        let stake_account_0 = state.solido.validators.entries[0].find_stake_account_address(
+        let stake_account_0 = state.validators.entries[0].find_stake_account_address(
             &state.solido_program_id,
             &state.solido_address,
             0,
@@ -1763,13 +2105,13 @@ mod test {
         assert_eq!(
             state.try_stake_deposit().unwrap().output,
             MaintenanceOutput::StakeDeposit {
-                validator_vote_account: state.solido.validators.entries[0].pubkey,
+                validator_vote_account: state.validators.entries[0].pubkey,
                 amount: (MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap(),
                 stake_account: stake_account_0.0,
             }
         );
 
-        let stake_account_1 = state.solido.validators.entries[1].find_stake_account_address(
+        let stake_account_1 = state.validators.entries[1].find_stake_account_address(
             &state.solido_program_id,
             &state.solido_address,
             0,
@@ -1778,7 +2120,7 @@ mod test {
 
         // Pretend that the amount was actually staked.
         state.reserve_account.lamports -= 2 * MINIMUM_STAKE_ACCOUNT_BALANCE.0;
-        let validator = &mut state.solido.validators.entries[0].entry;
+        let validator = &mut state.validators.entries[0].entry;
         validator.stake_accounts_balance = validator
             .stake_accounts_balance
             .add((MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap())
@@ -1789,7 +2131,7 @@ mod test {
         assert_eq!(
             state.try_stake_deposit().unwrap().output,
             MaintenanceOutput::StakeDeposit {
-                validator_vote_account: state.solido.validators.entries[1].pubkey,
+                validator_vote_account: state.validators.entries[1].pubkey,
                 amount: (MINIMUM_STAKE_ACCOUNT_BALANCE * 2).unwrap(),
                 stake_account: stake_account_1.0,
             }
@@ -1805,7 +2147,7 @@ mod test {
                 state
                     .solido
                     .maintainers
-                    .add(Pubkey::new_unique(), ())
+                    .add(Pubkey::new_unique(), MAINTAINER_ALL)
                     .unwrap();
             }
 
@@ -1861,12 +2203,54 @@ mod test {
         }
     }
 
+    #[test]
+    fn deactivate_validator_if_delinquent_checks_vote_credits() {
+        let mut state = new_empty_solido();
+        state.clock.epoch = 5;
+        state.solido.min_vote_credits = 1;
+        state.solido.vote_credits_epochs = 1;
+
+        state.validators.maximum_entries = 1;
+        let validator = Validator {
+            added_in_epoch: 5,
+            ..Validator::new()
+        };
+        state
+            .validators
+            .add(Pubkey::new_unique(), validator)
+            .unwrap();
+        state.validator_vote_accounts.push(VoteState::default());
+
+        // The validator has no credits history yet, and it was added in the
+        // current epoch, so it is too new to judge.
+        assert_eq!(state.try_deactivate_validator_if_delinquent(), None);
+
+        // Credits from before the last completed epoch do not count.
+        state.validator_vote_accounts[0].increment_credits(3);
+        assert!(state.try_deactivate_validator_if_delinquent().is_some());
+
+        // But once the validator has credits in the last completed epoch, it's fine.
+        state.validator_vote_accounts[0].increment_credits(4);
+        assert_eq!(state.try_deactivate_validator_if_delinquent(), None);
+
+        // Without any history, the validator is delinquent once it has been in
+        // the list for long enough.
+        state.validator_vote_accounts[0] = VoteState::default();
+        assert_eq!(state.try_deactivate_validator_if_delinquent(), None);
+        state.clock.epoch = 6;
+        assert!(state.try_deactivate_validator_if_delinquent().is_some());
+    }
+
     #[test]
     fn next_maintainer_duty_returns_slot_greater_than_current_slot() {
         let mut state = new_empty_solido();
         let maintainer = Pubkey::new_unique();
         state.solido.maintainers.maximum_entries = 1;
-        state.solido.maintainers.add(maintainer, ()).unwrap();
+        state
+            .solido
+            .maintainers
+            .add(maintainer, MAINTAINER_ALL)
+            .unwrap();
 
         for _ in 0..10 {
             let next_slot = state.get_next_maintainer_duty_slot(&maintainer).unwrap();
@@ -3,6 +3,10 @@
 
 //! A type that stores a map (dictionary) from public key to some value `T`.
 
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::Serialize;
 use solana_program::pubkey::Pubkey;
@@ -162,6 +166,117 @@ impl<'a, T: 'a> std::iter::Iterator for IterEntriesMut<'a, T> {
     }
 }
 
+/// A view of a Borsh-serialized `AccountMap` that reads and writes individual
+/// entries in place, without deserializing the entire map.
+///
+/// Borsh encodes the map as a `u32` length, followed by the entries, followed
+/// by `maximum_entries`. Because entries have a constant size, entry `i` is at
+/// a fixed offset, and we can decode only the entries we need. The view cannot
+/// add or remove entries, use `AccountMap` for that.
+pub struct AccountMapView<T, D> {
+    data: D,
+    len: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<T, D> AccountMapView<T, D>
+where
+    T: BorshDeserialize + EntryConstantSize,
+    D: Deref<Target = [u8]>,
+{
+    const ENTRY_SIZE: usize = std::mem::size_of::<Pubkey>() + T::SIZE;
+
+    /// Create a view over the serialized map in `data`.
+    ///
+    /// Fails if `data` is too small to hold the number of entries that the
+    /// length prefix claims.
+    pub fn new(data: D) -> Result<Self, LidoError> {
+        if data.len() < 4 {
+            return Err(LidoError::InvalidAccountInfo);
+        }
+        let len = u32::from_le_bytes(data[..4].try_into().expect("Slice has length 4.")) as usize;
+        // The entries are followed by the u32 `maximum_entries`.
+        let required_len = len
+            .checked_mul(Self::ENTRY_SIZE)
+            .and_then(|n| n.checked_add(8))
+            .ok_or(LidoError::InvalidAccountInfo)?;
+        if data.len() < required_len {
+            return Err(LidoError::InvalidAccountInfo);
+        }
+        Ok(AccountMapView {
+            data,
+            len,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn maximum_entries(&self) -> u32 {
+        let offset = self.entry_offset(self.len);
+        let bytes = &self.data[offset..offset + 4];
+        u32::from_le_bytes(bytes.try_into().expect("Slice has length 4."))
+    }
+
+    fn entry_offset(&self, index: usize) -> usize {
+        4 + index * Self::ENTRY_SIZE
+    }
+
+    /// Return the index of the entry for `address`.
+    pub fn find(&self, address: &Pubkey) -> Result<usize, LidoError> {
+        (0..self.len)
+            .find(|&i| {
+                let offset = self.entry_offset(i);
+                &self.data[offset..offset + std::mem::size_of::<Pubkey>()] == address.as_ref()
+            })
+            .ok_or(LidoError::InvalidAccountMember)
+    }
+
+    /// Decode the entry at `index`.
+    ///
+    /// Panics if the index is out of bounds. Fails if the entry is not valid
+    /// Borsh.
+    pub fn get(&self, index: usize) -> Result<PubkeyAndEntry<T>, LidoError> {
+        assert!(index < self.len, "Index out of bounds.");
+        let offset = self.entry_offset(index);
+        PubkeyAndEntry::try_from_slice(&self.data[offset..offset + Self::ENTRY_SIZE])
+            .map_err(|_| LidoError::InvalidAccountInfo)
+    }
+
+    /// Return the index of the entry for `address`, and the decoded entry.
+    pub fn get_by_pubkey(&self, address: &Pubkey) -> Result<(usize, PubkeyAndEntry<T>), LidoError> {
+        let index = self.find(address)?;
+        Ok((index, self.get(index)?))
+    }
+
+    /// Decode the entries one by one.
+    pub fn iter(&self) -> impl Iterator<Item = Result<PubkeyAndEntry<T>, LidoError>> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+}
+
+impl<T, D> AccountMapView<T, D>
+where
+    T: BorshSerialize + BorshDeserialize + EntryConstantSize,
+    D: DerefMut<Target = [u8]>,
+{
+    /// Overwrite the value of the entry at `index`, the key stays the same.
+    pub fn set(&mut self, index: usize, value: &T) {
+        assert!(index < self.len, "Index out of bounds.");
+        let offset = self.entry_offset(index) + std::mem::size_of::<Pubkey>();
+        let mut buffer = &mut self.data[offset..offset + T::SIZE];
+        value
+            .serialize(&mut buffer)
+            .expect("Entries have a constant size, so they fit.");
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -170,6 +285,10 @@ mod test {
         const SIZE: usize = 4;
     }
 
+    impl EntryConstantSize for bool {
+        const SIZE: usize = 1;
+    }
+
     #[test]
     fn test_account_map_limit() {
         let mut map = AccountMap::new(1);
@@ -218,4 +337,76 @@ mod test {
             assert_eq!(*entry, 2);
         }
     }
+
+    #[test]
+    fn test_account_map_view_matches_borsh() {
+        let mut map: AccountMap<u32> = AccountMap::new(3);
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        map.add(keys[0], 10).unwrap();
+        map.add(keys[1], 11).unwrap();
+
+        // The account has room for more entries than are in use.
+        let mut data = vec![0_u8; AccountMap::<u32>::required_bytes(3)];
+        BorshSerialize::serialize(&map, &mut &mut data[..]).unwrap();
+
+        let view = AccountMapView::<u32, _>::new(&data[..]).unwrap();
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.maximum_entries(), 3);
+        assert_eq!(view.find(&keys[1]), Ok(1));
+        assert_eq!(
+            view.find(&Pubkey::new_unique()),
+            Err(LidoError::InvalidAccountMember)
+        );
+        assert_eq!(
+            view.iter().collect::<Result<Vec<_>, _>>(),
+            Ok(map.entries.clone())
+        );
+
+        let mut view = AccountMapView::<u32, _>::new(&mut data[..]).unwrap();
+        view.set(1, &12);
+        assert_eq!(view.get_by_pubkey(&keys[1]).unwrap().1.entry, 12);
+
+        map.get_mut(&keys[1]).unwrap().entry = 12;
+        let decoded: AccountMap<u32> = AccountMap::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(decoded, map);
+    }
+
+    #[test]
+    fn test_account_map_view_rejects_truncated_data() {
+        let mut map: AccountMap<u32> = AccountMap::new(2);
+        map.add(Pubkey::new_unique(), 0).unwrap();
+        map.add(Pubkey::new_unique(), 1).unwrap();
+        let data = map.try_to_vec().unwrap();
+
+        assert!(AccountMapView::<u32, _>::new(&data[..]).is_ok());
+        assert_eq!(
+            AccountMapView::<u32, _>::new(&data[..data.len() - 1]).err(),
+            Some(LidoError::InvalidAccountInfo)
+        );
+    }
+
+    #[test]
+    fn test_account_map_view_rejects_malformed_entries() {
+        let mut map: AccountMap<bool> = AccountMap::new(2);
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        map.add(keys[0], true).unwrap();
+        map.add(keys[1], false).unwrap();
+        let mut data = map.try_to_vec().unwrap();
+
+        // Borsh only accepts 0 and 1 for a bool, corrupt the second entry.
+        let offset = 4 + 2 * std::mem::size_of::<Pubkey>() + 1;
+        data[offset] = 2;
+
+        let view = AccountMapView::<bool, _>::new(&data[..]).unwrap();
+        assert_eq!(view.get(0).map(|pe| pe.entry), Ok(true));
+        assert_eq!(view.get(1), Err(LidoError::InvalidAccountInfo));
+        assert_eq!(
+            view.get_by_pubkey(&keys[1]),
+            Err(LidoError::InvalidAccountInfo)
+        );
+        assert_eq!(
+            view.iter().collect::<Result<Vec<_>, _>>(),
+            Err(LidoError::InvalidAccountInfo)
+        );
+    }
 }
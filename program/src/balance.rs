@@ -10,17 +10,21 @@ use crate::state::{Validator, Validators};
 use crate::{
     error::LidoError,
     token,
-    token::{Lamports, Rational},
+    token::{ArithmeticError, Lamports, Rational},
 };
 
 /// Compute the ideal stake balance for each validator.
 ///
 /// The validator order in the result is the same as in `current_balance`.
 ///
-/// This function targets a uniform distribution over all active validators.
+/// This function targets a distribution over all active validators that is
+/// proportional to their weight. On top of that, every active validator gets
+/// its directed stake, limited to `max_directed_stake_bps` of the total. The
+/// directed stake is taken off the total before distributing it by weight.
 pub fn get_target_balance(
     undelegated_lamports: Lamports,
     validators: &Validators,
+    max_directed_stake_bps: u64,
 ) -> Result<Vec<Lamports>, LidoError> {
     let total_delegated_lamports: token::Result<Lamports> = validators
         .iter_entries()
@@ -30,32 +34,75 @@ pub fn get_target_balance(
     let total_lamports = total_delegated_lamports.and_then(|t| t + undelegated_lamports)?;
 
     // We only want to target validators that are not in the process of being
-    // removed.
-    let num_active_validators = validators.iter_active().count() as u64;
+    // removed, and that have a nonzero weight. The sum of u32 weights cannot
+    // overflow a u64 for any number of validators that fits in an account.
+    let total_weight: u64 = validators
+        .iter_entries()
+        .map(|v| v.effective_weight())
+        .sum();
 
-    // No active validators.
-    if num_active_validators == 0 {
+    // No active validators that we could stake with.
+    if total_weight == 0 {
         return Err(LidoError::NoActiveValidators);
     }
 
-    let lamports_per_validator = total_lamports
-        .mul(Rational {
-            numerator: 1,
-            denominator: num_active_validators,
-        })
-        .expect("Does not divide by zero because `num_active_validators != 0`");
-
-    // Target an uniform distribution.
-    let mut target_balance: Vec<Lamports> = validators
+    let max_directed_stake = total_lamports.mul(Rational {
+        numerator: max_directed_stake_bps,
+        denominator: 10_000,
+    })?;
+    let mut directed_stake: Vec<Lamports> = validators
         .iter_entries()
         .map(|validator| {
-            if validator.active {
-                lamports_per_validator
+            if validator.effective_weight() > 0 {
+                std::cmp::min(validator.directed_stake, max_directed_stake)
             } else {
                 Lamports(0)
             }
         })
         .collect();
+    let mut total_directed_stake = directed_stake
+        .iter()
+        .cloned()
+        .sum::<token::Result<Lamports>>()?;
+
+    // Directed stake is not reduced by withdrawals from the reserve, so after
+    // many of those, the directed stake can exceed what there is to
+    // distribute. In that case, scale it down so it fits.
+    if total_directed_stake > total_lamports {
+        for stake in directed_stake.iter_mut() {
+            *stake = stake
+                .mul(Rational {
+                    numerator: total_lamports.0,
+                    denominator: total_directed_stake.0,
+                })
+                .expect("Does not overflow because total_lamports < total_directed_stake.");
+        }
+        total_directed_stake = directed_stake
+            .iter()
+            .cloned()
+            .sum::<token::Result<Lamports>>()
+            .expect("Does not overflow, is at most total_lamports.");
+    }
+
+    let undirected_lamports = (total_lamports - total_directed_stake)
+        .expect("Does not underflow because we scaled down the directed stake.");
+
+    // Target a distribution proportional to the weights, on top of the directed stake.
+    let mut target_balance: Vec<Lamports> = validators
+        .iter_entries()
+        .zip(directed_stake)
+        .map(|(validator, directed)| {
+            let undirected = undirected_lamports
+                .mul(Rational {
+                    numerator: validator.effective_weight(),
+                    denominator: total_weight,
+                })
+                .expect("Does not overflow because weight <= total_weight, and total_weight != 0.");
+            (directed + undirected).expect(
+                "Does not overflow because the sum over all validators is at most total_lamports.",
+            )
+        })
+        .collect();
 
     // The total lamports to distribute may be slightly larger than the total
     // lamports we distributed so far, because we round down.
@@ -68,20 +115,25 @@ pub fn get_target_balance(
     let mut remainder = (total_lamports - total_lamports_distributed)
         .expect("Does not underflow because we distribute at most total_lamports.");
 
-    assert!(remainder.0 < num_active_validators);
-
-    // Distribute the remainder among the first few active validators, give them
-    // one Lamport each. This does mean that the validators early in the list
-    // are in a more beneficial position because their stake target is one
-    // Lamport higher, but to put that number into perspective, the transaction
-    // fee per signature is 10k Lamports at the time of writing. Also, there is
-    // a minimum amount we can stake, so in practice, validators will never be
-    // as close to their target that the one Lamport matters anyway.
+    // We lose less than one Lamport to rounding per validator with nonzero weight.
+    let num_weighted_validators = validators
+        .iter_entries()
+        .filter(|v| v.effective_weight() > 0)
+        .count() as u64;
+    assert!(remainder.0 < num_weighted_validators);
+
+    // Distribute the remainder among the first few validators with nonzero
+    // weight, give them one Lamport each. This does mean that the validators
+    // early in the list are in a more beneficial position because their stake
+    // target is one Lamport higher, but to put that number into perspective,
+    // the transaction fee per signature is 10k Lamports at the time of writing.
+    // Also, there is a minimum amount we can stake, so in practice, validators
+    // will never be as close to their target that the one Lamport matters anyway.
     for (target, validator) in target_balance.iter_mut().zip(validators.iter_entries()) {
         if remainder == Lamports(0) {
             break;
         }
-        if validator.active {
+        if validator.effective_weight() > 0 {
             *target = (*target + Lamports(1)).expect(
                 "Does not overflow because per-validator balance is at most total_lamports.",
             );
@@ -103,10 +155,11 @@ pub fn get_target_balance(
 }
 
 /// Get the index of the validator to unstake from, if we need to unstake at all.
-
+///
 /// If any validator is more than threshold away from its target, this function
 /// will try to unstake, and return the index of the validator where unstaking
-/// will have the largest impact.
+/// will have the largest impact. The targets are proportional to the validator
+/// weights, see `get_target_balance`.
 pub fn get_unstake_validator_index(
     validators: &Validators,
     target_balance: &[Lamports],
@@ -162,9 +215,11 @@ pub fn get_unstake_validator_index(
 }
 
 /// Given a list of validators and their target balance, return the index of the
-/// validator that has less stake, and the amount by which it is below its target.
+/// validator that has the least stake relative to its weight, and the amount by
+/// which it is below its target.
 ///
-/// This assumes that there is at least one active validator. Panics otherwise.
+/// This assumes that there is at least one active validator with nonzero
+/// weight. Panics otherwise.
 pub fn get_minimum_stake_validator_index_amount(
     validators: &Validators,
     target_balance: &[Lamports],
@@ -176,12 +231,12 @@ pub fn get_minimum_stake_validator_index_amount(
     );
 
     // Our initial index, that will be returned when no validator is below its target,
-    // is the first active validator.
+    // is the first active validator with nonzero weight.
     let mut index = validators
         .iter_entries()
-        .position(|v| v.active)
+        .position(|v| v.effective_weight() > 0)
         .expect("get_minimum_stake_validator_index_amount requires at least one active validator.");
-    let mut lowest_balance = validators.entries[index].entry.effective_stake_balance();
+    let mut lowest_stake_per_weight = validators.entries[index].entry.stake_per_weight();
     let mut amount = Lamports(
         target_balance[index]
             .0
@@ -189,14 +244,16 @@ pub fn get_minimum_stake_validator_index_amount(
     );
 
     for (i, (validator, target)) in validators.iter_entries().zip(target_balance).enumerate() {
-        if validator.active && validator.effective_stake_balance() < lowest_balance {
+        if validator.effective_weight() > 0
+            && validator.stake_per_weight() < lowest_stake_per_weight
+        {
             index = i;
             amount = Lamports(
                 target
                     .0
                     .saturating_sub(validator.effective_stake_balance().0),
             );
-            lowest_balance = validator.effective_stake_balance();
+            lowest_stake_per_weight = validator.stake_per_weight();
         }
     }
 
@@ -213,6 +270,49 @@ pub fn get_validator_to_withdraw(
         .ok_or(LidoError::NoActiveValidators)
 }
 
+/// Compute the penalty for withdrawing `amount` from a validator that has
+/// `balance` effective stake, and whose stake target is `target`.
+///
+/// Withdrawing from a validator that is above its target does not make the
+/// pool less balanced, so only the part of `amount` that brings the validator
+/// further below its target is penalized. The marginal penalty rate is
+/// proportional to how far below its target the validator is, and it is
+/// `max_penalty_bps` when the validator is left with nothing. The penalty is
+/// the integral of that rate over the penalized part, so it is charged at the
+/// average of the shortfall before and after the withdrawal. This way,
+/// splitting a withdrawal into smaller ones does not change the total penalty.
+pub fn get_imbalance_penalty(
+    target: Lamports,
+    balance: Lamports,
+    amount: Lamports,
+    max_penalty_bps: u64,
+) -> token::Result<Lamports> {
+    let balance_after = (balance - amount)?;
+    if target == Lamports(0) {
+        return Ok(Lamports(0));
+    }
+
+    let shortfall_before = target.0.saturating_sub(balance.0);
+    let shortfall_after = target.0.saturating_sub(balance_after.0);
+    let penalized_amount = Lamports(shortfall_after - shortfall_before);
+
+    // The average shortfall is half of this sum, we fold the factor 1/2 into
+    // the denominator of the rate below.
+    let shortfall_sum = shortfall_before
+        .checked_add(shortfall_after)
+        .ok_or(ArithmeticError)?;
+
+    penalized_amount
+        .mul(Rational {
+            numerator: shortfall_sum,
+            denominator: target.0,
+        })?
+        .mul(Rational {
+            numerator: max_penalty_bps,
+            denominator: 2 * 10_000,
+        })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -225,7 +325,7 @@ mod test {
         let mut validators = Validators::new_fill_default(1);
         validators.entries[0].entry.stake_accounts_balance = Lamports(100);
         let undelegated_stake = Lamports(50);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets[0], Lamports(150));
 
         // With only one validator, that one is the least balanced. It is
@@ -244,7 +344,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(99);
 
         let undelegated_stake = Lamports(50);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(125), Lamports(125)]);
 
         // The second validator is further away from its target.
@@ -263,7 +363,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(99);
 
         let undelegated_stake = Lamports(51);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(126), Lamports(125)]);
 
         // The second validator is further from its target, by one Lamport.
@@ -280,7 +380,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(50);
 
         let undelegated_stake = Lamports(0);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(50), Lamports(50)]);
 
         assert_eq!(
@@ -297,7 +397,7 @@ mod test {
         validators.entries[2].entry.stake_accounts_balance = Lamports(99);
 
         let undelegated_stake = Lamports(51);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(126), Lamports(0), Lamports(125)]);
 
         assert_eq!(
@@ -317,7 +417,7 @@ mod test {
         validators.entries[2].entry.stake_accounts_balance = Lamports(300);
 
         let undelegated_stake = Lamports(0);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(250), Lamports(0), Lamports(250)]);
 
         assert_eq!(
@@ -326,6 +426,56 @@ mod test {
         );
     }
 
+    #[test]
+    fn get_target_balance_is_proportional_to_weight() {
+        // 300 Lamports delegated + 100 undelegated, split 1:3.
+        let mut validators = Validators::new_fill_default(2);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(100);
+        validators.entries[0].entry.weight = 1;
+        validators.entries[1].entry.stake_accounts_balance = Lamports(200);
+        validators.entries[1].entry.weight = 3;
+
+        let undelegated_stake = Lamports(100);
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
+        assert_eq!(targets, [Lamports(100), Lamports(300)]);
+
+        // The second validator has more stake, but less stake per weight.
+        assert_eq!(
+            get_minimum_stake_validator_index_amount(&validators, &targets[..]),
+            (1, Lamports(100))
+        );
+    }
+
+    #[test]
+    fn get_target_balance_works_with_zero_weight_for_non_integer_multiple() {
+        // 101 Lamports split 1:0:2, the remainder goes to the first validator
+        // with nonzero weight.
+        let mut validators = Validators::new_fill_default(3);
+        validators.entries[0].entry.weight = 1;
+        validators.entries[1].entry.weight = 0;
+        validators.entries[2].entry.weight = 2;
+
+        let undelegated_stake = Lamports(101);
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
+        assert_eq!(targets, [Lamports(34), Lamports(0), Lamports(67)]);
+
+        assert_eq!(
+            get_minimum_stake_validator_index_amount(&validators, &targets[..]),
+            (0, Lamports(34))
+        );
+    }
+
+    #[test]
+    fn get_target_balance_all_zero_weight() {
+        let mut validators = Validators::new_fill_default(2);
+        validators.entries[0].entry.weight = 0;
+        validators.entries[1].entry.weight = 0;
+
+        let undelegated_stake = Lamports(50);
+        let result = get_target_balance(undelegated_stake, &validators, 0);
+        assert_eq!(result, Err(LidoError::NoActiveValidators));
+    }
+
     #[test]
     fn get_target_balance_all_inactive() {
         // No active validators exist.
@@ -338,7 +488,7 @@ mod test {
         validators.entries[2].entry.active = false;
 
         let undelegated_stake = Lamports(0);
-        let result = get_target_balance(undelegated_stake, &validators);
+        let result = get_target_balance(undelegated_stake, &validators, 0);
         assert!(result.is_err());
     }
 
@@ -353,7 +503,7 @@ mod test {
         validators.entries[0].entry.active = false;
 
         let undelegated_stake = Lamports(0);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(
             get_minimum_stake_validator_index_amount(&validators, &targets[..]),
             (1, Lamports(0)),
@@ -368,7 +518,7 @@ mod test {
         validators.entries[2].entry.stake_accounts_balance = Lamports(100);
 
         let undelegated_stake = Lamports(200);
-        let targets = get_target_balance(undelegated_stake, &validators).unwrap();
+        let targets = get_target_balance(undelegated_stake, &validators, 0).unwrap();
         assert_eq!(targets, [Lamports(168), Lamports(167), Lamports(167)]);
 
         assert_eq!(
@@ -384,7 +534,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(16);
         validators.entries[2].entry.stake_accounts_balance = Lamports(10);
 
-        let targets = get_target_balance(Lamports(0), &validators).unwrap();
+        let targets = get_target_balance(Lamports(0), &validators, 0).unwrap();
 
         let minimum_unstake = get_unstake_validator_index(
             &validators,
@@ -413,7 +563,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(16);
         validators.entries[2].entry.stake_accounts_balance = Lamports(10);
 
-        let targets = get_target_balance(Lamports(0), &validators).unwrap();
+        let targets = get_target_balance(Lamports(0), &validators, 0).unwrap();
 
         // Test below the threshold.
         let minimum_unstake = get_unstake_validator_index(
@@ -434,7 +584,7 @@ mod test {
         validators.entries[1].entry.stake_accounts_balance = Lamports(15);
         validators.entries[2].entry.stake_accounts_balance = Lamports(0);
 
-        let targets = get_target_balance(Lamports(0), &validators).unwrap();
+        let targets = get_target_balance(Lamports(0), &validators, 0).unwrap();
 
         // Test get the unstake index even if the validator is not below the threshold but some other is.
         let minimum_unstake = get_unstake_validator_index(
@@ -447,4 +597,98 @@ mod test {
         );
         assert_eq!(minimum_unstake, Some((0, Lamports(6))))
     }
+
+    #[test]
+    fn get_target_balance_adds_directed_stake_up_to_maximum() {
+        let mut validators = Validators::new_fill_default(3);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(100);
+        validators.entries[0].entry.directed_stake = Lamports(30);
+        validators.entries[1].entry.stake_accounts_balance = Lamports(100);
+        validators.entries[1].entry.directed_stake = Lamports(90);
+        validators.entries[2].entry.stake_accounts_balance = Lamports(100);
+
+        // Without a maximum share, directed stake has no effect.
+        let targets = get_target_balance(Lamports(0), &validators, 0).unwrap();
+        assert_eq!(targets, [Lamports(100), Lamports(100), Lamports(100)]);
+
+        // With a maximum of 20% = 60 Lamports, the first validator gets its
+        // full 30 of directed stake, the second one is capped at 60, and the
+        // remaining 210 Lamports are distributed by weight.
+        let targets = get_target_balance(Lamports(0), &validators, 2_000).unwrap();
+        assert_eq!(targets, [Lamports(100), Lamports(130), Lamports(70)]);
+
+        // The validator with the most directed stake has the least stake per
+        // weight, so that is where new stake goes.
+        assert_eq!(
+            get_minimum_stake_validator_index_amount(&validators, &targets[..]),
+            (1, Lamports(30))
+        );
+
+        // Inactive validators do not get their directed stake.
+        validators.entries[1].entry.active = false;
+        let targets = get_target_balance(Lamports(0), &validators, 2_000).unwrap();
+        assert_eq!(targets, [Lamports(165), Lamports(0), Lamports(135)]);
+    }
+
+    #[test]
+    fn get_target_balance_scales_down_directed_stake_above_total() {
+        let mut validators = Validators::new_fill_default(2);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(50);
+        validators.entries[0].entry.directed_stake = Lamports(100);
+        validators.entries[1].entry.stake_accounts_balance = Lamports(50);
+        validators.entries[1].entry.directed_stake = Lamports(100);
+
+        let targets = get_target_balance(Lamports(0), &validators, 10_000).unwrap();
+        assert_eq!(targets, [Lamports(50), Lamports(50)]);
+    }
+
+    #[test]
+    fn get_imbalance_penalty_only_penalizes_shortfall() {
+        // Staying above the target is free.
+        assert_eq!(
+            get_imbalance_penalty(Lamports(100), Lamports(150), Lamports(50), 100),
+            Ok(Lamports(0))
+        );
+
+        // Of the 450 withdrawn, 400 go below the target. The validator ends up
+        // 40% below its target, so on average the shortfall is 20%, and the
+        // rate is a fifth of the 10% maximum.
+        assert_eq!(
+            get_imbalance_penalty(Lamports(1_000), Lamports(1_050), Lamports(450), 1_000),
+            Ok(Lamports(8))
+        );
+
+        // When the validator is already below its target, all of the amount
+        // is penalized. Emptying it goes from a 20% to a 100% shortfall, so
+        // the rate is 60% of the maximum.
+        assert_eq!(
+            get_imbalance_penalty(Lamports(1_000), Lamports(800), Lamports(800), 1_000),
+            Ok(Lamports(48))
+        );
+
+        // A validator that should have no stake is never penalized.
+        assert_eq!(
+            get_imbalance_penalty(Lamports(0), Lamports(800), Lamports(800), 1_000),
+            Ok(Lamports(0))
+        );
+
+        // Withdrawing more than the balance is an error.
+        assert!(get_imbalance_penalty(Lamports(0), Lamports(1), Lamports(2), 1_000).is_err());
+    }
+
+    #[test]
+    fn get_imbalance_penalty_does_not_depend_on_split() {
+        let target = Lamports(1_000);
+        let balance = Lamports(800);
+
+        let penalty_once = get_imbalance_penalty(target, balance, Lamports(800), 1_000).unwrap();
+
+        let penalty_first = get_imbalance_penalty(target, balance, Lamports(400), 1_000).unwrap();
+        let penalty_second =
+            get_imbalance_penalty(target, Lamports(400), Lamports(400), 1_000).unwrap();
+
+        assert_eq!(penalty_first, Lamports(16));
+        assert_eq!(penalty_second, Lamports(32));
+        assert_eq!((penalty_first + penalty_second).unwrap(), penalty_once);
+    }
 }
@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Helpers for other on-chain programs that query Solido through a CPI.
+//!
+//! Depend on this crate with the `no-entrypoint` feature to use these from a
+//! different program.
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    msg,
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+};
+
+use crate::{
+    instruction::{
+        get_exchange_rate as get_exchange_rate_instruction, GetExchangeRateAccountsMeta,
+    },
+    state::ExchangeRate,
+};
+
+/// Call `GetExchangeRate` on the Solido program, and return the exchange rate it reports.
+///
+/// The caller is responsible for checking that `solido_program` is the Solido
+/// program it expects, and that `solido` is the Solido instance it expects;
+/// this function only checks that the return data came from `solido_program`.
+pub fn get_exchange_rate<'a>(
+    solido_program: &AccountInfo<'a>,
+    solido: &AccountInfo<'a>,
+) -> Result<ExchangeRate, ProgramError> {
+    let instruction = get_exchange_rate_instruction(
+        solido_program.key,
+        &GetExchangeRateAccountsMeta { lido: *solido.key },
+    );
+    invoke(&instruction, &[solido.clone(), solido_program.clone()])?;
+
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *solido_program.key => {
+            ExchangeRate::try_from_slice(&data).map_err(|_| {
+                msg!("Failed to deserialize the exchange rate from the return data.");
+                ProgramError::InvalidAccountData
+            })
+        }
+        Some((program_id, _)) => {
+            msg!(
+                "Expected return data from program {}, but it was set by {}.",
+                solido_program.key,
+                program_id
+            );
+            Err(ProgramError::IncorrectProgramId)
+        }
+        None => {
+            msg!("Program {} did not set return data.", solido_program.key);
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
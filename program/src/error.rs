@@ -171,6 +171,65 @@ pub enum LidoError {
 
     /// Validation commission is more than 100%
     ValidationCommissionOutOfBounds = 48,
+
+    /// The instant unstake fee is more than 100% (10_000 basis points).
+    InstantUnstakeFeeOutOfBounds = 49,
+
+    /// The withdrawal ticket does not belong to this Solido instance or owner.
+    InvalidWithdrawalTicket = 50,
+
+    /// The withdrawal ticket cannot be claimed until the next epoch.
+    WithdrawalTicketNotClaimable = 51,
+
+    /// The number of epochs to count vote credits over is 0, or exceeds the
+    /// history that vote accounts keep.
+    InvalidVoteCreditsEpochs = 52,
+
+    /// There is no pending manager, or the signer is not the pending manager.
+    InvalidPendingManager = 53,
+
+    /// The operation is paused by the manager.
+    ProgramPaused = 54,
+
+    /// The validator list account is not the one of this Solido instance.
+    InvalidValidatorList = 55,
+
+    /// The Solido state has a version that this operation does not support,
+    /// it needs to be migrated with `MigrateState` first.
+    InvalidLidoVersion = 56,
+
+    /// The deposit would bring the SOL under management over the maximum set
+    /// by the manager.
+    DepositExceedsMaximumTotalSol = 57,
+
+    /// The deposit is smaller than the minimum deposit set by the manager.
+    DepositBelowMinimum = 58,
+
+    /// The maximum directed stake is more than 100% (10_000 basis points).
+    MaxDirectedStakeOutOfBounds = 59,
+
+    /// The reserve buffer is more than 100% (10_000 basis points).
+    ReserveBufferOutOfBounds = 60,
+
+    /// A stake or unstake account in the seed range of a validator does not
+    /// exist, the seed range has a gap.
+    StakeAccountSeedGap = 61,
+
+    /// At the current exchange rate, the stSOL supply is worth more SOL than
+    /// Solido manages.
+    ExchangeRateInconsistent = 62,
+
+    /// The withdrawal fee is more than 100% (10_000 basis points).
+    WithdrawalFeeOutOfBounds = 63,
+
+    /// The signer does not hold the role that the instruction requires.
+    InvalidRoleAuthority = 64,
+
+    /// The maintainer is not permitted to perform this operation.
+    MaintainerOperationNotPermitted = 65,
+
+    /// The stake account has a lockup that is still in force.
+    StakeAccountLocked = 66,
 }
 
 // Just reuse the generated Debug impl for Display. It shows the variant names.
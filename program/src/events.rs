@@ -0,0 +1,332 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Structured events that Solido logs for every instruction that changes state.
+//!
+//! Every event is Borsh-serialized and logged with `sol_log_data`, so it shows
+//! up in the transaction logs as a `Program data: <base64>` line. Off-chain
+//! consumers can decode those lines with [`SolidoEvent::decode`], rather than
+//! parsing the human-readable `msg!` lines, which are not a stable interface.
+//!
+//! New variants must be appended at the end, so the Borsh tag of existing
+//! variants does not change.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::{ExchangeRate, ReserveBuffer, RewardDistribution, Role};
+use crate::token::{Lamports, StLamports};
+use crate::util::{serialize_b58, serialize_b58_slice};
+
+/// The fee paid to a single fee recipient when distributing rewards.
+#[derive(Clone, Debug, Eq, PartialEq, BorshDeserialize, BorshSerialize, Serialize)]
+pub struct FeePayment {
+    #[serde(serialize_with = "serialize_b58")]
+    pub st_sol_account: Pubkey,
+    pub amount: Lamports,
+    pub amount_st_sol: StLamports,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, BorshDeserialize, BorshSerialize, Serialize)]
+pub enum SolidoEvent {
+    /// A new Solido instance was initialized.
+    Initialized {
+        #[serde(serialize_with = "serialize_b58")]
+        manager: Pubkey,
+        reward_distribution: RewardDistribution,
+    },
+
+    /// A user deposited SOL into the reserve and received stSOL.
+    Deposit {
+        #[serde(serialize_with = "serialize_b58")]
+        recipient: Pubkey,
+        amount: Lamports,
+        st_sol_minted: StLamports,
+    },
+
+    /// A user deposited an active stake account and received stSOL.
+    DepositStake {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        #[serde(serialize_with = "serialize_b58")]
+        recipient: Pubkey,
+        amount: Lamports,
+        st_sol_minted: StLamports,
+    },
+
+    /// A maintainer staked SOL from the reserve with a validator.
+    StakeDeposit {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        amount: Lamports,
+    },
+
+    /// A maintainer moved stake from a validator into a deactivating unstake account.
+    Unstake {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        amount: Lamports,
+    },
+
+    /// The exchange rate was updated for a new epoch.
+    ExchangeRateUpdated {
+        exchange_rate: ExchangeRate,
+    },
+
+    /// The stake accounts of a validator were observed, and inactive SOL was
+    /// withdrawn to the reserve.
+    StakeAccountBalanceUpdated {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        withdrawn_to_reserve: Lamports,
+        rewards: Lamports,
+    },
+
+    /// Rewards were split between stSOL appreciation and the fee recipients.
+    FeesDistributed {
+        rewards: Lamports,
+        st_sol_appreciation: Lamports,
+        fees: Vec<FeePayment>,
+    },
+
+    /// A user burned stSOL in exchange for a stake account. The `penalty` is
+    /// the imbalance penalty of `WithdrawFromAnyValidator` that stayed in the
+    /// pool, it is zero for `Withdraw`.
+    Withdraw {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        #[serde(serialize_with = "serialize_b58")]
+        stake_account: Pubkey,
+        amount: StLamports,
+        sol_withdrawn: Lamports,
+        fee: StLamports,
+        penalty: Lamports,
+    },
+
+    /// A user burned stSOL in exchange for SOL from the reserve.
+    InstantUnstake {
+        amount: StLamports,
+        sol_paid: Lamports,
+        fee: StLamports,
+    },
+
+    /// A user burned stSOL in exchange for a withdrawal ticket.
+    WithdrawalRequested {
+        #[serde(serialize_with = "serialize_b58")]
+        withdrawal_ticket: Pubkey,
+        amount: StLamports,
+        sol_owed: Lamports,
+    },
+
+    /// A withdrawal ticket was paid out and closed.
+    WithdrawalClaimed {
+        #[serde(serialize_with = "serialize_b58")]
+        withdrawal_ticket: Pubkey,
+        amount: Lamports,
+    },
+
+    ValidatorAdded {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+    },
+
+    ValidatorDeactivated {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+    },
+
+    ValidatorRemoved {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+    },
+
+    ValidatorWeightChanged {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        weight: u32,
+    },
+
+    /// Two stake accounts of a validator were merged.
+    StakeMerged {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        from_seed: u64,
+        to_seed: u64,
+    },
+
+    MaintainerAdded {
+        #[serde(serialize_with = "serialize_b58")]
+        maintainer: Pubkey,
+    },
+
+    MaintainerRemoved {
+        #[serde(serialize_with = "serialize_b58")]
+        maintainer: Pubkey,
+    },
+
+    RewardDistributionChanged {
+        reward_distribution: RewardDistribution,
+    },
+
+    FeeRecipientsChanged {
+        #[serde(serialize_with = "serialize_b58_slice")]
+        fee_recipients: Vec<Pubkey>,
+    },
+
+    MaxCommissionPercentageChanged {
+        max_commission_percentage: u8,
+    },
+
+    InstantUnstakeFeeChanged {
+        instant_unstake_fee_bps: u64,
+    },
+
+    MinVoteCreditsChanged {
+        min_vote_credits: u64,
+        vote_credits_epochs: u64,
+    },
+
+    NewManagerProposed {
+        #[serde(serialize_with = "serialize_b58")]
+        pending_manager: Pubkey,
+    },
+
+    ManagerChanged {
+        #[serde(serialize_with = "serialize_b58")]
+        old_manager: Pubkey,
+        #[serde(serialize_with = "serialize_b58")]
+        new_manager: Pubkey,
+    },
+
+    PausedOperationsChanged {
+        paused_operations: u8,
+    },
+
+    ValidatorListResized {
+        maximum_entries: u32,
+    },
+
+    StateMigrated {
+        from_version: u8,
+        to_version: u8,
+        #[serde(serialize_with = "serialize_b58")]
+        validator_list: Pubkey,
+    },
+
+    DepositLimitsChanged {
+        max_total_sol: Lamports,
+        min_deposit: Lamports,
+    },
+
+    /// A deposit was directed to a validator. `amount` is the part of the
+    /// deposit that was added to the validator's directed stake.
+    StakeDirected {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        amount: Lamports,
+        directed_stake: Lamports,
+    },
+
+    MaxDirectedStakeChanged {
+        max_directed_stake_bps: u64,
+    },
+
+    /// The manager deactivated a validator and unstaked all of its stake
+    /// accounts. `amount` is the total balance that is now being unstaked.
+    ValidatorEmergencyUnstaked {
+        #[serde(serialize_with = "serialize_b58")]
+        validator_vote_account: Pubkey,
+        num_stake_accounts: u64,
+        amount: Lamports,
+    },
+
+    ReserveBufferChanged {
+        reserve_buffer: ReserveBuffer,
+    },
+
+    WithdrawalFeeChanged {
+        withdrawal_fee_bps: u64,
+    },
+
+    RoleChanged {
+        role: Role,
+        #[serde(serialize_with = "serialize_b58")]
+        old_authority: Pubkey,
+        #[serde(serialize_with = "serialize_b58")]
+        new_authority: Pubkey,
+    },
+
+    MaintainerPermissionsChanged {
+        #[serde(serialize_with = "serialize_b58")]
+        maintainer: Pubkey,
+        permissions: u8,
+    },
+}
+
+impl SolidoEvent {
+    /// Log the event as program data.
+    pub fn emit(&self) {
+        let data = self
+            .try_to_vec()
+            .expect("Serializing into a Vec does not fail.");
+        sol_log_data(&[&data]);
+    }
+
+    /// Decode an event from the data logged by [`SolidoEvent::emit`].
+    ///
+    /// Returns `None` if the data is not a Solido event.
+    pub fn decode(data: &[u8]) -> Option<SolidoEvent> {
+        SolidoEvent::try_from_slice(data).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::FeeRecipient;
+
+    #[test]
+    fn test_event_roundtrip() {
+        let events = [
+            SolidoEvent::Deposit {
+                recipient: Pubkey::new_unique(),
+                amount: Lamports(1_000),
+                st_sol_minted: StLamports(900),
+            },
+            SolidoEvent::FeesDistributed {
+                rewards: Lamports(100),
+                st_sol_appreciation: Lamports(95),
+                fees: vec![FeePayment {
+                    st_sol_account: Pubkey::new_unique(),
+                    amount: Lamports(5),
+                    amount_st_sol: StLamports(4),
+                }],
+            },
+            SolidoEvent::RewardDistributionChanged {
+                reward_distribution: RewardDistribution {
+                    fee_recipients: vec![FeeRecipient {
+                        st_sol_account: Pubkey::new_unique(),
+                        parts: 5,
+                    }],
+                    st_sol_appreciation: 95,
+                },
+            },
+        ];
+        for event in events.iter() {
+            let data = event.try_to_vec().unwrap();
+            assert_eq!(SolidoEvent::decode(&data).as_ref(), Some(event));
+        }
+    }
+
+    #[test]
+    fn test_event_decode_rejects_trailing_data() {
+        let event = SolidoEvent::PausedOperationsChanged {
+            paused_operations: 1,
+        };
+        let mut data = event.try_to_vec().unwrap();
+        data.push(0);
+        assert_eq!(SolidoEvent::decode(&data), None);
+        assert_eq!(SolidoEvent::decode(&[0xff]), None);
+    }
+}
@@ -18,7 +18,7 @@ use solana_program::{
 use crate::{
     accounts_struct, accounts_struct_meta,
     error::LidoError,
-    state::RewardDistribution,
+    state::{ReserveBuffer, RewardDistribution, Role},
     token::{Lamports, StLamports},
 };
 
@@ -47,7 +47,8 @@ pub enum LidoInstruction {
     /// Withdraw a given amount of stSOL.
     ///
     /// Caller provides some `amount` of StLamports that are to be burned in
-    /// order to withdraw SOL.
+    /// order to withdraw SOL. A fee of `withdrawal_fee_bps` is paid to the
+    /// treasury (the first fee recipient) in stSOL, the remainder is burned.
     Withdraw {
         #[allow(dead_code)] // but it's not
         amount: StLamports,
@@ -77,6 +78,13 @@ pub enum LidoInstruction {
     /// Claim rewards from the validator account and distribute rewards.
     CollectValidatorFee,
     ClaimValidatorFee,
+
+    /// Replace the reward distribution, including the list of fee recipients.
+    ///
+    /// Takes the stSOL account of every new fee recipient, in the order of
+    /// `new_reward_distribution.fee_recipients`.
+    ///
+    /// Requires the fee admin to sign.
     ChangeRewardDistribution {
         #[allow(dead_code)] // but it's not
         new_reward_distribution: RewardDistribution,
@@ -84,13 +92,13 @@ pub enum LidoInstruction {
 
     /// Add a new validator to the validator set.
     ///
-    /// Requires the manager to sign.
+    /// Requires the validator admin to sign.
     /// Deprecated in favour of AddValidatorV2
     AddValidator,
 
     /// Set the `active` flag to false for a given validator.
     ///
-    /// Requires the manager to sign.
+    /// Requires the validator admin to sign.
     ///
     /// Deactivation initiates the validator removal process:
     ///
@@ -114,7 +122,7 @@ pub enum LidoInstruction {
 
     /// Add a new validator to the validator set.
     ///
-    /// Requires the manager to sign.
+    /// Requires the validator admin to sign.
     AddValidatorV2,
 
     /// Check if validator increased his commission over maximum allowed
@@ -127,11 +135,250 @@ pub enum LidoInstruction {
     /// If validators exeed the threshold they will be deactivated by
     /// DeactivateValidatorIfCommissionExceedsMax.
     ///
-    /// Requires the manager to sign.
+    /// Requires the validator admin to sign.
     SetMaxValidationCommission {
         #[allow(dead_code)] // but it's not
         max_commission_percentage: u8, // percent in [0, 100]
     },
+
+    /// Burn stSOL and receive SOL from the reserve immediately.
+    ///
+    /// Caller provides some `amount` of StLamports. A fee of
+    /// `instant_unstake_fee_bps` is paid to the treasury (the first fee
+    /// recipient) in stSOL, the
+    /// remainder is burned and exchanged for SOL at the current exchange rate.
+    /// The SOL is paid out of the reserve, so the amount is limited by the
+    /// available reserve balance.
+    InstantUnstake {
+        #[allow(dead_code)] // but it's not
+        amount: StLamports,
+    },
+
+    /// Set the fee charged on `InstantUnstake`, in basis points.
+    ///
+    /// Requires the fee admin to sign.
+    SetInstantUnstakeFee {
+        #[allow(dead_code)] // but it's not
+        instant_unstake_fee_bps: u64, // basis points in [0, 10_000]
+    },
+
+    /// Burn stSOL and create a withdrawal ticket for the SOL it is worth.
+    ///
+    /// The amount of SOL owed is fixed at the current exchange rate. The
+    /// maintainers unstake enough SOL to cover open tickets, and once it is
+    /// back in the reserve, the ticket can be redeemed with `ClaimWithdrawal`.
+    RequestWithdrawal {
+        #[allow(dead_code)] // but it's not
+        amount: StLamports,
+    },
+
+    /// Pay out the SOL owed by a withdrawal ticket, and close the ticket.
+    ///
+    /// The ticket can be claimed starting from the epoch after it was created,
+    /// if the reserve holds enough SOL.
+    ClaimWithdrawal,
+
+    /// Set the weight of a validator, which determines its share of the stake.
+    ///
+    /// Requires the validator admin to sign.
+    SetValidatorWeight {
+        #[allow(dead_code)] // but it's not
+        weight: u32,
+    },
+
+    /// Check if a validator earned fewer vote credits than the minimum over
+    /// the last epochs, and deactivate it if it did.
+    ///
+    /// Requires no permission
+    DeactivateValidatorIfDelinquent,
+
+    /// Set the minimum number of vote credits that validators must earn over
+    /// the last `vote_credits_epochs` epochs. Validators that earn fewer will
+    /// be deactivated by `DeactivateValidatorIfDelinquent`.
+    ///
+    /// Requires the validator admin to sign.
+    SetMinVoteCredits {
+        #[allow(dead_code)] // but it's not
+        min_vote_credits: u64,
+        #[allow(dead_code)] // but it's not
+        vote_credits_epochs: u64, // number of epochs in [1, 64]
+    },
+
+    /// Propose a new manager, that takes over once it signs `AcceptManager`.
+    ///
+    /// Requires the manager to sign.
+    ProposeNewManager,
+
+    /// Make the pending manager the manager.
+    ///
+    /// Requires the pending manager to sign.
+    AcceptManager,
+
+    /// Change the stSOL accounts that receive fees, keeping their shares.
+    ///
+    /// Takes a new stSOL account for every fee recipient, in the order of
+    /// `reward_distribution.fee_recipients`. To add or remove recipients, use
+    /// `ChangeRewardDistribution`.
+    ///
+    /// Requires the fee admin to sign.
+    ChangeFeeRecipients,
+
+    /// Set which operations are paused, to stop deposits, withdrawals or
+    /// staking during an incident.
+    ///
+    /// Requires the emergency guardian to sign.
+    SetPausedOperations {
+        #[allow(dead_code)] // but it's not
+        paused_operations: u8, // combination of the `PAUSE_*` bits
+    },
+
+    /// Grow the validator list account so it can hold `max_validators`.
+    ///
+    /// The funder pays for the additional rent. The account can grow by at
+    /// most `MAX_PERMITTED_DATA_INCREASE` bytes per instruction.
+    ///
+    /// Requires the validator admin to sign.
+    ResizeValidatorList {
+        #[allow(dead_code)] // but it's not
+        max_validators: u32,
+    },
+
+    /// Convert the Solido state from version 1 to the current version.
+    ///
+    /// The validators that version 1 stored inline move to the validator list
+    /// account, which must be owned by the program, zero-initialized, and
+    /// sized for the maximum number of validators. Fields that are new in the
+    /// current version get the defaults that `Initialize` uses. If the Solido
+    /// account is too small for the new layout, it is grown, and the funder
+    /// pays for the additional rent.
+    ///
+    /// Requires the manager to sign.
+    MigrateState,
+
+    /// Deposit a fully active stake account in exchange for stSOL.
+    ///
+    /// The stake account must be delegated to an active validator that is part
+    /// of this Solido instance, and it must not have a lockup in force. Its
+    /// staker and withdrawer authority move to the Solido stake authority, and
+    /// it is either merged into the validator's last stake account, or becomes
+    /// the validator's next stake account. The depositor receives stSOL for the
+    /// full balance of the stake account, at the current exchange rate.
+    ///
+    /// This can be called by anybody who is the staker and withdrawer of the
+    /// stake account.
+    DepositStake,
+
+    /// Report the current exchange rate, without modifying any state.
+    ///
+    /// Sets the return data of the transaction to the Borsh-serialized
+    /// `ExchangeRate`, so other programs can price stSOL through a CPI, without
+    /// having to deserialize the Solido state themselves. See `crate::cpi` for
+    /// a helper that performs this call.
+    ///
+    /// This can be called by anybody.
+    GetExchangeRate,
+
+    /// Set the maximum amount of SOL under management, and the minimum amount
+    /// per deposit. A value of 0 disables the respective limit.
+    ///
+    /// Requires the fee admin to sign.
+    SetDepositLimits {
+        #[allow(dead_code)] // but it's not
+        max_total_sol: Lamports,
+        #[allow(dead_code)] // but it's not
+        min_deposit: Lamports,
+    },
+
+    /// Deposit a given amount of SOL, like `Deposit`, and direct it to a
+    /// preferred validator.
+    ///
+    /// The amount is added to the validator's directed stake, as far as that
+    /// stays within `max_directed_stake_bps` of the SOL under management. The
+    /// validator must be active and have a nonzero weight.
+    ///
+    /// This can be called by anybody.
+    DepositDirected {
+        #[allow(dead_code)] // but it's not
+        amount: Lamports,
+    },
+
+    /// Set the maximum directed stake per validator, in basis points of the
+    /// SOL under management. A value of 0 disables directed stake.
+    ///
+    /// Requires the validator admin to sign.
+    SetMaxDirectedStake {
+        #[allow(dead_code)] // but it's not
+        max_directed_stake_bps: u64, // basis points in [0, 10_000]
+    },
+
+    /// Deactivate a validator and unstake all of its stake at once.
+    ///
+    /// Requires the emergency guardian to sign.
+    ///
+    /// This is meant for a compromised validator. Like `DeactivateValidator`,
+    /// it sets the `active` flag to false, but rather than leaving the
+    /// maintainer to unstake gradually, it deactivates every stake account of
+    /// the validator in place, in one go, without needing unstake accounts.
+    /// After the stake has become inactive, `UpdateStakeAccountBalance`
+    /// withdraws it into the reserve and removes the stake accounts.
+    EmergencyUnstakeValidator,
+
+    /// Set the amount of SOL that `StakeDeposit` leaves in the reserve, either
+    /// a fixed amount, or basis points of the SOL under management.
+    ///
+    /// Requires the fee admin to sign.
+    SetReserveBuffer {
+        #[allow(dead_code)] // but it's not
+        reserve_buffer: ReserveBuffer,
+    },
+
+    /// Check that the Solido state is consistent, without changing it.
+    ///
+    /// Confirms that the tracked balances of the validator are covered by the
+    /// balances of its stake and unstake accounts, that there are no gaps in
+    /// its seed ranges, and that the SOL managed by Solido covers the value of
+    /// the stSOL supply at the current exchange rate. Fails with a
+    /// descriptive error otherwise.
+    ///
+    /// This can be called by anybody, and is meant to be run with
+    /// `simulateTransaction`.
+    CheckInvariants,
+
+    /// Set the fee charged on `Withdraw`, in basis points.
+    ///
+    /// Requires the fee admin to sign.
+    SetWithdrawalFee {
+        #[allow(dead_code)] // but it's not
+        withdrawal_fee_bps: u64, // basis points in [0, 10_000]
+    },
+
+    /// Withdraw a given amount of stSOL from any validator.
+    ///
+    /// Unlike `Withdraw`, the validator does not need to be the one with the
+    /// most stake, and the amount is not capped. Instead, if the withdrawal
+    /// leaves the validator below its stake target, an imbalance penalty is
+    /// deducted from the SOL withdrawn, and it stays in the pool. The
+    /// withdrawal fee applies as for `Withdraw`.
+    WithdrawFromAnyValidator {
+        #[allow(dead_code)] // but it's not
+        amount: StLamports,
+    },
+
+    /// Assign a `Role` to a new authority.
+    ///
+    /// Requires the manager to sign.
+    SetRole {
+        #[allow(dead_code)] // but it's not
+        role: Role,
+    },
+
+    /// Set which maintenance operations a maintainer is permitted to perform.
+    ///
+    /// Requires the maintainer admin to sign.
+    SetMaintainerPermissions {
+        #[allow(dead_code)] // but it's not
+        permissions: u8, // combination of the `MAINTAINER_*` bits
+    },
 }
 
 impl LidoInstruction {
@@ -151,19 +398,15 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
-        pub manager {
-            is_signer: false,
-            is_writable: false,
-        },
-        pub st_sol_mint {
+        pub validator_list {
             is_signer: false,
-            is_writable: false,
+            is_writable: true,
         },
-        pub treasury_account {
+        pub manager {
             is_signer: false,
             is_writable: false,
         },
-        pub developer_account {
+        pub st_sol_mint {
             is_signer: false,
             is_writable: false,
         },
@@ -173,6 +416,12 @@ accounts_struct! {
         },
         const sysvar_rent = sysvar::rent::id(),
         const spl_token = spl_token::id(),
+        // The stSOL accounts of the fee recipients, in the order of
+        // `reward_distribution.fee_recipients`.
+        pub ...fee_recipients {
+            is_signer: false,
+            is_writable: true,
+        },
     }
 }
 
@@ -257,6 +506,10 @@ accounts_struct! {
             // Needs to be writable for us to update the metrics.
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub st_sol_account_owner {
             is_signer: true,
             is_writable: false,
@@ -265,6 +518,7 @@ accounts_struct! {
         pub st_sol_account {
             is_signer: false,
             // Is writable due to st_sol burn (spl_token::instruction::burn)
+            // and the fee transfer (spl_token::instruction::transfer).
             is_writable: true,
         },
         pub st_sol_mint {
@@ -272,6 +526,11 @@ accounts_struct! {
             // Is writable due to st_sol burn (spl_token::instruction::burn)
             is_writable: true,
         },
+        pub treasury_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee transfer (spl_token::instruction::transfer) to treasury
+            is_writable: true,
+        },
         pub validator_vote_account {
             is_signer: false,
             is_writable: false,
@@ -320,6 +579,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub maintainer {
             is_signer: true,
             is_writable: false,
@@ -387,6 +650,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub maintainer {
             is_signer: true,
             is_writable: false,
@@ -444,6 +711,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: false,
+        },
         pub reserve {
             is_signer: false,
             is_writable: false,
@@ -559,13 +830,11 @@ accounts_struct! {
             is_signer: true,
             is_writable: false,
         },
-        pub treasury_account {
-            is_signer: false,
-            is_writable: false,
-        },
-        pub developer_account {
+        // The stSOL accounts of the new fee recipients, in the order of
+        // `new_reward_distribution.fee_recipients`.
+        pub ...fee_recipients {
             is_signer: false,
-            is_writable: false,
+            is_writable: true,
         },
     }
 }
@@ -593,6 +862,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub validator_vote_account_to_remove {
             is_signer: false,
             is_writable: false,
@@ -614,6 +887,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub manager {
             is_signer: true,
             is_writable: false,
@@ -726,6 +1003,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub validator_vote_account {
             is_signer: false,
             is_writable: false,
@@ -768,6 +1049,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub manager {
             is_signer: true,
             is_writable: false,
@@ -795,6 +1080,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         // The validator to update the balance for.
         pub validator_vote_account {
             is_signer: false,
@@ -829,17 +1118,6 @@ accounts_struct! {
             is_writable: false,
         },
 
-        pub treasury_st_sol_account {
-            is_signer: false,
-            // Is writable due to fee mint (spl_token::instruction::mint_to) to treasury
-            is_writable: true,
-        },
-        pub developer_st_sol_account {
-            is_signer: false,
-            // Is writable due to fee mint (spl_token::instruction::mint_to) to developer
-            is_writable: true,
-        },
-
         // Needed for minting rewards.
         const spl_token_program = spl_token::id(),
 
@@ -853,9 +1131,13 @@ accounts_struct! {
         // Needed to withdraw from stake accounts.
         const stake_program = stake_program::program::id(),
 
-        // The validator's stake accounts, from the begin seed until (but
-        // excluding) the end seed.
-        pub ...stake_accounts {
+        // The stSOL accounts of the fee recipients, in the order of
+        // `reward_distribution.fee_recipients`, followed by the validator's
+        // stake accounts, from the begin seed until (but excluding) the end
+        // seed, and then its unstake accounts in the same way. The fee
+        // recipient accounts are writable due to fee mint
+        // (spl_token::instruction::mint_to).
+        pub ...fee_recipients_and_stake_accounts {
             is_signer: false,
             is_writable: true,
         },
@@ -880,6 +1162,10 @@ accounts_struct! {
             is_signer: false,
             is_writable: true,
         },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
         pub validator_vote_account_to_deactivate {
             is_signer: false,
             is_writable: false,
@@ -925,3 +1211,944 @@ pub fn set_max_commission_percentage(
         data: data.to_vec(),
     }
 }
+
+accounts_struct! {
+    InstantUnstakeAccountsMeta, InstantUnstakeAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Needs to be writable for us to update the metrics.
+            is_writable: true,
+        },
+        pub st_sol_account_owner {
+            is_signer: true,
+            is_writable: false,
+        },
+        // This should be owned by the user.
+        pub st_sol_account {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            // and the fee transfer (spl_token::instruction::transfer).
+            is_writable: true,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            is_writable: true,
+        },
+        pub treasury_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee transfer (spl_token::instruction::transfer) to treasury
+            is_writable: true,
+        },
+        pub reserve_account {
+            is_signer: false,
+            // Is writable due to transfer (system_instruction::transfer) from
+            // reserve_account to recipient
+            is_writable: true,
+        },
+        // Account that receives the SOL.
+        pub recipient {
+            is_signer: false,
+            // Is writable due to transfer (system_instruction::transfer) from
+            // reserve_account to recipient
+            is_writable: true,
+        },
+        const spl_token = spl_token::id(),
+        const system_program = system_program::id(),
+        const sysvar_clock = sysvar::clock::id(),
+    }
+}
+
+pub fn instant_unstake(
+    program_id: &Pubkey,
+    accounts: &InstantUnstakeAccountsMeta,
+    amount: StLamports,
+) -> Instruction {
+    let data = LidoInstruction::InstantUnstake { amount };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetInstantUnstakeFeeMeta, SetInstantUnstakeFeeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_instant_unstake_fee(
+    program_id: &Pubkey,
+    accounts: &SetInstantUnstakeFeeMeta,
+    instant_unstake_fee_bps: u64,
+) -> Instruction {
+    let data = LidoInstruction::SetInstantUnstakeFee {
+        instant_unstake_fee_bps,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    RequestWithdrawalAccountsMeta, RequestWithdrawalAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Needs to be writable for us to update the SOL owed to tickets.
+            is_writable: true,
+        },
+        pub st_sol_account_owner {
+            is_signer: true,
+            // Is writable because it pays for the ticket account's rent.
+            is_writable: true,
+        },
+        // This should be owned by the user.
+        pub st_sol_account {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            is_writable: true,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            is_writable: true,
+        },
+        // Uninitialized account that will hold the withdrawal ticket.
+        pub withdrawal_ticket {
+            is_signer: true,
+            is_writable: true,
+        },
+        const spl_token = spl_token::id(),
+        const system_program = system_program::id(),
+        const sysvar_clock = sysvar::clock::id(),
+    }
+}
+
+pub fn request_withdrawal(
+    program_id: &Pubkey,
+    accounts: &RequestWithdrawalAccountsMeta,
+    amount: StLamports,
+) -> Instruction {
+    let data = LidoInstruction::RequestWithdrawal { amount };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ClaimWithdrawalAccountsMeta, ClaimWithdrawalAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Needs to be writable for us to update the SOL owed to tickets.
+            is_writable: true,
+        },
+        pub ticket_owner {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub withdrawal_ticket {
+            is_signer: false,
+            // Is writable because we close the ticket.
+            is_writable: true,
+        },
+        pub reserve_account {
+            is_signer: false,
+            // Is writable due to transfer (system_instruction::transfer) from
+            // reserve_account to recipient
+            is_writable: true,
+        },
+        // Account that receives the SOL, and the rent of the closed ticket.
+        pub recipient {
+            is_signer: false,
+            is_writable: true,
+        },
+        const system_program = system_program::id(),
+        const sysvar_clock = sysvar::clock::id(),
+    }
+}
+
+pub fn claim_withdrawal(
+    program_id: &Pubkey,
+    accounts: &ClaimWithdrawalAccountsMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ClaimWithdrawal.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetValidatorWeightMeta, SetValidatorWeightInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_validator_weight(
+    program_id: &Pubkey,
+    accounts: &SetValidatorWeightMeta,
+    weight: u32,
+) -> Instruction {
+    let data = LidoInstruction::SetValidatorWeight { weight };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    DeactivateValidatorIfDelinquentMeta, DeactivateValidatorIfDelinquentInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_vote_account_to_deactivate {
+            is_signer: false,
+            is_writable: false,
+        },
+        const sysvar_clock = sysvar::clock::id(),
+    }
+}
+
+pub fn deactivate_validator_if_delinquent(
+    program_id: &Pubkey,
+    accounts: &DeactivateValidatorIfDelinquentMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::DeactivateValidatorIfDelinquent.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetMinVoteCreditsMeta, SetMinVoteCreditsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_min_vote_credits(
+    program_id: &Pubkey,
+    accounts: &SetMinVoteCreditsMeta,
+    min_vote_credits: u64,
+    vote_credits_epochs: u64,
+) -> Instruction {
+    let data = LidoInstruction::SetMinVoteCredits {
+        min_vote_credits,
+        vote_credits_epochs,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ProposeNewManagerMeta, ProposeNewManagerInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub new_manager {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn propose_new_manager(program_id: &Pubkey, accounts: &ProposeNewManagerMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ProposeNewManager.to_vec(),
+    }
+}
+
+accounts_struct! {
+    AcceptManagerMeta, AcceptManagerInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub new_manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn accept_manager(program_id: &Pubkey, accounts: &AcceptManagerMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::AcceptManager.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ChangeFeeRecipientsMeta, ChangeFeeRecipientsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        // The new stSOL account for every fee recipient, in the order of
+        // `reward_distribution.fee_recipients`.
+        pub ...fee_recipients {
+            is_signer: false,
+            is_writable: true,
+        },
+    }
+}
+
+pub fn change_fee_recipients(
+    program_id: &Pubkey,
+    accounts: &ChangeFeeRecipientsMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::ChangeFeeRecipients.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetPausedOperationsMeta, SetPausedOperationsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_paused_operations(
+    program_id: &Pubkey,
+    accounts: &SetPausedOperationsMeta,
+    paused_operations: u8,
+) -> Instruction {
+    let data = LidoInstruction::SetPausedOperations { paused_operations };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    ResizeValidatorListMeta, ResizeValidatorListInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            // Is writable due to realloc and the rent top-up.
+            is_writable: true,
+        },
+        pub funder {
+            is_signer: true,
+            // Is writable due to transfer (system_instruction::transfer) from
+            // funder to validator_list.
+            is_writable: true,
+        },
+        const system_program = system_program::id(),
+    }
+}
+
+pub fn resize_validator_list(
+    program_id: &Pubkey,
+    accounts: &ResizeValidatorListMeta,
+    max_validators: u32,
+) -> Instruction {
+    let data = LidoInstruction::ResizeValidatorList { max_validators };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    MigrateStateMeta, MigrateStateInfo {
+        pub lido {
+            is_signer: false,
+            // Is writable due to the new layout, realloc and the rent top-up.
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub funder {
+            is_signer: true,
+            // Is writable due to transfer (system_instruction::transfer) from
+            // funder to lido.
+            is_writable: true,
+        },
+        const system_program = system_program::id(),
+    }
+}
+
+pub fn migrate_state(program_id: &Pubkey, accounts: &MigrateStateMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::MigrateState.to_vec(),
+    }
+}
+
+accounts_struct! {
+    DepositStakeAccountsMeta, DepositStakeAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Is writable due to the metrics.
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The stake account to deposit, owned by the depositor.
+        pub user_stake_account {
+            is_signer: false,
+            // Is writable due to the authority change, and the merge or split
+            // into a Solido stake account.
+            is_writable: true,
+        },
+        // Staker and withdrawer of `user_stake_account`.
+        pub user_stake_authority {
+            is_signer: true,
+            is_writable: false,
+        },
+        // stSOL SPL token account that receives the minted stSOL.
+        pub recipient {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            is_writable: true,
+        },
+        // To merge the deposited stake into the validator's stake account at
+        // seed `stake_seeds.end - 1`, this must be set to that account. To make
+        // the deposited stake the validator's new stake account at seed
+        // `stake_seeds.end`, this should be set to the same value as
+        // `stake_account_end`.
+        pub stake_account_merge_into {
+            is_signer: false,
+            is_writable: true,
+        },
+        // Must be set to the program-derived stake account for the given
+        // validator, with seed `stake_seeds.end`.
+        pub stake_account_end {
+            is_signer: false,
+            // Is writable due to the split of `user_stake_account` into it,
+            // when it becomes the new stake account.
+            is_writable: true,
+        },
+        pub stake_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        const spl_token = spl_token::id(),
+        const sysvar_clock = sysvar::clock::id(),
+        const stake_history = stake_history::id(),
+        const system_program = system_program::id(),
+        const stake_program = stake_program::program::id(),
+    }
+}
+
+pub fn deposit_stake(program_id: &Pubkey, accounts: &DepositStakeAccountsMeta) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::DepositStake.to_vec(),
+    }
+}
+
+accounts_struct! {
+    GetExchangeRateAccountsMeta, GetExchangeRateAccountsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn get_exchange_rate(
+    program_id: &Pubkey,
+    accounts: &GetExchangeRateAccountsMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::GetExchangeRate.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetDepositLimitsMeta, SetDepositLimitsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_deposit_limits(
+    program_id: &Pubkey,
+    accounts: &SetDepositLimitsMeta,
+    max_total_sol: Lamports,
+    min_deposit: Lamports,
+) -> Instruction {
+    let data = LidoInstruction::SetDepositLimits {
+        max_total_sol,
+        min_deposit,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    DepositDirectedAccountsMeta, DepositDirectedAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Needs to be writable for us to update the metrics.
+            is_writable: true,
+        },
+        pub user {
+            is_signer: true,
+            // Is writable due to transfer (system_instruction::transfer) from user to
+            // reserve_account
+            is_writable: true,
+        },
+        pub recipient {
+            is_signer: false,
+            // Is writable due to mint to (spl_token::instruction::mint_to) recipient from
+            // st_sol_mint
+            is_writable: true,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to mint to (spl_token::instruction::mint_to) recipient from
+            // st_sol_mint
+            is_writable: true,
+        },
+        pub reserve_account {
+            is_signer: false,
+            // Is writable due to transfer (system_instruction::transfer) from user to
+            // reserve_account
+            is_writable: true,
+        },
+        pub mint_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            // Needs to be writable for us to update the directed stake.
+            is_writable: true,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        const spl_token = spl_token::id(),
+        const system_program = system_program::id(),
+    }
+}
+
+pub fn deposit_directed(
+    program_id: &Pubkey,
+    accounts: &DepositDirectedAccountsMeta,
+    amount: Lamports,
+) -> Instruction {
+    let data = LidoInstruction::DepositDirected { amount };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetMaxDirectedStakeMeta, SetMaxDirectedStakeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_max_directed_stake(
+    program_id: &Pubkey,
+    accounts: &SetMaxDirectedStakeMeta,
+    max_directed_stake_bps: u64,
+) -> Instruction {
+    let data = LidoInstruction::SetMaxDirectedStake {
+        max_directed_stake_bps,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    EmergencyUnstakeValidatorMeta, EmergencyUnstakeValidatorInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        // Stake authority, to be able to deactivate the stake.
+        pub stake_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        // Required to call `solana_program::stake::instruction::deactivate_stake`.
+        const sysvar_clock = sysvar::clock::id(),
+        // Required to call cross-program.
+        const stake_program = stake_program::program::id(),
+
+        // The validator's stake accounts, from the begin seed until (but
+        // excluding) the end seed. They are writable due to deactivate.
+        pub ...stake_accounts {
+            is_signer: false,
+            is_writable: true,
+        },
+    }
+}
+
+pub fn emergency_unstake_validator(
+    program_id: &Pubkey,
+    accounts: &EmergencyUnstakeValidatorMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::EmergencyUnstakeValidator.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetReserveBufferMeta, SetReserveBufferInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_reserve_buffer(
+    program_id: &Pubkey,
+    accounts: &SetReserveBufferMeta,
+    reserve_buffer: ReserveBuffer,
+) -> Instruction {
+    let data = LidoInstruction::SetReserveBuffer { reserve_buffer };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    CheckInvariantsAccountsMeta, CheckInvariantsAccountsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The validator to check the stake accounts for.
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub reserve {
+            is_signer: false,
+            is_writable: false,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            is_writable: false,
+        },
+        // The validator's stake accounts, from the begin seed until (but
+        // excluding) the end seed, followed by its unstake accounts in the
+        // same way. They are not modified, they are only writable because
+        // `accounts_struct!` requires that of variadic accounts.
+        pub ...stake_accounts {
+            is_signer: false,
+            is_writable: true,
+        },
+    }
+}
+
+pub fn check_invariants(
+    program_id: &Pubkey,
+    accounts: &CheckInvariantsAccountsMeta,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: LidoInstruction::CheckInvariants.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetWithdrawalFeeMeta, SetWithdrawalFeeInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_withdrawal_fee(
+    program_id: &Pubkey,
+    accounts: &SetWithdrawalFeeMeta,
+    withdrawal_fee_bps: u64,
+) -> Instruction {
+    let data = LidoInstruction::SetWithdrawalFee { withdrawal_fee_bps };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    WithdrawFromAnyValidatorAccountsMeta, WithdrawFromAnyValidatorAccountsInfo {
+        pub lido {
+            is_signer: false,
+            // Needs to be writable for us to update the metrics.
+            is_writable: true,
+        },
+        pub validator_list {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub st_sol_account_owner {
+            is_signer: true,
+            is_writable: false,
+        },
+        // This should be owned by the user.
+        pub st_sol_account {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            // and the fee transfer (spl_token::instruction::transfer).
+            is_writable: true,
+        },
+        pub st_sol_mint {
+            is_signer: false,
+            // Is writable due to st_sol burn (spl_token::instruction::burn)
+            is_writable: true,
+        },
+        pub treasury_st_sol_account {
+            is_signer: false,
+            // Is writable due to fee transfer (spl_token::instruction::transfer) to treasury
+            is_writable: true,
+        },
+        pub validator_vote_account {
+            is_signer: false,
+            is_writable: false,
+        },
+        // Stake account to withdraw from.
+        pub source_stake_account {
+            is_signer: false,
+            // Is writable due to split stake (solana_program::stake::instruction::split)
+            is_writable: true,
+        },
+        // Stake where the withdrawn amounts will go.
+        pub destination_stake_account {
+            is_signer: true,
+            // Is writable due to split stake (solana_program::stake::instruction::split) and
+            // transfer of stake authority (solana_program::stake::instruction::authorize
+            is_writable: true,
+        },
+        // Used to split stake accounts and burn tokens.
+        pub stake_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+        // Needed to compute the validator's stake target.
+        pub reserve {
+            is_signer: false,
+            is_writable: false,
+        },
+        const spl_token = spl_token::id(),
+        const sysvar_clock = sysvar::clock::id(),
+        const system_program = system_program::id(),
+        const stake_program = stake_program::program::id(),
+    }
+}
+
+pub fn withdraw_from_any_validator(
+    program_id: &Pubkey,
+    accounts: &WithdrawFromAnyValidatorAccountsMeta,
+    amount: StLamports,
+) -> Instruction {
+    let data = LidoInstruction::WithdrawFromAnyValidator { amount };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetRoleMeta, SetRoleInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub new_authority {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_role(program_id: &Pubkey, accounts: &SetRoleMeta, role: Role) -> Instruction {
+    let data = LidoInstruction::SetRole { role };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
+
+accounts_struct! {
+    SetMaintainerPermissionsMeta, SetMaintainerPermissionsInfo {
+        pub lido {
+            is_signer: false,
+            is_writable: true,
+        },
+        pub manager {
+            is_signer: true,
+            is_writable: false,
+        },
+        pub maintainer {
+            is_signer: false,
+            is_writable: false,
+        },
+    }
+}
+
+pub fn set_maintainer_permissions(
+    program_id: &Pubkey,
+    accounts: &SetMaintainerPermissionsMeta,
+    permissions: u8,
+) -> Instruction {
+    let data = LidoInstruction::SetMaintainerPermissions { permissions };
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_vec(),
+        data: data.to_vec(),
+    }
+}
@@ -9,10 +9,13 @@ pub mod entrypoint;
 pub mod account_map;
 pub mod accounts;
 pub mod balance;
+pub mod cpi;
 pub mod error;
+pub mod events;
 pub mod instruction;
 pub(crate) mod logic;
 pub mod metrics;
+pub mod migration;
 pub(crate) mod process_management;
 pub mod processor;
 pub mod stake_account;
@@ -63,6 +66,12 @@ pub const MINIMUM_STAKE_ACCOUNT_BALANCE: token::Lamports = token::Lamports(1_000
 /// The maximum number of unstake accounts that a validator can have simultaneously.
 pub const MAXIMUM_UNSTAKE_ACCOUNTS: u64 = 3;
 
+/// The imbalance penalty rate of `WithdrawFromAnyValidator`, in basis points,
+/// when a withdrawal leaves the validator with no stake at all.
+///
+/// See `balance::get_imbalance_penalty` for how the rate scales below that.
+pub const MAXIMUM_IMBALANCE_PENALTY_BPS: u64 = 100;
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -14,9 +14,11 @@ use solana_program::{
 use crate::processor::StakeType;
 use crate::STAKE_AUTHORITY;
 use crate::{
+    account_map::PubkeyAndEntry,
     error::LidoError,
+    events::{FeePayment, SolidoEvent},
     instruction::{UnstakeAccountsInfo, UpdateStakeAccountBalanceInfo, WithdrawAccountsInfo},
-    state::Lido,
+    state::{Lido, Validator, Validators},
     token::{Lamports, StLamports},
     MINT_AUTHORITY, RESERVE_ACCOUNT,
 };
@@ -33,6 +35,49 @@ pub(crate) fn check_rent_exempt(
     Ok(())
 }
 
+/// Check that a new validator list account can be used to hold up to
+/// `max_validators` validators.
+///
+/// The account must be rent-exempt, owned by the Lido program, not in use yet,
+/// and have exactly the size that the validators require.
+pub(crate) fn check_new_validator_list(
+    program_id: &Pubkey,
+    rent: &Rent,
+    validator_list: &AccountInfo,
+    max_validators: u32,
+) -> ProgramResult {
+    check_rent_exempt(rent, validator_list, "Validator list account")?;
+    if validator_list.owner != program_id {
+        msg!(
+            "Validator list {} is owned by {}, but should be owned by the Lido program ({}).",
+            validator_list.key,
+            validator_list.owner,
+            program_id
+        );
+        return Err(LidoError::InvalidOwner.into());
+    }
+    let is_uninitialized = validator_list.data.borrow().iter().all(|byte| *byte == 0);
+    if !is_uninitialized {
+        msg!(
+            "Account {} appears to be in use already, refusing to overwrite.",
+            validator_list.key
+        );
+        return Err(LidoError::AlreadyInUse.into());
+    }
+
+    // Bytes required for validators, they live in the validator list account.
+    let bytes_for_validators = Validators::required_bytes(max_validators as usize);
+    if bytes_for_validators != validator_list.data_len() {
+        msg!(
+            "Incorrect allocated bytes for the validator list: max_validators bytes: {}, should be {}",
+            bytes_for_validators,
+            validator_list.data_len()
+        );
+        return Err(LidoError::InvalidLidoSize.into());
+    }
+    Ok(())
+}
+
 /// Check if the mint program coin supply is zero and the mint authority is set
 /// to `mint_authority`.
 /// The check has to be done only in Solido's initialization phase, since we
@@ -225,35 +270,25 @@ pub fn mint_st_sol_to<'a>(
 ///
 /// * The stSOL mint must be the one configured in the Solido instance.
 /// * The account account must be an stSOL SPL token account.
-pub fn burn_st_sol<'a, 'b>(
+pub fn burn_st_sol<'a>(
     solido: &Lido,
-    accounts: &WithdrawAccountsInfo<'a, 'b>,
+    spl_token_program: &AccountInfo<'a>,
+    st_sol_mint: &AccountInfo<'a>,
+    st_sol_account: &AccountInfo<'a>,
+    st_sol_account_owner: &AccountInfo<'a>,
     amount: StLamports,
 ) -> ProgramResult {
-    solido.check_mint_is_st_sol_mint(accounts.st_sol_mint)?;
-    solido.check_is_st_sol_account(accounts.st_sol_account)?;
-
-    let st_sol_account: spl_token::state::Account =
-        spl_token::state::Account::unpack_from_slice(&accounts.st_sol_account.data.borrow())?;
-
-    // Check if the user is the account owner.
-    if &st_sol_account.owner != accounts.st_sol_account_owner.key {
-        msg!(
-            "Token is owned by {}, but provided owner is {}.",
-            st_sol_account.owner,
-            accounts.st_sol_account_owner.key,
-        );
-        return Err(LidoError::InvalidTokenOwner.into());
-    }
+    solido.check_mint_is_st_sol_mint(st_sol_mint)?;
+    check_st_sol_account_owner(solido, st_sol_account, st_sol_account_owner)?;
 
     // The SPL token program supports multisig-managed mints, but we do not
     // use those.
     let burn_signers = [];
     let instruction = spl_token::instruction::burn(
-        accounts.spl_token.key,
-        accounts.st_sol_account.key,
-        accounts.st_sol_mint.key,
-        accounts.st_sol_account_owner.key,
+        spl_token_program.key,
+        st_sol_account.key,
+        st_sol_mint.key,
+        st_sol_account_owner.key,
         &burn_signers,
         amount.0,
     )?;
@@ -261,13 +296,71 @@ pub fn burn_st_sol<'a, 'b>(
     invoke(
         &instruction,
         &[
-            accounts.st_sol_account.clone(),
-            accounts.st_sol_mint.clone(),
-            accounts.st_sol_account_owner.clone(),
-            accounts.spl_token.clone(),
+            st_sol_account.clone(),
+            st_sol_mint.clone(),
+            st_sol_account_owner.clone(),
+            spl_token_program.clone(),
+        ],
+    )
+}
+
+/// Transfer `amount` stSOL from `source`, owned by `source_owner`, to `destination`.
+pub fn transfer_st_sol<'a>(
+    solido: &Lido,
+    spl_token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    source_owner: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    amount: StLamports,
+) -> ProgramResult {
+    check_st_sol_account_owner(solido, source, source_owner)?;
+    solido.check_is_st_sol_account(destination)?;
+
+    // The SPL token program supports multisig-managed accounts, but we do not
+    // use those.
+    let transfer_signers = [];
+    let instruction = spl_token::instruction::transfer(
+        spl_token_program.key,
+        source.key,
+        destination.key,
+        source_owner.key,
+        &transfer_signers,
+        amount.0,
+    )?;
+
+    invoke(
+        &instruction,
+        &[
+            source.clone(),
+            destination.clone(),
+            source_owner.clone(),
+            spl_token_program.clone(),
         ],
     )
 }
+
+/// Check that `st_sol_account` is an stSOL account owned by `st_sol_account_owner`.
+fn check_st_sol_account_owner(
+    solido: &Lido,
+    st_sol_account: &AccountInfo,
+    st_sol_account_owner: &AccountInfo,
+) -> ProgramResult {
+    solido.check_is_st_sol_account(st_sol_account)?;
+
+    let st_sol_account: spl_token::state::Account =
+        spl_token::state::Account::unpack_from_slice(&st_sol_account.data.borrow())?;
+
+    // Check if the user is the account owner.
+    if &st_sol_account.owner != st_sol_account_owner.key {
+        msg!(
+            "Token is owned by {}, but provided owner is {}.",
+            st_sol_account.owner,
+            st_sol_account_owner.key,
+        );
+        return Err(LidoError::InvalidTokenOwner.into());
+    }
+    Ok(())
+}
 // Set the stake and withdraw authority of the destination stake account to the
 // user’s pubkey.
 pub fn transfer_stake_authority(
@@ -316,12 +409,13 @@ pub fn transfer_stake_authority(
     )
 }
 
-/// Mints developer and treasury fees proportional to rewards.
+/// Mints the fees of all fee recipients, proportional to rewards.
 /// This function can only be called after the exchange rate is updated with
 /// `process_update_exchange_rate`.
-pub fn distribute_fees(
+pub fn distribute_fees<'a, 'b>(
     lido: &mut Lido,
-    accounts: &UpdateStakeAccountBalanceInfo,
+    accounts: &UpdateStakeAccountBalanceInfo<'a, 'b>,
+    fee_recipient_accounts: &'a [AccountInfo<'b>],
     clock: &Clock,
     rewards: Lamports,
 ) -> ProgramResult {
@@ -332,48 +426,52 @@ pub fn distribute_fees(
     // Confirm that the passed accounts are the ones configured in the state,
     // and confirm that they can receive stSOL.
     lido.check_mint_is_st_sol_mint(accounts.st_sol_mint)?;
-    lido.check_treasury_fee_st_sol_account(accounts.treasury_st_sol_account)?;
-    lido.check_developer_fee_st_sol_account(accounts.developer_st_sol_account)?;
+    lido.check_fee_recipient_st_sol_accounts(fee_recipient_accounts)?;
 
     lido.check_exchange_rate_last_epoch(clock, "distribute_fees")?;
 
     let fees = lido.reward_distribution.split_reward(rewards)?;
+    let mut fee_payments = Vec::with_capacity(fees.recipient_amounts.len());
 
     // Convert all fees to stSOL according to the previously updated exchange rate.
     // In the case of fees, the SOL is already part of one of the stake accounts,
     // but we do still need to mint stSOL to represent it.
-
-    let treasury_amount = lido.exchange_rate.exchange_sol(fees.treasury_amount)?;
-    let developer_amount = lido.exchange_rate.exchange_sol(fees.developer_amount)?;
-
-    // The treasury and developer fee we can mint and pay immediately.
-    mint_st_sol_to(
-        lido,
-        accounts.lido.key,
-        accounts.spl_token_program,
-        accounts.st_sol_mint,
-        accounts.mint_authority,
-        accounts.treasury_st_sol_account,
-        treasury_amount,
-    )?;
-    mint_st_sol_to(
-        lido,
-        accounts.lido.key,
-        accounts.spl_token_program,
-        accounts.st_sol_mint,
-        accounts.mint_authority,
-        accounts.developer_st_sol_account,
-        developer_amount,
-    )?;
-
-    // Also record our rewards in the metrics.
-    lido.metrics
-        .observe_fee_treasury(fees.treasury_amount, treasury_amount)?;
-    lido.metrics
-        .observe_fee_developer(fees.developer_amount, developer_amount)?;
+    for (recipient_account, fee_amount) in fee_recipient_accounts
+        .iter()
+        .zip(fees.recipient_amounts.iter())
+    {
+        let fee_amount_st_sol = lido.exchange_rate.exchange_sol(*fee_amount)?;
+
+        // The fees we can mint and pay immediately.
+        mint_st_sol_to(
+            lido,
+            accounts.lido.key,
+            accounts.spl_token_program,
+            accounts.st_sol_mint,
+            accounts.mint_authority,
+            recipient_account,
+            fee_amount_st_sol,
+        )?;
+
+        // Also record our rewards in the metrics.
+        lido.metrics
+            .observe_fee(recipient_account.key, *fee_amount, fee_amount_st_sol)?;
+        fee_payments.push(FeePayment {
+            st_sol_account: *recipient_account.key,
+            amount: *fee_amount,
+            amount_st_sol: fee_amount_st_sol,
+        });
+    }
     lido.metrics
         .observe_reward_st_sol_appreciation(fees.st_sol_appreciation_amount)?;
 
+    SolidoEvent::FeesDistributed {
+        rewards,
+        st_sol_appreciation: fees.st_sol_appreciation_amount,
+        fees: fee_payments,
+    }
+    .emit();
+
     Ok(())
 }
 
@@ -381,11 +479,9 @@ pub fn distribute_fees(
 /// by the validator's seeds. Returns the destination bump seed.
 pub fn check_unstake_accounts(
     program_id: &Pubkey,
-    lido: &Lido,
+    validator: &PubkeyAndEntry<Validator>,
     accounts: &UnstakeAccountsInfo,
 ) -> Result<u8, ProgramError> {
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
-
     // If a validator doesn't have a stake account, it cannot be unstaked.
     if !validator.entry.has_stake_accounts() {
         msg!(
@@ -12,42 +12,31 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::Serialize;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
 
+use crate::state::RewardDistribution;
 use crate::token::{self, Lamports, StLamports};
+use crate::util::serialize_b58;
 
 #[repr(C)]
 #[derive(
     Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
 )]
 pub struct Metrics {
-    /// Fees paid to the treasury, in total since we started tracking, before conversion to stSOL.
+    /// Fees paid to each of the current fee recipients, see [`FeeRecipientMetrics`].
     ///
-    /// Note: rewards are paid in stSOL, so the treasury did not actually receive
-    /// this SOL; it is the SOL that the treasury would have, if it could convert
-    /// its fees into SOL immediately after receiving them.
-    #[serde(rename = "fee_treasury_total_lamports")]
-    pub fee_treasury_sol_total: Lamports,
+    /// When a recipient is removed from the reward distribution, its totals are
+    /// dropped, so this holds at most `MAXIMUM_FEE_RECIPIENTS` entries.
+    pub fee_recipients: Vec<FeeRecipientMetrics>,
 
     /// Fees paid to validators, in total since we started tracking, before conversion to stSOL.
     #[serde(rename = "fee_validation_total_lamports")]
     pub fee_validation_sol_total: Lamports,
 
-    /// Fees paid to the developer, in total since we started tracking, before conversion to stSOL.
-    #[serde(rename = "fee_developer_total_lamports")]
-    pub fee_developer_sol_total: Lamports,
-
     /// Total rewards that benefited stSOL holders, in total, since we started tracking.
     #[serde(rename = "st_sol_appreciation_total_lamports")]
     pub st_sol_appreciation_sol_total: Lamports,
 
-    /// Fees paid to the treasury, in total since we started tracking.
-    ///
-    /// The current value of this stSOL will be different than the value at the
-    /// time the fees were paid; [`fee_treasury_sol_total`] tracks the SOL at the
-    /// time the fees were paid.
-    #[serde(rename = "fee_treasury_total_st_lamports")]
-    pub fee_treasury_st_sol_total: StLamports,
-
     /// Fees paid to validators, in total since we started tracking.
     ///
     /// The current value of this stSOL will be different than the value at the
@@ -56,14 +45,6 @@ pub struct Metrics {
     #[serde(rename = "fee_validation_total_st_lamports")]
     pub fee_validation_st_sol_total: StLamports,
 
-    /// Fees paid to the developer, in total since we started tracking.
-    ///
-    /// The current value of this stSOL will be different than the value at the
-    /// time the fees were paid; [`fee_developer_sol_total`] tracks the SOL at the
-    /// time the fees were paid.
-    #[serde(rename = "fee_developer_total_st_lamports")]
-    pub fee_developer_st_sol_total: StLamports,
-
     /// Histogram of deposits, including the total amount deposited since we started tracking.
     pub deposit_amount: LamportsHistogram,
     /// Total amount withdrawn since the beginning.
@@ -72,36 +53,76 @@ pub struct Metrics {
     // track the amount in StSOL, SOL and the total number the function was
     // called.
     pub withdraw_amount: WithdrawMetric,
+
+    /// Total amount unstaked instantly from the reserve since the beginning.
+    ///
+    /// The stSOL amount here excludes the fee, it is the amount that was burned.
+    pub instant_unstake_amount: WithdrawMetric,
+
+    /// Fees paid to the treasury for instant unstakes, in total since we started tracking.
+    #[serde(rename = "fee_instant_unstake_total_st_lamports")]
+    pub fee_instant_unstake_st_sol_total: StLamports,
+
+    /// Fees paid to the treasury for withdrawals, in total since we started tracking.
+    #[serde(rename = "fee_withdrawal_total_st_lamports")]
+    pub fee_withdrawal_st_sol_total: StLamports,
 }
 
 impl Metrics {
     pub fn new() -> Self {
         Self {
-            fee_treasury_sol_total: Lamports(0),
+            fee_recipients: Vec::new(),
             fee_validation_sol_total: Lamports(0),
-            fee_developer_sol_total: Lamports(0),
             st_sol_appreciation_sol_total: Lamports(0),
 
-            fee_treasury_st_sol_total: StLamports(0),
             fee_validation_st_sol_total: StLamports(0),
-            fee_developer_st_sol_total: StLamports(0),
 
             deposit_amount: LamportsHistogram::new(),
             withdraw_amount: WithdrawMetric::default(),
+            instant_unstake_amount: WithdrawMetric::default(),
+            fee_instant_unstake_st_sol_total: StLamports(0),
+            fee_withdrawal_st_sol_total: StLamports(0),
         }
     }
 
-    pub fn observe_fee_treasury(
+    /// Record a fee paid to the fee recipient with stSOL account `st_sol_account`.
+    pub fn observe_fee(
         &mut self,
+        st_sol_account: &Pubkey,
         amount_sol: Lamports,
         amount_st_sol: StLamports,
     ) -> token::Result<()> {
-        self.fee_treasury_sol_total = (self.fee_treasury_sol_total + amount_sol)?;
-        self.fee_treasury_st_sol_total = (self.fee_treasury_st_sol_total + amount_st_sol)?;
+        let index = match self
+            .fee_recipients
+            .iter()
+            .position(|m| &m.st_sol_account == st_sol_account)
+        {
+            Some(index) => index,
+            None => {
+                self.fee_recipients.push(FeeRecipientMetrics {
+                    st_sol_account: *st_sol_account,
+                    ..FeeRecipientMetrics::default()
+                });
+                self.fee_recipients.len() - 1
+            }
+        };
+        let metrics = &mut self.fee_recipients[index];
+        metrics.fee_sol_total = (metrics.fee_sol_total + amount_sol)?;
+        metrics.fee_st_sol_total = (metrics.fee_st_sol_total + amount_st_sol)?;
 
         Ok(())
     }
 
+    /// Drop the totals of recipients that are no longer in `reward_distribution`.
+    pub fn retain_fee_recipients(&mut self, reward_distribution: &RewardDistribution) {
+        self.fee_recipients.retain(|m| {
+            reward_distribution
+                .fee_recipients
+                .iter()
+                .any(|recipient| recipient.st_sol_account == m.st_sol_account)
+        });
+    }
+
     pub fn observe_fee_validation(
         &mut self,
         amount_sol: Lamports,
@@ -113,17 +134,6 @@ impl Metrics {
         Ok(())
     }
 
-    pub fn observe_fee_developer(
-        &mut self,
-        amount_sol: Lamports,
-        amount_st_sol: StLamports,
-    ) -> token::Result<()> {
-        self.fee_developer_sol_total = (self.fee_developer_sol_total + amount_sol)?;
-        self.fee_developer_st_sol_total = (self.fee_developer_st_sol_total + amount_st_sol)?;
-
-        Ok(())
-    }
-
     pub fn observe_reward_st_sol_appreciation(&mut self, amount: Lamports) -> token::Result<()> {
         self.st_sol_appreciation_sol_total = (self.st_sol_appreciation_sol_total + amount)?;
 
@@ -133,15 +143,59 @@ impl Metrics {
     pub fn observe_deposit(&mut self, amount: Lamports) -> ProgramResult {
         self.deposit_amount.observe(amount)
     }
+    /// Record a withdrawal, `st_sol_amount` excludes the fee, it is the amount that was burned.
     pub fn observe_withdrawal(
         &mut self,
         st_sol_amount: StLamports,
         sol_amount: Lamports,
+        fee_st_sol: StLamports,
+    ) -> token::Result<()> {
+        self.withdraw_amount.observe(st_sol_amount, sol_amount)?;
+        self.fee_withdrawal_st_sol_total = (self.fee_withdrawal_st_sol_total + fee_st_sol)?;
+        Ok(())
+    }
+
+    pub fn observe_instant_unstake(
+        &mut self,
+        st_sol_amount: StLamports,
+        sol_amount: Lamports,
+        fee_st_sol: StLamports,
     ) -> token::Result<()> {
-        self.withdraw_amount.observe(st_sol_amount, sol_amount)
+        self.instant_unstake_amount
+            .observe(st_sol_amount, sol_amount)?;
+        self.fee_instant_unstake_st_sol_total =
+            (self.fee_instant_unstake_st_sol_total + fee_st_sol)?;
+        Ok(())
     }
 }
 
+/// Fees paid to a single fee recipient, in total since we started tracking.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct FeeRecipientMetrics {
+    /// The stSOL account of the recipient.
+    #[serde(serialize_with = "serialize_b58")]
+    pub st_sol_account: Pubkey,
+
+    /// Fees paid to the recipient, before conversion to stSOL.
+    ///
+    /// Note: rewards are paid in stSOL, so the recipient did not actually receive
+    /// this SOL; it is the SOL that the recipient would have, if it could convert
+    /// its fees into SOL immediately after receiving them.
+    #[serde(rename = "fee_total_lamports")]
+    pub fee_sol_total: Lamports,
+
+    /// Fees paid to the recipient.
+    ///
+    /// The current value of this stSOL will be different than the value at the
+    /// time the fees were paid; [`fee_sol_total`] tracks the SOL at the time the
+    /// fees were paid.
+    #[serde(rename = "fee_total_st_lamports")]
+    pub fee_st_sol_total: StLamports,
+}
+
 /// A histogram to count SOL values.
 ///
 /// The buckets increment by a factor of 10 each. The smallest bucket is
@@ -237,16 +291,46 @@ impl WithdrawMetric {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::state::FeeRecipient;
 
     #[test]
-    fn test_metrics_observe_fee_treasury() {
+    fn test_metrics_observe_fee() {
+        let treasury = Pubkey::new_unique();
+        let developer = Pubkey::new_unique();
         let mut m = Metrics::new();
-        m.observe_fee_treasury(Lamports(100), StLamports(100))
+        m.observe_fee(&treasury, Lamports(100), StLamports(100))
             .unwrap();
-        m.observe_fee_treasury(Lamports(100), StLamports(80))
+        m.observe_fee(&developer, Lamports(10), StLamports(10))
             .unwrap();
-        assert_eq!(m.fee_treasury_sol_total, Lamports(200));
-        assert_eq!(m.fee_treasury_st_sol_total, StLamports(180));
+        m.observe_fee(&treasury, Lamports(100), StLamports(80))
+            .unwrap();
+        assert_eq!(
+            m.fee_recipients,
+            vec![
+                FeeRecipientMetrics {
+                    st_sol_account: treasury,
+                    fee_sol_total: Lamports(200),
+                    fee_st_sol_total: StLamports(180),
+                },
+                FeeRecipientMetrics {
+                    st_sol_account: developer,
+                    fee_sol_total: Lamports(10),
+                    fee_st_sol_total: StLamports(10),
+                },
+            ]
+        );
+
+        // Totals of recipients that are no longer configured get dropped.
+        let reward_distribution = RewardDistribution {
+            fee_recipients: vec![FeeRecipient {
+                st_sol_account: developer,
+                parts: 1,
+            }],
+            st_sol_appreciation: 1,
+        };
+        m.retain_fee_recipients(&reward_distribution);
+        assert_eq!(m.fee_recipients.len(), 1);
+        assert_eq!(m.fee_recipients[0].st_sol_account, developer);
     }
 
     #[test]
@@ -261,22 +345,40 @@ mod test {
     }
 
     #[test]
-    fn test_metrics_observe_fee_developer() {
+    fn test_metrics_observe_reward_st_sol_appreciation() {
         let mut m = Metrics::new();
-        m.observe_fee_developer(Lamports(100), StLamports(100))
+        m.observe_reward_st_sol_appreciation(Lamports(100)).unwrap();
+        m.observe_reward_st_sol_appreciation(Lamports(200)).unwrap();
+        assert_eq!(m.st_sol_appreciation_sol_total, Lamports(300));
+    }
+
+    #[test]
+    fn test_metrics_observe_instant_unstake() {
+        let mut m = Metrics::new();
+        m.observe_instant_unstake(StLamports(90), Lamports(100), StLamports(10))
             .unwrap();
-        m.observe_fee_developer(Lamports(100), StLamports(80))
+        m.observe_instant_unstake(StLamports(180), Lamports(200), StLamports(20))
             .unwrap();
-        assert_eq!(m.fee_developer_sol_total, Lamports(200));
-        assert_eq!(m.fee_developer_st_sol_total, StLamports(180));
+        assert_eq!(m.instant_unstake_amount.count, 2);
+        assert_eq!(
+            m.instant_unstake_amount.total_st_sol_amount,
+            StLamports(270)
+        );
+        assert_eq!(m.instant_unstake_amount.total_sol_amount, Lamports(300));
+        assert_eq!(m.fee_instant_unstake_st_sol_total, StLamports(30));
     }
 
     #[test]
-    fn test_metrics_observe_reward_st_sol_appreciation() {
+    fn test_metrics_observe_withdrawal() {
         let mut m = Metrics::new();
-        m.observe_reward_st_sol_appreciation(Lamports(100)).unwrap();
-        m.observe_reward_st_sol_appreciation(Lamports(200)).unwrap();
-        assert_eq!(m.st_sol_appreciation_sol_total, Lamports(300));
+        m.observe_withdrawal(StLamports(90), Lamports(100), StLamports(10))
+            .unwrap();
+        m.observe_withdrawal(StLamports(180), Lamports(200), StLamports(0))
+            .unwrap();
+        assert_eq!(m.withdraw_amount.count, 2);
+        assert_eq!(m.withdraw_amount.total_st_sol_amount, StLamports(270));
+        assert_eq!(m.withdraw_amount.total_sol_amount, Lamports(300));
+        assert_eq!(m.fee_withdrawal_st_sol_total, StLamports(10));
     }
 
     #[test]
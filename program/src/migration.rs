@@ -0,0 +1,414 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! Previous layouts of the `Lido` state, and their migration to the current one.
+//!
+//! When the layout of `Lido` changes, `LIDO_VERSION` is bumped, and the old
+//! layout is frozen here so that `MigrateState` can still read it. The frozen
+//! types must never change, they describe data that is already on-chain.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{
+    borsh::try_from_slice_unchecked, msg, program_error::ProgramError, pubkey::Pubkey,
+};
+
+use crate::account_map::{AccountMap, AccountSet, EntryConstantSize, PubkeyAndEntry};
+use crate::error::LidoError;
+use crate::metrics::{FeeRecipientMetrics, LamportsHistogram, Metrics, WithdrawMetric};
+use crate::state::{
+    ExchangeRate, ExchangeRateHistory, FeeRecipient, Lido, Maintainers, ReserveBuffer,
+    RewardDistribution, Roles, SeedRange, Validator, Validators, DEFAULT_VALIDATOR_WEIGHT,
+    LIDO_VERSION, MAINTAINER_ALL,
+};
+use crate::token::{Lamports, StLamports};
+
+/// Version of the original `Lido` layout, that stored the validators inline.
+pub const LIDO_VERSION_V1: u8 = 1;
+
+/// Size of a serialized `ValidatorV1`.
+pub const VALIDATOR_V1_CONSTANT_SIZE: usize = 49;
+
+/// `Metrics` as stored by version 1, before instant unstake was tracked.
+#[repr(C)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct MetricsV1 {
+    pub fee_treasury_sol_total: Lamports,
+    pub fee_validation_sol_total: Lamports,
+    pub fee_developer_sol_total: Lamports,
+    pub st_sol_appreciation_sol_total: Lamports,
+    pub fee_treasury_st_sol_total: StLamports,
+    pub fee_validation_st_sol_total: StLamports,
+    pub fee_developer_st_sol_total: StLamports,
+    pub deposit_amount: LamportsHistogram,
+    pub withdraw_amount: WithdrawMetric,
+}
+
+/// `RewardDistribution` as stored by version 1, with a fixed set of fee recipients.
+#[repr(C)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct RewardDistributionV1 {
+    pub treasury_fee: u32,
+    pub developer_fee: u32,
+    pub st_sol_appreciation: u32,
+}
+
+/// The fee recipients as stored by version 1, separate from their shares.
+#[repr(C)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct FeeRecipientsV1 {
+    pub treasury_account: Pubkey,
+    pub developer_account: Pubkey,
+}
+
+/// `Validator` as stored by version 1, before validators had a weight.
+#[repr(C)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct ValidatorV1 {
+    pub stake_seeds: SeedRange,
+    pub unstake_seeds: SeedRange,
+    pub stake_accounts_balance: Lamports,
+    pub unstake_accounts_balance: Lamports,
+    pub active: bool,
+}
+
+impl EntryConstantSize for ValidatorV1 {
+    const SIZE: usize = VALIDATOR_V1_CONSTANT_SIZE;
+}
+
+/// `Lido` as stored by version 1, with the validators inline in the account.
+#[repr(C)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq)]
+pub struct LidoV1 {
+    pub lido_version: u8,
+    pub manager: Pubkey,
+    pub st_sol_mint: Pubkey,
+    pub exchange_rate: ExchangeRate,
+    pub sol_reserve_account_bump_seed: u8,
+    pub stake_authority_bump_seed: u8,
+    pub mint_authority_bump_seed: u8,
+    pub reward_distribution: RewardDistributionV1,
+    pub fee_recipients: FeeRecipientsV1,
+    pub metrics: MetricsV1,
+    pub validators: AccountMap<ValidatorV1>,
+    pub max_commission_percentage: u8,
+    pub maintainers: AccountSet,
+}
+
+impl LidoV1 {
+    /// Convert the state to the current layout.
+    ///
+    /// The validators move out of the `Lido` account, into the account at
+    /// `validator_list`. Fields that did not exist in version 1 get the same
+    /// values that `Initialize` gives them, so the migrated instance behaves
+    /// exactly as before until the manager changes them.
+    pub fn migrate(self, validator_list: Pubkey) -> (Lido, Validators) {
+        let validators = Validators {
+            entries: self
+                .validators
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: Validator {
+                        stake_seeds: pe.entry.stake_seeds,
+                        unstake_seeds: pe.entry.unstake_seeds,
+                        stake_accounts_balance: pe.entry.stake_accounts_balance,
+                        unstake_accounts_balance: pe.entry.unstake_accounts_balance,
+                        active: pe.entry.active,
+                        weight: DEFAULT_VALIDATOR_WEIGHT,
+                        directed_stake: Lamports(0),
+                        added_in_epoch: 0,
+                    },
+                })
+                .collect(),
+            maximum_entries: self.validators.maximum_entries,
+        };
+        // Existing maintainers keep all the permissions they had before.
+        let maintainers = Maintainers {
+            entries: self
+                .maintainers
+                .entries
+                .into_iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: MAINTAINER_ALL,
+                })
+                .collect(),
+            maximum_entries: self.maintainers.maximum_entries,
+        };
+
+        // The treasury becomes the first fee recipient, and the developer the
+        // second one.
+        let reward_distribution = RewardDistribution {
+            fee_recipients: vec![
+                FeeRecipient {
+                    st_sol_account: self.fee_recipients.treasury_account,
+                    parts: self.reward_distribution.treasury_fee,
+                },
+                FeeRecipient {
+                    st_sol_account: self.fee_recipients.developer_account,
+                    parts: self.reward_distribution.developer_fee,
+                },
+            ],
+            st_sol_appreciation: self.reward_distribution.st_sol_appreciation,
+        };
+
+        let metrics = Metrics {
+            fee_recipients: vec![
+                FeeRecipientMetrics {
+                    st_sol_account: self.fee_recipients.treasury_account,
+                    fee_sol_total: self.metrics.fee_treasury_sol_total,
+                    fee_st_sol_total: self.metrics.fee_treasury_st_sol_total,
+                },
+                FeeRecipientMetrics {
+                    st_sol_account: self.fee_recipients.developer_account,
+                    fee_sol_total: self.metrics.fee_developer_sol_total,
+                    fee_st_sol_total: self.metrics.fee_developer_st_sol_total,
+                },
+            ],
+            fee_validation_sol_total: self.metrics.fee_validation_sol_total,
+            st_sol_appreciation_sol_total: self.metrics.st_sol_appreciation_sol_total,
+            fee_validation_st_sol_total: self.metrics.fee_validation_st_sol_total,
+            deposit_amount: self.metrics.deposit_amount,
+            withdraw_amount: self.metrics.withdraw_amount,
+            instant_unstake_amount: WithdrawMetric::default(),
+            fee_instant_unstake_st_sol_total: StLamports(0),
+            fee_withdrawal_st_sol_total: StLamports(0),
+        };
+
+        let lido = Lido {
+            lido_version: LIDO_VERSION,
+            manager: self.manager,
+            st_sol_mint: self.st_sol_mint,
+            exchange_rate: self.exchange_rate,
+            sol_reserve_account_bump_seed: self.sol_reserve_account_bump_seed,
+            stake_authority_bump_seed: self.stake_authority_bump_seed,
+            mint_authority_bump_seed: self.mint_authority_bump_seed,
+            reward_distribution,
+            metrics,
+            validator_list,
+            max_commission_percentage: self.max_commission_percentage,
+            instant_unstake_fee_bps: 0,
+            withdrawal_tickets_owed: Lamports(0),
+            min_vote_credits: 0,
+            vote_credits_epochs: 1,
+            pending_manager: Pubkey::default(),
+            paused_operations: 0,
+            exchange_rate_history: ExchangeRateHistory::default(),
+            max_total_sol: Lamports(0),
+            min_deposit: Lamports(0),
+            max_directed_stake_bps: 0,
+            reserve_buffer: ReserveBuffer::default(),
+            withdrawal_fee_bps: 0,
+            roles: Roles::new_all(self.manager),
+            maintainers,
+        };
+
+        (lido, validators)
+    }
+}
+
+/// The `Lido` state in any of the layouts that the program can read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VersionedLido {
+    V1(Box<LidoV1>),
+    Current(Box<Lido>),
+}
+
+impl VersionedLido {
+    /// Deserialize the state, using the layout indicated by its version byte.
+    pub fn deserialize(data: &[u8]) -> Result<VersionedLido, ProgramError> {
+        match data.first() {
+            Some(&LIDO_VERSION_V1) => {
+                Ok(VersionedLido::V1(Box::new(try_from_slice_unchecked(data)?)))
+            }
+            Some(&LIDO_VERSION) => Ok(VersionedLido::Current(Box::new(try_from_slice_unchecked(
+                data,
+            )?))),
+            Some(version) => {
+                msg!("Unknown Solido state version {}.", version);
+                Err(LidoError::InvalidLidoVersion.into())
+            }
+            None => {
+                msg!("Solido state is empty.");
+                Err(LidoError::InvalidLidoSize.into())
+            }
+        }
+    }
+
+    /// Return the version of the layout.
+    pub fn version(&self) -> u8 {
+        match self {
+            VersionedLido::V1(_) => LIDO_VERSION_V1,
+            VersionedLido::Current(_) => LIDO_VERSION,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use solana_program::borsh::get_instance_packed_len;
+
+    fn new_lido_v1() -> LidoV1 {
+        let mut validators = AccountMap::new(3);
+        validators
+            .add(
+                Pubkey::new_unique(),
+                ValidatorV1 {
+                    stake_seeds: SeedRange { begin: 2, end: 5 },
+                    unstake_seeds: SeedRange { begin: 1, end: 1 },
+                    stake_accounts_balance: Lamports(300),
+                    unstake_accounts_balance: Lamports(0),
+                    active: false,
+                },
+            )
+            .unwrap();
+        let mut maintainers = AccountSet::new(2);
+        maintainers.add(Pubkey::new_unique(), ()).unwrap();
+
+        LidoV1 {
+            lido_version: LIDO_VERSION_V1,
+            manager: Pubkey::new_unique(),
+            st_sol_mint: Pubkey::new_unique(),
+            exchange_rate: ExchangeRate {
+                computed_in_epoch: 7,
+                st_sol_supply: StLamports(100),
+                sol_balance: Lamports(110),
+            },
+            sol_reserve_account_bump_seed: 1,
+            stake_authority_bump_seed: 2,
+            mint_authority_bump_seed: 3,
+            reward_distribution: RewardDistributionV1 {
+                treasury_fee: 2,
+                developer_fee: 1,
+                st_sol_appreciation: 7,
+            },
+            fee_recipients: FeeRecipientsV1 {
+                treasury_account: Pubkey::new_unique(),
+                developer_account: Pubkey::new_unique(),
+            },
+            metrics: MetricsV1 {
+                fee_treasury_sol_total: Lamports(5),
+                withdraw_amount: WithdrawMetric {
+                    total_st_sol_amount: StLamports(9),
+                    total_sol_amount: Lamports(10),
+                    count: 1,
+                },
+                ..MetricsV1::default()
+            },
+            validators,
+            max_commission_percentage: 5,
+            maintainers,
+        }
+    }
+
+    #[test]
+    fn test_validator_v1_constant_size() {
+        let validator = ValidatorV1::default();
+        assert_eq!(
+            get_instance_packed_len(&validator).unwrap(),
+            VALIDATOR_V1_CONSTANT_SIZE
+        );
+    }
+
+    #[test]
+    fn test_deserialize_dispatches_on_version() {
+        let lido_v1 = new_lido_v1();
+        let mut data = lido_v1.try_to_vec().unwrap();
+        // Accounts are usually larger than the serialized state.
+        data.extend_from_slice(&[0; 50]);
+        assert_eq!(
+            VersionedLido::deserialize(&data),
+            Ok(VersionedLido::V1(Box::new(lido_v1.clone())))
+        );
+
+        let (lido, _validators) = lido_v1.migrate(Pubkey::new_unique());
+        let data = lido.try_to_vec().unwrap();
+        assert_eq!(
+            VersionedLido::deserialize(&data),
+            Ok(VersionedLido::Current(Box::new(lido)))
+        );
+
+        assert_eq!(
+            VersionedLido::deserialize(&[LIDO_VERSION + 1, 0, 0]),
+            Err(LidoError::InvalidLidoVersion.into())
+        );
+        assert_eq!(
+            VersionedLido::deserialize(&[]),
+            Err(LidoError::InvalidLidoSize.into())
+        );
+    }
+
+    #[test]
+    fn test_migrate_v1_preserves_fields_and_sets_defaults() {
+        let lido_v1 = new_lido_v1();
+        let validator_list = Pubkey::new_unique();
+        let (lido, validators) = lido_v1.clone().migrate(validator_list);
+
+        assert_eq!(lido.lido_version, LIDO_VERSION);
+        assert_eq!(lido.manager, lido_v1.manager);
+        assert_eq!(lido.exchange_rate, lido_v1.exchange_rate);
+        assert_eq!(
+            lido.reward_distribution,
+            RewardDistribution {
+                fee_recipients: vec![
+                    FeeRecipient {
+                        st_sol_account: lido_v1.fee_recipients.treasury_account,
+                        parts: 2,
+                    },
+                    FeeRecipient {
+                        st_sol_account: lido_v1.fee_recipients.developer_account,
+                        parts: 1,
+                    },
+                ],
+                st_sol_appreciation: 7,
+            }
+        );
+        assert_eq!(
+            lido.reward_distribution.treasury_account(),
+            Some(&lido_v1.fee_recipients.treasury_account)
+        );
+        assert_eq!(lido.metrics.fee_recipients[0].fee_sol_total, Lamports(5));
+        assert_eq!(lido.metrics.fee_recipients[1].fee_sol_total, Lamports(0));
+        assert_eq!(lido.metrics.withdraw_amount.count, 1);
+        assert_eq!(
+            lido.metrics.instant_unstake_amount,
+            WithdrawMetric::default()
+        );
+        assert_eq!(lido.validator_list, validator_list);
+        assert_eq!(lido.max_commission_percentage, 5);
+        assert_eq!(lido.instant_unstake_fee_bps, 0);
+        assert_eq!(lido.withdrawal_tickets_owed, Lamports(0));
+        assert_eq!(lido.min_vote_credits, 0);
+        assert_eq!(lido.vote_credits_epochs, 1);
+        assert_eq!(lido.pending_manager, Pubkey::default());
+        assert_eq!(lido.paused_operations, 0);
+        assert_eq!(lido.max_total_sol, Lamports(0));
+        assert_eq!(lido.min_deposit, Lamports(0));
+        assert_eq!(lido.max_directed_stake_bps, 0);
+        assert_eq!(lido.reserve_buffer, ReserveBuffer::default());
+        assert_eq!(lido.withdrawal_fee_bps, 0);
+        assert_eq!(lido.roles, Roles::new_all(lido_v1.manager));
+        assert_eq!(lido.maintainers.maximum_entries, 2);
+        assert_eq!(lido.maintainers.len(), 1);
+        assert_eq!(
+            lido.maintainers.entries[0].pubkey,
+            lido_v1.maintainers.entries[0].pubkey
+        );
+        assert_eq!(lido.maintainers.entries[0].entry, MAINTAINER_ALL);
+
+        assert_eq!(validators.maximum_entries, 3);
+        assert_eq!(validators.len(), 1);
+        let old = &lido_v1.validators.entries[0];
+        let new = &validators.entries[0];
+        assert_eq!(new.pubkey, old.pubkey);
+        assert_eq!(new.entry.stake_seeds, old.entry.stake_seeds);
+        assert_eq!(new.entry.stake_accounts_balance, Lamports(300));
+        assert!(!new.entry.active);
+        assert_eq!(new.entry.weight, DEFAULT_VALIDATOR_WEIGHT);
+        assert_eq!(new.entry.directed_stake, Lamports(0));
+        assert_eq!(new.entry.added_in_epoch, 0);
+    }
+}
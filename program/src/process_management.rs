@@ -1,24 +1,41 @@
 // SPDX-FileCopyrightText: 2021 Chorus One AG
 // SPDX-License-Identifier: GPL-3.0
 
+use solana_program::clock::Clock;
 use solana_program::rent::Rent;
 use solana_program::sysvar::Sysvar;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey};
-use solana_program::{program::invoke_signed, program_error::ProgramError};
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    system_instruction,
+};
 
-use crate::logic::check_rent_exempt;
+use crate::events::SolidoEvent;
+use crate::logic::{check_new_validator_list, check_rent_exempt};
+use crate::migration::{VersionedLido, LIDO_VERSION_V1};
 use crate::processor::StakeType;
-use crate::state::Lido;
+use crate::state::{Lido, Validators, LIDO_VERSION};
 use crate::vote_state::PartialVoteState;
 use crate::{
     error::LidoError,
     instruction::{
-        AddMaintainerInfo, AddValidatorInfoV2, ChangeRewardDistributionInfo,
-        DeactivateValidatorIfCommissionExceedsMaxInfo, DeactivateValidatorInfo, MergeStakeInfo,
-        RemoveMaintainerInfo, RemoveValidatorInfo, SetMaxValidationCommissionInfo,
+        AcceptManagerInfo, AddMaintainerInfo, AddValidatorInfoV2, ChangeFeeRecipientsInfo,
+        ChangeRewardDistributionInfo, DeactivateValidatorIfCommissionExceedsMaxInfo,
+        DeactivateValidatorIfDelinquentInfo, DeactivateValidatorInfo,
+        EmergencyUnstakeValidatorInfo, MergeStakeInfo, MigrateStateInfo, ProposeNewManagerInfo,
+        RemoveMaintainerInfo, RemoveValidatorInfo, ResizeValidatorListInfo, SetDepositLimitsInfo,
+        SetInstantUnstakeFeeInfo, SetMaintainerPermissionsInfo, SetMaxDirectedStakeInfo,
+        SetMaxValidationCommissionInfo, SetMinVoteCreditsInfo, SetPausedOperationsInfo,
+        SetReserveBufferInfo, SetRoleInfo, SetValidatorWeightInfo, SetWithdrawalFeeInfo,
+    },
+    state::{ReserveBuffer, RewardDistribution, Role, Validator, MAINTAINER_ALL, PAUSE_ALL},
+    token::Lamports,
+    vote_state::{
+        can_judge_vote_credits, get_credits_in_last_epochs, get_vote_account_commission,
+        get_vote_account_epoch_credits, MAX_EPOCH_CREDITS_HISTORY,
     },
-    state::{RewardDistribution, Validator},
-    vote_state::get_vote_account_commission,
     STAKE_AUTHORITY,
 };
 
@@ -29,23 +46,28 @@ pub fn process_change_reward_distribution(
 ) -> ProgramResult {
     let accounts = ChangeRewardDistributionInfo::try_from_slice(accounts_raw)?;
     let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    lido.check_manager(accounts.manager)?;
-
-    lido.check_is_st_sol_account(accounts.treasury_account)?;
-    lido.check_is_st_sol_account(accounts.developer_account)?;
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
 
+    new_reward_distribution.check_fee_recipients()?;
     lido.reward_distribution = new_reward_distribution;
-    lido.fee_recipients.treasury_account = *accounts.treasury_account.key;
-    lido.fee_recipients.developer_account = *accounts.developer_account.key;
+    lido.check_fee_recipient_st_sol_accounts(accounts.fee_recipients)?;
+    lido.metrics
+        .retain_fee_recipients(&lido.reward_distribution);
+
+    SolidoEvent::RewardDistributionChanged {
+        reward_distribution: lido.reward_distribution.clone(),
+    }
+    .emit();
 
     lido.save(accounts.lido)
 }
 
 pub fn process_add_validator(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
     let accounts = AddValidatorInfoV2::try_from_slice(accounts_raw)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
     let rent = &Rent::get()?;
-    lido.check_manager(accounts.manager)?;
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
 
     check_rent_exempt(
         rent,
@@ -61,34 +83,46 @@ pub fn process_add_validator(program_id: &Pubkey, accounts_raw: &[AccountInfo])
         lido.max_commission_percentage,
     )?;
 
-    lido.validators
-        .add(*accounts.validator_vote_account.key, Validator::new())?;
+    let validator = Validator {
+        added_in_epoch: Clock::get()?.epoch,
+        ..Validator::new()
+    };
+    validators.add(*accounts.validator_vote_account.key, validator)?;
 
-    lido.save(accounts.lido)
+    SolidoEvent::ValidatorAdded {
+        validator_vote_account: *accounts.validator_vote_account.key,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
 }
 
 /// Remove a validator.
 ///
 /// This instruction is the final cleanup step in the validator removal process,
 /// and it is callable by anybody. Initiation of the removal (`DeactivateValidator`)
-/// is restricted to the manager, but once a validator is inactive, and there is
+/// is restricted to the validator admin, but once a validator is inactive, and there is
 /// no more stake delegated to it, removing it from the list can be done by anybody.
 pub fn process_remove_validator(
     program_id: &Pubkey,
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = RemoveValidatorInfo::try_from_slice(accounts_raw)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
 
-    let removed_validator = lido
-        .validators
-        .remove(accounts.validator_vote_account_to_remove.key)?;
+    let removed_validator = validators.remove(accounts.validator_vote_account_to_remove.key)?;
 
     let result = removed_validator.check_can_be_removed();
     Validator::show_removed_error_msg(&result);
     result?;
 
-    lido.save(accounts.lido)
+    SolidoEvent::ValidatorRemoved {
+        validator_vote_account: *accounts.validator_vote_account_to_remove.key,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
 }
 
 /// Set the `active` flag to false for a given validator.
@@ -100,17 +134,99 @@ pub fn process_deactivate_validator(
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = DeactivateValidatorInfo::try_from_slice(accounts_raw)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    lido.check_manager(accounts.manager)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account_to_deactivate.key)?;
+    let validator = validators.get_mut(accounts.validator_vote_account_to_deactivate.key)?;
 
     validator.entry.active = false;
     msg!("Validator {} deactivated.", validator.pubkey);
+    SolidoEvent::ValidatorDeactivated {
+        validator_vote_account: validator.pubkey,
+    }
+    .emit();
 
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)
+}
+
+/// Checks if a validator earned fewer vote credits than `min_vote_credits`
+/// over the last `vote_credits_epochs` completed epochs, and deactivates it
+/// if it did.
+pub fn process_deactivate_validator_if_delinquent(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = DeactivateValidatorIfDelinquentInfo::try_from_slice(accounts_raw)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let clock = Clock::get()?;
+
+    let vote_account = accounts.validator_vote_account_to_deactivate;
+    if vote_account.owner != &solana_program::vote::program::id() {
+        msg!(
+            "Expected validator's vote account to be owned by {}, it's owned by {} instead.",
+            solana_program::vote::program::id(),
+            vote_account.owner
+        );
+        return Err(LidoError::ValidatorVoteAccountHasDifferentOwner.into());
+    }
+
+    let data = vote_account.data.borrow();
+    let epoch_credits = match get_vote_account_epoch_credits(&data) {
+        Some(epoch_credits) => epoch_credits,
+        None => {
+            msg!(
+                "Could not read the credits of vote account {}, its layout is not supported.",
+                vote_account.key
+            );
+            return Err(LidoError::InvalidVoteAccount.into());
+        }
+    };
+
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator = validators.get_mut(accounts.validator_vote_account_to_deactivate.key)?;
+
+    if !validator.entry.active {
+        return Ok(());
+    }
+
+    if !can_judge_vote_credits(
+        &epoch_credits,
+        validator.entry.added_in_epoch,
+        clock.epoch,
+        lido.vote_credits_epochs,
+    ) {
+        msg!(
+            "Vote account {} has credits for {} epochs and was added in epoch {}, \
+            too recently to judge it over {} epochs.",
+            vote_account.key,
+            epoch_credits.len(),
+            validator.entry.added_in_epoch,
+            lido.vote_credits_epochs,
+        );
+        return Ok(());
+    }
+    let credits = get_credits_in_last_epochs(&epoch_credits, clock.epoch, lido.vote_credits_epochs);
+
+    if credits >= lido.min_vote_credits {
+        return Ok(());
+    }
+
+    validator.entry.active = false;
+    msg!(
+        "Validator {} earned {} vote credits in the last {} epochs, less than the minimum of {}.",
+        validator.pubkey,
+        credits,
+        lido.vote_credits_epochs,
+        lido.min_vote_credits,
+    );
+    msg!("Validator {} deactivated.", validator.pubkey);
+    SolidoEvent::ValidatorDeactivated {
+        validator_vote_account: validator.pubkey,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
 }
 
 /// Set the `active` flag to false for a given validator if it's commission is
@@ -123,7 +239,7 @@ pub fn process_deactivate_validator_if_commission_exceeds_max(
     accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = DeactivateValidatorIfCommissionExceedsMaxInfo::try_from_slice(accounts_raw)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
 
     let data = accounts.validator_vote_account_to_deactivate.data.borrow();
     let commission = get_vote_account_commission(&data).ok_or(ProgramError::AccountDataTooSmall)?;
@@ -132,9 +248,8 @@ pub fn process_deactivate_validator_if_commission_exceeds_max(
         return Ok(());
     }
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account_to_deactivate.key)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator = validators.get_mut(accounts.validator_vote_account_to_deactivate.key)?;
 
     if !validator.entry.active {
         return Ok(());
@@ -142,17 +257,28 @@ pub fn process_deactivate_validator_if_commission_exceeds_max(
 
     validator.entry.active = false;
     msg!("Validator {} deactivated.", validator.pubkey);
+    SolidoEvent::ValidatorDeactivated {
+        validator_vote_account: validator.pubkey,
+    }
+    .emit();
 
-    lido.save(accounts.lido)
+    validators.save(accounts.validator_list)
 }
 
-/// Adds a maintainer to the list of maintainers
+/// Adds a maintainer to the list of maintainers, with permission to perform
+/// all maintenance operations.
 pub fn process_add_maintainer(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
     let accounts = AddMaintainerInfo::try_from_slice(accounts_raw)?;
     let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    lido.check_manager(accounts.manager)?;
+    lido.check_role(Role::MaintainerAdmin, accounts.manager)?;
 
-    lido.maintainers.add(*accounts.maintainer.key, ())?;
+    lido.maintainers
+        .add(*accounts.maintainer.key, MAINTAINER_ALL)?;
+
+    SolidoEvent::MaintainerAdded {
+        maintainer: *accounts.maintainer.key,
+    }
+    .emit();
 
     lido.save(accounts.lido)
 }
@@ -164,10 +290,15 @@ pub fn process_remove_maintainer(
 ) -> ProgramResult {
     let accounts = RemoveMaintainerInfo::try_from_slice(accounts_raw)?;
     let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    lido.check_manager(accounts.manager)?;
+    lido.check_role(Role::MaintainerAdmin, accounts.manager)?;
 
     lido.maintainers.remove(accounts.maintainer.key)?;
 
+    SolidoEvent::MaintainerRemoved {
+        maintainer: *accounts.maintainer.key,
+    }
+    .emit();
+
     lido.save(accounts.lido)
 }
 
@@ -185,21 +316,416 @@ pub fn process_set_max_commission_percentage(
     let accounts = SetMaxValidationCommissionInfo::try_from_slice(accounts_raw)?;
     let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
 
-    lido.check_manager(accounts.manager)?;
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
 
     lido.max_commission_percentage = max_commission_percentage;
 
+    SolidoEvent::MaxCommissionPercentageChanged {
+        max_commission_percentage,
+    }
+    .emit();
+
     lido.save(accounts.lido)
 }
 
-/// TODO(#186) Allow validator to change fee account
-/// Called by the validator, changes the fee account which the validator
-/// receives tokens
-pub fn _process_change_validator_fee_account(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
+/// Sets the fee charged on `InstantUnstake`, in basis points.
+pub fn process_set_instant_unstake_fee(
+    program_id: &Pubkey,
+    instant_unstake_fee_bps: u64,
+    accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
-    unimplemented!()
+    if instant_unstake_fee_bps > 10_000 {
+        return Err(LidoError::InstantUnstakeFeeOutOfBounds.into());
+    }
+
+    let accounts = SetInstantUnstakeFeeInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
+
+    lido.instant_unstake_fee_bps = instant_unstake_fee_bps;
+
+    SolidoEvent::InstantUnstakeFeeChanged {
+        instant_unstake_fee_bps,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Sets the minimum number of vote credits that validators must earn, and the
+/// number of epochs to count them over.
+pub fn process_set_min_vote_credits(
+    program_id: &Pubkey,
+    min_vote_credits: u64,
+    vote_credits_epochs: u64,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if vote_credits_epochs == 0 || vote_credits_epochs > MAX_EPOCH_CREDITS_HISTORY {
+        msg!(
+            "Vote credits must be counted over 1 to {} epochs, got {}.",
+            MAX_EPOCH_CREDITS_HISTORY,
+            vote_credits_epochs
+        );
+        return Err(LidoError::InvalidVoteCreditsEpochs.into());
+    }
+
+    let accounts = SetMinVoteCreditsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+
+    lido.min_vote_credits = min_vote_credits;
+    lido.vote_credits_epochs = vote_credits_epochs;
+
+    SolidoEvent::MinVoteCreditsChanged {
+        min_vote_credits,
+        vote_credits_epochs,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Sets the weight of a validator, which determines its share of the stake.
+pub fn process_set_validator_weight(
+    program_id: &Pubkey,
+    weight: u32,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = SetValidatorWeightInfo::try_from_slice(accounts_raw)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator = validators.get_mut(accounts.validator_vote_account.key)?;
+    validator.entry.weight = weight;
+
+    msg!(
+        "Solido: Set weight of validator {} to {}.",
+        accounts.validator_vote_account.key,
+        weight
+    );
+    SolidoEvent::ValidatorWeightChanged {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        weight,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
+}
+
+/// Proposes a new manager, which takes over once it accepts with `AcceptManager`.
+pub fn process_propose_new_manager(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ProposeNewManagerInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+
+    lido.check_manager(accounts.manager)?;
+
+    lido.pending_manager = *accounts.new_manager.key;
+    msg!(
+        "Solido: Proposed {} as the new manager.",
+        lido.pending_manager
+    );
+    SolidoEvent::NewManagerProposed {
+        pending_manager: lido.pending_manager,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Makes the pending manager the manager, it needs to sign the transaction.
+///
+/// Roles that are still held by the old manager move to the new manager, so the
+/// old manager keeps no administrative powers after a handover.
+pub fn process_accept_manager(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = AcceptManagerInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+
+    lido.check_pending_manager(accounts.new_manager)?;
+
+    msg!(
+        "Solido: Manager changed from {} to {}.",
+        lido.manager,
+        lido.pending_manager
+    );
+    SolidoEvent::ManagerChanged {
+        old_manager: lido.manager,
+        new_manager: lido.pending_manager,
+    }
+    .emit();
+    for &role in Role::ALL.iter() {
+        if lido.roles.get(role) == &lido.manager {
+            *lido.roles.get_mut(role) = lido.pending_manager;
+            SolidoEvent::RoleChanged {
+                role,
+                old_authority: lido.manager,
+                new_authority: lido.pending_manager,
+            }
+            .emit();
+        }
+    }
+    lido.manager = lido.pending_manager;
+    lido.pending_manager = Pubkey::default();
+
+    lido.save(accounts.lido)
+}
+
+/// Change the stSOL accounts that receive fees, keeping the shares of the recipients.
+///
+/// Requires the fee admin to sign.
+pub fn process_change_fee_recipients(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ChangeFeeRecipientsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
+
+    if accounts.fee_recipients.len() != lido.reward_distribution.fee_recipients.len() {
+        msg!(
+            "Expected a new stSOL account for each of the {} fee recipients, but got {}.",
+            lido.reward_distribution.fee_recipients.len(),
+            accounts.fee_recipients.len()
+        );
+        return Err(LidoError::InvalidFeeRecipient.into());
+    }
+    for (recipient, st_sol_account) in lido
+        .reward_distribution
+        .fee_recipients
+        .iter_mut()
+        .zip(accounts.fee_recipients)
+    {
+        recipient.st_sol_account = *st_sol_account.key;
+    }
+    lido.reward_distribution.check_fee_recipients()?;
+    lido.check_fee_recipient_st_sol_accounts(accounts.fee_recipients)?;
+    lido.metrics
+        .retain_fee_recipients(&lido.reward_distribution);
+
+    SolidoEvent::FeeRecipientsChanged {
+        fee_recipients: accounts.fee_recipients.iter().map(|a| *a.key).collect(),
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Set which operations are paused.
+///
+/// Requires the emergency guardian to sign.
+pub fn process_set_paused_operations(
+    program_id: &Pubkey,
+    paused_operations: u8,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if paused_operations & !PAUSE_ALL != 0 {
+        msg!(
+            "Invalid paused operations {:#010b}, only bits in {:#010b} can be set.",
+            paused_operations,
+            PAUSE_ALL
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let accounts = SetPausedOperationsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::EmergencyGuardian, accounts.manager)?;
+
+    msg!(
+        "Solido: Paused operations changed from {:#010b} to {:#010b}.",
+        lido.paused_operations,
+        paused_operations
+    );
+    lido.paused_operations = paused_operations;
+
+    SolidoEvent::PausedOperationsChanged { paused_operations }.emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Set the maximum SOL under management and the minimum deposit.
+///
+/// Requires the fee admin to sign.
+pub fn process_set_deposit_limits(
+    program_id: &Pubkey,
+    max_total_sol: Lamports,
+    min_deposit: Lamports,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = SetDepositLimitsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
+
+    msg!(
+        "Solido: Deposit limits changed from maximum total {} and minimum deposit {}, to maximum total {} and minimum deposit {}.",
+        lido.max_total_sol,
+        lido.min_deposit,
+        max_total_sol,
+        min_deposit
+    );
+    lido.max_total_sol = max_total_sol;
+    lido.min_deposit = min_deposit;
+
+    SolidoEvent::DepositLimitsChanged {
+        max_total_sol,
+        min_deposit,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Grow the validator list account so it can hold `max_validators` validators.
+///
+/// The funder pays for the additional rent. Requires the validator admin to sign.
+pub fn process_resize_validator_list(
+    program_id: &Pubkey,
+    max_validators: u32,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ResizeValidatorListInfo::try_from_slice(accounts_raw)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+
+    if max_validators <= validators.maximum_entries {
+        msg!(
+            "The validator list can already hold {} validators, it can only grow, not shrink to {}.",
+            validators.maximum_entries,
+            max_validators
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The account may already be larger than needed, if the caller allocated
+    // more space up front. Then there is nothing to grow.
+    let old_size = accounts.validator_list.data_len();
+    let new_size = Validators::required_bytes(max_validators as usize);
+    if new_size.saturating_sub(old_size) > MAX_PERMITTED_DATA_INCREASE {
+        msg!(
+            "Growing the validator list from {} to {} bytes exceeds the maximum increase of {} bytes per instruction.",
+            old_size,
+            new_size,
+            MAX_PERMITTED_DATA_INCREASE
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if new_size > old_size {
+        grow_account(
+            accounts.validator_list,
+            accounts.funder,
+            accounts.system_program,
+            new_size,
+        )?;
+    }
+
+    msg!(
+        "Solido: Resized validator list from {} to {} validators.",
+        validators.maximum_entries,
+        max_validators
+    );
+    validators.maximum_entries = max_validators;
+
+    SolidoEvent::ValidatorListResized {
+        maximum_entries: max_validators,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
+}
+
+/// Grow `account` to `new_size` bytes, and top it up from `funder` so it stays
+/// rent exempt at its new size.
+fn grow_account<'a>(
+    account: &AccountInfo<'a>,
+    funder: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let lamports_needed = rent
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if lamports_needed > 0 {
+        invoke(
+            &system_instruction::transfer(funder.key, account.key, lamports_needed),
+            &[funder.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_size, false)
+}
+
+/// Convert the Solido state from version 1 to the current version.
+///
+/// See `LidoInstruction::MigrateState` for the requirements on the accounts.
+pub fn process_migrate_state(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = MigrateStateInfo::try_from_slice(accounts_raw)?;
+    if accounts.lido.owner != program_id {
+        msg!(
+            "Lido state is owned by {}, but should be owned by the Lido program ({}).",
+            accounts.lido.owner,
+            program_id
+        );
+        return Err(LidoError::InvalidOwner.into());
+    }
+
+    let lido_v1 = match VersionedLido::deserialize(&accounts.lido.data.borrow())? {
+        VersionedLido::V1(lido_v1) => lido_v1,
+        VersionedLido::Current(_) => {
+            msg!(
+                "Lido state is at version {} already, there is nothing to migrate.",
+                LIDO_VERSION
+            );
+            return Err(LidoError::InvalidLidoVersion.into());
+        }
+    };
+
+    let rent = Rent::get()?;
+    check_new_validator_list(
+        program_id,
+        &rent,
+        accounts.validator_list,
+        lido_v1.validators.maximum_entries,
+    )?;
+
+    let (lido, validators) = lido_v1.migrate(*accounts.validator_list.key);
+    lido.check_manager(accounts.manager)?;
+
+    // Without the validators, the new layout is usually smaller than the old
+    // one, but for instances with few validators it may not fit.
+    let new_size = Lido::calculate_size(lido.maintainers.maximum_entries);
+    if accounts.lido.data_len() < new_size {
+        grow_account(
+            accounts.lido,
+            accounts.funder,
+            accounts.system_program,
+            new_size,
+        )?;
+    }
+
+    msg!(
+        "Solido: Migrated state from version {} to {}, moved {} validators to {}.",
+        LIDO_VERSION_V1,
+        LIDO_VERSION,
+        validators.len(),
+        accounts.validator_list.key
+    );
+    SolidoEvent::StateMigrated {
+        from_version: LIDO_VERSION_V1,
+        to_version: LIDO_VERSION,
+        validator_list: *accounts.validator_list.key,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)?;
+    lido.save(accounts.lido)
 }
 
 /// Merge two stake accounts from the beginning of the validator's stake
@@ -211,11 +737,10 @@ pub fn _process_change_validator_fee_account(
 /// All fully active stake accounts precede the activating stake accounts.
 pub fn process_merge_stake(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
     let accounts = MergeStakeInfo::try_from_slice(accounts_raw)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
 
-    let mut validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let mut validator = validators.get_mut(accounts.validator_vote_account.key)?;
     let from_seed = validator.entry.stake_seeds.begin;
     let to_seed = validator.entry.stake_seeds.begin + 1;
 
@@ -288,5 +813,259 @@ pub fn process_merge_stake(program_id: &Pubkey, accounts_raw: &[AccountInfo]) ->
         ]],
     )?;
 
+    SolidoEvent::StakeMerged {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        from_seed,
+        to_seed,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
+}
+
+/// Set the maximum directed stake per validator.
+///
+/// Requires the validator admin to sign.
+pub fn process_set_max_directed_stake(
+    program_id: &Pubkey,
+    max_directed_stake_bps: u64,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if max_directed_stake_bps > 10_000 {
+        return Err(LidoError::MaxDirectedStakeOutOfBounds.into());
+    }
+
+    let accounts = SetMaxDirectedStakeInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::ValidatorAdmin, accounts.manager)?;
+
+    msg!(
+        "Solido: Max directed stake changed from {} to {} basis points.",
+        lido.max_directed_stake_bps,
+        max_directed_stake_bps
+    );
+    lido.max_directed_stake_bps = max_directed_stake_bps;
+
+    SolidoEvent::MaxDirectedStakeChanged {
+        max_directed_stake_bps,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Deactivate a validator and deactivate all of its stake accounts in place.
+///
+/// Requires the emergency guardian to sign.
+pub fn process_emergency_unstake_validator(
+    program_id: &Pubkey,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = EmergencyUnstakeValidatorInfo::try_from_slice(accounts_raw)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::EmergencyGuardian, accounts.manager)?;
+    lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator = validators.get_mut(accounts.validator_vote_account.key)?;
+
+    let n_stake_accounts = validator.entry.stake_seeds.end - validator.entry.stake_seeds.begin;
+    let n_accounts = accounts.stake_accounts.len() as u64;
+    if n_accounts != n_stake_accounts {
+        msg!(
+            "Wrong number of accounts provided, expected {} stake accounts, but got {} accounts.",
+            n_stake_accounts,
+            n_accounts
+        );
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    let mut amount = Lamports(0);
+    for (seed, stake_account) in validator
+        .entry
+        .stake_seeds
+        .into_iter()
+        .zip(accounts.stake_accounts.iter())
+    {
+        let (stake_account_address, _) = validator.find_stake_account_address(
+            program_id,
+            accounts.lido.key,
+            seed,
+            StakeType::Stake,
+        );
+        if &stake_account_address != stake_account.key {
+            msg!(
+                "Wrong stake account provided for seed {}: expected {} but got {}.",
+                seed,
+                stake_account_address,
+                stake_account.key,
+            );
+            return Err(LidoError::InvalidStakeAccount.into());
+        }
+
+        let deactivate_stake_instruction = solana_program::stake::instruction::deactivate_stake(
+            stake_account.key,
+            accounts.stake_authority.key,
+        );
+        invoke_signed(
+            &deactivate_stake_instruction,
+            &[
+                stake_account.clone(),
+                accounts.sysvar_clock.clone(),
+                accounts.stake_authority.clone(),
+                accounts.stake_program.clone(),
+            ],
+            &[&[
+                &accounts.lido.key.to_bytes(),
+                STAKE_AUTHORITY,
+                &[lido.stake_authority_bump_seed],
+            ]],
+        )?;
+
+        amount = (amount + Lamports(stake_account.lamports()))?;
+    }
+
+    // The stake stays in the stake accounts while it deactivates. Once it is
+    // inactive, `UpdateStakeAccountBalance` withdraws it into the reserve and
+    // removes the stake accounts, after which the validator can be removed.
+    validator.entry.active = false;
+
+    msg!(
+        "Solido: Validator {} deactivated, unstaking {} from {} stake accounts.",
+        validator.pubkey,
+        amount,
+        n_stake_accounts
+    );
+    SolidoEvent::ValidatorEmergencyUnstaked {
+        validator_vote_account: validator.pubkey,
+        num_stake_accounts: n_stake_accounts,
+        amount,
+    }
+    .emit();
+
+    validators.save(accounts.validator_list)
+}
+
+/// Set the amount of SOL that `StakeDeposit` leaves in the reserve.
+///
+/// Requires the fee admin to sign.
+pub fn process_set_reserve_buffer(
+    program_id: &Pubkey,
+    reserve_buffer: ReserveBuffer,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if let ReserveBuffer::Bps(bps) = reserve_buffer {
+        if bps > 10_000 {
+            return Err(LidoError::ReserveBufferOutOfBounds.into());
+        }
+    }
+
+    let accounts = SetReserveBufferInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
+
+    msg!(
+        "Solido: Reserve buffer changed from {:?} to {:?}.",
+        lido.reserve_buffer,
+        reserve_buffer
+    );
+    lido.reserve_buffer = reserve_buffer;
+
+    SolidoEvent::ReserveBufferChanged { reserve_buffer }.emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Sets the fee charged on `Withdraw`, in basis points.
+pub fn process_set_withdrawal_fee(
+    program_id: &Pubkey,
+    withdrawal_fee_bps: u64,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if withdrawal_fee_bps > 10_000 {
+        return Err(LidoError::WithdrawalFeeOutOfBounds.into());
+    }
+
+    let accounts = SetWithdrawalFeeInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::FeeAdmin, accounts.manager)?;
+
+    msg!(
+        "Solido: Withdrawal fee changed from {} to {} basis points.",
+        lido.withdrawal_fee_bps,
+        withdrawal_fee_bps
+    );
+    lido.withdrawal_fee_bps = withdrawal_fee_bps;
+
+    SolidoEvent::WithdrawalFeeChanged { withdrawal_fee_bps }.emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Assign `role` to a new authority.
+///
+/// Requires the manager to sign.
+pub fn process_set_role(
+    program_id: &Pubkey,
+    role: Role,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = SetRoleInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_manager(accounts.manager)?;
+
+    let old_authority = *lido.roles.get(role);
+    let new_authority = *accounts.new_authority.key;
+    msg!(
+        "Solido: Changed the {} from {} to {}.",
+        role,
+        old_authority,
+        new_authority
+    );
+    *lido.roles.get_mut(role) = new_authority;
+    SolidoEvent::RoleChanged {
+        role,
+        old_authority,
+        new_authority,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Set which `MAINTAINER_*` operations a maintainer is permitted to perform.
+///
+/// Requires the maintainer admin to sign.
+pub fn process_set_maintainer_permissions(
+    program_id: &Pubkey,
+    permissions: u8,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    if permissions & !MAINTAINER_ALL != 0 {
+        msg!(
+            "Invalid maintainer permissions {:#010b}, only bits in {:#010b} can be set.",
+            permissions,
+            MAINTAINER_ALL
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let accounts = SetMaintainerPermissionsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_role(Role::MaintainerAdmin, accounts.manager)?;
+
+    let maintainer = lido.maintainers.get_mut(accounts.maintainer.key)?;
+    msg!(
+        "Solido: Permissions of maintainer {} changed from {:#010b} to {:#010b}.",
+        maintainer.pubkey,
+        maintainer.entry,
+        permissions
+    );
+    maintainer.entry = permissions;
+    SolidoEvent::MaintainerPermissionsChanged {
+        maintainer: *accounts.maintainer.key,
+        permissions,
+    }
+    .emit();
+
     lido.save(accounts.lido)
 }
@@ -6,46 +6,66 @@
 use std::ops::{Add, Sub};
 
 use crate::{
+    account_map::PubkeyAndEntry,
+    balance::{get_imbalance_penalty, get_target_balance},
     error::LidoError,
+    events::SolidoEvent,
     instruction::{
-        DepositAccountsInfo, InitializeAccountsInfo, LidoInstruction, StakeDepositAccountsInfo,
-        UnstakeAccountsInfo, UpdateExchangeRateAccountsInfo, UpdateStakeAccountBalanceInfo,
-        WithdrawAccountsInfo,
+        CheckInvariantsAccountsInfo, ClaimWithdrawalAccountsInfo, DepositAccountsInfo,
+        DepositDirectedAccountsInfo, DepositStakeAccountsInfo, GetExchangeRateAccountsInfo,
+        InitializeAccountsInfo, InstantUnstakeAccountsInfo, LidoInstruction,
+        RequestWithdrawalAccountsInfo, StakeDepositAccountsInfo, UnstakeAccountsInfo,
+        UpdateExchangeRateAccountsInfo, UpdateStakeAccountBalanceInfo, WithdrawAccountsInfo,
+        WithdrawFromAnyValidatorAccountsInfo,
     },
     logic::{
-        burn_st_sol, check_mint, check_rent_exempt, check_unstake_accounts,
-        create_account_even_if_funded, distribute_fees, initialize_stake_account_undelegated,
-        mint_st_sol_to, split_stake_account, transfer_stake_authority, CreateAccountOptions,
+        burn_st_sol, check_mint, check_new_validator_list, check_rent_exempt,
+        check_unstake_accounts, create_account_even_if_funded, distribute_fees,
+        get_reserve_available_balance, initialize_stake_account_undelegated, mint_st_sol_to,
+        split_stake_account, transfer_st_sol, transfer_stake_authority, CreateAccountOptions,
         SplitStakeAccounts,
     },
     metrics::Metrics,
     process_management::{
-        process_add_maintainer, process_add_validator, process_change_reward_distribution,
+        process_accept_manager, process_add_maintainer, process_add_validator,
+        process_change_fee_recipients, process_change_reward_distribution,
         process_deactivate_validator, process_deactivate_validator_if_commission_exceeds_max,
-        process_merge_stake, process_remove_maintainer, process_remove_validator,
-        process_set_max_commission_percentage,
+        process_deactivate_validator_if_delinquent, process_emergency_unstake_validator,
+        process_merge_stake, process_migrate_state, process_propose_new_manager,
+        process_remove_maintainer, process_remove_validator, process_resize_validator_list,
+        process_set_deposit_limits, process_set_instant_unstake_fee,
+        process_set_maintainer_permissions, process_set_max_commission_percentage,
+        process_set_max_directed_stake, process_set_min_vote_credits,
+        process_set_paused_operations, process_set_reserve_buffer, process_set_role,
+        process_set_validator_weight, process_set_withdrawal_fee,
     },
-    stake_account::{deserialize_stake_account, StakeAccount},
+    stake_account::{deserialize_stake_account, deserialize_stake_lockup, StakeAccount},
     state::{
-        ExchangeRate, FeeRecipients, Lido, Maintainers, RewardDistribution, Validator, Validators,
-        LIDO_CONSTANT_SIZE, LIDO_VERSION,
+        ExchangeRate, ExchangeRateHistory, Lido, Maintainers, ReserveBuffer, RewardDistribution,
+        Roles, SeedRange, Validator, Validators, WithdrawalTicket, LIDO_CONSTANT_SIZE,
+        LIDO_VERSION, MAINTAINER_STAKE_DEPOSIT, MAINTAINER_UNSTAKE, PAUSE_DEPOSIT,
+        PAUSE_STAKE_DEPOSIT, PAUSE_UNSTAKE, PAUSE_WITHDRAW, WITHDRAWAL_TICKET_SIZE,
     },
-    token::{Lamports, Rational, StLamports},
-    MAXIMUM_UNSTAKE_ACCOUNTS, MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY, RESERVE_ACCOUNT,
-    STAKE_AUTHORITY, VALIDATOR_STAKE_ACCOUNT, VALIDATOR_UNSTAKE_ACCOUNT,
+    token::{self, Lamports, Rational, StLamports},
+    MAXIMUM_IMBALANCE_PENALTY_BPS, MAXIMUM_UNSTAKE_ACCOUNTS, MINIMUM_STAKE_ACCOUNT_BALANCE,
+    MINT_AUTHORITY, RESERVE_ACCOUNT, STAKE_AUTHORITY, VALIDATOR_STAKE_ACCOUNT,
+    VALIDATOR_UNSTAKE_ACCOUNT,
 };
 
-use solana_program::stake::{self as stake_program};
+use solana_program::stake::{
+    self as stake_program,
+    state::{Lockup, StakeAuthorize},
+};
 use solana_program::stake_history::StakeHistory;
 use {
-    borsh::BorshDeserialize,
+    borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::AccountInfo,
         clock::Clock,
         entrypoint::ProgramResult,
         msg,
         native_token::LAMPORTS_PER_SOL,
-        program::{invoke, invoke_signed},
+        program::{invoke, invoke_signed, set_return_data},
         program_error::ProgramError,
         pubkey::Pubkey,
         rent::Rent,
@@ -79,15 +99,14 @@ pub fn process_initialize(
         );
         return Err(LidoError::AlreadyInUse.into());
     }
+    check_new_validator_list(program_id, rent, accounts.validator_list, max_validators)?;
 
     // Bytes required for maintainers
     let bytes_for_maintainers = Maintainers::required_bytes(max_maintainers as usize);
-    // Bytes required for validators
-    let bytes_for_validators = Validators::required_bytes(max_validators as usize);
     // Calculate the expected lido's size
-    let bytes_sum = LIDO_CONSTANT_SIZE + bytes_for_validators + bytes_for_maintainers;
+    let bytes_sum = LIDO_CONSTANT_SIZE + bytes_for_maintainers;
     if bytes_sum != accounts.lido.data_len() {
-        msg!("Incorrect allocated bytes for the provided constrains: max_validator bytes: {}, max_maintainers bytes: {}, constant_size: {}, sum is {}, should be {}", bytes_for_validators, bytes_for_maintainers, LIDO_CONSTANT_SIZE, bytes_sum, accounts.lido.data_len());
+        msg!("Incorrect allocated bytes for the provided constrains: max_maintainers bytes: {}, constant_size: {}, sum is {}, should be {}", bytes_for_maintainers, LIDO_CONSTANT_SIZE, bytes_sum, accounts.lido.data_len());
         return Err(LidoError::InvalidLidoSize.into());
     }
 
@@ -109,6 +128,7 @@ pub fn process_initialize(
     if max_commission_percentage > 100 {
         return Err(LidoError::ValidationCommissionOutOfBounds.into());
     }
+    reward_distribution.check_fee_recipients()?;
 
     // Initialize fee structure
     let lido = Lido {
@@ -120,37 +140,55 @@ pub fn process_initialize(
         mint_authority_bump_seed: mint_bump_seed,
         stake_authority_bump_seed: deposit_bump_seed,
         reward_distribution,
-        fee_recipients: FeeRecipients {
-            treasury_account: *accounts.treasury_account.key,
-            developer_account: *accounts.developer_account.key,
-        },
         metrics: Metrics::new(),
         maintainers: Maintainers::new(max_maintainers),
-        validators: Validators::new(max_validators),
+        validator_list: *accounts.validator_list.key,
         max_commission_percentage,
+        instant_unstake_fee_bps: 0,
+        withdrawal_tickets_owed: Lamports(0),
+        min_vote_credits: 0,
+        vote_credits_epochs: 1,
+        pending_manager: Pubkey::default(),
+        paused_operations: 0,
+        exchange_rate_history: ExchangeRateHistory::default(),
+        max_total_sol: Lamports(0),
+        min_deposit: Lamports(0),
+        max_directed_stake_bps: 0,
+        reserve_buffer: ReserveBuffer::default(),
+        withdrawal_fee_bps: 0,
+        roles: Roles::new_all(*accounts.manager.key),
     };
 
     // Confirm that the fee recipients are actually stSOL accounts.
-    lido.check_is_st_sol_account(accounts.treasury_account)?;
-    lido.check_is_st_sol_account(accounts.developer_account)?;
+    lido.check_fee_recipient_st_sol_accounts(accounts.fee_recipients)?;
+
+    SolidoEvent::Initialized {
+        manager: lido.manager,
+        reward_distribution: lido.reward_distribution.clone(),
+    }
+    .emit();
 
+    Validators::new(max_validators).save(accounts.validator_list)?;
     lido.save(accounts.lido)
 }
 
-pub fn process_deposit(
+/// Transfer `amount` from the user to the reserve, and mint stSOL in return.
+///
+/// Shared by `Deposit` and `DepositDirected`, the caller saves `lido`.
+fn deposit_sol(
     program_id: &Pubkey,
+    lido: &mut Lido,
+    accounts: &DepositAccountsInfo,
     amount: Lamports,
-    accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
-    let accounts = DepositAccountsInfo::try_from_slice(accounts_raw)?;
-
     if amount == Lamports(0) {
         msg!("Amount must be greater than zero");
         return Err(ProgramError::InvalidArgument);
     }
 
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_DEPOSIT, "Deposit")?;
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve_account)?;
+    lido.check_deposit_limits(amount, lido.get_st_sol_supply(accounts.st_sol_mint)?)?;
 
     invoke(
         &system_instruction::transfer(accounts.user.key, accounts.reserve_account.key, amount.0),
@@ -164,7 +202,7 @@ pub fn process_deposit(
     let st_sol_amount = lido.exchange_rate.exchange_sol(amount)?;
 
     mint_st_sol_to(
-        &lido,
+        lido,
         accounts.lido.key,
         accounts.spl_token,
         accounts.st_sol_mint,
@@ -181,27 +219,335 @@ pub fn process_deposit(
         amount,
         st_sol_amount
     );
+    SolidoEvent::Deposit {
+        recipient: *accounts.recipient.key,
+        amount,
+        st_sol_minted: st_sol_amount,
+    }
+    .emit();
 
     lido.metrics.deposit_amount.observe(amount)?;
-    lido.save(accounts.lido)
+    Ok(())
 }
 
-pub fn process_stake_deposit(
+pub fn process_deposit(
     program_id: &Pubkey,
     amount: Lamports,
-    raw_accounts: &[AccountInfo],
+    accounts_raw: &[AccountInfo],
 ) -> ProgramResult {
-    let accounts = StakeDepositAccountsInfo::try_from_slice(raw_accounts)?;
+    let accounts = DepositAccountsInfo::try_from_slice(accounts_raw)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    deposit_sol(program_id, &mut lido, &accounts, amount)?;
+    lido.save(accounts.lido)
+}
 
+pub fn process_deposit_directed(
+    program_id: &Pubkey,
+    amount: Lamports,
+    accounts_raw: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = DepositDirectedAccountsInfo::try_from_slice(accounts_raw)?;
     let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    let (validator_index, mut validator) = lido
+        .borrow_validators(program_id, accounts.validator_list)?
+        .get_by_pubkey(accounts.validator_vote_account.key)?;
 
-    lido.check_maintainer(accounts.maintainer)?;
-    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
+    if validator.entry.effective_weight() == 0 {
+        msg!(
+            "Validator {} is inactive or has weight 0, deposits cannot be directed to it.",
+            validator.pubkey
+        );
+        return Err(LidoError::StakeToInactiveValidator.into());
+    }
+
+    let deposit_accounts = DepositAccountsInfo {
+        lido: accounts.lido,
+        user: accounts.user,
+        recipient: accounts.recipient,
+        st_sol_mint: accounts.st_sol_mint,
+        reserve_account: accounts.reserve_account,
+        mint_authority: accounts.mint_authority,
+        spl_token: accounts.spl_token,
+        system_program: accounts.system_program,
+    };
+    deposit_sol(program_id, &mut lido, &deposit_accounts, amount)?;
+
+    // The stSOL supply now includes the deposit, so the deposit counts towards
+    // the SOL under management that limits the directed stake.
+    let sol_under_management =
+        lido.get_sol_under_management_estimate(lido.get_st_sol_supply(accounts.st_sol_mint)?)?;
+    let max_directed_stake = lido.get_max_directed_stake(sol_under_management)?;
+    let directed_amount = validator
+        .entry
+        .add_directed_stake(amount, max_directed_stake)?;
+
+    msg!(
+        "Solido: Directed {} to {}, it now has {} of directed stake.",
+        directed_amount,
+        validator.pubkey,
+        validator.entry.directed_stake
+    );
+    SolidoEvent::StakeDirected {
+        validator_vote_account: validator.pubkey,
+        amount: directed_amount,
+        directed_stake: validator.entry.directed_stake,
+    }
+    .emit();
+
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
+    lido.save(accounts.lido)
+}
+
+pub fn process_deposit_stake(program_id: &Pubkey, accounts_raw: &[AccountInfo]) -> ProgramResult {
+    let accounts = DepositStakeAccountsInfo::try_from_slice(accounts_raw)?;
+    let clock = Clock::get()?;
+    let stake_history = StakeHistory::from_account_info(accounts.stake_history)?;
+
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_DEPOSIT, "DepositStake")?;
     lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
-    lido.check_can_stake_amount(accounts.reserve, amount)?;
 
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
+    // Only decode the validator that we deposit to, and write it back at the end.
+    let (validator_index, mut validator) = lido
+        .borrow_validators(program_id, accounts.validator_list)?
+        .get_by_pubkey(accounts.validator_vote_account.key)?;
+
+    if !validator.entry.active || validator.entry.weight == 0 {
+        msg!(
+            "Validator {} is inactive or has weight 0, new deposits are not allowed",
+            validator.pubkey
+        );
+        return Err(LidoError::StakeToInactiveValidator.into());
+    }
+
+    if accounts.user_stake_account.owner != &stake_program::program::id() {
+        msg!(
+            "Stake account {} is owned by {}, but should be owned by the stake program.",
+            accounts.user_stake_account.key,
+            accounts.user_stake_account.owner
+        );
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+    let stake = deserialize_stake_account(&accounts.user_stake_account.data.borrow())?;
+    if &stake.delegation.voter_pubkey != accounts.validator_vote_account.key {
+        msg!(
+            "Stake account {} is delegated to {}, but should be delegated to {}.",
+            accounts.user_stake_account.key,
+            stake.delegation.voter_pubkey,
+            accounts.validator_vote_account.key
+        );
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    // A lockup would stop us from withdrawing the stake later on, and the
+    // custodian could still move it, so we only accept unlocked accounts.
+    let lockup = deserialize_stake_lockup(&accounts.user_stake_account.data.borrow())?;
+    if lockup != Lockup::default() && lockup.is_in_force(&clock, None) {
+        msg!(
+            "Stake account {} is locked up until epoch {} and timestamp {}, \
+            with custodian {}.",
+            accounts.user_stake_account.key,
+            lockup.epoch,
+            lockup.unix_timestamp,
+            lockup.custodian
+        );
+        return Err(LidoError::StakeAccountLocked.into());
+    }
+
+    // The deposited account becomes one of our stake accounts, so it needs to
+    // be fully active like them, to be able to merge it later on.
+    let amount = Lamports(accounts.user_stake_account.lamports());
+    let user_stake =
+        StakeAccount::from_delegated_account(amount, &stake, &clock, &stake_history, 0);
+    if !user_stake.is_active() {
+        msg!(
+            "Stake account {} is not fully active, it has {} activating and {} deactivating.",
+            accounts.user_stake_account.key,
+            user_stake.balance.activating,
+            user_stake.balance.deactivating
+        );
+        return Err(LidoError::WrongStakeState.into());
+    }
+    if amount < MINIMUM_STAKE_ACCOUNT_BALANCE {
+        msg!(
+            "Stake account holds {}, but need at least {}.",
+            amount,
+            MINIMUM_STAKE_ACCOUNT_BALANCE
+        );
+        return Err(LidoError::InvalidAmount.into());
+    }
+    lido.check_deposit_limits(amount, lido.get_st_sol_supply(accounts.st_sol_mint)?)?;
+
+    // Hand the stake account over to the Solido stake authority. The depositor
+    // signed the transaction, so we can invoke with their signature.
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        invoke(
+            &stake_program::instruction::authorize(
+                accounts.user_stake_account.key,
+                accounts.user_stake_authority.key,
+                accounts.stake_authority.key,
+                stake_authorize,
+                None,
+            ),
+            &[
+                accounts.user_stake_account.clone(),
+                accounts.sysvar_clock.clone(),
+                accounts.user_stake_authority.clone(),
+                accounts.stake_program.clone(),
+            ],
+        )?;
+    }
+
+    // Compute the stSOL before the deposit changes our balance, the exchange
+    // rate is fixed for the epoch anyway.
+    let st_sol_amount = lido.exchange_rate.exchange_sol(amount)?;
+
+    let stake_authority_bump_seed = [lido.stake_authority_bump_seed];
+    let stake_authority_seeds = &[
+        accounts.lido.key.as_ref(),
+        STAKE_AUTHORITY,
+        &stake_authority_bump_seed[..],
+    ][..];
+
+    // Like in `StakeDeposit`, we have two options:
+    //
+    // 1. The deposited stake becomes a new stake account for the validator,
+    //    at seed `stake_seeds.end`. We split all of its stake into the
+    //    program-derived account at that seed.
+    //
+    // 2. We merge the deposited stake into the validator's stake account at
+    //    seed `stake_seeds.end - 1`. The stake program checks that the two
+    //    accounts can be merged, so they must both be fully active and have
+    //    observed the same credits.
+    //
+    // The caller picks case 1 by passing the same account twice.
+    if accounts.stake_account_end.key == accounts.stake_account_merge_into.key {
+        let stake_account_bump_seed = Lido::check_stake_account(
+            program_id,
+            accounts.lido.key,
+            &validator,
+            validator.entry.stake_seeds.end,
+            accounts.stake_account_end,
+            VALIDATOR_STAKE_ACCOUNT,
+        )?;
+        if accounts.stake_account_end.data.borrow().len() > 0 {
+            msg!(
+                "Stake account {} contains data, aborting.",
+                accounts.stake_account_end.key
+            );
+            return Err(LidoError::WrongStakeState.into());
+        }
+
+        let stake_account_seed = validator.entry.stake_seeds.end.to_le_bytes();
+        let stake_account_bump_seed = [stake_account_bump_seed];
+        let stake_account_seeds = &[
+            accounts.lido.key.as_ref(),
+            validator.pubkey.as_ref(),
+            VALIDATOR_STAKE_ACCOUNT,
+            &stake_account_seed[..],
+            &stake_account_bump_seed[..],
+        ][..];
+
+        msg!(
+            "Registering deposited stake as stake account at seed {} ...",
+            validator.entry.stake_seeds.end
+        );
+        split_stake_account(
+            accounts.lido.key,
+            &lido,
+            &SplitStakeAccounts {
+                source_stake_account: accounts.user_stake_account,
+                destination_stake_account: accounts.stake_account_end,
+                authority: accounts.stake_authority,
+                system_program: accounts.system_program,
+                stake_program: accounts.stake_program,
+            },
+            amount,
+            &[stake_account_seeds],
+        )?;
+
+        // We now consumed this stake account, bump the index.
+        validator.entry.stake_seeds.end += 1;
+    } else {
+        if validator.entry.stake_seeds.end <= validator.entry.stake_seeds.begin {
+            msg!("Can only merge if there is at least one stake account to merge into.");
+            return Err(LidoError::InvalidStakeAccount.into());
+        }
+        Lido::check_stake_account(
+            program_id,
+            accounts.lido.key,
+            &validator,
+            // Does not underflow, because end > begin >= 0.
+            validator.entry.stake_seeds.end - 1,
+            accounts.stake_account_merge_into,
+            VALIDATOR_STAKE_ACCOUNT,
+        )?;
+        msg!(
+            "Merging deposited stake into stake account at seed {} ...",
+            validator.entry.stake_seeds.end - 1
+        );
+        let merge_instructions = stake_program::instruction::merge(
+            accounts.stake_account_merge_into.key,
+            accounts.user_stake_account.key,
+            accounts.stake_authority.key,
+        );
+        assert_eq!(merge_instructions.len(), 1);
+        invoke_signed(
+            &merge_instructions[0],
+            &[
+                accounts.stake_account_merge_into.clone(),
+                accounts.user_stake_account.clone(),
+                accounts.sysvar_clock.clone(),
+                accounts.stake_history.clone(),
+                accounts.stake_authority.clone(),
+                accounts.stake_program.clone(),
+            ],
+            &[stake_authority_seeds],
+        )?;
+    }
+
+    validator.entry.stake_accounts_balance = (validator.entry.stake_accounts_balance + amount)?;
+
+    mint_st_sol_to(
+        &lido,
+        accounts.lido.key,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.mint_authority,
+        accounts.recipient,
+        st_sol_amount,
+    )?;
+
+    msg!(
+        "Solido: Deposited stake account {} holding {}, minted {} in return.",
+        accounts.user_stake_account.key,
+        amount,
+        st_sol_amount
+    );
+    SolidoEvent::DepositStake {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        recipient: *accounts.recipient.key,
+        amount,
+        st_sol_minted: st_sol_amount,
+    }
+    .emit();
+
+    lido.metrics.deposit_amount.observe(amount)?;
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
+    lido.save(accounts.lido)
+}
 
+/// Check that new stake can go to `validator`.
+///
+/// The validator must be active, have a nonzero weight, and there must be no
+/// other such validator with a lower balance per weight that we could stake to.
+/// `validators` are the entries as decoded by a `ValidatorsView`.
+fn check_can_stake_to_validator(
+    validators: impl Iterator<Item = Result<PubkeyAndEntry<Validator>, LidoError>>,
+    validator: &PubkeyAndEntry<Validator>,
+) -> ProgramResult {
     if !validator.entry.active {
         msg!(
             "Validator {} is inactive, new deposits are not allowed",
@@ -210,43 +556,87 @@ pub fn process_stake_deposit(
         return Err(LidoError::StakeToInactiveValidator.into());
     }
 
-    // Confirm that there is no other active validator with a lower balance that
-    // we could stake to. This alone is not sufficient to guarantee a uniform
-    // stake balance, but it limits the power that maintainers have to disturb
-    // the balance. More importantly, it ensures that when two maintainers create
-    // the same StakeDeposit transaction, only one of them succeeds.
-    let minimum_stake_validator = lido
-        .validators
-        .iter_active_entries()
-        .min_by_key(|pair| pair.entry.effective_stake_balance())
-        .ok_or(LidoError::NoActiveValidators)?;
+    if validator.entry.weight == 0 {
+        msg!(
+            "Validator {} has weight 0, new deposits are not allowed",
+            validator.pubkey
+        );
+        return Err(LidoError::StakeToInactiveValidator.into());
+    }
+
+    // Confirm that there is no other active validator with a lower balance per
+    // weight that we could stake to. This alone is not sufficient to guarantee
+    // a balance proportional to the weights, but it limits the power that
+    // maintainers have to disturb the balance. More importantly, it ensures
+    // that when two maintainers create the same StakeDeposit transaction, only
+    // one of them succeeds.
+    let mut minimum_stake_validator: Option<PubkeyAndEntry<Validator>> = None;
+    for pair in validators {
+        let pair = pair?;
+        if !pair.entry.active || pair.entry.weight == 0 {
+            continue;
+        }
+        let is_lower = match &minimum_stake_validator {
+            None => true,
+            Some(minimum) => pair.entry.stake_per_weight() < minimum.entry.stake_per_weight(),
+        };
+        if is_lower {
+            minimum_stake_validator = Some(pair);
+        }
+    }
+    let minimum_stake_validator = minimum_stake_validator.ok_or(LidoError::NoActiveValidators)?;
 
     // Note that we compare balances, not keys, because the minimum might not be unique.
-    if validator.entry.effective_stake_balance()
-        > minimum_stake_validator.entry.effective_stake_balance()
-    {
+    if validator.entry.stake_per_weight() > minimum_stake_validator.entry.stake_per_weight() {
         msg!(
-            "Refusing to stake with {}, who has {} stake, \
-            because {} has less stake: {}. Stake there instead.",
+            "Refusing to stake with {}, who has {} stake at weight {}, \
+            because {} has less stake per weight: {} at weight {}. Stake there instead.",
             validator.pubkey,
             validator.entry.effective_stake_balance(),
+            validator.entry.weight,
             minimum_stake_validator.pubkey,
             minimum_stake_validator.entry.effective_stake_balance(),
+            minimum_stake_validator.entry.weight,
         );
         return Err(LidoError::ValidatorWithLessStakeExists.into());
     }
 
-    // From now on we will not reference other Lido fields, so we can get the
-    // validator as mutable. This is a bit wasteful, but we can optimize when we
-    // need dozens of validators, for now we are under the compute limit.
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+    Ok(())
+}
+
+pub fn process_stake_deposit(
+    program_id: &Pubkey,
+    amount: Lamports,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = StakeDepositAccountsInfo::try_from_slice(raw_accounts)?;
+
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_STAKE_DEPOSIT, "StakeDeposit")?;
+
+    lido.check_maintainer(
+        accounts.maintainer,
+        MAINTAINER_STAKE_DEPOSIT,
+        "StakeDeposit",
+    )?;
+    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
+    lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
+
+    // We only decode the validators one by one to compare them, and at the end
+    // we write back only the validator that we staked to.
+    let (validator_index, mut validator) = {
+        let validators = lido.borrow_validators(program_id, accounts.validator_list)?;
+        let stake_accounts_balance = validators.sum_stake_accounts_balance()?;
+        lido.check_can_stake_amount(stake_accounts_balance, accounts.reserve, amount)?;
+        let (index, validator) = validators.get_by_pubkey(accounts.validator_vote_account.key)?;
+        check_can_stake_to_validator(validators.iter(), &validator)?;
+        (index, validator)
+    };
 
     let stake_account_bump_seed = Lido::check_stake_account(
         program_id,
         accounts.lido.key,
-        validator,
+        &validator,
         validator.entry.stake_seeds.end,
         accounts.stake_account_end,
         VALIDATOR_STAKE_ACCOUNT,
@@ -355,7 +745,7 @@ pub fn process_stake_deposit(
         Lido::check_stake_account(
             program_id,
             accounts.lido.key,
-            validator,
+            &validator,
             // Does not underflow, because end > begin >= 0.
             validator.entry.stake_seeds.end - 1,
             accounts.stake_account_merge_into,
@@ -396,7 +786,15 @@ pub fn process_stake_deposit(
         )?;
     }
 
-    lido.save(accounts.lido)
+    SolidoEvent::StakeDeposit {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        amount,
+    }
+    .emit();
+
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
+    Ok(())
 }
 
 /// Unstakes from a validator, the funds are moved to the stake defined by the
@@ -407,12 +805,14 @@ pub fn process_unstake(
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts = UnstakeAccountsInfo::try_from_slice(raw_accounts)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    lido.check_maintainer(accounts.maintainer)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_UNSTAKE, "Unstake")?;
+    lido.check_maintainer(accounts.maintainer, MAINTAINER_UNSTAKE, "Unstake")?;
     lido.check_stake_authority(program_id, accounts.lido.key, accounts.stake_authority)?;
-    let destination_bump_seed = check_unstake_accounts(program_id, &lido, &accounts)?;
-
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
+    let (validator_index, mut validator) = lido
+        .borrow_validators(program_id, accounts.validator_list)?
+        .get_by_pubkey(accounts.validator_vote_account.key)?;
+    let destination_bump_seed = check_unstake_accounts(program_id, &validator, &accounts)?;
 
     // Because `WithdrawInactiveStake` needs to reference all stake and unstake
     // accounts in a single transaction, we shouldn't have too many of them.
@@ -474,10 +874,6 @@ pub fn process_unstake(
         ]],
     )?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
-
     if validator.entry.active {
         // For active validators, we don't allow their stake accounts to contain
         // less than the minimum stake account balance.
@@ -511,7 +907,15 @@ pub fn process_unstake(
     validator.entry.unstake_accounts_balance = (validator.entry.unstake_accounts_balance + amount)?;
     validator.entry.unstake_seeds.end += 1;
 
-    lido.save(accounts.lido)
+    SolidoEvent::Unstake {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        amount,
+    }
+    .emit();
+
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
+    Ok(())
 }
 
 pub fn process_update_exchange_rate(
@@ -535,12 +939,33 @@ pub fn process_update_exchange_rate(
     }
 
     lido.exchange_rate.computed_in_epoch = clock.epoch;
-    lido.exchange_rate.sol_balance = lido.get_sol_balance(&rent, accounts.reserve)?;
+    let stake_accounts_balance = lido
+        .borrow_validators(program_id, accounts.validator_list)?
+        .sum_stake_accounts_balance()?;
+    lido.exchange_rate.sol_balance =
+        lido.get_sol_balance_with_stake(stake_accounts_balance, &rent, accounts.reserve)?;
     lido.exchange_rate.st_sol_supply = lido.get_st_sol_supply(accounts.st_sol_mint)?;
+    lido.exchange_rate_history.push(lido.exchange_rate)?;
+
+    SolidoEvent::ExchangeRateUpdated {
+        exchange_rate: lido.exchange_rate,
+    }
+    .emit();
 
     lido.save(accounts.lido)
 }
 
+/// Set the return data to the current exchange rate, for consumption through CPI.
+pub fn process_get_exchange_rate(
+    program_id: &Pubkey,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = GetExchangeRateAccountsInfo::try_from_slice(raw_accounts)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    set_return_data(&lido.exchange_rate.try_to_vec()?);
+    Ok(())
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum StakeType {
     Stake,
@@ -665,24 +1090,35 @@ pub fn process_update_stake_account_balance(
     // and confirm that they can receive stSOL.
     lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
 
-    let validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
+    // Only decode the validator that we update, and write it back at the end.
+    let (validator_index, mut validator) = lido
+        .borrow_validators(program_id, accounts.validator_list)?
+        .get_by_pubkey(accounts.validator_vote_account.key)?;
 
     let mut stake_observed_total = Lamports(0);
     let mut excess_removed = Lamports(0);
+    let n_fee_recipients = lido.reward_distribution.fee_recipients.len() as u64;
     let n_stake_accounts = validator.entry.stake_seeds.end - validator.entry.stake_seeds.begin;
     let n_unstake_accounts =
         validator.entry.unstake_seeds.end - validator.entry.unstake_seeds.begin;
 
-    if accounts.stake_accounts.len() as u64 != n_stake_accounts + n_unstake_accounts {
-        msg!("Wrong number of stake accounts provided, expected {} stake accounts and {} unstake accounts, \
-            but got {} accounts.", n_stake_accounts, n_unstake_accounts, accounts.stake_accounts.len());
+    let n_accounts = accounts.fee_recipients_and_stake_accounts.len() as u64;
+    if n_accounts != n_fee_recipients + n_stake_accounts + n_unstake_accounts {
+        msg!(
+            "Wrong number of accounts provided, expected {} fee recipients, {} stake accounts and \
+            {} unstake accounts, but got {} accounts.",
+            n_fee_recipients,
+            n_stake_accounts,
+            n_unstake_accounts,
+            n_accounts
+        );
         return Err(LidoError::InvalidStakeAccount.into());
     }
-    // Does not panic, because len = n_stake_accounts + n_unstake_accounts >= n_stake_accounts.
-    let (stake_accounts, unstake_accounts) =
-        accounts.stake_accounts.split_at(n_stake_accounts as usize);
+    // Does not panic, because len = n_fee_recipients + n_stake_accounts + n_unstake_accounts.
+    let (fee_recipient_accounts, all_stake_accounts) = accounts
+        .fee_recipients_and_stake_accounts
+        .split_at(n_fee_recipients as usize);
+    let (stake_accounts, unstake_accounts) = all_stake_accounts.split_at(n_stake_accounts as usize);
 
     // Visit the stake accounts one by one, and check how much SOL is in there.
     for (seed, provided_stake_account) in validator
@@ -713,9 +1149,24 @@ pub fn process_update_stake_account_balance(
         };
 
         let stake_account = get_stake_account(&withdraw_opts)?;
-        let amount = (stake_account.balance.inactive
-            - Lamports(rent.minimum_balance(provided_stake_account.data_len())))
-        .expect("Should have at least the payed rent");
+
+        // Stake accounts are only deactivated in place by
+        // `EmergencyUnstakeValidator`. Once such an account is fully inactive,
+        // withdraw all of it, which removes the account, so we bump the begin
+        // seed. Like for unstake accounts below, we only do this at the
+        // beginning, to not leave holes in the list of stake accounts. For
+        // other accounts, we withdraw everything except the rent-exempt
+        // reserve, which is the excess that is not delegated.
+        let amount = if validator.entry.stake_seeds.begin == seed
+            && stake_account.balance.inactive == stake_account.balance.total()
+        {
+            validator.entry.stake_seeds.begin += 1;
+            stake_account.balance.inactive
+        } else {
+            (stake_account.balance.inactive
+                - Lamports(rent.minimum_balance(provided_stake_account.data_len())))
+            .expect("Should have at least the payed rent")
+        };
 
         withdraw_inactive_sol(&withdraw_opts, amount)?;
 
@@ -816,55 +1267,287 @@ pub fn process_update_stake_account_balance(
         .add(validator.entry.unstake_accounts_balance)
         .expect("If Solido has enough SOL to make this overflow, something has gone very wrong.");
 
-    distribute_fees(&mut lido, &accounts, &clock, rewards)?;
+    SolidoEvent::StakeAccountBalanceUpdated {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        withdrawn_to_reserve: (excess_removed + unstake_removed)?,
+        rewards,
+    }
+    .emit();
+
+    distribute_fees(
+        &mut lido,
+        &accounts,
+        fee_recipient_accounts,
+        &clock,
+        rewards,
+    )?;
 
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
     lido.save(accounts.lido)
 }
 
-/// Splits a stake account from a validator's stake account.
-/// This function can only be called after the exchange rate is updated with
-/// `process_update_exchange_rate`.
-pub fn process_withdraw(
+/// Sum the balances of the stake or unstake accounts of `validator` in the
+/// given seed range, and confirm that none of them are missing.
+fn get_stake_accounts_balance(
+    program_id: &Pubkey,
+    solido_address: &Pubkey,
+    validator: &PubkeyAndEntry<Validator>,
+    seeds: &SeedRange,
+    provided_accounts: &[AccountInfo],
+    stake_type: StakeType,
+) -> Result<Lamports, ProgramError> {
+    let mut total = Lamports(0);
+    for (seed, provided_account) in seeds.into_iter().zip(provided_accounts) {
+        let (address, _bump_seed) =
+            validator.find_stake_account_address(program_id, solido_address, seed, stake_type);
+        let balance = check_address_and_get_balance(&address, provided_account, seed, stake_type)?;
+        if balance == Lamports(0) {
+            msg!(
+                "{} account at seed {} does not exist, but it is in the seed range {}..{}.",
+                stake_type,
+                seed,
+                seeds.begin,
+                seeds.end
+            );
+            return Err(LidoError::StakeAccountSeedGap.into());
+        }
+        total = (total + balance)?;
+    }
+    Ok(total)
+}
+
+/// Check that the tracked balances and exchange rate are consistent with the
+/// accounts. This function is permissionless, and does not modify any state.
+pub fn process_check_invariants(
     program_id: &Pubkey,
-    amount: StLamports,
     raw_accounts: &[AccountInfo],
 ) -> ProgramResult {
-    let accounts = WithdrawAccountsInfo::try_from_slice(raw_accounts)?;
-    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
-    let clock = Clock::get()?;
-    lido.check_exchange_rate_last_epoch(&clock, "Withdraw")?;
+    let accounts = CheckInvariantsAccountsInfo::try_from_slice(raw_accounts)?;
+    let lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
+    let rent = Rent::get()?;
 
-    // We should withdraw from the validator that has the most effective stake.
-    // With effective here we mean "total in stake accounts" - "total in unstake
-    // accounts", regardless of whether the stake in those accounts is active or not.
-    let validator = lido.validators.get(accounts.validator_vote_account.key)?;
-
-    // Confirm that there is no other validator with a higher balance that
-    // we could withdraw from. This alone is not sufficient to guarantee a uniform
-    // stake balance, but prevents things from becoming more unbalanced than
-    // necessary.
-    let maximum_stake_validator = lido
-        .validators
-        .entries
-        .iter()
-        .max_by_key(|pair| pair.entry.effective_stake_balance())
-        .ok_or(LidoError::NoActiveValidators)?;
+    let validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator = validators.get(accounts.validator_vote_account.key)?;
 
-    // Note that we compare balances, not keys, because the maximum might not be unique.
-    if validator.entry.effective_stake_balance()
-        < maximum_stake_validator.entry.effective_stake_balance()
-    {
+    let n_stake_accounts = validator.entry.stake_seeds.end - validator.entry.stake_seeds.begin;
+    let n_unstake_accounts =
+        validator.entry.unstake_seeds.end - validator.entry.unstake_seeds.begin;
+    let n_accounts = accounts.stake_accounts.len() as u64;
+    if n_accounts != n_stake_accounts + n_unstake_accounts {
         msg!(
-            "Refusing to withdraw from {}, who has {} stake, \
-            because {} has more stake: {}. Withdraw from there instead.",
-            validator.pubkey,
-            validator.entry.effective_stake_balance(),
-            maximum_stake_validator.pubkey,
-            maximum_stake_validator.entry.effective_stake_balance(),
+            "Wrong number of accounts provided, expected {} stake accounts and {} unstake \
+            accounts, but got {} accounts.",
+            n_stake_accounts,
+            n_unstake_accounts,
+            n_accounts
         );
-        return Err(LidoError::ValidatorWithMoreStakeExists.into());
+        return Err(LidoError::InvalidStakeAccount.into());
     }
-
+    // Does not panic, because len = n_stake_accounts + n_unstake_accounts.
+    let (stake_accounts, unstake_accounts) =
+        accounts.stake_accounts.split_at(n_stake_accounts as usize);
+
+    let stake_observed_total = get_stake_accounts_balance(
+        program_id,
+        accounts.lido.key,
+        validator,
+        &validator.entry.stake_seeds,
+        stake_accounts,
+        StakeType::Stake,
+    )?;
+    let unstake_observed_total = get_stake_accounts_balance(
+        program_id,
+        accounts.lido.key,
+        validator,
+        &validator.entry.unstake_seeds,
+        unstake_accounts,
+        StakeType::Unstake,
+    )?;
+
+    // The unstake balance is part of the stake balance, confirm that before
+    // computing the effective stake balance, which would panic otherwise.
+    Validator::observe_balance(
+        validator.entry.stake_accounts_balance,
+        validator.entry.unstake_accounts_balance,
+        "Tracked stake",
+    )?;
+    // Rewards and donations can make the observed balances larger than the
+    // tracked ones, until `UpdateStakeAccountBalance` observes them.
+    Validator::observe_balance(
+        stake_observed_total,
+        validator.entry.effective_stake_balance(),
+        "Stake",
+    )?;
+    Validator::observe_balance(
+        unstake_observed_total,
+        validator.entry.unstake_accounts_balance,
+        "Unstake",
+    )?;
+
+    // Deposits mint stSOL at the exchange rate, and withdrawals burn it at the
+    // same rate, both rounding in favor of Solido, and rewards only increase
+    // the SOL balance. So at the exchange rate, the stSOL supply can never be
+    // worth more than the SOL that Solido manages.
+    let sol_balance = lido.get_sol_balance(&validators, &rent, accounts.reserve)?;
+    let st_sol_supply = lido.get_st_sol_supply(accounts.st_sol_mint)?;
+    let st_sol_value = lido.get_sol_under_management_estimate(st_sol_supply)?;
+    if st_sol_value > sol_balance {
+        msg!(
+            "The stSOL supply of {} is worth {} at the exchange rate of epoch {}, \
+            but Solido manages only {}.",
+            st_sol_supply,
+            st_sol_value,
+            lido.exchange_rate.computed_in_epoch,
+            sol_balance
+        );
+        return Err(LidoError::ExchangeRateInconsistent.into());
+    }
+
+    msg!("All invariants hold.");
+    Ok(())
+}
+
+/// Pay `fee` to the treasury, burn `amount_to_burn` of the user's stSOL, and
+/// split `sol_to_withdraw` off the source stake account into a new stake
+/// account that the user controls.
+///
+/// This is the part of `Withdraw` that is shared with `WithdrawFromAnyValidator`,
+/// the caller is responsible for updating the validator's balance.
+fn withdraw_stake_to_user(
+    lido: &mut Lido,
+    accounts: &WithdrawAccountsInfo,
+    fee: StLamports,
+    amount_to_burn: StLamports,
+    sol_to_withdraw: Lamports,
+    penalty: Lamports,
+) -> ProgramResult {
+    // Pay the fee to the treasury, then burn the remainder.
+    if fee > StLamports(0) {
+        transfer_st_sol(
+            lido,
+            accounts.spl_token,
+            accounts.st_sol_account,
+            accounts.st_sol_account_owner,
+            accounts.treasury_st_sol_account,
+            fee,
+        )?;
+    }
+    burn_st_sol(
+        lido,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.st_sol_account,
+        accounts.st_sol_account_owner,
+        amount_to_burn,
+    )?;
+
+    // Update withdrawal metrics.
+    lido.metrics
+        .observe_withdrawal(amount_to_burn, sol_to_withdraw, fee)?;
+
+    split_stake_account(
+        accounts.lido.key,
+        lido,
+        &SplitStakeAccounts {
+            source_stake_account: accounts.source_stake_account,
+            destination_stake_account: accounts.destination_stake_account,
+            authority: accounts.stake_authority,
+            system_program: accounts.system_program,
+            stake_program: accounts.stake_program,
+        },
+        sol_to_withdraw,
+        &[],
+    )?;
+
+    // Give control of the stake to the user.
+    transfer_stake_authority(accounts, lido.stake_authority_bump_seed)?;
+
+    // Explain what we did in the logs, because block explorers can be an
+    // inscrutable mess of accounts, especially without special parsers for
+    // Solido transactions. With the logs, we can still identify what happened.
+    msg!(
+        "Solido: Withdrew {} for {}, paid {} in fees and {} in imbalance penalty.",
+        amount_to_burn,
+        sol_to_withdraw,
+        fee,
+        penalty,
+    );
+    SolidoEvent::Withdraw {
+        validator_vote_account: *accounts.validator_vote_account.key,
+        stake_account: *accounts.destination_stake_account.key,
+        amount: amount_to_burn,
+        sol_withdrawn: sol_to_withdraw,
+        fee,
+        penalty,
+    }
+    .emit();
+
+    Ok(())
+}
+
+/// Splits a stake account from a validator's stake account.
+/// This function can only be called after the exchange rate is updated with
+/// `process_update_exchange_rate`.
+pub fn process_withdraw(
+    program_id: &Pubkey,
+    amount: StLamports,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = WithdrawAccountsInfo::try_from_slice(raw_accounts)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_WITHDRAW, "Withdraw")?;
+    let clock = Clock::get()?;
+    lido.check_exchange_rate_last_epoch(&clock, "Withdraw")?;
+    lido.check_treasury_fee_st_sol_account(accounts.treasury_st_sol_account)?;
+
+    // We should withdraw from the validator that has the most effective stake.
+    // With effective here we mean "total in stake accounts" - "total in unstake
+    // accounts", regardless of whether the stake in those accounts is active or not.
+    // We decode the validators one by one to compare them, and at the end we
+    // write back only the validator that we withdrew from.
+    let (validator_index, mut validator) = {
+        let validators = lido.borrow_validators(program_id, accounts.validator_list)?;
+        let (index, validator) = validators.get_by_pubkey(accounts.validator_vote_account.key)?;
+
+        // Confirm that there is no other validator with a higher balance that
+        // we could withdraw from. This alone is not sufficient to guarantee a
+        // uniform stake balance, but prevents things from becoming more
+        // unbalanced than necessary.
+        let mut maximum_stake_validator: Option<PubkeyAndEntry<Validator>> = None;
+        for pair in validators.iter() {
+            let pair = pair?;
+            let is_higher = match &maximum_stake_validator {
+                None => true,
+                Some(maximum) => {
+                    pair.entry.effective_stake_balance() >= maximum.entry.effective_stake_balance()
+                }
+            };
+            if is_higher {
+                maximum_stake_validator = Some(pair);
+            }
+        }
+        let maximum_stake_validator =
+            maximum_stake_validator.ok_or(LidoError::NoActiveValidators)?;
+
+        // Note that we compare balances, not keys, because the maximum might not be unique.
+        if validator.entry.effective_stake_balance()
+            < maximum_stake_validator.entry.effective_stake_balance()
+        {
+            msg!(
+                "Refusing to withdraw from {}, who has {} stake, \
+                because {} has more stake: {}. Withdraw from there instead.",
+                validator.pubkey,
+                validator.entry.effective_stake_balance(),
+                maximum_stake_validator.pubkey,
+                maximum_stake_validator.entry.effective_stake_balance(),
+            );
+            return Err(LidoError::ValidatorWithMoreStakeExists.into());
+        }
+        (index, validator)
+    };
+
     let (stake_account, _) = validator.find_stake_account_address(
         program_id,
         accounts.lido.key,
@@ -878,16 +1561,15 @@ pub fn process_withdraw(
     }
 
     // Reduce validator's balance
-    let sol_to_withdraw = match lido.exchange_rate.exchange_st_sol(amount) {
+    let fee = lido.get_withdrawal_fee(amount)?;
+    let amount_to_burn = (amount - fee)?;
+    let sol_to_withdraw = match lido.exchange_rate.exchange_st_sol(amount_to_burn) {
         Ok(amount) => amount,
         Err(err) => {
             msg!("Cannot exchange stSOL for SOL, because no stSTOL has been minted.");
             return Err(err.into());
         }
     };
-    let provided_validator = lido
-        .validators
-        .get_mut(accounts.validator_vote_account.key)?;
 
     let source_balance = Lamports(accounts.source_stake_account.lamports());
 
@@ -924,36 +1606,414 @@ pub fn process_withdraw(
         return Err(LidoError::InvalidAmount.into());
     }
 
+    validator
+        .entry
+        .remove_directed_stake_for_withdrawal(sol_to_withdraw)?;
+    validator.entry.stake_accounts_balance =
+        (validator.entry.stake_accounts_balance - sol_to_withdraw)?;
+
+    withdraw_stake_to_user(
+        &mut lido,
+        &accounts,
+        fee,
+        amount_to_burn,
+        sol_to_withdraw,
+        Lamports(0),
+    )?;
+
+    lido.borrow_validators_mut(program_id, accounts.validator_list)?
+        .set(validator_index, &validator.entry);
+    lido.save(accounts.lido)
+}
+
+/// Splits a stake account from any validator's stake account, and deducts an
+/// imbalance penalty if that leaves the validator below its stake target.
+/// Like `process_withdraw`, this can only be called after the exchange rate is
+/// updated with `process_update_exchange_rate`.
+pub fn process_withdraw_from_any_validator(
+    program_id: &Pubkey,
+    amount: StLamports,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = WithdrawFromAnyValidatorAccountsInfo::try_from_slice(raw_accounts)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_WITHDRAW, "WithdrawFromAnyValidator")?;
+    let clock = Clock::get()?;
+    lido.check_exchange_rate_last_epoch(&clock, "WithdrawFromAnyValidator")?;
+    lido.check_treasury_fee_st_sol_account(accounts.treasury_st_sol_account)?;
+    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve)?;
+    let rent = Rent::get()?;
+
+    let mut validators = lido.deserialize_validators(program_id, accounts.validator_list)?;
+    let validator_index = validators
+        .entries
+        .iter()
+        .position(|pair| &pair.pubkey == accounts.validator_vote_account.key)
+        .ok_or(LidoError::InvalidAccountMember)?;
+    let validator = &validators.entries[validator_index];
+
+    let (stake_account, _) = validator.find_stake_account_address(
+        program_id,
+        accounts.lido.key,
+        validator.entry.stake_seeds.begin,
+        StakeType::Stake,
+    );
+    if &stake_account != accounts.source_stake_account.key {
+        msg!("Stake account is different than the calculated by the given seed, should be {}, is {}.",
+        stake_account, accounts.source_stake_account.key);
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    let fee = lido.get_withdrawal_fee(amount)?;
+    let amount_to_burn = (amount - fee)?;
+    let sol_gross = match lido.exchange_rate.exchange_st_sol(amount_to_burn) {
+        Ok(amount) => amount,
+        Err(err) => {
+            msg!("Cannot exchange stSOL for SOL, because no stSTOL has been minted.");
+            return Err(err.into());
+        }
+    };
+
+    // The penalty is based on the same targets that `StakeDeposit` and
+    // `Unstake` work towards. It is not withdrawn, so it stays in the pool and
+    // accrues to the remaining stSOL holders.
+    let stake_accounts_balance: Lamports = validators
+        .iter_entries()
+        .map(|v| v.stake_accounts_balance)
+        .sum::<token::Result<Lamports>>()?;
+    let undelegated_lamports =
+        lido.get_reserve_stakeable_balance(stake_accounts_balance, &rent, accounts.reserve)?;
+    let targets = get_target_balance(
+        undelegated_lamports,
+        &validators,
+        lido.max_directed_stake_bps,
+    )?;
+    let penalty = get_imbalance_penalty(
+        targets[validator_index],
+        validator.entry.effective_stake_balance(),
+        sol_gross,
+        MAXIMUM_IMBALANCE_PENALTY_BPS,
+    )?;
+    let sol_to_withdraw = (sol_gross - penalty)?;
+
+    let source_balance = Lamports(accounts.source_stake_account.lamports());
+    let remaining_balance = (source_balance - sol_to_withdraw)?;
+    if remaining_balance < MINIMUM_STAKE_ACCOUNT_BALANCE {
+        msg!(
+            "Withdrawal will leave the stake account with less than the minimum \
+            stake account balance. Maximum amount to withdraw is {}, tried to \
+            withdraw {}.",
+            (source_balance - MINIMUM_STAKE_ACCOUNT_BALANCE)
+                .expect("We do not allow the balance to fall below the minimum"),
+            sol_to_withdraw
+        );
+        return Err(LidoError::InvalidAmount.into());
+    }
+
+    let provided_validator = &mut validators.entries[validator_index];
+    provided_validator
+        .entry
+        .remove_directed_stake_for_withdrawal(sol_to_withdraw)?;
     provided_validator.entry.stake_accounts_balance =
         (provided_validator.entry.stake_accounts_balance - sol_to_withdraw)?;
 
-    // Burn stSol tokens
-    burn_st_sol(&lido, &accounts, amount)?;
+    let withdraw_accounts = WithdrawAccountsInfo {
+        lido: accounts.lido,
+        validator_list: accounts.validator_list,
+        st_sol_account_owner: accounts.st_sol_account_owner,
+        st_sol_account: accounts.st_sol_account,
+        st_sol_mint: accounts.st_sol_mint,
+        treasury_st_sol_account: accounts.treasury_st_sol_account,
+        validator_vote_account: accounts.validator_vote_account,
+        source_stake_account: accounts.source_stake_account,
+        destination_stake_account: accounts.destination_stake_account,
+        stake_authority: accounts.stake_authority,
+        spl_token: accounts.spl_token,
+        sysvar_clock: accounts.sysvar_clock,
+        system_program: accounts.system_program,
+        stake_program: accounts.stake_program,
+    };
+    withdraw_stake_to_user(
+        &mut lido,
+        &withdraw_accounts,
+        fee,
+        amount_to_burn,
+        sol_to_withdraw,
+        penalty,
+    )?;
 
-    // Update withdrawal metrics.
-    lido.metrics.observe_withdrawal(amount, sol_to_withdraw)?;
+    validators.save(accounts.validator_list)?;
+    lido.save(accounts.lido)
+}
 
-    split_stake_account(
-        accounts.lido.key,
+/// Burn stSOL and pay out SOL from the reserve, minus the instant unstake fee.
+pub fn process_instant_unstake(
+    program_id: &Pubkey,
+    amount: StLamports,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = InstantUnstakeAccountsInfo::try_from_slice(raw_accounts)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_WITHDRAW, "InstantUnstake")?;
+    let clock = Clock::get()?;
+    let rent = Rent::get()?;
+
+    if amount == StLamports(0) {
+        msg!("Amount must be greater than zero");
+        return Err(LidoError::InvalidAmount.into());
+    }
+
+    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve_account)?;
+    lido.check_treasury_fee_st_sol_account(accounts.treasury_st_sol_account)?;
+    lido.check_exchange_rate_last_epoch(&clock, "InstantUnstake")?;
+
+    let fee = lido.get_instant_unstake_fee(amount)?;
+    let amount_to_burn = (amount - fee)?;
+    let sol_to_pay = match lido.exchange_rate.exchange_st_sol(amount_to_burn) {
+        Ok(amount) => amount,
+        Err(err) => {
+            msg!("Cannot exchange stSOL for SOL, because no stSTOL has been minted.");
+            return Err(err.into());
+        }
+    };
+
+    let available_reserve_amount =
+        lido.get_reserve_unowed_balance(&rent, accounts.reserve_account)?;
+    if sol_to_pay > available_reserve_amount {
+        msg!(
+            "The reserve has only {} available, but the unstake requires {}.",
+            available_reserve_amount,
+            sol_to_pay,
+        );
+        return Err(LidoError::AmountExceedsReserve.into());
+    }
+
+    // Pay the fee to the treasury, then burn the remainder.
+    if fee > StLamports(0) {
+        transfer_st_sol(
+            &lido,
+            accounts.spl_token,
+            accounts.st_sol_account,
+            accounts.st_sol_account_owner,
+            accounts.treasury_st_sol_account,
+            fee,
+        )?;
+    }
+    burn_st_sol(
         &lido,
-        &SplitStakeAccounts {
-            source_stake_account: accounts.source_stake_account,
-            destination_stake_account: accounts.destination_stake_account,
-            authority: accounts.stake_authority,
-            system_program: accounts.system_program,
-            stake_program: accounts.stake_program,
-        },
-        sol_to_withdraw,
-        &[],
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.st_sol_account,
+        accounts.st_sol_account_owner,
+        amount_to_burn,
     )?;
 
-    // Give control of the stake to the user.
-    transfer_stake_authority(&accounts, lido.stake_authority_bump_seed)?;
+    let reserve_account_bump_seed = [lido.sol_reserve_account_bump_seed];
+    let reserve_account_seeds = &[
+        accounts.lido.key.as_ref(),
+        RESERVE_ACCOUNT,
+        &reserve_account_bump_seed[..],
+    ][..];
+    invoke_signed(
+        &system_instruction::transfer(
+            accounts.reserve_account.key,
+            accounts.recipient.key,
+            sol_to_pay.0,
+        ),
+        &[
+            accounts.reserve_account.clone(),
+            accounts.recipient.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[reserve_account_seeds],
+    )?;
 
-    // Explain what we did in the logs, because block explorers can be an
-    // inscrutable mess of accounts, especially without special parsers for
-    // Solido transactions. With the logs, we can still identify what happened.
-    msg!("Solido: Withdrew {} for {}.", amount, sol_to_withdraw);
+    lido.metrics
+        .observe_instant_unstake(amount_to_burn, sol_to_pay, fee)?;
+
+    msg!(
+        "Solido: Instantly unstaked {} for {}, paid {} in fees.",
+        amount_to_burn,
+        sol_to_pay,
+        fee
+    );
+    SolidoEvent::InstantUnstake {
+        amount: amount_to_burn,
+        sol_paid: sol_to_pay,
+        fee,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Burn stSOL and create a withdrawal ticket for the SOL it is worth.
+pub fn process_request_withdrawal(
+    program_id: &Pubkey,
+    amount: StLamports,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = RequestWithdrawalAccountsInfo::try_from_slice(raw_accounts)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_WITHDRAW, "RequestWithdrawal")?;
+    let clock = Clock::get()?;
+    let rent = Rent::get()?;
+
+    if amount == StLamports(0) {
+        msg!("Amount must be greater than zero");
+        return Err(LidoError::InvalidAmount.into());
+    }
+
+    lido.check_exchange_rate_last_epoch(&clock, "RequestWithdrawal")?;
+
+    let sol_owed = match lido.exchange_rate.exchange_st_sol(amount) {
+        Ok(amount) => amount,
+        Err(err) => {
+            msg!("Cannot exchange stSOL for SOL, because no stSTOL has been minted.");
+            return Err(err.into());
+        }
+    };
+
+    burn_st_sol(
+        &lido,
+        accounts.spl_token,
+        accounts.st_sol_mint,
+        accounts.st_sol_account,
+        accounts.st_sol_account_owner,
+        amount,
+    )?;
+
+    // The ticket owner pays for the ticket account, and gets the rent back
+    // when the ticket is claimed.
+    invoke(
+        &system_instruction::create_account(
+            accounts.st_sol_account_owner.key,
+            accounts.withdrawal_ticket.key,
+            rent.minimum_balance(WITHDRAWAL_TICKET_SIZE),
+            WITHDRAWAL_TICKET_SIZE as u64,
+            program_id,
+        ),
+        &[
+            accounts.st_sol_account_owner.clone(),
+            accounts.withdrawal_ticket.clone(),
+            accounts.system_program.clone(),
+        ],
+    )?;
+
+    let ticket = WithdrawalTicket {
+        solido: *accounts.lido.key,
+        owner: *accounts.st_sol_account_owner.key,
+        amount: sol_owed,
+        created_epoch: clock.epoch,
+    };
+    ticket.save(accounts.withdrawal_ticket)?;
+
+    lido.withdrawal_tickets_owed = (lido.withdrawal_tickets_owed + sol_owed)?;
+
+    msg!(
+        "Solido: Requested withdrawal of {} for {}, ticket {}.",
+        amount,
+        sol_owed,
+        accounts.withdrawal_ticket.key
+    );
+    SolidoEvent::WithdrawalRequested {
+        withdrawal_ticket: *accounts.withdrawal_ticket.key,
+        amount,
+        sol_owed,
+    }
+    .emit();
+
+    lido.save(accounts.lido)
+}
+
+/// Pay out the SOL owed by a withdrawal ticket from the reserve, and close the ticket.
+pub fn process_claim_withdrawal(
+    program_id: &Pubkey,
+    raw_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts = ClaimWithdrawalAccountsInfo::try_from_slice(raw_accounts)?;
+    let mut lido = Lido::deserialize_lido(program_id, accounts.lido)?;
+    lido.check_not_paused(PAUSE_WITHDRAW, "ClaimWithdrawal")?;
+    let clock = Clock::get()?;
+    let rent = Rent::get()?;
+
+    lido.check_reserve_account(program_id, accounts.lido.key, accounts.reserve_account)?;
+
+    let ticket = WithdrawalTicket::deserialize_withdrawal_ticket(
+        program_id,
+        accounts.lido.key,
+        accounts.withdrawal_ticket,
+    )?;
+    if &ticket.owner != accounts.ticket_owner.key {
+        msg!(
+            "Withdrawal ticket is owned by {}, but provided owner is {}.",
+            ticket.owner,
+            accounts.ticket_owner.key,
+        );
+        return Err(LidoError::InvalidWithdrawalTicket.into());
+    }
+    if ticket.created_epoch >= clock.epoch {
+        msg!(
+            "Withdrawal ticket was created in epoch {}, it can be claimed from epoch {} onwards.",
+            ticket.created_epoch,
+            ticket.created_epoch + 1,
+        );
+        return Err(LidoError::WithdrawalTicketNotClaimable.into());
+    }
+
+    let available_reserve_amount = get_reserve_available_balance(&rent, accounts.reserve_account)?;
+    if ticket.amount > available_reserve_amount {
+        msg!(
+            "The reserve has only {} available, but the ticket is for {}.",
+            available_reserve_amount,
+            ticket.amount,
+        );
+        msg!("Please wait for the maintainers to unstake, and try again later.");
+        return Err(LidoError::AmountExceedsReserve.into());
+    }
+
+    let reserve_account_bump_seed = [lido.sol_reserve_account_bump_seed];
+    let reserve_account_seeds = &[
+        accounts.lido.key.as_ref(),
+        RESERVE_ACCOUNT,
+        &reserve_account_bump_seed[..],
+    ][..];
+    invoke_signed(
+        &system_instruction::transfer(
+            accounts.reserve_account.key,
+            accounts.recipient.key,
+            ticket.amount.0,
+        ),
+        &[
+            accounts.reserve_account.clone(),
+            accounts.recipient.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[reserve_account_seeds],
+    )?;
+
+    // Close the ticket, the rent goes to the recipient as well.
+    let ticket_lamports = accounts.withdrawal_ticket.lamports();
+    **accounts.withdrawal_ticket.lamports.borrow_mut() = 0;
+    **accounts.recipient.lamports.borrow_mut() = accounts
+        .recipient
+        .lamports()
+        .checked_add(ticket_lamports)
+        .ok_or(LidoError::CalculationFailure)?;
+    accounts.withdrawal_ticket.data.borrow_mut().fill(0);
+
+    lido.withdrawal_tickets_owed = (lido.withdrawal_tickets_owed - ticket.amount)?;
+
+    msg!(
+        "Solido: Claimed withdrawal ticket {} for {}.",
+        accounts.withdrawal_ticket.key,
+        ticket.amount
+    );
+    SolidoEvent::WithdrawalClaimed {
+        withdrawal_ticket: *accounts.withdrawal_ticket.key,
+        amount: ticket.amount,
+    }
+    .emit();
 
     lido.save(accounts.lido)
 }
@@ -1008,5 +2068,69 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
         LidoInstruction::SetMaxValidationCommission {
             max_commission_percentage,
         } => process_set_max_commission_percentage(program_id, max_commission_percentage, accounts),
+        LidoInstruction::InstantUnstake { amount } => {
+            process_instant_unstake(program_id, amount, accounts)
+        }
+        LidoInstruction::SetInstantUnstakeFee {
+            instant_unstake_fee_bps,
+        } => process_set_instant_unstake_fee(program_id, instant_unstake_fee_bps, accounts),
+        LidoInstruction::RequestWithdrawal { amount } => {
+            process_request_withdrawal(program_id, amount, accounts)
+        }
+        LidoInstruction::ClaimWithdrawal => process_claim_withdrawal(program_id, accounts),
+        LidoInstruction::SetValidatorWeight { weight } => {
+            process_set_validator_weight(program_id, weight, accounts)
+        }
+        LidoInstruction::DeactivateValidatorIfDelinquent => {
+            process_deactivate_validator_if_delinquent(program_id, accounts)
+        }
+        LidoInstruction::SetMinVoteCredits {
+            min_vote_credits,
+            vote_credits_epochs,
+        } => process_set_min_vote_credits(
+            program_id,
+            min_vote_credits,
+            vote_credits_epochs,
+            accounts,
+        ),
+        LidoInstruction::ProposeNewManager => process_propose_new_manager(program_id, accounts),
+        LidoInstruction::AcceptManager => process_accept_manager(program_id, accounts),
+        LidoInstruction::ChangeFeeRecipients => process_change_fee_recipients(program_id, accounts),
+        LidoInstruction::SetPausedOperations { paused_operations } => {
+            process_set_paused_operations(program_id, paused_operations, accounts)
+        }
+        LidoInstruction::ResizeValidatorList { max_validators } => {
+            process_resize_validator_list(program_id, max_validators, accounts)
+        }
+        LidoInstruction::MigrateState => process_migrate_state(program_id, accounts),
+        LidoInstruction::DepositStake => process_deposit_stake(program_id, accounts),
+        LidoInstruction::GetExchangeRate => process_get_exchange_rate(program_id, accounts),
+        LidoInstruction::SetDepositLimits {
+            max_total_sol,
+            min_deposit,
+        } => process_set_deposit_limits(program_id, max_total_sol, min_deposit, accounts),
+        LidoInstruction::DepositDirected { amount } => {
+            process_deposit_directed(program_id, amount, accounts)
+        }
+        LidoInstruction::SetMaxDirectedStake {
+            max_directed_stake_bps,
+        } => process_set_max_directed_stake(program_id, max_directed_stake_bps, accounts),
+        LidoInstruction::EmergencyUnstakeValidator => {
+            process_emergency_unstake_validator(program_id, accounts)
+        }
+        LidoInstruction::SetReserveBuffer { reserve_buffer } => {
+            process_set_reserve_buffer(program_id, reserve_buffer, accounts)
+        }
+        LidoInstruction::CheckInvariants => process_check_invariants(program_id, accounts),
+        LidoInstruction::SetWithdrawalFee { withdrawal_fee_bps } => {
+            process_set_withdrawal_fee(program_id, withdrawal_fee_bps, accounts)
+        }
+        LidoInstruction::WithdrawFromAnyValidator { amount } => {
+            process_withdraw_from_any_validator(program_id, amount, accounts)
+        }
+        LidoInstruction::SetRole { role } => process_set_role(program_id, role, accounts),
+        LidoInstruction::SetMaintainerPermissions { permissions } => {
+            process_set_maintainer_permissions(program_id, permissions, accounts)
+        }
     }
 }
@@ -7,7 +7,11 @@ use std::iter::Sum;
 use std::ops::Add;
 
 use crate::{error::LidoError, token, token::Lamports};
-use solana_program::stake::{self as stake_program, instruction::StakeInstruction, state::Stake};
+use solana_program::stake::{
+    self as stake_program,
+    instruction::StakeInstruction,
+    state::{Lockup, Stake},
+};
 use solana_program::{
     clock::{Clock, Epoch},
     instruction::AccountMeta,
@@ -85,6 +89,13 @@ fn take_u64_le(data: &[u8]) -> (u64, &[u8]) {
     (u64::from_le_bytes(prefix), &data[8..])
 }
 
+/// Consume a little-endian `i64` from the data start, return it and the remainder.
+fn take_i64_le(data: &[u8]) -> (i64, &[u8]) {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&data[..8]);
+    (i64::from_le_bytes(prefix), &data[8..])
+}
+
 /// Consume a little-endian `f64` from the data start, return it and the remainder.
 fn take_f64_le(data: &[u8]) -> (f64, &[u8]) {
     let mut prefix = [0u8; 8];
@@ -114,6 +125,34 @@ pub fn deserialize_rent_exempt_reserve(account_data: &[u8]) -> Result<Lamports,
     Ok(Lamports(rent_exempt_reserve))
 }
 
+/// Deserialize the `meta.lockup` field in a `StakeState::Stake` account.
+pub fn deserialize_stake_lockup(account_data: &[u8]) -> Result<Lockup, ProgramError> {
+    let data = account_data;
+
+    // The tag and the `Meta` struct take 124 bytes, the lockup is at the end.
+    if data.len() < 124 {
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    let (type_, data) = take_u32_le(data);
+    if type_ != 2 {
+        msg!("Stake state should have been StakeState::Stake");
+        return Err(LidoError::InvalidStakeAccount.into());
+    }
+
+    // Skip `rent_exempt_reserve` and the `Authorized` struct with its two pubkeys.
+    let (_, data) = data.split_at(8 + 32 + 32);
+    let (unix_timestamp, data) = take_i64_le(data);
+    let (epoch, data) = take_u64_le(data);
+    let (custodian, _suffix) = take_pubkey(data);
+
+    Ok(Lockup {
+        unix_timestamp,
+        epoch,
+        custodian,
+    })
+}
+
 /// We deserialize the stake account manually here, because `solana_program`
 /// does not expose a deserializer for it.
 pub fn deserialize_stake_account(account_data: &[u8]) -> Result<Stake, ProgramError> {
@@ -3,6 +3,8 @@
 
 //! State transition types
 
+use std::cell::{Ref, RefMut};
+use std::fmt;
 use std::ops::Range;
 
 use serde::Serialize;
@@ -23,22 +25,67 @@ use crate::processor::StakeType;
 use crate::token::{self, Lamports, Rational, StLamports};
 use crate::util::serialize_b58;
 use crate::{
-    account_map::{AccountMap, AccountSet, EntryConstantSize, PubkeyAndEntry},
+    account_map::{AccountMap, AccountMapView, EntryConstantSize, PubkeyAndEntry},
     MINIMUM_STAKE_ACCOUNT_BALANCE, MINT_AUTHORITY, RESERVE_ACCOUNT, STAKE_AUTHORITY,
 };
 use crate::{VALIDATOR_STAKE_ACCOUNT, VALIDATOR_UNSTAKE_ACCOUNT};
 
-pub const LIDO_VERSION: u8 = 1;
+/// Version of the `Lido` layout that the program reads and writes.
+///
+/// Bump this when the layout changes, and add a migration from the previous
+/// version to `crate::migration`.
+pub const LIDO_VERSION: u8 = 2;
 
-/// Size of a serialized `Lido` struct excluding validators and maintainers.
+/// Size of a serialized `Lido` struct excluding maintainers.
 ///
 /// To update this, run the tests and replace the value here with the test output.
-pub const LIDO_CONSTANT_SIZE: usize = 353;
-pub const VALIDATOR_CONSTANT_SIZE: usize = 49;
+pub const LIDO_CONSTANT_SIZE: usize = 1619;
+pub const VALIDATOR_CONSTANT_SIZE: usize = 69;
+
+/// Number of exchange rates that `Lido::exchange_rate_history` holds.
+pub const EXCHANGE_RATE_HISTORY_LEN: usize = 16;
+
+/// Maximum number of entries in `RewardDistribution::fee_recipients`.
+///
+/// The `Lido` account reserves space for this many recipients, and for their
+/// metrics, so the list can change without resizing the account.
+pub const MAXIMUM_FEE_RECIPIENTS: usize = 8;
+
+/// Weight that validators get when they are added.
+pub const DEFAULT_VALIDATOR_WEIGHT: u32 = 100;
+
+/// Bit in `Lido::paused_operations` that pauses `Deposit`.
+pub const PAUSE_DEPOSIT: u8 = 1 << 0;
+/// Bit in `Lido::paused_operations` that pauses `Withdraw`, `InstantUnstake`,
+/// `RequestWithdrawal` and `ClaimWithdrawal`.
+pub const PAUSE_WITHDRAW: u8 = 1 << 1;
+/// Bit in `Lido::paused_operations` that pauses `StakeDeposit`.
+pub const PAUSE_STAKE_DEPOSIT: u8 = 1 << 2;
+/// Bit in `Lido::paused_operations` that pauses `Unstake`.
+pub const PAUSE_UNSTAKE: u8 = 1 << 3;
+/// All bits that can be set in `Lido::paused_operations`.
+pub const PAUSE_ALL: u8 = PAUSE_DEPOSIT | PAUSE_WITHDRAW | PAUSE_STAKE_DEPOSIT | PAUSE_UNSTAKE;
+
+/// Bit in the permissions of a maintainer that allows it to call `StakeDeposit`.
+pub const MAINTAINER_STAKE_DEPOSIT: u8 = 1 << 0;
+/// Bit in the permissions of a maintainer that allows it to call `Unstake`.
+pub const MAINTAINER_UNSTAKE: u8 = 1 << 1;
+/// All bits that can be set in the permissions of a maintainer. Maintainers
+/// start out with all of them.
+pub const MAINTAINER_ALL: u8 = MAINTAINER_STAKE_DEPOSIT | MAINTAINER_UNSTAKE;
 
 pub type Validators = AccountMap<Validator>;
 
 impl Validators {
+    /// Serialize the validators into the validator list account.
+    ///
+    /// The account must be large enough to hold `maximum_entries` validators,
+    /// `ResizeValidatorList` grows it.
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+
     pub fn iter_active(&self) -> impl Iterator<Item = &Validator> {
         self.iter_entries().filter(|&v| v.active)
     }
@@ -47,7 +94,35 @@ impl Validators {
         self.entries.iter().filter(|&v| v.entry.active)
     }
 }
-pub type Maintainers = AccountSet;
+
+/// A view of the validator list account that decodes individual validators.
+///
+/// Instructions that touch only one validator use this instead of
+/// `Validators`, so they do not decode, allocate, and re-encode the entire
+/// list. Their cost still grows with the size of the list: they scan it to
+/// find the validator, and `StakeDeposit` and `Withdraw` decode every entry
+/// once to compare it with the one they operate on. Instructions that need the
+/// targets of all validators, such as `WithdrawFromAnyValidator`, still load
+/// `Validators`.
+pub type ValidatorsView<D> = AccountMapView<Validator, D>;
+
+impl<D: std::ops::Deref<Target = [u8]>> ValidatorsView<D> {
+    /// Return the sum of `stake_accounts_balance` over all validators.
+    pub fn sum_stake_accounts_balance(&self) -> Result<Lamports, LidoError> {
+        let mut sum = Lamports(0);
+        for validator in self.iter() {
+            sum = (sum + validator?.entry.stake_accounts_balance)?;
+        }
+        Ok(sum)
+    }
+}
+
+/// The maintainers, and for each of them the `MAINTAINER_*` operations that it
+/// is permitted to perform.
+///
+/// Maintenance operations that need no maintainer, such as `MergeStake` and
+/// `UpdateExchangeRate`, can be performed by anybody, so they have no bit.
+pub type Maintainers = AccountMap<u8>;
 
 impl EntryConstantSize for Validator {
     const SIZE: usize = VALIDATOR_CONSTANT_SIZE;
@@ -57,6 +132,10 @@ impl EntryConstantSize for () {
     const SIZE: usize = 0;
 }
 
+impl EntryConstantSize for u8 {
+    const SIZE: usize = 1;
+}
+
 /// The exchange rate used for deposits and rewards distribution.
 ///
 /// The exchange rate of SOL to stSOL is determined by the SOL balance of
@@ -126,7 +205,16 @@ impl EntryConstantSize for () {
 /// 5. Etc.
 #[repr(C)]
 #[derive(
-    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    BorshDeserialize,
+    BorshSerialize,
+    BorshSchema,
+    Eq,
+    PartialEq,
+    Serialize,
 )]
 pub struct ExchangeRate {
     /// The epoch in which we last called `UpdateExchangeRate`.
@@ -180,6 +268,192 @@ impl ExchangeRate {
     }
 }
 
+/// The exchange rates of the last `EXCHANGE_RATE_HISTORY_LEN` updates.
+///
+/// `UpdateExchangeRate` appends to this, so integrators can compute the yield
+/// over multiple epochs on-chain. Entries are sorted by ascending epoch, the
+/// last entry is the most recent one. Entries that have not been written yet
+/// are all zeros.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct ExchangeRateHistory(pub [ExchangeRate; EXCHANGE_RATE_HISTORY_LEN]);
+
+impl ExchangeRateHistory {
+    /// Append `exchange_rate` at the end, dropping the oldest entry.
+    ///
+    /// Fails if `exchange_rate` is not from a later epoch than the most recent
+    /// entry, so that entries stay sorted by ascending epoch.
+    pub fn push(&mut self, exchange_rate: ExchangeRate) -> Result<(), LidoError> {
+        if let Some(last) = self.iter().last() {
+            if exchange_rate.computed_in_epoch <= last.computed_in_epoch {
+                msg!(
+                    "Exchange rate of epoch {} cannot follow the exchange rate of epoch {} in the history.",
+                    exchange_rate.computed_in_epoch,
+                    last.computed_in_epoch
+                );
+                return Err(LidoError::ExchangeRateInconsistent);
+            }
+        }
+        // The entry at index 0 is the oldest, we move it to the end to be
+        // overwritten.
+        self.0.rotate_left(1);
+        self.0[EXCHANGE_RATE_HISTORY_LEN - 1] = exchange_rate;
+        Ok(())
+    }
+
+    /// Iterate over the entries that have been written, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &ExchangeRate> {
+        let empty = ExchangeRate::default();
+        self.0.iter().filter(move |rate| **rate != empty)
+    }
+
+    /// Return the exchange rate that was computed in `epoch`, if it is still
+    /// in the history.
+    pub fn get(&self, epoch: Epoch) -> Option<&ExchangeRate> {
+        self.iter().find(|rate| rate.computed_in_epoch == epoch)
+    }
+}
+
+/// SOL to keep liquid in the reserve, rather than staking it.
+#[derive(
+    Copy, Clone, Debug, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub enum ReserveBuffer {
+    /// Keep a fixed amount of SOL in the reserve.
+    Amount(Lamports),
+
+    /// Keep a fraction of the SOL under management in the reserve, in basis
+    /// points in [0, 10_000].
+    Bps(u64),
+}
+
+impl Default for ReserveBuffer {
+    fn default() -> Self {
+        ReserveBuffer::Amount(Lamports(0))
+    }
+}
+
+impl ReserveBuffer {
+    /// Return the amount of SOL to keep in the reserve, given the SOL under
+    /// management.
+    pub fn get_amount(&self, sol_under_management: Lamports) -> token::Result<Lamports> {
+        match *self {
+            ReserveBuffer::Amount(amount) => Ok(amount),
+            ReserveBuffer::Bps(bps) => {
+                sol_under_management
+                    * Rational {
+                        numerator: bps,
+                        denominator: 10_000,
+                    }
+            }
+        }
+    }
+}
+
+/// An administrative role, that can be held by a different key than the manager.
+#[derive(
+    Copy, Clone, Debug, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub enum Role {
+    /// Adds and deactivates validators, and sets their weights and the
+    /// requirements that they need to meet.
+    ValidatorAdmin,
+
+    /// Sets the fees, fee recipients, deposit limits and reserve buffer.
+    FeeAdmin,
+
+    /// Adds and removes maintainers.
+    MaintainerAdmin,
+
+    /// Pauses operations and unstakes from validators in an emergency.
+    EmergencyGuardian,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::ValidatorAdmin,
+        Role::FeeAdmin,
+        Role::MaintainerAdmin,
+        Role::EmergencyGuardian,
+    ];
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::ValidatorAdmin => write!(f, "validator admin"),
+            Role::FeeAdmin => write!(f, "fee admin"),
+            Role::MaintainerAdmin => write!(f, "maintainer admin"),
+            Role::EmergencyGuardian => write!(f, "emergency guardian"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Role, &'static str> {
+        match s {
+            "validator-admin" => Ok(Role::ValidatorAdmin),
+            "fee-admin" => Ok(Role::FeeAdmin),
+            "maintainer-admin" => Ok(Role::MaintainerAdmin),
+            "emergency-guardian" => Ok(Role::EmergencyGuardian),
+            _ => Err("Invalid role, expected 'validator-admin', 'fee-admin', \
+                'maintainer-admin' or 'emergency-guardian'."),
+        }
+    }
+}
+
+/// The keys that hold each `Role`.
+///
+/// The manager assigns the roles with `SetRole`. Every role can be a different
+/// multisig, with a threshold that matches the risk of the role.
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct Roles {
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_admin: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    pub fee_admin: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    pub maintainer_admin: Pubkey,
+    #[serde(serialize_with = "serialize_b58")]
+    pub emergency_guardian: Pubkey,
+}
+
+impl Roles {
+    /// All roles held by the same key, which is how a new instance starts out.
+    pub fn new_all(authority: Pubkey) -> Roles {
+        Roles {
+            validator_admin: authority,
+            fee_admin: authority,
+            maintainer_admin: authority,
+            emergency_guardian: authority,
+        }
+    }
+
+    pub fn get(&self, role: Role) -> &Pubkey {
+        match role {
+            Role::ValidatorAdmin => &self.validator_admin,
+            Role::FeeAdmin => &self.fee_admin,
+            Role::MaintainerAdmin => &self.maintainer_admin,
+            Role::EmergencyGuardian => &self.emergency_guardian,
+        }
+    }
+
+    pub fn get_mut(&mut self, role: Role) -> &mut Pubkey {
+        match role {
+            Role::ValidatorAdmin => &mut self.validator_admin,
+            Role::FeeAdmin => &mut self.fee_admin,
+            Role::MaintainerAdmin => &mut self.maintainer_admin,
+            Role::EmergencyGuardian => &mut self.emergency_guardian,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(
     Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
@@ -204,12 +478,9 @@ pub struct Lido {
     pub stake_authority_bump_seed: u8,
     pub mint_authority_bump_seed: u8,
 
-    /// How rewards are distributed.
+    /// How rewards are distributed, and the stSOL accounts of the fee recipients.
     pub reward_distribution: RewardDistribution,
 
-    /// Accounts of the fee recipients.
-    pub fee_recipients: FeeRecipients,
-
     /// Metrics for informational purposes.
     ///
     /// Metrics are only written to, no program logic should depend on these values.
@@ -217,12 +488,85 @@ pub struct Lido {
     /// these metrics.
     pub metrics: Metrics,
 
-    /// Map of enrolled validators, maps their vote account to `Validator` details.
-    pub validators: Validators,
+    /// Account that holds the map of enrolled validators, which maps their vote
+    /// account to `Validator` details.
+    ///
+    /// The map lives in a separate account so it can be grown with
+    /// `ResizeValidatorList`, load it with `Lido::deserialize_validators`.
+    #[serde(serialize_with = "serialize_b58")]
+    pub validator_list: Pubkey,
 
     /// Maximum validation commission percentage in [0, 100]
     pub max_commission_percentage: u8,
 
+    /// Fee charged on `InstantUnstake`, in basis points of the stSOL amount.
+    ///
+    /// The fee is paid in stSOL to the treasury, the remainder is burned and
+    /// paid out in SOL from the reserve.
+    pub instant_unstake_fee_bps: u64,
+
+    /// Total amount of SOL owed to open withdrawal tickets.
+    ///
+    /// This SOL no longer backs any stSOL, so it is excluded from the SOL
+    /// balance that determines the exchange rate, and from the reserve balance
+    /// that can be staked.
+    pub withdrawal_tickets_owed: Lamports,
+
+    /// Minimum number of vote credits that an active validator must earn over
+    /// the last `vote_credits_epochs` completed epochs.
+    ///
+    /// Validators that earn fewer credits can be deactivated by anybody with
+    /// `DeactivateValidatorIfDelinquent`. A value of 0 disables the check.
+    pub min_vote_credits: u64,
+
+    /// Number of completed epochs over which to count vote credits, in range
+    /// [1, `MAX_EPOCH_CREDITS_HISTORY`].
+    pub vote_credits_epochs: u64,
+
+    /// Manager proposed with `ProposeNewManager`, that can become the manager
+    /// by signing `AcceptManager`.
+    ///
+    /// Set to `Pubkey::default()` when there is no pending manager.
+    pub pending_manager: Pubkey,
+
+    /// Operations paused by the manager, as a combination of the `PAUSE_*` bits.
+    ///
+    /// Paused operations fail with `ProgramPaused`. Bookkeeping such as
+    /// `UpdateExchangeRate` and `UpdateStakeAccountBalance` is never paused.
+    pub paused_operations: u8,
+
+    /// Exchange rates of the most recent epochs, see `ExchangeRateHistory`.
+    pub exchange_rate_history: ExchangeRateHistory,
+
+    /// Maximum amount of SOL under management, deposits that would exceed it
+    /// fail with `DepositExceedsMaximumTotalSol`. A value of 0 disables the cap.
+    pub max_total_sol: Lamports,
+
+    /// Minimum amount of SOL per deposit, smaller deposits fail with
+    /// `DepositBelowMinimum`. A value of 0 disables the check.
+    pub min_deposit: Lamports,
+
+    /// Maximum directed stake that counts towards the stake target of a
+    /// single validator, in basis points of the SOL under management.
+    ///
+    /// A value of 0 disables directed stake.
+    pub max_directed_stake_bps: u64,
+
+    /// SOL that `StakeDeposit` leaves in the reserve, so it stays available
+    /// for withdrawals and rebalancing without waiting for an epoch.
+    pub reserve_buffer: ReserveBuffer,
+
+    /// Fee charged on `Withdraw`, in basis points of the stSOL amount.
+    ///
+    /// The fee is paid in stSOL to the treasury, the remainder is burned and
+    /// paid out in a stake account.
+    pub withdrawal_fee_bps: u64,
+
+    /// Keys that can execute the administrative functions of each `Role`.
+    ///
+    /// The manager itself only assigns roles, and proposes a new manager.
+    pub roles: Roles,
+
     /// The set of maintainers.
     ///
     /// Maintainers are granted low security risk privileges. Maintainers are
@@ -242,23 +586,105 @@ impl Lido {
             );
             return Err(LidoError::InvalidOwner.into());
         }
+        // Check the version before deserializing, an older layout would
+        // otherwise be misinterpreted, or fail with an unhelpful error.
+        let version = lido.data.borrow().first().copied();
+        if version != Some(LIDO_VERSION) {
+            msg!(
+                "Lido state has version {:?}, but this program expects version {}. Migrate it with MigrateState.",
+                version,
+                LIDO_VERSION
+            );
+            return Err(LidoError::InvalidLidoVersion.into());
+        }
         let lido = try_from_slice_unchecked::<Lido>(&lido.data.borrow())?;
         Ok(lido)
     }
 
-    /// Calculates the total size of Lido given two variables: `max_validators`
-    /// and `max_maintainers`, the maximum number of maintainers and validators,
-    /// respectively. It creates default structures for both and sum its sizes
-    /// with Lido's constant size.
-    pub fn calculate_size(max_validators: u32, max_maintainers: u32) -> usize {
+    /// Deserialize the validators from the validator list account of this instance.
+    pub fn deserialize_validators(
+        &self,
+        program_id: &Pubkey,
+        validator_list: &AccountInfo,
+    ) -> Result<Validators, ProgramError> {
+        self.check_validator_list(program_id, validator_list)?;
+        let validators = try_from_slice_unchecked::<Validators>(&validator_list.data.borrow())?;
+        Ok(validators)
+    }
+
+    /// Borrow the validator list account of this instance, to read individual
+    /// validators without deserializing the entire list.
+    ///
+    /// The borrow must be released before any CPI that receives the account.
+    pub fn borrow_validators<'a>(
+        &self,
+        program_id: &Pubkey,
+        validator_list: &'a AccountInfo,
+    ) -> Result<ValidatorsView<Ref<'a, [u8]>>, ProgramError> {
+        self.check_validator_list(program_id, validator_list)?;
+        let data = Ref::map(validator_list.data.borrow(), |data| &**data);
+        Ok(ValidatorsView::new(data)?)
+    }
+
+    /// Like `borrow_validators`, but allows overwriting individual validators.
+    pub fn borrow_validators_mut<'a>(
+        &self,
+        program_id: &Pubkey,
+        validator_list: &'a AccountInfo,
+    ) -> Result<ValidatorsView<RefMut<'a, [u8]>>, ProgramError> {
+        self.check_validator_list(program_id, validator_list)?;
+        let data = RefMut::map(validator_list.data.borrow_mut(), |data| &mut **data);
+        Ok(ValidatorsView::new(data)?)
+    }
+
+    /// Confirm that `validator_list` is the validator list of this instance.
+    fn check_validator_list(
+        &self,
+        program_id: &Pubkey,
+        validator_list: &AccountInfo,
+    ) -> ProgramResult {
+        if validator_list.key != &self.validator_list {
+            msg!(
+                "Expected validator list {}, but got {}.",
+                self.validator_list,
+                validator_list.key
+            );
+            return Err(LidoError::InvalidValidatorList.into());
+        }
+        if validator_list.owner != program_id {
+            msg!(
+                "Validator list is owned by {}, but should be owned by the Lido program ({}).",
+                validator_list.owner,
+                program_id
+            );
+            return Err(LidoError::InvalidOwner.into());
+        }
+        Ok(())
+    }
+
+    /// Calculates the total size of Lido given `max_maintainers`, the maximum
+    /// number of maintainers. It creates a default structure for the
+    /// maintainers and sums its size with Lido's constant size.
+    ///
+    /// Validators live in a separate account, see `Validators::required_bytes`.
+    pub fn calculate_size(max_maintainers: u32) -> usize {
         let lido_instance = Lido {
-            validators: Validators::new_fill_default(max_validators),
             maintainers: Maintainers::new_fill_default(max_maintainers),
-            ..Default::default()
+            ..Lido::new_fill_fee_recipients()
         };
         get_instance_packed_len(&lido_instance).unwrap()
     }
 
+    /// Return a default instance with `MAXIMUM_FEE_RECIPIENTS` fee recipients,
+    /// and as many entries in the fee metrics, to determine the size to reserve.
+    fn new_fill_fee_recipients() -> Lido {
+        let mut lido = Lido::default();
+        lido.reward_distribution.fee_recipients =
+            vec![FeeRecipient::default(); MAXIMUM_FEE_RECIPIENTS];
+        lido.metrics.fee_recipients = vec![Default::default(); MAXIMUM_FEE_RECIPIENTS];
+        lido
+    }
+
     /// Confirm that the given account is Solido's stSOL mint.
     pub fn check_mint_is_st_sol_mint(&self, mint_account_info: &AccountInfo) -> ProgramResult {
         if &self.st_sol_mint != mint_account_info.key {
@@ -315,15 +741,145 @@ impl Lido {
         Ok(())
     }
 
-    /// Checks if the passed maintainer belong to the list of maintainers
-    pub fn check_maintainer(&self, maintainer: &AccountInfo) -> ProgramResult {
-        if self.maintainers.get(maintainer.key).is_err() {
+    /// Checks if the passed account is the one that holds `role`.
+    pub fn check_role(&self, role: Role, authority: &AccountInfo) -> ProgramResult {
+        let expected = self.roles.get(role);
+        if expected != authority.key {
+            msg!(
+                "Invalid {}, expected {} but got {}.",
+                role,
+                expected,
+                authority.key
+            );
+            return Err(LidoError::InvalidRoleAuthority.into());
+        }
+        Ok(())
+    }
+
+    /// Checks if the passed manager is the pending manager proposed by the current manager.
+    pub fn check_pending_manager(&self, pending_manager: &AccountInfo) -> ProgramResult {
+        if self.pending_manager == Pubkey::default() {
+            msg!("There is no pending manager, propose one with ProposeNewManager first.");
+            return Err(LidoError::InvalidPendingManager.into());
+        }
+        if &self.pending_manager != pending_manager.key {
+            msg!(
+                "Invalid pending manager, expected {} but got {}.",
+                self.pending_manager,
+                pending_manager.key
+            );
+            return Err(LidoError::InvalidPendingManager.into());
+        }
+        Ok(())
+    }
+
+    /// Return whether any of the given `PAUSE_*` operations is paused.
+    pub fn is_paused(&self, operations: u8) -> bool {
+        self.paused_operations & operations != 0
+    }
+
+    /// Confirm that the given `PAUSE_*` operation is not paused.
+    pub fn check_not_paused(&self, operation: u8, operation_name: &str) -> ProgramResult {
+        if self.is_paused(operation) {
+            msg!("{} is paused by the manager.", operation_name);
+            return Err(LidoError::ProgramPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Return the SOL value of `st_sol_supply` at the exchange rate of this epoch.
+    ///
+    /// This approximates the SOL under management, excluding rewards that have
+    /// not been observed yet, without having to load the validator list.
+    pub fn get_sol_under_management_estimate(
+        &self,
+        st_sol_supply: StLamports,
+    ) -> Result<Lamports, LidoError> {
+        // Before the first exchange rate update, stSOL is worth 1 SOL.
+        if self.exchange_rate.st_sol_supply == StLamports(0) {
+            Ok(Lamports(st_sol_supply.0))
+        } else {
+            self.exchange_rate.exchange_st_sol(st_sol_supply)
+        }
+    }
+
+    /// Return the maximum directed stake of a single validator, given the SOL
+    /// under management.
+    pub fn get_max_directed_stake(
+        &self,
+        sol_under_management: Lamports,
+    ) -> token::Result<Lamports> {
+        sol_under_management
+            * Rational {
+                numerator: self.max_directed_stake_bps,
+                denominator: 10_000,
+            }
+    }
+
+    /// Confirm that a deposit of `amount` respects `min_deposit` and `max_total_sol`.
+    ///
+    /// The SOL under management is estimated with
+    /// `get_sol_under_management_estimate`, this avoids loading the validator
+    /// list for every deposit.
+    pub fn check_deposit_limits(
+        &self,
+        amount: Lamports,
+        st_sol_supply: StLamports,
+    ) -> ProgramResult {
+        if amount < self.min_deposit {
+            msg!(
+                "Deposit of {} is below the minimum deposit of {}.",
+                amount,
+                self.min_deposit
+            );
+            return Err(LidoError::DepositBelowMinimum.into());
+        }
+
+        if self.max_total_sol == Lamports(0) {
+            return Ok(());
+        }
+
+        let sol_under_management = self.get_sol_under_management_estimate(st_sol_supply)?;
+        let total_after_deposit = (sol_under_management + amount)?;
+        if total_after_deposit > self.max_total_sol {
             msg!(
-                "Invalid maintainer, account {} is not present in the maintainers list.",
-                maintainer.key
+                "Deposit of {} would bring the SOL under management to {}, but the maximum is {}.",
+                amount,
+                total_after_deposit,
+                self.max_total_sol
             );
+            return Err(LidoError::DepositExceedsMaximumTotalSol.into());
+        }
+
+        Ok(())
+    }
 
-            return Err(LidoError::InvalidMaintainer.into());
+    /// Checks if the passed maintainer belong to the list of maintainers, and
+    /// that it is permitted to perform the given `MAINTAINER_*` operation.
+    pub fn check_maintainer(
+        &self,
+        maintainer: &AccountInfo,
+        operation: u8,
+        operation_name: &str,
+    ) -> ProgramResult {
+        let permissions = match self.maintainers.get(maintainer.key) {
+            Ok(pair) => pair.entry,
+            Err(_) => {
+                msg!(
+                    "Invalid maintainer, account {} is not present in the maintainers list.",
+                    maintainer.key
+                );
+                return Err(LidoError::InvalidMaintainer.into());
+            }
+        };
+        if permissions & operation == 0 {
+            msg!(
+                "Maintainer {} is not permitted to perform {}, its permissions are {:#010b}.",
+                maintainer.key,
+                operation_name,
+                permissions
+            );
+            return Err(LidoError::MaintainerOperationNotPermitted.into());
         }
         Ok(())
     }
@@ -332,25 +888,42 @@ impl Lido {
     ///
     /// Also confirm that the recipient is still an stSOL account.
     pub fn check_treasury_fee_st_sol_account(&self, st_sol_account: &AccountInfo) -> ProgramResult {
-        if &self.fee_recipients.treasury_account != st_sol_account.key {
+        if self.reward_distribution.treasury_account() != Some(st_sol_account.key) {
             msg!("Invalid treasury fee stSOL account, not the same as the one stored in state.");
             return Err(LidoError::InvalidFeeRecipient.into());
         }
         self.check_is_st_sol_account(st_sol_account)
     }
 
-    /// Check if the passed developer fee account is the one configured.
+    /// Check that the passed accounts are the stSOL accounts of the fee
+    /// recipients, in the order of `reward_distribution.fee_recipients`.
     ///
-    /// Also confirm that the recipient is still an stSOL account.
-    pub fn check_developer_fee_st_sol_account(
+    /// Also confirm that the recipients are still stSOL accounts.
+    pub fn check_fee_recipient_st_sol_accounts(
         &self,
-        st_sol_account: &AccountInfo,
+        st_sol_accounts: &[AccountInfo],
     ) -> ProgramResult {
-        if &self.fee_recipients.developer_account != st_sol_account.key {
-            msg!("Invalid developer fee stSOL account, not the same as the one stored in state.");
+        let fee_recipients = &self.reward_distribution.fee_recipients;
+        if st_sol_accounts.len() != fee_recipients.len() {
+            msg!(
+                "Expected {} fee recipient stSOL accounts, but got {}.",
+                fee_recipients.len(),
+                st_sol_accounts.len()
+            );
             return Err(LidoError::InvalidFeeRecipient.into());
         }
-        self.check_is_st_sol_account(st_sol_account)
+        for (recipient, st_sol_account) in fee_recipients.iter().zip(st_sol_accounts) {
+            if &recipient.st_sol_account != st_sol_account.key {
+                msg!(
+                    "Invalid fee recipient stSOL account, expected {} but got {}.",
+                    recipient.st_sol_account,
+                    st_sol_account.key
+                );
+                return Err(LidoError::InvalidFeeRecipient.into());
+            }
+            self.check_is_st_sol_account(st_sol_account)?;
+        }
+        Ok(())
     }
 
     /// Return the address of the reserve account, the account where SOL gets
@@ -442,9 +1015,13 @@ impl Lido {
     }
 
     /// Confirm that the amount to stake is more than the minimum stake amount,
-    /// and that we have sufficient SOL in the reserve.
+    /// and that we have sufficient SOL in the reserve, above the reserve buffer.
+    ///
+    /// `stake_accounts_balance` is the sum of the stake account balances of
+    /// all validators, see `get_sol_balance`.
     pub fn check_can_stake_amount(
         &self,
+        stake_accounts_balance: Lamports,
         reserve: &AccountInfo,
         amount: Lamports,
     ) -> Result<(), ProgramError> {
@@ -460,11 +1037,12 @@ impl Lido {
 
         let rent: Rent = Rent::get()?;
 
-        let available_reserve_amount = get_reserve_available_balance(&rent, reserve)?;
+        let available_reserve_amount =
+            self.get_reserve_stakeable_balance(stake_accounts_balance, &rent, reserve)?;
         if amount > available_reserve_amount {
             msg!(
                 "The requested amount {} is greater than the available amount {}, \
-                considering rent-exemption",
+                considering rent-exemption, open withdrawal tickets and the reserve buffer",
                 amount,
                 available_reserve_amount
             );
@@ -522,30 +1100,76 @@ impl Lido {
     ///
     /// This includes staked as well as non-staked SOL. It excludes SOL in the
     /// reserve that effectively locked because it is needed to keep the reserve
-    /// rent-exempt.
+    /// rent-exempt, and SOL that is owed to open withdrawal tickets.
     ///
     /// The computation is based on the amount of SOL per validator that we track
     /// ourselves, so if there are any unobserved rewards in the stake accounts,
     /// these will not be included.
     pub fn get_sol_balance(
         &self,
+        validators: &Validators,
         rent: &Rent,
         reserve: &AccountInfo,
     ) -> Result<Lamports, LidoError> {
-        let effective_reserve_balance = get_reserve_available_balance(rent, reserve)?;
-
         // The remaining SOL managed is all in stake accounts.
-        let validator_balance: token::Result<Lamports> = self
-            .validators
+        let validator_balance: Lamports = validators
             .iter_entries()
             .map(|v| v.stake_accounts_balance)
-            .sum();
+            .sum::<token::Result<Lamports>>()?;
+
+        self.get_sol_balance_with_stake(validator_balance, rent, reserve)
+    }
+
+    /// Like `get_sol_balance`, but with the sum of the stake account balances
+    /// of all validators already computed, for example with
+    /// `ValidatorsView::sum_stake_accounts_balance`.
+    pub fn get_sol_balance_with_stake(
+        &self,
+        stake_accounts_balance: Lamports,
+        rent: &Rent,
+        reserve: &AccountInfo,
+    ) -> Result<Lamports, LidoError> {
+        let effective_reserve_balance = get_reserve_available_balance(rent, reserve)?;
 
-        let result = validator_balance.and_then(|s| s + effective_reserve_balance)?;
+        let result = (stake_accounts_balance + effective_reserve_balance)
+            .and_then(|s| s - self.withdrawal_tickets_owed)?;
 
         Ok(result)
     }
 
+    /// Return the SOL in the reserve that is not owed to withdrawal tickets.
+    ///
+    /// This is the amount that can be staked or paid out immediately. Excludes
+    /// the rent-exempt amount, like `get_reserve_available_balance`.
+    pub fn get_reserve_unowed_balance(
+        &self,
+        rent: &Rent,
+        reserve: &AccountInfo,
+    ) -> Result<Lamports, LidoError> {
+        let available = get_reserve_available_balance(rent, reserve)?;
+        Ok(Lamports(
+            available.0.saturating_sub(self.withdrawal_tickets_owed.0),
+        ))
+    }
+
+    /// Return the SOL in the reserve that can be staked.
+    ///
+    /// This is the balance that is not owed to withdrawal tickets, minus the
+    /// `reserve_buffer`. A buffer in basis points is a fraction of the SOL
+    /// balance from `get_sol_balance_with_stake`.
+    pub fn get_reserve_stakeable_balance(
+        &self,
+        stake_accounts_balance: Lamports,
+        rent: &Rent,
+        reserve: &AccountInfo,
+    ) -> Result<Lamports, LidoError> {
+        let unowed = self.get_reserve_unowed_balance(rent, reserve)?;
+        let sol_under_management =
+            self.get_sol_balance_with_stake(stake_accounts_balance, rent, reserve)?;
+        let buffer = self.reserve_buffer.get_amount(sol_under_management)?;
+        Ok(Lamports(unowed.0.saturating_sub(buffer.0)))
+    }
+
     /// Return the total amount of stSOL in existence.
     ///
     /// The total is the amount minted so far
@@ -558,6 +1182,28 @@ impl Lido {
         Ok(minted_supply)
     }
 
+    /// Return the part of `amount` that is charged as fee for an instant unstake.
+    ///
+    /// The fee is rounded down, the remainder is what gets exchanged for SOL.
+    pub fn get_instant_unstake_fee(&self, amount: StLamports) -> token::Result<StLamports> {
+        amount
+            * Rational {
+                numerator: self.instant_unstake_fee_bps,
+                denominator: 10_000,
+            }
+    }
+
+    /// Return the part of `amount` that is charged as fee for a withdrawal.
+    ///
+    /// The fee is rounded down, the remainder is what gets exchanged for SOL.
+    pub fn get_withdrawal_fee(&self, amount: StLamports) -> token::Result<StLamports> {
+        amount
+            * Rational {
+                numerator: self.withdrawal_fee_bps,
+                denominator: 10_000,
+            }
+    }
+
     pub fn check_exchange_rate_last_epoch(
         &self,
         clock: &Clock,
@@ -594,6 +1240,27 @@ pub struct Validator {
     /// Controls if a validator is allowed to have new stake deposits.
     /// When removing a validator, this flag should be set to `false`.
     pub active: bool,
+
+    /// Relative share of the stake that this validator should receive.
+    ///
+    /// Stake targets are proportional to the weight, among active validators.
+    /// A validator with weight 0 receives no new stake.
+    pub weight: u32,
+
+    /// Amount of SOL that depositors directed to this validator with
+    /// `DepositDirected`, and that was not withdrawn since.
+    ///
+    /// `get_target_balance` adds this on top of the validator's share of the
+    /// stake, up to `Lido::max_directed_stake_bps` of the total. Withdrawals
+    /// from the validator reduce it proportionally.
+    pub directed_stake: Lamports,
+
+    /// Epoch in which the validator was added to the validator list.
+    ///
+    /// Once the validator has been in the list for `Lido::vote_credits_epochs`
+    /// epochs, `DeactivateValidatorIfDelinquent` counts the epochs for which its
+    /// vote account has no credits as zero credits.
+    pub added_in_epoch: Epoch,
 }
 
 #[repr(C)]
@@ -653,6 +1320,72 @@ impl Validator {
             .expect("Unstake balance cannot exceed the validator's total stake balance.")
     }
 
+    /// Return the weight to use for the stake target, which is 0 for inactive validators.
+    pub fn effective_weight(&self) -> u64 {
+        if self.active {
+            self.weight as u64
+        } else {
+            0
+        }
+    }
+
+    /// Return the effective stake balance in excess of the directed stake.
+    pub fn undirected_stake_balance(&self) -> Lamports {
+        Lamports(
+            self.effective_stake_balance()
+                .0
+                .saturating_sub(self.directed_stake.0),
+        )
+    }
+
+    /// Return the undirected stake balance divided by the weight.
+    ///
+    /// Directed stake is excluded, so a validator with directed stake gets
+    /// its share of the undirected stake on top of it, like in
+    /// `get_target_balance`.
+    ///
+    /// Only meaningful for validators with nonzero weight, the comparison
+    /// with a zero denominator is undefined.
+    pub fn stake_per_weight(&self) -> Rational {
+        Rational {
+            numerator: self.undirected_stake_balance().0,
+            denominator: self.weight as u64,
+        }
+    }
+
+    /// Record `amount` of directed stake, such that the total does not exceed
+    /// `max_directed_stake`. Returns the amount that was recorded.
+    pub fn add_directed_stake(
+        &mut self,
+        amount: Lamports,
+        max_directed_stake: Lamports,
+    ) -> token::Result<Lamports> {
+        let room = Lamports(max_directed_stake.0.saturating_sub(self.directed_stake.0));
+        let recorded = std::cmp::min(amount, room);
+        self.directed_stake = (self.directed_stake + recorded)?;
+        Ok(recorded)
+    }
+
+    /// Reduce the directed stake in proportion to `withdrawn` out of the
+    /// current stake balance.
+    ///
+    /// Call this before subtracting `withdrawn` from `stake_accounts_balance`.
+    pub fn remove_directed_stake_for_withdrawal(
+        &mut self,
+        withdrawn: Lamports,
+    ) -> token::Result<()> {
+        if self.stake_accounts_balance == Lamports(0) {
+            return Ok(());
+        }
+        let reduction = (self.directed_stake
+            * Rational {
+                numerator: withdrawn.0,
+                denominator: self.stake_accounts_balance.0,
+            })?;
+        self.directed_stake = Lamports(self.directed_stake.0.saturating_sub(reduction.0));
+        Ok(())
+    }
+
     pub fn observe_balance(observed: Lamports, tracked: Lamports, info: &str) -> ProgramResult {
         if observed < tracked {
             msg!(
@@ -676,6 +1409,9 @@ impl Default for Validator {
             stake_accounts_balance: Lamports(0),
             unstake_accounts_balance: Lamports(0),
             active: true,
+            weight: DEFAULT_VALIDATOR_WEIGHT,
+            directed_stake: Lamports(0),
+            added_in_epoch: 0,
         }
     }
 }
@@ -762,47 +1498,89 @@ impl PubkeyAndEntry<Validator> {
     }
 }
 
-/// Determines how rewards are split up among these parties, represented as the
-/// number of parts of the total. For example, if each party has 1 part, then
-/// they all get an equal share of the reward.
+/// Determines how rewards are split up among the fee recipients and stSOL
+/// holders, represented as the number of parts of the total. For example, if
+/// each party has 1 part, then they all get an equal share of the reward.
 #[derive(
     Clone, Default, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema, Serialize,
 )]
 pub struct RewardDistribution {
-    pub treasury_fee: u32,
-    pub developer_fee: u32,
+    /// Recipients of fees, at most `MAXIMUM_FEE_RECIPIENTS`.
+    ///
+    /// The first recipient is the treasury, it also receives the fee on
+    /// `InstantUnstake`.
+    pub fee_recipients: Vec<FeeRecipient>,
+
+    /// Parts of the reward that are not paid out, but make stSOL appreciate.
     pub st_sol_appreciation: u32,
 }
 
-/// Specifies the fee recipients, accounts that should be created by Lido's minter
+/// An stSOL account that receives a share of the rewards as fee.
 #[derive(
     Clone, Default, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema, Serialize,
 )]
-pub struct FeeRecipients {
-    #[serde(serialize_with = "serialize_b58")]
-    pub treasury_account: Pubkey,
+pub struct FeeRecipient {
+    /// stSOL SPL token account that the fee is minted to.
     #[serde(serialize_with = "serialize_b58")]
-    pub developer_account: Pubkey,
+    pub st_sol_account: Pubkey,
+
+    /// Parts of the reward that this recipient receives.
+    pub parts: u32,
 }
 
 impl RewardDistribution {
     pub fn sum(&self) -> u64 {
-        // These adds don't overflow because we widen from u32 to u64 first.
-        self.treasury_fee as u64 + self.developer_fee as u64 + self.st_sol_appreciation as u64
+        // These adds don't overflow because we widen from u32 to u64 first,
+        // and there are at most `MAXIMUM_FEE_RECIPIENTS` terms.
+        self.fee_recipients
+            .iter()
+            .map(|recipient| recipient.parts as u64)
+            .sum::<u64>()
+            + self.st_sol_appreciation as u64
     }
 
-    pub fn treasury_fraction(&self) -> Rational {
+    pub fn fraction(&self, parts: u32) -> Rational {
         Rational {
-            numerator: self.treasury_fee as u64,
+            numerator: parts as u64,
             denominator: self.sum(),
         }
     }
 
-    pub fn developer_fraction(&self) -> Rational {
-        Rational {
-            numerator: self.developer_fee as u64,
-            denominator: self.sum(),
+    /// Return the stSOL account of the treasury, the first fee recipient.
+    pub fn treasury_account(&self) -> Option<&Pubkey> {
+        self.fee_recipients
+            .first()
+            .map(|recipient| &recipient.st_sol_account)
+    }
+
+    /// Confirm that there is at least one, and at most `MAXIMUM_FEE_RECIPIENTS`
+    /// fee recipients, and that no stSOL account occurs twice.
+    pub fn check_fee_recipients(&self) -> ProgramResult {
+        if self.fee_recipients.is_empty() {
+            msg!("There must be at least one fee recipient, the treasury.");
+            return Err(LidoError::InvalidFeeRecipient.into());
+        }
+        if self.fee_recipients.len() > MAXIMUM_FEE_RECIPIENTS {
+            msg!(
+                "Got {} fee recipients, but there can be at most {}.",
+                self.fee_recipients.len(),
+                MAXIMUM_FEE_RECIPIENTS
+            );
+            return Err(LidoError::MaximumNumberOfAccountsExceeded.into());
+        }
+        for (i, recipient) in self.fee_recipients.iter().enumerate() {
+            if self.fee_recipients[..i]
+                .iter()
+                .any(|other| other.st_sol_account == recipient.st_sol_account)
+            {
+                msg!(
+                    "Fee recipient {} occurs more than once.",
+                    recipient.st_sol_account
+                );
+                return Err(LidoError::DuplicatedEntry.into());
+            }
         }
+        Ok(())
     }
 
     /// Split the reward according to the distribution defined in this instance.
@@ -818,21 +1596,22 @@ impl RewardDistribution {
     /// donation to the pool, and makes the SOL value of stSOL go up. It is not
     /// included in the output, as nothing needs to be done to handle it.
     pub fn split_reward(&self, amount: Lamports) -> token::Result<Fees> {
-        use std::ops::Add;
-
-        let treasury_amount = (amount * self.treasury_fraction())?;
-        let developer_amount = (amount * self.developer_fraction())?;
+        let mut total_fees = Lamports(0);
+        let mut recipient_amounts = Vec::with_capacity(self.fee_recipients.len());
+        for recipient in self.fee_recipients.iter() {
+            let recipient_amount = (amount * self.fraction(recipient.parts))?;
+            total_fees = (total_fees + recipient_amount)?;
+            recipient_amounts.push(recipient_amount);
+        }
 
         // Sanity check: We should not produce more fees than we had to split in
         // the first place.
-        let total_fees = Lamports(0).add(treasury_amount)?.add(developer_amount)?;
         assert!(total_fees <= amount);
 
         let st_sol_appreciation_amount = (amount - total_fees)?;
 
         let result = Fees {
-            treasury_amount,
-            developer_amount,
+            recipient_amounts,
             st_sol_appreciation_amount,
         };
 
@@ -846,8 +1625,8 @@ impl RewardDistribution {
 /// is implicitly the remainder.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Fees {
-    pub treasury_amount: Lamports,
-    pub developer_amount: Lamports,
+    /// Fee per recipient, in the order of `RewardDistribution::fee_recipients`.
+    pub recipient_amounts: Vec<Lamports>,
 
     /// Remainder of the reward.
     ///
@@ -856,6 +1635,75 @@ pub struct Fees {
     pub st_sol_appreciation_amount: Lamports,
 }
 
+/// Size of a serialized `WithdrawalTicket`.
+pub const WITHDRAWAL_TICKET_SIZE: usize = 80;
+
+/// A claim on SOL from the reserve, created by `RequestWithdrawal`.
+///
+/// The stSOL was burned when the ticket was created, the SOL amount is fixed
+/// at the exchange rate of that moment. The ticket can be claimed with
+/// `ClaimWithdrawal` in a later epoch, once the maintainers have unstaked
+/// enough SOL and it has been returned to the reserve.
+#[repr(C)]
+#[derive(
+    Clone, Debug, Default, BorshDeserialize, BorshSerialize, BorshSchema, Eq, PartialEq, Serialize,
+)]
+pub struct WithdrawalTicket {
+    /// The Solido instance that owes the SOL.
+    #[serde(serialize_with = "serialize_b58")]
+    pub solido: Pubkey,
+
+    /// The account that can claim the ticket.
+    #[serde(serialize_with = "serialize_b58")]
+    pub owner: Pubkey,
+
+    /// Amount of SOL owed to the owner.
+    pub amount: Lamports,
+
+    /// Epoch in which the ticket was created.
+    pub created_epoch: Epoch,
+}
+
+impl WithdrawalTicket {
+    pub fn deserialize_withdrawal_ticket(
+        program_id: &Pubkey,
+        solido_address: &Pubkey,
+        ticket: &AccountInfo,
+    ) -> Result<WithdrawalTicket, ProgramError> {
+        if ticket.owner != program_id {
+            msg!(
+                "Withdrawal ticket is owned by {}, but should be owned by the Lido program ({}).",
+                ticket.owner,
+                program_id
+            );
+            return Err(LidoError::InvalidOwner.into());
+        }
+        if ticket.data_len() != WITHDRAWAL_TICKET_SIZE {
+            msg!(
+                "Withdrawal ticket should be {} bytes, but it is {} bytes.",
+                WITHDRAWAL_TICKET_SIZE,
+                ticket.data_len()
+            );
+            return Err(LidoError::InvalidWithdrawalTicket.into());
+        }
+        let result = WithdrawalTicket::try_from_slice(&ticket.data.borrow())?;
+        if &result.solido != solido_address {
+            msg!(
+                "Withdrawal ticket belongs to Solido instance {}, not {}.",
+                result.solido,
+                solido_address
+            );
+            return Err(LidoError::InvalidWithdrawalTicket.into());
+        }
+        Ok(result)
+    }
+
+    pub fn save(&self, account: &AccountInfo) -> ProgramResult {
+        BorshSerialize::serialize(self, &mut *account.data.borrow_mut())?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test_lido {
     use super::*;
@@ -886,6 +1734,35 @@ mod test_lido {
         }
     }
 
+    #[test]
+    fn test_directed_stake_bookkeeping() {
+        let mut validator = Validator::default();
+        validator.stake_accounts_balance = Lamports(1_000);
+
+        // Directed stake is recorded up to the maximum.
+        assert_eq!(
+            validator.add_directed_stake(Lamports(300), Lamports(500)),
+            Ok(Lamports(300))
+        );
+        assert_eq!(
+            validator.add_directed_stake(Lamports(300), Lamports(500)),
+            Ok(Lamports(200))
+        );
+        assert_eq!(validator.directed_stake, Lamports(500));
+        assert_eq!(
+            validator.add_directed_stake(Lamports(300), Lamports(400)),
+            Ok(Lamports(0))
+        );
+        assert_eq!(validator.directed_stake, Lamports(500));
+        assert_eq!(validator.undirected_stake_balance(), Lamports(500));
+
+        // Withdrawing 10% of the stake removes 10% of the directed stake.
+        validator
+            .remove_directed_stake_for_withdrawal(Lamports(100))
+            .unwrap();
+        assert_eq!(validator.directed_stake, Lamports(450));
+    }
+
     #[test]
     fn test_validators_size() {
         let validator = get_instance_packed_len(&Validator::default()).unwrap();
@@ -903,31 +1780,31 @@ mod test_lido {
     #[test]
     fn test_lido_constant_size() {
         // The minimal size of the struct is its size without any validators and
-        // maintainers.
-        let minimal = Lido::default();
+        // maintainers, but with the space for fee recipients reserved.
+        let minimal = Lido::new_fill_fee_recipients();
         let mut data = Vec::new();
         BorshSerialize::serialize(&minimal, &mut data).unwrap();
 
         let num_entries = 0;
-        let size_validators = Validators::required_bytes(num_entries);
         let size_maintainers = Maintainers::required_bytes(num_entries);
 
-        assert_eq!(
-            data.len() - size_validators - size_maintainers,
-            LIDO_CONSTANT_SIZE
-        );
+        assert_eq!(data.len() - size_maintainers, LIDO_CONSTANT_SIZE);
+    }
+
+    #[test]
+    fn test_withdrawal_ticket_size() {
+        let ticket = WithdrawalTicket::default();
+        let mut data = Vec::new();
+        BorshSerialize::serialize(&ticket, &mut data).unwrap();
+        assert_eq!(data.len(), WITHDRAWAL_TICKET_SIZE);
     }
 
     #[test]
     fn test_lido_serialization_roundtrips() {
         use solana_sdk::borsh::try_from_slice_unchecked;
 
-        let mut validators = Validators::new(10_000);
-        validators
-            .add(Pubkey::new_unique(), Validator::new())
-            .unwrap();
         let maintainers = Maintainers::new(1);
-        let lido = Lido {
+        let mut lido = Lido {
             lido_version: 0,
             manager: Pubkey::new_unique(),
             st_sol_mint: Pubkey::new_unique(),
@@ -940,19 +1817,42 @@ mod test_lido {
             stake_authority_bump_seed: 2,
             mint_authority_bump_seed: 3,
             reward_distribution: RewardDistribution {
-                treasury_fee: 2,
-                developer_fee: 4,
+                fee_recipients: vec![
+                    FeeRecipient {
+                        st_sol_account: Pubkey::new_unique(),
+                        parts: 2,
+                    },
+                    FeeRecipient {
+                        st_sol_account: Pubkey::new_unique(),
+                        parts: 4,
+                    },
+                ],
                 st_sol_appreciation: 7,
             },
-            fee_recipients: FeeRecipients {
-                treasury_account: Pubkey::new_unique(),
-                developer_account: Pubkey::new_unique(),
-            },
             metrics: Metrics::new(),
-            validators: validators,
+            validator_list: Pubkey::new_unique(),
             maintainers: maintainers,
             max_commission_percentage: 5,
+            instant_unstake_fee_bps: 30,
+            withdrawal_tickets_owed: Lamports(7),
+            min_vote_credits: 1_000,
+            vote_credits_epochs: 3,
+            pending_manager: Pubkey::new_unique(),
+            paused_operations: PAUSE_DEPOSIT | PAUSE_UNSTAKE,
+            exchange_rate_history: ExchangeRateHistory::default(),
+            max_total_sol: Lamports(1_000_000_000_000),
+            min_deposit: Lamports(1_000_000),
+            max_directed_stake_bps: 1_000,
+            reserve_buffer: ReserveBuffer::Bps(500),
+            withdrawal_fee_bps: 25,
+            roles: Roles {
+                validator_admin: Pubkey::new_unique(),
+                fee_admin: Pubkey::new_unique(),
+                maintainer_admin: Pubkey::new_unique(),
+                emergency_guardian: Pubkey::new_unique(),
+            },
         };
+        lido.exchange_rate_history.push(lido.exchange_rate).unwrap();
         let mut data = Vec::new();
         BorshSerialize::serialize(&lido, &mut data).unwrap();
 
@@ -960,6 +1860,79 @@ mod test_lido {
         assert_eq!(lido, lido_restored);
     }
 
+    #[test]
+    fn test_reserve_buffer_get_amount() {
+        let sol_under_management = Lamports(2_000);
+        assert_eq!(
+            ReserveBuffer::default().get_amount(sol_under_management),
+            Ok(Lamports(0))
+        );
+        assert_eq!(
+            ReserveBuffer::Amount(Lamports(300)).get_amount(sol_under_management),
+            Ok(Lamports(300))
+        );
+        assert_eq!(
+            ReserveBuffer::Bps(250).get_amount(sol_under_management),
+            Ok(Lamports(50))
+        );
+        assert_eq!(
+            ReserveBuffer::Bps(10_000).get_amount(sol_under_management),
+            Ok(sol_under_management)
+        );
+    }
+
+    #[test]
+    fn test_exchange_rate_history_keeps_most_recent_epochs() {
+        let mut history = ExchangeRateHistory::default();
+        assert_eq!(history.iter().count(), 0);
+
+        let rate = |epoch: Epoch| ExchangeRate {
+            computed_in_epoch: epoch,
+            sol_balance: Lamports(100 + epoch),
+            st_sol_supply: StLamports(100),
+        };
+
+        history.push(rate(1)).unwrap();
+        history.push(rate(2)).unwrap();
+        assert_eq!(
+            history.iter().cloned().collect::<Vec<_>>(),
+            vec![rate(1), rate(2)]
+        );
+        assert_eq!(history.get(2), Some(&rate(2)));
+        assert_eq!(history.get(3), None);
+
+        // Once the history is full, the oldest entries are dropped.
+        for epoch in 3..=EXCHANGE_RATE_HISTORY_LEN as Epoch + 2 {
+            history.push(rate(epoch)).unwrap();
+        }
+        assert_eq!(history.iter().count(), EXCHANGE_RATE_HISTORY_LEN);
+        assert_eq!(history.iter().next(), Some(&rate(3)));
+        assert_eq!(history.get(2), None);
+    }
+
+    #[test]
+    fn test_exchange_rate_history_rejects_epochs_that_are_not_newer() {
+        let mut history = ExchangeRateHistory::default();
+        let rate = |epoch: Epoch| ExchangeRate {
+            computed_in_epoch: epoch,
+            sol_balance: Lamports(100 + epoch),
+            st_sol_supply: StLamports(100),
+        };
+
+        history.push(rate(5)).unwrap();
+        assert_eq!(
+            history.push(rate(5)),
+            Err(LidoError::ExchangeRateInconsistent)
+        );
+        assert_eq!(
+            history.push(rate(4)),
+            Err(LidoError::ExchangeRateInconsistent)
+        );
+
+        // The rejected entries did not modify the history.
+        assert_eq!(history.iter().cloned().collect::<Vec<_>>(), vec![rate(5)]);
+    }
+
     #[test]
     fn test_exchange_when_balance_and_supply_are_zero() {
         let rate = ExchangeRate {
@@ -1075,13 +2048,14 @@ mod test_lido {
 
         let rent = &Rent::default();
         let mut lido = Lido::default();
+        let mut validators = Validators::new(1);
         let key = Pubkey::default();
         let mut amount = rent.minimum_balance(0);
         let mut reserve_account =
             AccountInfo::new(&key, true, true, &mut amount, &mut [], &key, false, 0);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
             Ok(Lamports(0))
         );
 
@@ -1089,24 +2063,36 @@ mod test_lido {
         reserve_account.lamports = Rc::new(RefCell::new(&mut new_amount));
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
             Ok(Lamports(10))
         );
 
-        lido.validators.maximum_entries = 1;
-        lido.validators
+        validators
             .add(Pubkey::new_unique(), Validator::new())
             .unwrap();
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(37);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(37);
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
             Ok(Lamports(10 + 37))
         );
 
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(u64::MAX);
+        // SOL owed to withdrawal tickets does not count towards the balance,
+        // and cannot be staked from the reserve.
+        lido.withdrawal_tickets_owed = Lamports(7);
+        assert_eq!(
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
+            Ok(Lamports(10 + 37 - 7))
+        );
+        assert_eq!(
+            lido.get_reserve_unowed_balance(&rent, &reserve_account),
+            Ok(Lamports(3))
+        );
+        lido.withdrawal_tickets_owed = Lamports(0);
+
+        validators.entries[0].entry.stake_accounts_balance = Lamports(u64::MAX);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
             Err(LidoError::CalculationFailure)
         );
 
@@ -1114,10 +2100,10 @@ mod test_lido {
         reserve_account.lamports = Rc::new(RefCell::new(&mut new_amount));
         // The amount here is more than the rent exemption that gets discounted
         // from the reserve, causing an overflow.
-        lido.validators.entries[0].entry.stake_accounts_balance = Lamports(5_000_000);
+        validators.entries[0].entry.stake_accounts_balance = Lamports(5_000_000);
 
         assert_eq!(
-            lido.get_sol_balance(&rent, &reserve_account),
+            lido.get_sol_balance(&validators, &rent, &reserve_account),
             Err(LidoError::CalculationFailure)
         );
     }
@@ -1171,9 +2157,12 @@ mod test_lido {
 
     #[test]
     fn test_split_reward() {
+        let recipient = |parts| FeeRecipient {
+            st_sol_account: Pubkey::new_unique(),
+            parts,
+        };
         let mut spec = RewardDistribution {
-            treasury_fee: 3,
-            developer_fee: 1,
+            fee_recipients: vec![recipient(3), recipient(1)],
             st_sol_appreciation: 0,
         };
 
@@ -1182,8 +2171,7 @@ mod test_lido {
             // there is no remainder.
             spec.split_reward(Lamports(600)).unwrap(),
             Fees {
-                treasury_amount: Lamports(450),
-                developer_amount: Lamports(150),
+                recipient_amounts: vec![Lamports(450), Lamports(150)],
                 st_sol_appreciation_amount: Lamports(0),
             },
         );
@@ -1193,8 +2181,7 @@ mod test_lido {
             // rounded down.
             spec.split_reward(Lamports(1_003)).unwrap(),
             Fees {
-                treasury_amount: Lamports(752),
-                developer_amount: Lamports(250),
+                recipient_amounts: vec![Lamports(752), Lamports(250)],
                 st_sol_appreciation_amount: Lamports(1),
             },
         );
@@ -1205,26 +2192,164 @@ mod test_lido {
         assert_eq!(
             spec.split_reward(Lamports(100)).unwrap(),
             Fees {
-                treasury_amount: Lamports(3),
-                developer_amount: Lamports(1),
+                recipient_amounts: vec![Lamports(3), Lamports(1)],
                 st_sol_appreciation_amount: Lamports(96),
             },
         );
 
         let spec_coprime = RewardDistribution {
-            treasury_fee: 17,
-            developer_fee: 19,
+            fee_recipients: vec![recipient(17), recipient(19)],
             st_sol_appreciation: 0,
         };
         assert_eq!(
             spec_coprime.split_reward(Lamports(1_000)).unwrap(),
             Fees {
-                treasury_amount: Lamports(472),
-                developer_amount: Lamports(527),
+                recipient_amounts: vec![Lamports(472), Lamports(527)],
                 st_sol_appreciation_amount: Lamports(1),
             },
         );
+
+        // Any number of recipients can share in the fees.
+        let spec_many = RewardDistribution {
+            fee_recipients: vec![recipient(2), recipient(1), recipient(1), recipient(1)],
+            st_sol_appreciation: 95,
+        };
+        assert_eq!(
+            spec_many.split_reward(Lamports(1_000)).unwrap(),
+            Fees {
+                recipient_amounts: vec![Lamports(20), Lamports(10), Lamports(10), Lamports(10)],
+                st_sol_appreciation_amount: Lamports(950),
+            },
+        );
+    }
+
+    #[test]
+    fn test_check_fee_recipients() {
+        let recipient = FeeRecipient {
+            st_sol_account: Pubkey::new_unique(),
+            parts: 1,
+        };
+        let mut spec = RewardDistribution {
+            fee_recipients: vec![],
+            st_sol_appreciation: 1,
+        };
+        assert_eq!(
+            spec.check_fee_recipients(),
+            Err(LidoError::InvalidFeeRecipient.into())
+        );
+
+        spec.fee_recipients.push(recipient.clone());
+        assert_eq!(spec.check_fee_recipients(), Ok(()));
+        assert_eq!(spec.treasury_account(), Some(&recipient.st_sol_account));
+
+        spec.fee_recipients.push(recipient);
+        assert_eq!(
+            spec.check_fee_recipients(),
+            Err(LidoError::DuplicatedEntry.into())
+        );
+
+        spec.fee_recipients = (0..=MAXIMUM_FEE_RECIPIENTS)
+            .map(|_| FeeRecipient {
+                st_sol_account: Pubkey::new_unique(),
+                parts: 1,
+            })
+            .collect();
+        assert_eq!(
+            spec.check_fee_recipients(),
+            Err(LidoError::MaximumNumberOfAccountsExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_get_instant_unstake_fee() {
+        let mut lido = Lido::default();
+        assert_eq!(
+            lido.get_instant_unstake_fee(StLamports(1_000)),
+            Ok(StLamports(0))
+        );
+
+        // 0.3% fee.
+        lido.instant_unstake_fee_bps = 30;
+        assert_eq!(
+            lido.get_instant_unstake_fee(StLamports(1_000_000)),
+            Ok(StLamports(3_000))
+        );
+        // The fee is rounded down.
+        assert_eq!(
+            lido.get_instant_unstake_fee(StLamports(999)),
+            Ok(StLamports(2))
+        );
+
+        lido.instant_unstake_fee_bps = 10_000;
+        assert_eq!(
+            lido.get_instant_unstake_fee(StLamports(1_234)),
+            Ok(StLamports(1_234))
+        );
+    }
+
+    #[test]
+    fn test_get_withdrawal_fee() {
+        let mut lido = Lido::default();
+        assert_eq!(
+            lido.get_withdrawal_fee(StLamports(1_000)),
+            Ok(StLamports(0))
+        );
+
+        // 0.25% fee, rounded down.
+        lido.withdrawal_fee_bps = 25;
+        assert_eq!(
+            lido.get_withdrawal_fee(StLamports(1_000_000)),
+            Ok(StLamports(2_500))
+        );
+        assert_eq!(lido.get_withdrawal_fee(StLamports(399)), Ok(StLamports(0)));
+    }
+
+    #[test]
+    fn test_check_deposit_limits() {
+        let mut lido = Lido::default();
+        // Without limits, anything goes.
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(1), StLamports(u64::MAX - 1)),
+            Ok(())
+        );
+
+        lido.min_deposit = Lamports(100);
+        lido.max_total_sol = Lamports(1_000);
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(99), StLamports(0)),
+            Err(LidoError::DepositBelowMinimum.into())
+        );
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(100), StLamports(0)),
+            Ok(())
+        );
+
+        // Before the first exchange rate update, stSOL is valued 1:1.
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(500), StLamports(500)),
+            Ok(())
+        );
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(501), StLamports(500)),
+            Err(LidoError::DepositExceedsMaximumTotalSol.into())
+        );
+
+        // At 1 stSOL = 2 SOL, 400 stSOL is worth 800 SOL.
+        lido.exchange_rate = ExchangeRate {
+            computed_in_epoch: 1,
+            st_sol_supply: StLamports(100),
+            sol_balance: Lamports(200),
+        };
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(200), StLamports(400)),
+            Ok(())
+        );
+        assert_eq!(
+            lido.check_deposit_limits(Lamports(201), StLamports(400)),
+            Err(LidoError::DepositExceedsMaximumTotalSol.into())
+        );
     }
+
     #[test]
     fn test_n_val() {
         let n_validators: u64 = 10_000;
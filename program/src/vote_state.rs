@@ -2,14 +2,30 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use crate::error::LidoError;
-use solana_program::{account_info::AccountInfo, msg, pubkey::Pubkey};
-use std::convert::TryInto;
+use solana_program::{account_info::AccountInfo, clock::Epoch, msg, pubkey::Pubkey};
+use std::convert::{TryFrom, TryInto};
 
 /// Structure used to read the first 4 fields of a Solana `VoteAccount`.
 /// The original `VoteAccount` structure cannot be used in a Solana
 /// program due to size constrains.
 
 const PARTIAL_VOTE_STATE_LEN: usize = 69;
+
+/// Maximum number of epochs for which a vote account keeps its credits.
+pub const MAX_EPOCH_CREDITS_HISTORY: u64 = 64;
+
+/// Version tag of the only vote state layout that we know how to parse.
+const VOTE_STATE_VERSION_CURRENT: u32 = 1;
+
+/// Number of entries in the circular buffer of prior voters in a vote account.
+const MAX_PRIOR_VOTERS: usize = 32;
+
+/// Credits of a vote account in an epoch: `(epoch, credits, prev_credits)`.
+///
+/// The credits are cumulative, the credits earned in the epoch are
+/// `credits - prev_credits`.
+pub type EpochCredits = (Epoch, u64, u64);
+
 #[derive(Debug, PartialEq)]
 pub struct PartialVoteState {
     /// comes from an enum inside the `VoteState` structure
@@ -49,7 +65,7 @@ impl PartialVoteState {
                 .try_into()
                 .map_err(|_| LidoError::InvalidVoteAccount)?,
         );
-        if version != 1 {
+        if version != VOTE_STATE_VERSION_CURRENT {
             msg!(
                 "Vote State account version should be 1, it's {} instead.",
                 version
@@ -82,11 +98,128 @@ pub fn get_vote_account_commission(vote_account_data: &[u8]) -> Option<u8> {
     vote_account_data.get(68).copied() // Read 1 byte for u8.
 }
 
+/// Read a little-endian u64 at the given offset.
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read the length prefix of a collection at the given offset, and return the
+/// length and the offset after the collection, given the size of an element.
+fn skip_collection(data: &[u8], offset: usize, element_size: usize) -> Option<(usize, usize)> {
+    let len = usize::try_from(read_u64(data, offset)?).ok()?;
+    let end = len
+        .checked_mul(element_size)?
+        .checked_add(offset)?
+        .checked_add(8)?;
+    if end > data.len() {
+        return None;
+    }
+    Some((len, end))
+}
+
+/// Read the `epoch_credits` field of a vote account.
+///
+/// The fields that precede it have a variable size, so we have to walk over
+/// them. Returns `None` if the data is too small to hold the vote state, or if
+/// it has a layout other than the one we know.
+pub fn get_vote_account_epoch_credits(vote_account_data: &[u8]) -> Option<Vec<EpochCredits>> {
+    let data = vote_account_data;
+
+    let version = u32::from_le_bytes(data.get(0..4)?.try_into().ok()?);
+    if version != VOTE_STATE_VERSION_CURRENT {
+        return None;
+    }
+
+    // Skip the version, node pubkey, authorized withdrawer, and commission.
+    let offset = PARTIAL_VOTE_STATE_LEN;
+
+    // Skip `votes`, a list of (slot: u64, confirmation_count: u32).
+    let (_, offset) = skip_collection(data, offset, 12)?;
+
+    // Skip `root_slot`, an `Option<u64>`.
+    let offset = match data.get(offset)? {
+        0 => offset + 1,
+        1 => offset + 9,
+        _ => return None,
+    };
+
+    // Skip `authorized_voters`, a map from epoch to pubkey.
+    let (_, offset) = skip_collection(data, offset, 8 + 32)?;
+
+    // Skip `prior_voters`, a circular buffer of (pubkey, epoch, epoch), followed
+    // by an u64 index and a bool.
+    let offset = offset.checked_add(MAX_PRIOR_VOTERS * (32 + 8 + 8) + 8 + 1)?;
+
+    // Read `epoch_credits`, a list of (epoch, credits, prev_credits).
+    let (len, _) = skip_collection(data, offset, 24)?;
+    let mut epoch_credits = Vec::with_capacity(len);
+    for i in 0..len {
+        let entry_offset = offset + 8 + i * 24;
+        epoch_credits.push((
+            read_u64(data, entry_offset)?,
+            read_u64(data, entry_offset + 8)?,
+            read_u64(data, entry_offset + 16)?,
+        ));
+    }
+
+    Some(epoch_credits)
+}
+
+/// Return whether we can tell if a validator is delinquent from its credits in
+/// the `num_epochs` completed epochs before `current_epoch`.
+///
+/// That is the case when the vote account has a credits history of at least
+/// `num_epochs` epochs, or when the validator has been in the validator list
+/// since `added_in_epoch` for at least `num_epochs` epochs; then the epochs for
+/// which the vote account has no credits count as zero. Validators that
+/// satisfy neither are too new to judge.
+pub fn can_judge_vote_credits(
+    epoch_credits: &[EpochCredits],
+    added_in_epoch: Epoch,
+    current_epoch: Epoch,
+    num_epochs: u64,
+) -> bool {
+    epoch_credits.len() as u64 >= num_epochs
+        || current_epoch.saturating_sub(added_in_epoch) >= num_epochs
+}
+
+/// Return the number of credits earned in the `num_epochs` completed epochs
+/// before `current_epoch`.
+///
+/// Epochs for which there is no entry count as zero credits.
+pub fn get_credits_in_last_epochs(
+    epoch_credits: &[EpochCredits],
+    current_epoch: Epoch,
+    num_epochs: u64,
+) -> u64 {
+    let first_epoch = current_epoch.saturating_sub(num_epochs);
+    epoch_credits
+        .iter()
+        .filter(|(epoch, _, _)| *epoch >= first_epoch && *epoch < current_epoch)
+        .map(|(_, credits, prev_credits)| credits.saturating_sub(*prev_credits))
+        .sum()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use std::str::FromStr;
 
+    /// Pad the vote account excerpt up to `epoch_credits`, and append those.
+    fn append_epoch_credits(data: &mut Vec<u8>, epoch_credits: &[EpochCredits]) {
+        // The excerpts end inside `prior_voters`, which starts at offset 126.
+        data.resize(126 + MAX_PRIOR_VOTERS * 48 + 8 + 1, 0);
+        data.extend_from_slice(&(epoch_credits.len() as u64).to_le_bytes());
+        for (epoch, credits, prev_credits) in epoch_credits {
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(&credits.to_le_bytes());
+            data.extend_from_slice(&prev_credits.to_le_bytes());
+        }
+        // `last_timestamp`, a slot and a unix timestamp.
+        data.extend_from_slice(&[0; 16]);
+    }
+
     #[test]
     fn test_deserialize() {
         // excerpt from actual vote account
@@ -154,4 +287,65 @@ mod test {
             Err(LidoError::InvalidVoteAccount)
         );
     }
+
+    #[test]
+    fn test_epoch_credits_truncated() {
+        let mut data = vec![0; 200];
+        data[0] = 1;
+        assert_eq!(get_vote_account_epoch_credits(&data), None);
+
+        // Version 1, no votes, no root slot, and one authorized voter.
+        let mut data = vec![0; 126];
+        data[0] = 1;
+        data[78] = 1;
+        append_epoch_credits(&mut data, &[(5, 10, 0)]);
+        assert_eq!(
+            get_vote_account_epoch_credits(&data),
+            Some(vec![(5, 10, 0)])
+        );
+        data.truncate(data.len() - 17);
+        assert_eq!(get_vote_account_epoch_credits(&data), None);
+    }
+
+    #[test]
+    fn test_epoch_credits_unknown_version() {
+        let mut data = vec![0; 126];
+        data[0] = 1;
+        data[78] = 1;
+        append_epoch_credits(&mut data, &[(5, 10, 0)]);
+
+        // Other versions have a different layout, we must not misparse them.
+        for version in [0_u32, 2, 3] {
+            data[0..4].copy_from_slice(&version.to_le_bytes());
+            assert_eq!(get_vote_account_epoch_credits(&data), None);
+        }
+    }
+
+    #[test]
+    fn test_can_judge_vote_credits() {
+        let epoch_credits = [(5, 150, 100), (6, 350, 150)];
+        assert!(can_judge_vote_credits(&epoch_credits, 7, 7, 1));
+        assert!(can_judge_vote_credits(&epoch_credits, 7, 7, 2));
+        assert!(!can_judge_vote_credits(&epoch_credits, 7, 7, 3));
+        assert!(!can_judge_vote_credits(&[], 7, 7, 1));
+
+        // Once the validator has been in the list for long enough, a short
+        // history is no excuse any more.
+        assert!(!can_judge_vote_credits(&epoch_credits, 7, 9, 3));
+        assert!(can_judge_vote_credits(&epoch_credits, 7, 10, 3));
+        assert!(can_judge_vote_credits(&[], 7, 8, 1));
+    }
+
+    #[test]
+    fn test_get_credits_in_last_epochs() {
+        let epoch_credits = [(3, 100, 0), (5, 150, 100), (6, 350, 150), (7, 360, 350)];
+
+        // Epoch 7 is still in progress, so it does not count.
+        assert_eq!(get_credits_in_last_epochs(&epoch_credits, 7, 1), 200);
+        assert_eq!(get_credits_in_last_epochs(&epoch_credits, 7, 2), 250);
+        // Epoch 4 has no credits at all.
+        assert_eq!(get_credits_in_last_epochs(&epoch_credits, 7, 3), 250);
+        assert_eq!(get_credits_in_last_epochs(&epoch_credits, 7, 64), 350);
+        assert_eq!(get_credits_in_last_epochs(&epoch_credits, 9, 1), 0);
+    }
 }
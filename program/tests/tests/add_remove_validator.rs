@@ -18,14 +18,14 @@ pub const TEST_STAKE_DEPOSIT_AMOUNT: Lamports = Lamports(10_000_000_000);
 async fn test_successful_add_validator() {
     let mut context = Context::new_with_maintainer().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
 
     let validator = context.add_validator().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert_eq!(solido.validators.entries[0].pubkey, validator.vote_account);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert_eq!(validators.entries[0].pubkey, validator.vote_account);
 
     // Adding the validator a second time should fail.
     let result = context.try_add_validator(&validator).await;
@@ -36,8 +36,8 @@ async fn test_successful_add_validator() {
 async fn test_add_validator_with_invalid_owner() {
     let mut context = Context::new_with_maintainer().await;
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
     let node_key = context.deterministic_keypair.new_keypair();
     let real_vote_account = context
         .create_vote_account(&node_key, Pubkey::new_unique(), 100)
@@ -63,21 +63,21 @@ async fn test_add_validator_with_invalid_owner() {
 #[tokio::test]
 async fn test_successful_remove_validator() {
     let mut context = Context::new_with_maintainer_and_validator().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     context.deactivate_validator(validator.pubkey).await;
     context
         .try_remove_validator(validator.pubkey)
         .await
         .unwrap();
 
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 0);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 0);
 }
 
 #[tokio::test]
 async fn test_removing_validator_with_stake_accounts_should_fail() {
     let (mut context, _) = Context::new_with_two_stake_accounts().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     let result = context.try_remove_validator(validator.pubkey).await;
 
     // The validator should not be able to be removed if it is still active
@@ -92,19 +92,19 @@ async fn test_deactivate_validator() {
     let validator = context.add_validator().await;
 
     // Initially, the validator should be active.
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert!(solido.validators.entries[0].entry.active);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert!(validators.entries[0].entry.active);
 
     context.deactivate_validator(validator.vote_account).await;
 
     // After deactivation, it should be inactive.
-    let solido = context.get_solido().await;
-    assert_eq!(solido.validators.len(), 1);
-    assert!(!solido.validators.entries[0].entry.active);
+    let validators = context.get_validators().await;
+    assert_eq!(validators.len(), 1);
+    assert!(!validators.entries[0].entry.active);
 
     // Deactivation is idempotent.
     context.deactivate_validator(validator.vote_account).await;
-    let solido_after_second_deactivation = context.get_solido().await;
-    assert_eq!(solido, solido_after_second_deactivation);
+    let validators_after_second_deactivation = context.get_validators().await;
+    assert_eq!(validators, validators_after_second_deactivation);
 }
@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+use lido::error::LidoError;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+/// Return the stSOL accounts of the current fee recipients, in order.
+async fn get_fee_recipient_accounts(context: &mut Context) -> Vec<Pubkey> {
+    context
+        .get_solido()
+        .await
+        .reward_distribution
+        .fee_recipients
+        .iter()
+        .map(|recipient| recipient.st_sol_account)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_successful_change_fee_recipients() {
+    let mut context = Context::new_with_maintainer().await;
+    let solido_before = context.get_solido().await;
+
+    let new_treasury_owner = context.deterministic_keypair.new_keypair();
+    let new_treasury_addr = context
+        .create_st_sol_account(new_treasury_owner.pubkey())
+        .await;
+
+    let new_developer_owner = context.deterministic_keypair.new_keypair();
+    let new_developer_addr = context
+        .create_st_sol_account(new_developer_owner.pubkey())
+        .await;
+
+    context
+        .try_change_fee_recipients(&[new_treasury_addr, new_developer_addr])
+        .await
+        .expect("Failed to change fee recipients.");
+
+    let solido = context.get_solido().await;
+    let recipients = &solido.reward_distribution.fee_recipients;
+    assert_eq!(recipients[0].st_sol_account, new_treasury_addr);
+    assert_eq!(recipients[1].st_sol_account, new_developer_addr);
+
+    // The shares themselves are unaffected.
+    let before = &solido_before.reward_distribution;
+    assert_eq!(recipients.len(), before.fee_recipients.len());
+    for (after, before) in recipients.iter().zip(&before.fee_recipients) {
+        assert_eq!(after.parts, before.parts);
+    }
+    assert_eq!(
+        solido.reward_distribution.st_sol_appreciation,
+        before.st_sol_appreciation
+    );
+}
+
+#[tokio::test]
+async fn test_change_fee_recipients_wrong_manager() {
+    let mut context = Context::new_with_maintainer().await;
+    let accounts = get_fee_recipient_accounts(&mut context).await;
+    context.manager = context.deterministic_keypair.new_keypair();
+
+    let result = context.try_change_fee_recipients(&accounts).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_change_fee_recipients_wrong_number_of_accounts() {
+    let mut context = Context::new_with_maintainer().await;
+    let accounts = get_fee_recipient_accounts(&mut context).await;
+
+    let result = context.try_change_fee_recipients(&accounts[..1]).await;
+    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+
+    let extra_owner = context.deterministic_keypair.new_keypair();
+    let extra_addr = context.create_st_sol_account(extra_owner.pubkey()).await;
+    let mut too_many = accounts.clone();
+    too_many.push(extra_addr);
+    let result = context.try_change_fee_recipients(&too_many).await;
+    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+}
+
+#[tokio::test]
+async fn test_change_fee_recipients_duplicate_account() {
+    let mut context = Context::new_with_maintainer().await;
+    let accounts = get_fee_recipient_accounts(&mut context).await;
+
+    let result = context
+        .try_change_fee_recipients(&[accounts[0], accounts[0]])
+        .await;
+    assert_solido_error!(result, LidoError::DuplicatedEntry);
+}
+
+#[tokio::test]
+async fn test_change_fee_recipients_wrong_minter() {
+    let mut context = Context::new_with_maintainer().await;
+
+    let wrong_mint_authority = context.deterministic_keypair.new_keypair();
+    let wrong_mint = context.create_mint(wrong_mint_authority.pubkey()).await;
+
+    // Create an SPL token account that is not stSOL.
+    context.st_sol_mint = wrong_mint;
+    let not_st_sol_owner = context.deterministic_keypair.new_keypair();
+    let not_st_sol_account = context
+        .create_st_sol_account(not_st_sol_owner.pubkey())
+        .await;
+
+    let accounts = get_fee_recipient_accounts(&mut context).await;
+
+    let result = context
+        .try_change_fee_recipients(&[accounts[0], not_st_sol_account])
+        .await;
+    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+
+    let result = context
+        .try_change_fee_recipients(&[not_st_sol_account, accounts[1]])
+        .await;
+    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+}
@@ -5,10 +5,11 @@ use solana_program_test::tokio;
 use solana_sdk::signature::Signer;
 
 use lido::error::LidoError;
-use lido::state::{FeeRecipients, RewardDistribution};
+use lido::state::{FeeRecipient, RewardDistribution, MAXIMUM_FEE_RECIPIENTS};
+use lido::token::Lamports;
 
 use testlib::assert_solido_error;
-use testlib::solido_context::Context;
+use testlib::solido_context::{Context, StakeDeposit};
 
 #[tokio::test]
 async fn test_successful_change_reward_distribution() {
@@ -17,20 +18,10 @@ async fn test_successful_change_reward_distribution() {
     let solido = context.get_solido().await;
     assert_eq!(solido.reward_distribution, context.reward_distribution);
     assert_eq!(
-        solido.fee_recipients.treasury_account,
-        context.treasury_st_sol_account,
-    );
-    assert_eq!(
-        solido.fee_recipients.developer_account,
-        context.developer_st_sol_account,
+        solido.reward_distribution.treasury_account(),
+        Some(&context.treasury_st_sol_account),
     );
 
-    let new_fee = RewardDistribution {
-        treasury_fee: 87,
-        developer_fee: 54,
-        st_sol_appreciation: 122,
-    };
-
     let new_treasury_owner = context.deterministic_keypair.new_keypair();
     let new_treasury_addr = context
         .create_st_sol_account(new_treasury_owner.pubkey())
@@ -41,21 +32,132 @@ async fn test_successful_change_reward_distribution() {
         .create_st_sol_account(new_developer_owner.pubkey())
         .await;
 
-    context
-        .try_change_reward_distribution(
-            &new_fee,
-            &FeeRecipients {
-                developer_account: new_developer_addr,
-                treasury_account: new_treasury_addr,
+    let new_fee = RewardDistribution {
+        fee_recipients: vec![
+            FeeRecipient {
+                st_sol_account: new_treasury_addr,
+                parts: 87,
+            },
+            FeeRecipient {
+                st_sol_account: new_developer_addr,
+                parts: 54,
             },
-        )
+        ],
+        st_sol_appreciation: 122,
+    };
+
+    context
+        .try_change_reward_distribution(&new_fee)
         .await
         .expect("Failed to change fees.");
 
     let solido = context.get_solido().await;
     assert_eq!(solido.reward_distribution, new_fee);
-    assert_eq!(solido.fee_recipients.treasury_account, new_treasury_addr,);
-    assert_eq!(solido.fee_recipients.developer_account, new_developer_addr,);
+}
+
+#[tokio::test]
+async fn test_change_reward_distribution_add_recipients() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+
+    // Next to the treasury and developer, also pay an insurance fund and an
+    // incentive pool.
+    let insurance_owner = context.deterministic_keypair.new_keypair();
+    let insurance_addr = context
+        .create_st_sol_account(insurance_owner.pubkey())
+        .await;
+    let incentives_owner = context.deterministic_keypair.new_keypair();
+    let incentives_addr = context
+        .create_st_sol_account(incentives_owner.pubkey())
+        .await;
+
+    let mut new_fee = context.reward_distribution.clone();
+    new_fee.fee_recipients.push(FeeRecipient {
+        st_sol_account: insurance_addr,
+        parts: 4,
+    });
+    new_fee.fee_recipients.push(FeeRecipient {
+        st_sol_account: incentives_addr,
+        parts: 1,
+    });
+    new_fee.st_sol_appreciation = 90;
+
+    context
+        .try_change_reward_distribution(&new_fee)
+        .await
+        .expect("Failed to change fees.");
+    assert_eq!(context.get_solido().await.reward_distribution, new_fee);
+
+    // Every recipient receives its share of the rewards.
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let amount = Lamports(10_000_000_000);
+    context.deposit(amount).await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, amount)
+        .await;
+    context.advance_to_normal_epoch(0);
+    context
+        .context
+        .increment_vote_account_credits(&vote_account, 1);
+    context.advance_to_normal_epoch(1);
+    context.update_exchange_rate().await;
+    context.update_stake_account_balance(vote_account).await;
+
+    for account in [
+        context.treasury_st_sol_account,
+        context.developer_st_sol_account,
+        insurance_addr,
+        incentives_addr,
+    ] {
+        assert!(context.get_st_sol_balance(account).await.0 > 0);
+    }
+
+    let solido = context.get_solido().await;
+    assert_eq!(solido.metrics.fee_recipients.len(), 4);
+}
+
+#[tokio::test]
+async fn test_change_reward_distribution_invalid_recipients() {
+    let mut context = Context::new_with_maintainer().await;
+    let treasury = context.treasury_st_sol_account;
+
+    // There must be at least one recipient.
+    let result = context
+        .try_change_reward_distribution(&RewardDistribution {
+            fee_recipients: Vec::new(),
+            st_sol_appreciation: 100,
+        })
+        .await;
+    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+
+    // A recipient can occur only once.
+    let recipient = FeeRecipient {
+        st_sol_account: treasury,
+        parts: 1,
+    };
+    let result = context
+        .try_change_reward_distribution(&RewardDistribution {
+            fee_recipients: vec![recipient.clone(), recipient.clone()],
+            st_sol_appreciation: 100,
+        })
+        .await;
+    assert_solido_error!(result, LidoError::DuplicatedEntry);
+
+    // The number of recipients is bounded.
+    let mut fee_recipients = Vec::new();
+    for _ in 0..MAXIMUM_FEE_RECIPIENTS + 1 {
+        let owner = context.deterministic_keypair.new_keypair();
+        fee_recipients.push(FeeRecipient {
+            st_sol_account: context.create_st_sol_account(owner.pubkey()).await,
+            parts: 1,
+        });
+    }
+    let result = context
+        .try_change_reward_distribution(&RewardDistribution {
+            fee_recipients,
+            st_sol_appreciation: 100,
+        })
+        .await;
+    assert_solido_error!(result, LidoError::MaximumNumberOfAccountsExceeded);
 }
 
 #[tokio::test]
@@ -74,25 +176,19 @@ async fn test_change_reward_distribution_wrong_minter() {
 
     let solido = context.get_solido().await;
 
-    let result = context
-        .try_change_reward_distribution(
-            &solido.reward_distribution,
-            &FeeRecipients {
-                developer_account: not_st_sol_account,
-                ..solido.fee_recipients
-            },
-        )
-        .await;
-    assert_solido_error!(result, LidoError::InvalidFeeRecipient);
-
-    let result = context
-        .try_change_reward_distribution(
-            &solido.reward_distribution,
-            &FeeRecipients {
-                treasury_account: not_st_sol_account,
-                ..solido.fee_recipients
-            },
-        )
-        .await;
+    for i in 0..solido.reward_distribution.fee_recipients.len() {
+        let mut new_fee = solido.reward_distribution.clone();
+        new_fee.fee_recipients[i].st_sol_account = not_st_sol_account;
+        let result = context.try_change_reward_distribution(&new_fee).await;
+        assert_solido_error!(result, LidoError::InvalidFeeRecipient);
+    }
+
+    // Adding a new recipient that is not an stSOL account fails too.
+    let mut new_fee = solido.reward_distribution.clone();
+    new_fee.fee_recipients.push(FeeRecipient {
+        st_sol_account: not_st_sol_account,
+        parts: 1,
+    });
+    let result = context.try_change_reward_distribution(&new_fee).await;
     assert_solido_error!(result, LidoError::InvalidFeeRecipient);
 }
@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::token::Lamports;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const STAKE_AMOUNT: Lamports = Lamports(10_000_000_000);
+
+#[tokio::test]
+async fn test_check_invariants_holds_through_stake_lifecycle() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.try_check_invariants(vote_account).await.unwrap();
+
+    context.deposit((STAKE_AMOUNT * 2).unwrap()).await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    context.try_check_invariants(vote_account).await.unwrap();
+
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    context.unstake(vote_account, Lamports(1_000_000_000)).await;
+    context.try_check_invariants(vote_account).await.unwrap();
+
+    // A donation is not observed yet, but it only makes the observed balance
+    // larger than the tracked balance.
+    let stake_account = context
+        .get_stake_and_unstake_account_addresses(vote_account)
+        .await[0];
+    context.fund(stake_account, Lamports(100)).await;
+    context.try_check_invariants(vote_account).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_check_invariants_requires_all_stake_accounts_in_order() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.deposit((STAKE_AMOUNT * 2).unwrap()).await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+
+    let mut accounts = context
+        .get_stake_and_unstake_account_addresses(vote_account)
+        .await;
+    assert_eq!(accounts.len(), 2);
+
+    accounts.swap(0, 1);
+    let result = context
+        .try_check_invariants_with_accounts(vote_account, accounts.clone())
+        .await;
+    assert_solido_error!(result, LidoError::InvalidStakeAccount);
+
+    accounts.pop();
+    let result = context
+        .try_check_invariants_with_accounts(vote_account, accounts)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidStakeAccount);
+}
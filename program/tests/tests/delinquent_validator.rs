@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use lido::error::LidoError;
+
+use solana_program_test::tokio;
+use solana_sdk::signature::Signer;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{id, Context};
+
+#[tokio::test]
+async fn test_set_min_vote_credits() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+
+    context.try_set_min_vote_credits(1_000, 3).await.unwrap();
+    let solido = context.get_solido().await;
+    assert_eq!(solido.min_vote_credits, 1_000);
+    assert_eq!(solido.vote_credits_epochs, 3);
+
+    // Vote accounts only keep the credits of the last 64 epochs.
+    assert_solido_error!(
+        context.try_set_min_vote_credits(1_000, 0).await,
+        LidoError::InvalidVoteCreditsEpochs
+    );
+    assert_solido_error!(
+        context.try_set_min_vote_credits(1_000, 65).await,
+        LidoError::InvalidVoteCreditsEpochs
+    );
+}
+
+#[tokio::test]
+async fn test_deactivate_validator_if_delinquent() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    // With the default minimum of 0 credits, no validator is delinquent.
+    context
+        .try_deactivate_validator_if_delinquent(vote_account)
+        .await
+        .unwrap();
+    let validator = &context.get_validators().await.entries[0];
+    assert_eq!(validator.entry.active, true);
+
+    // The test validator does not vote, so it has no credits history, and it
+    // was added in the current epoch, so it is too new to judge.
+    context.try_set_min_vote_credits(1, 1).await.unwrap();
+    context
+        .try_deactivate_validator_if_delinquent(vote_account)
+        .await
+        .unwrap();
+    let validator = &context.get_validators().await.entries[0];
+    assert_eq!(validator.entry.active, true);
+
+    // Once it has a history, but earned no credits in the last epoch, it is
+    // delinquent.
+    context
+        .set_vote_account_epoch_credits(vote_account, vec![(0, 10, 0)])
+        .await;
+    context
+        .try_deactivate_validator_if_delinquent(vote_account)
+        .await
+        .unwrap();
+    let validator = &context.get_validators().await.entries[0];
+    assert_eq!(validator.entry.active, false);
+}
+
+#[tokio::test]
+async fn test_deactivate_validator_if_delinquent_without_credits_history() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.try_set_min_vote_credits(1, 1).await.unwrap();
+
+    // The test validator never votes, so it never gets a credits history. Once
+    // it has been in the list for long enough, the missing epochs count as
+    // zero credits, and it is delinquent.
+    context.advance_to_normal_epoch(1);
+    context
+        .try_deactivate_validator_if_delinquent(vote_account)
+        .await
+        .unwrap();
+    let validator = &context.get_validators().await.entries[0];
+    assert_eq!(validator.entry.active, false);
+}
+
+#[tokio::test]
+async fn test_deactivate_validator_if_delinquent_rejects_non_vote_account() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.try_set_min_vote_credits(1, 1).await.unwrap();
+
+    // An account that is not owned by the vote program cannot be used to
+    // fake the credits of a validator.
+    let fake = context.create_account(&id(), 4_000).await.pubkey();
+    let result = context.try_deactivate_validator_if_delinquent(fake).await;
+    assert_solido_error!(result, LidoError::ValidatorVoteAccountHasDifferentOwner);
+}
@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::token::Lamports;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const SOL: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn test_set_max_directed_stake() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    assert_eq!(context.get_solido().await.max_directed_stake_bps, 0);
+
+    context.try_set_max_directed_stake(2_000).await.unwrap();
+    assert_eq!(context.get_solido().await.max_directed_stake_bps, 2_000);
+
+    let result = context.try_set_max_directed_stake(10_001).await;
+    assert_solido_error!(result, LidoError::MaxDirectedStakeOutOfBounds);
+
+    // Only the validator admin can change the maximum.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_set_max_directed_stake(0).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_directed_deposit_is_capped_and_attracts_stake() {
+    let mut context = Context::new_with_maintainer().await;
+    let v1 = context.add_validator().await;
+    let v2 = context.add_validator().await;
+    context.try_set_max_directed_stake(2_000).await.unwrap();
+
+    context.deposit(Lamports(10 * SOL)).await;
+    let (_, recipient) = context
+        .try_deposit_directed(Lamports(5 * SOL), v2.vote_account)
+        .await
+        .unwrap();
+
+    // A directed deposit is a deposit like any other.
+    assert_eq!(context.get_st_sol_balance(recipient).await.0, 5 * SOL);
+    let solido = context.get_solido().await;
+    assert_eq!(solido.metrics.deposit_amount.num_observations(), 2);
+
+    // Of the 15 SOL under management, at most 20% can be directed to one
+    // validator, so only 3 of the 5 SOL count as directed stake.
+    let validators = context.get_validators().await;
+    assert_eq!(
+        validators
+            .get(&v2.vote_account)
+            .unwrap()
+            .entry
+            .directed_stake,
+        Lamports(3 * SOL)
+    );
+    assert_eq!(
+        validators
+            .get(&v1.vote_account)
+            .unwrap()
+            .entry
+            .directed_stake,
+        Lamports(0)
+    );
+
+    // The first 3 SOL of stake at v2 do not count towards its share of the
+    // undirected stake.
+    context
+        .stake_deposit(v2.vote_account, StakeDeposit::Append, Lamports(2 * SOL))
+        .await;
+    context
+        .stake_deposit(v1.vote_account, StakeDeposit::Append, Lamports(2 * SOL))
+        .await;
+    let result = context
+        .try_stake_deposit(v1.vote_account, StakeDeposit::Append, Lamports(2 * SOL))
+        .await;
+    assert_solido_error!(result, LidoError::ValidatorWithLessStakeExists);
+    context
+        .stake_deposit(v2.vote_account, StakeDeposit::Append, Lamports(2 * SOL))
+        .await;
+}
+
+#[tokio::test]
+async fn test_directed_deposit_fails_for_zero_weight_validator() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.try_set_max_directed_stake(2_000).await.unwrap();
+    context
+        .try_set_validator_weight(vote_account, 0)
+        .await
+        .unwrap();
+
+    let result = context
+        .try_deposit_directed(Lamports(SOL), vote_account)
+        .await;
+    assert_solido_error!(result, LidoError::StakeToInactiveValidator);
+}
@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::token::Lamports;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+const MAX_TOTAL_SOL: Lamports = Lamports(1_000_000_000);
+const MIN_DEPOSIT: Lamports = Lamports(100_000_000);
+
+#[tokio::test]
+async fn test_set_deposit_limits() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let solido = context.get_solido().await;
+    assert_eq!(solido.max_total_sol, Lamports(0));
+    assert_eq!(solido.min_deposit, Lamports(0));
+
+    context
+        .try_set_deposit_limits(MAX_TOTAL_SOL, MIN_DEPOSIT)
+        .await
+        .unwrap();
+    let solido = context.get_solido().await;
+    assert_eq!(solido.max_total_sol, MAX_TOTAL_SOL);
+    assert_eq!(solido.min_deposit, MIN_DEPOSIT);
+
+    // Only the fee admin can set the limits.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context
+        .try_set_deposit_limits(Lamports(0), Lamports(0))
+        .await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_deposit_respects_limits() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context
+        .try_set_deposit_limits(MAX_TOTAL_SOL, MIN_DEPOSIT)
+        .await
+        .unwrap();
+
+    let result = context.try_deposit(Lamports(99_999_999)).await;
+    assert_solido_error!(result, LidoError::DepositBelowMinimum);
+
+    context.deposit(Lamports(600_000_000)).await;
+
+    let result = context.try_deposit(Lamports(400_000_001)).await;
+    assert_solido_error!(result, LidoError::DepositExceedsMaximumTotalSol);
+
+    // Filling up to exactly the cap is allowed.
+    context.deposit(Lamports(400_000_000)).await;
+
+    // Without a cap, deposits are accepted again.
+    context
+        .try_set_deposit_limits(Lamports(0), MIN_DEPOSIT)
+        .await
+        .unwrap();
+    context.deposit(Lamports(10_000_000_000)).await;
+
+    let solido = context.get_solido().await;
+    assert_eq!(solido.metrics.deposit_amount.num_observations(), 3);
+}
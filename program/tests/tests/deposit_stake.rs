@@ -0,0 +1,189 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program::pubkey::Pubkey;
+use solana_program::stake::state::Lockup;
+use solana_program_test::tokio;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+use lido::error::LidoError;
+use lido::token::{Lamports, StLamports};
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const STAKE_AMOUNT: Lamports = Lamports(10_000_000_000);
+
+/// Create a stake account owned by a new user, and delegate it to `vote_account`.
+async fn create_delegated_stake_account(
+    context: &mut Context,
+    vote_account: Pubkey,
+) -> (Keypair, Pubkey) {
+    let owner = context.deterministic_keypair.new_keypair();
+    let stake_account = context
+        .create_stake_account(STAKE_AMOUNT, owner.pubkey())
+        .await;
+    context
+        .delegate_stake_account(stake_account, vote_account, &owner)
+        .await;
+    (owner, stake_account)
+}
+
+#[tokio::test]
+async fn test_deposit_stake_append() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    let (owner, user_stake_account) =
+        create_delegated_stake_account(&mut context, vote_account).await;
+    context.advance_to_normal_epoch(1);
+
+    let recipient = context
+        .try_deposit_stake(
+            vote_account,
+            StakeDeposit::Append,
+            user_stake_account,
+            &owner,
+        )
+        .await
+        .unwrap();
+
+    // Without rewards, the exchange rate is 1:1, so we get stSOL for the full
+    // balance of the stake account.
+    assert_eq!(
+        context.get_st_sol_balance(recipient).await,
+        StLamports(STAKE_AMOUNT.0)
+    );
+
+    // The stake moved into a new Solido stake account.
+    let validators = context.get_validators().await;
+    let validator = &validators.entries[0].entry;
+    assert_eq!(validator.stake_seeds.end, 1);
+    assert_eq!(validator.stake_accounts_balance, STAKE_AMOUNT);
+
+    let stake_account = context
+        .get_stake_account_from_seed(&validators.entries[0], 0)
+        .await;
+    assert_eq!(stake_account.balance.total(), STAKE_AMOUNT);
+    assert!(stake_account.is_active());
+    assert_eq!(context.try_get_account(user_stake_account).await, None);
+}
+
+#[tokio::test]
+async fn test_deposit_stake_merge() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    // Give the validator a stake account that becomes active at the same time
+    // as the user's stake account, so the two can be merged.
+    context.deposit(STAKE_AMOUNT).await;
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    let (owner, user_stake_account) =
+        create_delegated_stake_account(&mut context, vote_account).await;
+    context.advance_to_normal_epoch(1);
+
+    let recipient = context
+        .try_deposit_stake(
+            vote_account,
+            StakeDeposit::Merge,
+            user_stake_account,
+            &owner,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        context.get_st_sol_balance(recipient).await,
+        StLamports(STAKE_AMOUNT.0)
+    );
+
+    // No new stake account was consumed, the stake was merged into the existing one.
+    let validators = context.get_validators().await;
+    let validator = &validators.entries[0].entry;
+    assert_eq!(validator.stake_seeds.end, 1);
+    assert_eq!(
+        validator.stake_accounts_balance,
+        (STAKE_AMOUNT + STAKE_AMOUNT).unwrap()
+    );
+
+    let stake_account = context
+        .get_stake_account_from_seed(&validators.entries[0], 0)
+        .await;
+    assert_eq!(
+        stake_account.balance.total(),
+        (STAKE_AMOUNT + STAKE_AMOUNT).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_stake_fails_for_activating_stake() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    // Without advancing the epoch, the stake is still activating.
+    let (owner, user_stake_account) =
+        create_delegated_stake_account(&mut context, vote_account).await;
+
+    let result = context
+        .try_deposit_stake(
+            vote_account,
+            StakeDeposit::Append,
+            user_stake_account,
+            &owner,
+        )
+        .await;
+    assert_solido_error!(result, LidoError::WrongStakeState);
+}
+
+#[tokio::test]
+async fn test_deposit_stake_fails_for_other_validator() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let other_validator = context.add_validator().await;
+
+    let (owner, user_stake_account) =
+        create_delegated_stake_account(&mut context, other_validator.vote_account).await;
+    context.advance_to_normal_epoch(1);
+
+    let result = context
+        .try_deposit_stake(
+            vote_account,
+            StakeDeposit::Append,
+            user_stake_account,
+            &owner,
+        )
+        .await;
+    assert_solido_error!(result, LidoError::InvalidStakeAccount);
+}
+
+#[tokio::test]
+async fn test_deposit_stake_fails_for_locked_stake() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    let owner = context.deterministic_keypair.new_keypair();
+    let lockup = Lockup {
+        unix_timestamp: 0,
+        epoch: 100,
+        custodian: context.deterministic_keypair.new_keypair().pubkey(),
+    };
+    let user_stake_account = context
+        .create_stake_account_with_lockup(STAKE_AMOUNT, owner.pubkey(), &lockup)
+        .await;
+    context
+        .delegate_stake_account(user_stake_account, vote_account, &owner)
+        .await;
+    context.advance_to_normal_epoch(1);
+
+    let result = context
+        .try_deposit_stake(
+            vote_account,
+            StakeDeposit::Append,
+            user_stake_account,
+            &owner,
+        )
+        .await;
+    assert_solido_error!(result, LidoError::StakeAccountLocked);
+}
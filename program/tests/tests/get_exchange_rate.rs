@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::token::{Lamports, StLamports};
+
+use testlib::exchange_rate_consumer::PriceStSol;
+use testlib::solido_context::Context;
+
+const DEPOSIT_AMOUNT: u64 = 100_000_000;
+
+#[tokio::test]
+async fn test_get_exchange_rate_through_cpi() {
+    let mut context = Context::new_with_maintainer().await;
+    context.deposit(Lamports(DEPOSIT_AMOUNT)).await;
+
+    // Donate to the reserve, so that 1 stSOL is worth 4 SOL after the update.
+    context
+        .fund(context.reserve_address, Lamports(3 * DEPOSIT_AMOUNT))
+        .await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    let epoch = context.get_clock().await.epoch;
+
+    context
+        .try_price_st_sol_through_cpi(PriceStSol {
+            amount: StLamports(1_000),
+            expected_value: Lamports(4_000),
+            expected_computed_in_epoch: epoch,
+        })
+        .await
+        .expect("The consumer program should observe the current exchange rate.");
+
+    // The consumer program fails if the rate does not match its expectation,
+    // so a successful transaction above is not vacuous.
+    let result = context
+        .try_price_st_sol_through_cpi(PriceStSol {
+            amount: StLamports(1_000),
+            expected_value: Lamports(1_000),
+            expected_computed_in_epoch: epoch,
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_get_exchange_rate_reports_stale_rate() {
+    let mut context = Context::new_with_maintainer().await;
+    context.deposit(Lamports(DEPOSIT_AMOUNT)).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+    let epoch = context.get_clock().await.epoch;
+
+    // Without an update, the rate is still the one of the previous epoch,
+    // and consumers can tell from `computed_in_epoch`.
+    context
+        .fund(context.reserve_address, Lamports(DEPOSIT_AMOUNT))
+        .await;
+    context.advance_to_normal_epoch(1);
+    context
+        .try_price_st_sol_through_cpi(PriceStSol {
+            amount: StLamports(1_000),
+            expected_value: Lamports(1_000),
+            expected_computed_in_epoch: epoch,
+        })
+        .await
+        .expect("The consumer program should observe the last computed exchange rate.");
+}
@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use lido::error::LidoError;
+use lido::token::{Lamports, StLamports};
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const TEST_DEPOSIT_AMOUNT: Lamports = Lamports(10_000_000_000);
+
+#[tokio::test]
+async fn test_instant_unstake_pays_fee_to_treasury() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    // 30 basis points, 0.3%.
+    context.try_set_instant_unstake_fee(30).await.unwrap();
+    assert_eq!(context.get_solido().await.instant_unstake_fee_bps, 30);
+
+    let recipient = context.deterministic_keypair.new_keypair().pubkey();
+    let reserve_before = context.get_sol_balance(context.reserve_address).await;
+    let treasury_before = context
+        .get_st_sol_balance(context.treasury_st_sol_account)
+        .await;
+
+    let amount = StLamports(1_000_000_000);
+    context
+        .instant_unstake(&user, token_addr, amount, recipient)
+        .await;
+
+    let solido = context.get_solido().await;
+    let fee = StLamports(3_000_000);
+    let sol_paid = solido
+        .exchange_rate
+        .exchange_st_sol((amount - fee).unwrap())
+        .unwrap();
+
+    assert_eq!(context.get_sol_balance(recipient).await, sol_paid);
+    assert_eq!(
+        context.get_sol_balance(context.reserve_address).await,
+        (reserve_before - sol_paid).unwrap()
+    );
+    assert_eq!(
+        context
+            .get_st_sol_balance(context.treasury_st_sol_account)
+            .await,
+        (treasury_before + fee).unwrap()
+    );
+    assert_eq!(
+        context.get_st_sol_balance(token_addr).await,
+        StLamports(9_000_000_000)
+    );
+
+    assert_eq!(solido.metrics.instant_unstake_amount.count, 1);
+    assert_eq!(
+        solido.metrics.instant_unstake_amount.total_st_sol_amount,
+        (amount - fee).unwrap()
+    );
+    assert_eq!(
+        solido.metrics.instant_unstake_amount.total_sol_amount,
+        sol_paid
+    );
+    assert_eq!(solido.metrics.fee_instant_unstake_st_sol_total, fee);
+}
+
+#[tokio::test]
+async fn test_instant_unstake_zero_fails() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    let recipient = context.deterministic_keypair.new_keypair().pubkey();
+    assert_solido_error!(
+        context
+            .try_instant_unstake(&user, token_addr, StLamports(0), recipient)
+            .await,
+        LidoError::InvalidAmount
+    );
+}
+
+#[tokio::test]
+async fn test_instant_unstake_more_than_reserve_fails() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+
+    // Move half of the reserve into a stake account.
+    let validator = context.validator.take().unwrap();
+    context
+        .stake_deposit(
+            validator.vote_account,
+            StakeDeposit::Append,
+            Lamports(5_000_000_000),
+        )
+        .await;
+    context.validator = Some(validator);
+
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    let recipient = context.deterministic_keypair.new_keypair().pubkey();
+    assert_solido_error!(
+        context
+            .try_instant_unstake(&user, token_addr, StLamports(6_000_000_000), recipient)
+            .await,
+        LidoError::AmountExceedsReserve
+    );
+}
+
+#[tokio::test]
+async fn test_set_instant_unstake_fee_out_of_bounds_fails() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+
+    assert_eq!(context.get_solido().await.instant_unstake_fee_bps, 0);
+    context.try_set_instant_unstake_fee(10_000).await.unwrap();
+    assert_solido_error!(
+        context.try_set_instant_unstake_fee(10_001).await,
+        LidoError::InstantUnstakeFeeOutOfBounds
+    );
+}
@@ -7,11 +7,12 @@
 //! expectations; there is no "right" answer, but we would like to know what
 //! how many accounts Solido can handle.
 
-use testlib::solido_context::{Context, StakeDeposit, ValidatorAccounts};
+use testlib::solido_context::{simulate_compute_units, Context, StakeDeposit, ValidatorAccounts};
 
-use lido::token::Lamports;
+use lido::token::{Lamports, StLamports};
 
 use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
 
 /// Test how many stake accounts per validator we can support.
 ///
@@ -96,3 +97,148 @@ async fn test_max_validators_maintainers() {
         // If we get here, then none of the transactions failed.
     }
 }
+
+/// Compute units that `StakeDeposit` and `Withdraw` may consume per validator
+/// in the list, on top of their cost with a single validator. Both decode every
+/// validator, to compare it with the one they operate on.
+const MAX_UNITS_PER_VALIDATOR_DECODE: u64 = 1_500;
+
+/// Compute units that `UpdateStakeAccountBalance` may consume per validator in
+/// the list. It decodes only one validator, but it scans the list to find it.
+const MAX_UNITS_PER_VALIDATOR_SCAN: u64 = 100;
+
+/// Test how many validators we can support for instructions that touch a single
+/// validator, and how their cost grows with the size of the list.
+///
+/// `StakeDeposit`, `Unstake`, `Withdraw` and `UpdateStakeAccountBalance` read
+/// individual validators through `ValidatorsView` instead of decoding the entire
+/// list into a `Validators`, so they should support many more validators than
+/// `test_max_validators_maintainers`. This test is mostly for informational
+/// purposes, if it fails, adjust the expected `max_validators` or the
+/// per-validator bounds above.
+///
+/// The runtime only meters BPF programs, and the test takes long, so it is
+/// ignored by default. To measure the compute units, run it with
+/// `cargo test-bpf -- --ignored test_max_validators_single_validator_instructions`.
+#[tokio::test]
+#[ignore]
+async fn test_max_validators_single_validator_instructions() {
+    let mut context = Context::new_with_maintainer().await;
+
+    // Measure at these sizes of the validator list, the last one is the
+    // maximum that we support.
+    let list_sizes: [u32; 4] = [1, 50, 100, 150];
+
+    // For every list size, the compute units of StakeDeposit, Withdraw, and
+    // UpdateStakeAccountBalance.
+    let mut measurements: Vec<(u32, [Option<u64>; 3])> = Vec::new();
+
+    let mut num_validators = 0;
+    let mut validator: Option<ValidatorAccounts> = None;
+    for (epoch, &list_size) in list_sizes.iter().enumerate() {
+        while num_validators < list_size {
+            num_validators += 1;
+            context
+                .memo(&format!("Adding validator {}.", num_validators))
+                .await;
+            validator = Some(context.add_validator().await);
+        }
+
+        // Use the last validator, it is the most expensive one to find, and
+        // it has the least stake, so we can stake to it.
+        let vote_account = validator.as_ref().unwrap().vote_account;
+        let amount = Lamports(2_000_000_000);
+        let (user, st_sol_account) = context.deposit(amount).await;
+
+        let (instruction, _) = context
+            .stake_deposit_instruction(vote_account, StakeDeposit::Append, amount)
+            .await;
+        let stake_deposit_units = simulate_compute_units(
+            &mut context.context,
+            &[instruction],
+            vec![context.maintainer.as_ref().unwrap()],
+        )
+        .await;
+        let stake_account = context
+            .stake_deposit(vote_account, StakeDeposit::Append, amount)
+            .await;
+
+        context.advance_to_normal_epoch(epoch as u64);
+        context.update_exchange_rate().await;
+
+        let instruction = context
+            .update_stake_account_balance_instruction(vote_account)
+            .await;
+        let update_balance_units =
+            simulate_compute_units(&mut context.context, &[instruction], vec![]).await;
+        context.update_stake_account_balance(vote_account).await;
+
+        // The validator that we just staked to now has the most stake, so we
+        // can withdraw from it.
+        let withdraw_amount = StLamports(100_000_000);
+        let new_stake = context.deterministic_keypair.new_keypair();
+        let instruction = context.withdraw_instruction(
+            user.pubkey(),
+            st_sol_account,
+            withdraw_amount,
+            vote_account,
+            stake_account,
+            new_stake.pubkey(),
+        );
+        let withdraw_units = simulate_compute_units(
+            &mut context.context,
+            &[instruction],
+            vec![&user, &new_stake],
+        )
+        .await;
+        context
+            .withdraw(
+                &user,
+                st_sol_account,
+                withdraw_amount,
+                vote_account,
+                stake_account,
+            )
+            .await;
+
+        println!(
+            "With {} validators: StakeDeposit {:?}, Withdraw {:?}, UpdateStakeAccountBalance {:?} compute units.",
+            list_size, stake_deposit_units, withdraw_units, update_balance_units,
+        );
+        measurements.push((
+            list_size,
+            [stake_deposit_units, withdraw_units, update_balance_units],
+        ));
+    }
+
+    // Unstake still works with the largest list. The stake account holds
+    // 1.9 SOL after the withdrawal, it has to keep the minimum balance.
+    let vote_account = validator.unwrap().vote_account;
+    context.unstake(vote_account, Lamports(500_000_000)).await;
+
+    let bounds = [
+        ("StakeDeposit", MAX_UNITS_PER_VALIDATOR_DECODE),
+        ("Withdraw", MAX_UNITS_PER_VALIDATOR_DECODE),
+        ("UpdateStakeAccountBalance", MAX_UNITS_PER_VALIDATOR_SCAN),
+    ];
+    let (base_size, base_units) = measurements[0];
+    for (list_size, units) in &measurements[1..] {
+        for (i, (name, max_units_per_validator)) in bounds.iter().enumerate() {
+            // Without metering, there is nothing to compare.
+            if let (Some(base), Some(units)) = (base_units[i], units[i]) {
+                let extra_validators = (list_size - base_size) as u64;
+                assert!(
+                    units <= base + extra_validators * max_units_per_validator,
+                    "{} consumed {} compute units with {} validators, \
+                    but {} with {} validators, that is more than {} per validator.",
+                    name,
+                    units,
+                    list_size,
+                    base,
+                    base_size,
+                    max_units_per_validator,
+                );
+            }
+        }
+    }
+}
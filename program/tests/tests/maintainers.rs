@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use testlib::assert_solido_error;
-use testlib::solido_context::Context;
+use testlib::solido_context::{Context, StakeDeposit};
 
 use lido::error::LidoError;
+use lido::state::{MAINTAINER_ALL, MAINTAINER_UNSTAKE};
+use lido::token::Lamports;
 use solana_program_test::tokio;
 use solana_sdk::signature::Signer;
 
@@ -25,6 +27,7 @@ async fn test_successful_add_remove_maintainer() {
     let solido = context.get_solido().await;
     assert_eq!(solido.maintainers.len(), 1);
     assert_eq!(solido.maintainers.entries[0].pubkey, maintainer.pubkey());
+    assert_eq!(solido.maintainers.entries[0].entry, MAINTAINER_ALL);
 
     // Adding the maintainer a second time should fail.
     let result = context.try_add_maintainer(maintainer.pubkey()).await;
@@ -44,3 +47,49 @@ async fn test_successful_add_remove_maintainer() {
     assert!(!has_maintainer);
     assert_eq!(solido.maintainers.len(), 0);
 }
+
+#[tokio::test]
+async fn test_maintainer_permissions() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let maintainer = context.maintainer.as_ref().unwrap().pubkey();
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.deposit(Lamports(10_000_000_000)).await;
+
+    context
+        .try_set_maintainer_permissions(maintainer, MAINTAINER_UNSTAKE)
+        .await
+        .unwrap();
+    let solido = context.get_solido().await;
+    assert_eq!(solido.maintainers.entries[0].entry, MAINTAINER_UNSTAKE);
+
+    let result = context
+        .try_stake_deposit(vote_account, StakeDeposit::Append, Lamports(2_000_000_000))
+        .await;
+    assert_solido_error!(result, LidoError::MaintainerOperationNotPermitted);
+
+    context
+        .try_set_maintainer_permissions(maintainer, MAINTAINER_ALL)
+        .await
+        .unwrap();
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, Lamports(2_000_000_000))
+        .await;
+
+    // Bits that do not correspond to an operation are rejected.
+    let result = context
+        .try_set_maintainer_permissions(maintainer, MAINTAINER_ALL + 1)
+        .await;
+    assert!(result.is_err());
+
+    // Only maintainers have permissions.
+    let not_maintainer = context.deterministic_keypair.new_keypair().pubkey();
+    let result = context
+        .try_set_maintainer_permissions(not_maintainer, MAINTAINER_ALL)
+        .await;
+    assert_solido_error!(result, LidoError::InvalidAccountMember);
+
+    // Only the maintainer admin can set permissions.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_set_maintainer_permissions(maintainer, 0).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use lido::error::LidoError;
+use lido::state::Roles;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+#[tokio::test]
+async fn test_successful_manager_handover() {
+    let mut context = Context::new_with_maintainer().await;
+    let old_manager = context.manager.pubkey();
+    let new_manager = context.deterministic_keypair.new_keypair();
+
+    context
+        .try_propose_new_manager(new_manager.pubkey())
+        .await
+        .expect("The current manager can propose a new manager.");
+    let solido = context.get_solido().await;
+    assert_eq!(solido.manager, old_manager);
+    assert_eq!(solido.pending_manager, new_manager.pubkey());
+
+    context
+        .try_accept_manager(&new_manager)
+        .await
+        .expect("The pending manager can accept.");
+    let solido = context.get_solido().await;
+    assert_eq!(solido.manager, new_manager.pubkey());
+    assert_eq!(solido.pending_manager, Pubkey::default());
+
+    // The roles that the old manager held moved along.
+    assert_eq!(solido.roles, Roles::new_all(new_manager.pubkey()));
+
+    // The old manager can no longer make changes.
+    let result = context.try_set_max_commission_percentage(5).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+
+    // The new manager can.
+    context.manager = new_manager;
+    context
+        .try_set_max_commission_percentage(5)
+        .await
+        .expect("The new manager can change parameters.");
+}
+
+#[tokio::test]
+async fn test_propose_new_manager_different_manager() {
+    let mut context = Context::new_with_maintainer().await;
+    let new_manager = context.deterministic_keypair.new_keypair();
+    context.manager = context.deterministic_keypair.new_keypair();
+
+    let result = context.try_propose_new_manager(new_manager.pubkey()).await;
+    assert_solido_error!(result, LidoError::InvalidManager);
+}
+
+#[tokio::test]
+async fn test_accept_manager_without_proposal() {
+    let mut context = Context::new_with_maintainer().await;
+    let new_manager = context.deterministic_keypair.new_keypair();
+
+    let result = context.try_accept_manager(&new_manager).await;
+    assert_solido_error!(result, LidoError::InvalidPendingManager);
+}
+
+#[tokio::test]
+async fn test_accept_manager_wrong_signer() {
+    let mut context = Context::new_with_maintainer().await;
+    let new_manager = context.deterministic_keypair.new_keypair();
+    let impostor = context.deterministic_keypair.new_keypair();
+
+    context
+        .try_propose_new_manager(new_manager.pubkey())
+        .await
+        .unwrap();
+    let result = context.try_accept_manager(&impostor).await;
+    assert_solido_error!(result, LidoError::InvalidPendingManager);
+
+    let solido = context.get_solido().await;
+    assert_ne!(solido.manager, impostor.pubkey());
+    assert_eq!(solido.pending_manager, new_manager.pubkey());
+}
@@ -8,7 +8,7 @@ use testlib::solido_context::Context;
 #[tokio::test]
 async fn test_set_max_commission_percentage() {
     let mut context = Context::new_with_maintainer_and_validator().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
 
     // increase max_commission_percentage
     let result = context.try_set_max_commission_percentage(context.max_commission_percentage + 1);
@@ -24,7 +24,7 @@ async fn test_set_max_commission_percentage() {
     assert_eq!(result.await.is_ok(), true);
 
     // check validator is not deactivated
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     assert_eq!(validator.entry.active, true);
 
     // Increase max_commission_percentage above 100%
@@ -41,6 +41,6 @@ async fn test_set_max_commission_percentage() {
     assert_eq!(result.await.is_ok(), true);
 
     // check validator is deactivated
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     assert_eq!(validator.entry.active, false);
 }
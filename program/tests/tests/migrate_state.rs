@@ -0,0 +1,196 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use borsh::BorshSerialize;
+use solana_program_test::tokio;
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::signer::Signer;
+
+use lido::account_map::{AccountMap, PubkeyAndEntry};
+use lido::error::LidoError;
+use lido::metrics::FeeRecipientMetrics;
+use lido::migration::{
+    FeeRecipientsV1, LidoV1, MetricsV1, RewardDistributionV1, ValidatorV1, LIDO_VERSION_V1,
+};
+use lido::state::{Lido, Validators};
+use lido::token::{Lamports, StLamports};
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{id, Context};
+
+/// Overwrite the Solido account with the version 1 layout of its current state.
+///
+/// Returns the state and validators before the downgrade, as the migration
+/// should restore them.
+async fn downgrade_to_v1(context: &mut Context) -> (Lido, Validators) {
+    let solido = context.get_solido().await;
+    let validators = context.get_validators().await;
+
+    // Version 1 had a fixed treasury and developer fee, which are the first
+    // two recipients in the test context. Metrics for a recipient only exist
+    // once it received a fee.
+    let recipients = &solido.reward_distribution.fee_recipients;
+    let fee_metrics: Vec<FeeRecipientMetrics> = recipients
+        .iter()
+        .map(|recipient| {
+            solido
+                .metrics
+                .fee_recipients
+                .iter()
+                .find(|m| m.st_sol_account == recipient.st_sol_account)
+                .cloned()
+                .unwrap_or(FeeRecipientMetrics {
+                    st_sol_account: recipient.st_sol_account,
+                    fee_sol_total: Lamports(0),
+                    fee_st_sol_total: StLamports(0),
+                })
+        })
+        .collect();
+
+    let solido_v1 = LidoV1 {
+        lido_version: LIDO_VERSION_V1,
+        manager: solido.manager,
+        st_sol_mint: solido.st_sol_mint,
+        exchange_rate: solido.exchange_rate,
+        sol_reserve_account_bump_seed: solido.sol_reserve_account_bump_seed,
+        stake_authority_bump_seed: solido.stake_authority_bump_seed,
+        mint_authority_bump_seed: solido.mint_authority_bump_seed,
+        reward_distribution: RewardDistributionV1 {
+            treasury_fee: recipients[0].parts,
+            developer_fee: recipients[1].parts,
+            st_sol_appreciation: solido.reward_distribution.st_sol_appreciation,
+        },
+        fee_recipients: FeeRecipientsV1 {
+            treasury_account: recipients[0].st_sol_account,
+            developer_account: recipients[1].st_sol_account,
+        },
+        metrics: MetricsV1 {
+            fee_treasury_sol_total: fee_metrics[0].fee_sol_total,
+            fee_validation_sol_total: solido.metrics.fee_validation_sol_total,
+            fee_developer_sol_total: fee_metrics[1].fee_sol_total,
+            st_sol_appreciation_sol_total: solido.metrics.st_sol_appreciation_sol_total,
+            fee_treasury_st_sol_total: fee_metrics[0].fee_st_sol_total,
+            fee_validation_st_sol_total: solido.metrics.fee_validation_st_sol_total,
+            fee_developer_st_sol_total: fee_metrics[1].fee_st_sol_total,
+            deposit_amount: solido.metrics.deposit_amount.clone(),
+            withdraw_amount: solido.metrics.withdraw_amount.clone(),
+        },
+        validators: AccountMap {
+            entries: validators
+                .entries
+                .iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: ValidatorV1 {
+                        stake_seeds: pe.entry.stake_seeds.clone(),
+                        unstake_seeds: pe.entry.unstake_seeds.clone(),
+                        stake_accounts_balance: pe.entry.stake_accounts_balance,
+                        unstake_accounts_balance: pe.entry.unstake_accounts_balance,
+                        active: pe.entry.active,
+                    },
+                })
+                .collect(),
+            maximum_entries: validators.maximum_entries,
+        },
+        max_commission_percentage: solido.max_commission_percentage,
+        maintainers: AccountMap {
+            entries: solido
+                .maintainers
+                .entries
+                .iter()
+                .map(|pe| PubkeyAndEntry {
+                    pubkey: pe.pubkey,
+                    entry: (),
+                })
+                .collect(),
+            maximum_entries: solido.maintainers.maximum_entries,
+        },
+    };
+
+    // Size the account for the serialized state only. That is smaller than the
+    // current layout needs, so the migration also has to grow the account.
+    let data = solido_v1.try_to_vec().unwrap();
+    let rent = context.get_rent().await;
+    let mut account = AccountSharedData::new(rent.minimum_balance(data.len()), data.len(), &id());
+    account.set_data(data);
+    context
+        .context
+        .set_account(&context.solido.pubkey(), &account);
+
+    // The migration tracks metrics for both recipients, also when they did
+    // not receive any fees yet.
+    let mut solido = solido;
+    solido.metrics.fee_recipients = fee_metrics;
+
+    (solido, validators)
+}
+
+#[tokio::test]
+async fn test_migrate_state_from_v1() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    context.deposit(Lamports(10_000_000_000)).await;
+    let (solido_before, validators_before) = downgrade_to_v1(&mut context).await;
+
+    // The program refuses to operate on the old layout.
+    let result = context.try_deposit(Lamports(1_000_000_000)).await;
+    assert_solido_error!(result, LidoError::InvalidLidoVersion);
+
+    let size = Validators::required_bytes(validators_before.maximum_entries as usize);
+    let new_validator_list = context.create_account(&id(), size).await.pubkey();
+    context.try_migrate_state(new_validator_list).await.unwrap();
+    context.validator_list = new_validator_list;
+
+    // The state is the same as before the downgrade, except for the new
+    // validator list. The fields that did not exist in version 1 had their
+    // default values before the downgrade as well.
+    let solido_after = context.get_solido().await;
+    assert_eq!(
+        solido_after,
+        Lido {
+            validator_list: new_validator_list,
+            ..solido_before
+        }
+    );
+    assert_eq!(context.get_validators().await, validators_before);
+
+    // The account grew and is still rent exempt.
+    let account = context.get_account(context.solido.pubkey()).await;
+    let rent = context.get_rent().await;
+    assert!(account.lamports >= rent.minimum_balance(account.data.len()));
+
+    // After the migration, the program works again.
+    context.deposit(Lamports(1_000_000_000)).await;
+}
+
+#[tokio::test]
+async fn test_migrate_state_fails_for_current_version() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let size = Validators::required_bytes(10);
+    let new_validator_list = context.create_account(&id(), size).await.pubkey();
+
+    let result = context.try_migrate_state(new_validator_list).await;
+    assert_solido_error!(result, LidoError::InvalidLidoVersion);
+}
+
+#[tokio::test]
+async fn test_migrate_state_does_not_overwrite_validator_list() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    downgrade_to_v1(&mut context).await;
+
+    // The existing validator list is in use already.
+    let validator_list = context.validator_list;
+    let result = context.try_migrate_state(validator_list).await;
+    assert_solido_error!(result, LidoError::AlreadyInUse);
+}
+
+#[tokio::test]
+async fn test_migrate_state_requires_manager() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (_, validators) = downgrade_to_v1(&mut context).await;
+    let size = Validators::required_bytes(validators.maximum_entries as usize);
+    let new_validator_list = context.create_account(&id(), size).await.pubkey();
+
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_migrate_state(new_validator_list).await;
+    assert_solido_error!(result, LidoError::InvalidManager);
+}
@@ -4,15 +4,32 @@
 #![cfg(feature = "test-bpf")]
 
 pub mod add_remove_validator;
+pub mod change_fee_recipients;
 pub mod change_reward_distribution;
+pub mod check_invariants;
+pub mod delinquent_validator;
 pub mod deposit;
+pub mod deposit_directed;
+pub mod deposit_limits;
+pub mod deposit_stake;
+pub mod get_exchange_rate;
+pub mod instant_unstake;
 pub mod limits;
 pub mod maintainers;
+pub mod manager;
 pub mod max_commission_percentage;
 pub mod merge_stake;
+pub mod migrate_state;
+pub mod pause;
+pub mod reserve_buffer;
+pub mod resize_validator_list;
+pub mod roles;
 pub mod solana_assumptions;
 pub mod stake_deposit;
 pub mod unstake;
 pub mod update_exchange_rate;
 pub mod update_stake_account_balance;
+pub mod validator_weight;
+pub mod withdraw_from_any_validator;
+pub mod withdrawal_tickets;
 pub mod withdrawals;
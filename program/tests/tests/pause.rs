@@ -0,0 +1,127 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::state::{PAUSE_ALL, PAUSE_DEPOSIT, PAUSE_STAKE_DEPOSIT, PAUSE_UNSTAKE, PAUSE_WITHDRAW};
+use lido::token::{Lamports, StLamports};
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const AMOUNT: Lamports = Lamports(10_000_000_000);
+
+#[tokio::test]
+async fn test_set_paused_operations() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    assert_eq!(context.get_solido().await.paused_operations, 0);
+
+    context
+        .try_set_paused_operations(PAUSE_DEPOSIT | PAUSE_UNSTAKE)
+        .await
+        .unwrap();
+    let solido = context.get_solido().await;
+    assert!(solido.is_paused(PAUSE_DEPOSIT));
+    assert!(solido.is_paused(PAUSE_UNSTAKE));
+    assert!(!solido.is_paused(PAUSE_WITHDRAW));
+    assert!(!solido.is_paused(PAUSE_STAKE_DEPOSIT));
+
+    // Bits that do not correspond to an operation are rejected.
+    let result = context.try_set_paused_operations(PAUSE_ALL + 1).await;
+    assert!(result.is_err());
+
+    // Only the emergency guardian can pause.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_set_paused_operations(0).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_pause_deposit() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+
+    context
+        .try_set_paused_operations(PAUSE_DEPOSIT)
+        .await
+        .unwrap();
+    let result = context.try_deposit(AMOUNT).await;
+    assert_solido_error!(result, LidoError::ProgramPaused);
+
+    context.try_set_paused_operations(0).await.unwrap();
+    context.deposit(AMOUNT).await;
+}
+
+#[tokio::test]
+async fn test_pause_stake_deposit_and_unstake() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.deposit(AMOUNT).await;
+
+    context
+        .try_set_paused_operations(PAUSE_STAKE_DEPOSIT)
+        .await
+        .unwrap();
+    let result = context
+        .try_stake_deposit(vote_account, StakeDeposit::Append, AMOUNT)
+        .await;
+    assert_solido_error!(result, LidoError::ProgramPaused);
+
+    context.try_set_paused_operations(0).await.unwrap();
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, AMOUNT)
+        .await;
+
+    context
+        .try_set_paused_operations(PAUSE_UNSTAKE)
+        .await
+        .unwrap();
+    let result = context
+        .try_unstake(vote_account, Lamports(2_000_000_000))
+        .await;
+    assert_solido_error!(result, LidoError::ProgramPaused);
+
+    // Bookkeeping keeps working while everything is paused.
+    context.try_set_paused_operations(PAUSE_ALL).await.unwrap();
+    context.advance_to_normal_epoch(1);
+    context
+        .try_update_exchange_rate()
+        .await
+        .expect("UpdateExchangeRate is never paused.");
+    context
+        .try_update_stake_account_balance(vote_account)
+        .await
+        .expect("UpdateStakeAccountBalance is never paused.");
+}
+
+#[tokio::test]
+async fn test_pause_withdraw() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    let (user, st_sol_account) = context.deposit(AMOUNT).await;
+    let stake_account = context
+        .stake_deposit(vote_account, StakeDeposit::Append, AMOUNT)
+        .await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    context
+        .try_set_paused_operations(PAUSE_WITHDRAW)
+        .await
+        .unwrap();
+    let amount = StLamports(1_000_000_000);
+    let result = context
+        .try_withdraw(&user, st_sol_account, amount, vote_account, stake_account)
+        .await;
+    assert_solido_error!(result, LidoError::ProgramPaused);
+    let result = context
+        .try_request_withdrawal(&user, st_sol_account, amount)
+        .await;
+    assert_solido_error!(result, LidoError::ProgramPaused);
+
+    context.try_set_paused_operations(0).await.unwrap();
+    context
+        .try_withdraw(&user, st_sol_account, amount, vote_account, stake_account)
+        .await
+        .expect("Withdrawing works again after unpausing.");
+}
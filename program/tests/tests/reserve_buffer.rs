@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::state::ReserveBuffer;
+use lido::token::Lamports;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+const SOL: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn test_set_reserve_buffer() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    assert_eq!(
+        context.get_solido().await.reserve_buffer,
+        ReserveBuffer::Amount(Lamports(0))
+    );
+
+    context
+        .try_set_reserve_buffer(ReserveBuffer::Amount(Lamports(5 * SOL)))
+        .await
+        .unwrap();
+    assert_eq!(
+        context.get_solido().await.reserve_buffer,
+        ReserveBuffer::Amount(Lamports(5 * SOL))
+    );
+
+    context
+        .try_set_reserve_buffer(ReserveBuffer::Bps(1_000))
+        .await
+        .unwrap();
+    assert_eq!(
+        context.get_solido().await.reserve_buffer,
+        ReserveBuffer::Bps(1_000)
+    );
+
+    let result = context
+        .try_set_reserve_buffer(ReserveBuffer::Bps(10_001))
+        .await;
+    assert_solido_error!(result, LidoError::ReserveBufferOutOfBounds);
+
+    // Only the fee admin can change the buffer.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context
+        .try_set_reserve_buffer(ReserveBuffer::default())
+        .await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_stake_deposit_leaves_reserve_buffer() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+    context.deposit(Lamports(10 * SOL)).await;
+
+    context
+        .try_set_reserve_buffer(ReserveBuffer::Amount(Lamports(4 * SOL)))
+        .await
+        .unwrap();
+    let result = context
+        .try_stake_deposit(vote_account, StakeDeposit::Append, Lamports(7 * SOL))
+        .await;
+    assert_solido_error!(result, LidoError::AmountExceedsReserve);
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, Lamports(6 * SOL))
+        .await;
+
+    // Half of the 10 SOL under management is more than the 4 SOL left in the
+    // reserve, so nothing can be staked.
+    context
+        .try_set_reserve_buffer(ReserveBuffer::Bps(5_000))
+        .await
+        .unwrap();
+    let result = context
+        .try_stake_deposit(vote_account, StakeDeposit::Append, Lamports(SOL))
+        .await;
+    assert_solido_error!(result, LidoError::AmountExceedsReserve);
+
+    // With a buffer of 20%, we can stake 2 SOL more.
+    context
+        .try_set_reserve_buffer(ReserveBuffer::Bps(2_000))
+        .await
+        .unwrap();
+    context
+        .stake_deposit(vote_account, StakeDeposit::Append, Lamports(2 * SOL))
+        .await;
+    let rent = context.get_rent().await;
+    assert_eq!(
+        context.get_sol_balance(context.reserve_address).await,
+        Lamports(rent.minimum_balance(0) + 2 * SOL)
+    );
+}
@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+
+use lido::error::LidoError;
+use lido::state::Validators;
+
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+#[tokio::test]
+async fn test_resize_validator_list() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let validators_before = context.get_validators().await;
+    let max_validators = validators_before.maximum_entries + 10;
+
+    context
+        .try_resize_validator_list(max_validators)
+        .await
+        .unwrap();
+
+    let validators_after = context.get_validators().await;
+    assert_eq!(validators_after.maximum_entries, max_validators);
+    assert_eq!(validators_after.entries, validators_before.entries);
+
+    // The account grew, and it is still rent exempt at the new size.
+    let account = context.get_account(context.validator_list).await;
+    let size = Validators::required_bytes(max_validators as usize);
+    assert_eq!(account.data.len(), size);
+    let rent = context.get_rent().await;
+    assert!(account.lamports >= rent.minimum_balance(size));
+
+    // We can still add validators after resizing.
+    context.add_validator().await;
+    assert_eq!(context.get_validators().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_resize_validator_list_can_only_grow() {
+    let mut context = Context::new_with_maintainer().await;
+    let max_validators = context.get_validators().await.maximum_entries;
+
+    let result = context.try_resize_validator_list(max_validators).await;
+    assert!(result.is_err());
+
+    let result = context.try_resize_validator_list(max_validators - 1).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_resize_validator_list_is_limited_per_instruction() {
+    let mut context = Context::new_with_maintainer().await;
+    let max_validators = context.get_validators().await.maximum_entries;
+
+    // This needs to grow the account by far more than 10 KiB in one go.
+    let result = context
+        .try_resize_validator_list(max_validators + 1_000)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_resize_validator_list_requires_manager() {
+    let mut context = Context::new_with_maintainer().await;
+    let max_validators = context.get_validators().await.maximum_entries;
+
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_resize_validator_list(max_validators + 1).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use lido::error::LidoError;
+use lido::state::{Role, Roles};
+
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+#[tokio::test]
+async fn test_roles_start_out_with_the_manager() {
+    let mut context = Context::new_with_maintainer().await;
+    let solido = context.get_solido().await;
+    assert_eq!(solido.roles, Roles::new_all(context.manager.pubkey()));
+}
+
+#[tokio::test]
+async fn test_set_role_separates_powers() {
+    let mut context = Context::new_with_maintainer().await;
+    let fee_admin = context.deterministic_keypair.new_keypair();
+
+    context
+        .try_set_role(Role::FeeAdmin, fee_admin.pubkey())
+        .await
+        .unwrap();
+    let solido = context.get_solido().await;
+    assert_eq!(solido.roles.fee_admin, fee_admin.pubkey());
+    assert_eq!(solido.roles.validator_admin, context.manager.pubkey());
+
+    // The manager no longer holds the fee admin role, but still holds the others.
+    let result = context.try_set_withdrawal_fee(10).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+    context.try_set_max_commission_percentage(5).await.unwrap();
+
+    // The fee admin can set fees, but cannot assign roles.
+    let manager = std::mem::replace(&mut context.manager, fee_admin);
+    context.try_set_withdrawal_fee(10).await.unwrap();
+    assert_eq!(context.get_solido().await.withdrawal_fee_bps, 10);
+    let result = context.try_set_role(Role::FeeAdmin, manager.pubkey()).await;
+    assert_solido_error!(result, LidoError::InvalidManager);
+}
@@ -19,8 +19,8 @@ async fn test_stake_deposit_append() {
     let validator = context.add_validator().await;
 
     // Sanity check before we start: the validator should have zero balance in zero stake accounts.
-    let solido_before = context.get_solido().await;
-    let validator_before = &solido_before.validators.entries[0].entry;
+    let validators_before = context.get_validators().await;
+    let validator_before = &validators_before.entries[0].entry;
     assert_eq!(validator_before.stake_accounts_balance, Lamports(0));
     assert_eq!(validator_before.stake_seeds.begin, 0);
     assert_eq!(validator_before.stake_seeds.end, 0);
@@ -44,9 +44,9 @@ async fn test_stake_deposit_append() {
 
     // We should also have recorded in the Solido state that this validator now
     // has balance in a stake account.
-    let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
 
-    let validator_after = &solido_after.validators.entries[0].entry;
+    let validator_after = &validators_after.entries[0].entry;
     assert_eq!(
         validator_after.stake_accounts_balance,
         TEST_STAKE_DEPOSIT_AMOUNT
@@ -95,8 +95,8 @@ async fn test_stake_deposit_merge() {
 
     // We should also have recorded in the Solido state that this validator now
     // has balance in a stake account.
-    let solido_after = context.get_solido().await;
-    let validator_after = &solido_after.validators.entries[0].entry;
+    let validators_after = context.get_validators().await;
+    let validator_after = &validators_after.entries[0].entry;
     assert_eq!(
         validator_after.stake_accounts_balance,
         (TEST_STAKE_DEPOSIT_AMOUNT * 2).unwrap(),
@@ -143,10 +143,10 @@ async fn test_stake_deposit_merge() {
 async fn test_stake_deposit_succeeds_despite_donation() {
     let mut context = Context::new_with_maintainer().await;
     context.add_validator().await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
 
-    let solido_before = context.get_solido().await;
-    let validator_before = &solido_before.validators.entries[0];
+    let validators_before = context.get_validators().await;
+    let validator_before = &validators_before.entries[0];
 
     // Figure out what the next stake account is going to be.
     let (stake_account_addr, _) = validator_before.find_stake_account_address(
@@ -173,16 +173,16 @@ async fn test_stake_deposit_succeeds_despite_donation() {
         .await;
 
     // The state does not record the additional balance yet though.
-    let solido = context.get_solido().await;
-    let validator_entry = &solido.validators.entries[0].entry;
+    let validators = context.get_validators().await;
+    let validator_entry = &validators.entries[0].entry;
     assert_eq!(
         validator_entry.stake_accounts_balance,
         TEST_STAKE_DEPOSIT_AMOUNT
     );
 
     context.update_stake_account_balance(validator.pubkey).await;
-    let solido = context.get_solido().await;
-    let validator_entry = &solido.validators.entries[0].entry;
+    let validators = context.get_validators().await;
+    let validator_entry = &validators.entries[0].entry;
     assert_eq!(
         validator_entry.stake_accounts_balance,
         (TEST_STAKE_DEPOSIT_AMOUNT + Lamports(107_000_000)).unwrap()
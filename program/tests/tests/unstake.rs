@@ -56,8 +56,8 @@ async fn test_successful_unstake() {
     let mut context = new_unstake_context(&[STAKE_AMOUNT]).await;
     let unstake_lamports = Lamports(1_000_000_000);
 
-    let solido = context.get_solido().await;
-    let validator = &solido.validators.entries[0];
+    let validators = context.get_validators().await;
+    let validator = &validators.entries[0];
 
     let stake_account_before = context.get_stake_account_from_seed(&validator, 0).await;
     context.unstake(validator.pubkey, unstake_lamports).await;
@@ -164,20 +164,20 @@ async fn test_unstake_from_inactive_validator() {
     assert!(is_insufficient_funds_error(result));
 
     // But unstaking exactly the stake account balance should work.
-    let solido_before = context.get_solido().await;
+    let validators_before = context.get_validators().await;
     context.unstake(vote_account, STAKE_AMOUNT).await;
-    let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
 
     assert_eq!(
-        solido_before.validators.entries[0].entry.stake_seeds.begin + 1,
-        solido_after.validators.entries[0].entry.stake_seeds.begin,
+        validators_before.entries[0].entry.stake_seeds.begin + 1,
+        validators_after.entries[0].entry.stake_seeds.begin,
         "Unstaking the full stake account amount should have bumped the steed.",
     );
 
     // We should be able to do it a second time and unstake the second stake account.
     context.unstake(vote_account, STAKE_AMOUNT).await;
 
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     assert_eq!(
         validator.entry.stake_seeds.begin, validator.entry.stake_seeds.end,
         "No stake accounts should be left after unstaking both."
@@ -202,7 +202,7 @@ async fn test_unstake_from_inactive_validator() {
 #[tokio::test]
 async fn test_unstake_with_funded_destination_stake() {
     let mut context = new_unstake_context(&[STAKE_AMOUNT]).await;
-    let validator = &context.get_solido().await.validators.entries[0];
+    let validator = &context.get_validators().await.entries[0];
     let (unstake_address, _) = validator.find_stake_account_address(
         &solido_context::id(),
         &context.solido.pubkey(),
@@ -237,8 +237,8 @@ async fn test_unstake_allows_at_most_three_unstake_accounts() {
     // Wait for the unstake accounts to deactivate.
     context.advance_to_normal_epoch(1);
 
-    let solido_before = context.get_solido().await;
-    let validator_before = &solido_before.validators.entries[0].entry;
+    let validators_before = context.get_validators().await;
+    let validator_before = &validators_before.entries[0].entry;
     assert_eq!(validator_before.unstake_seeds.begin, 0);
     assert_eq!(validator_before.unstake_seeds.end, 3);
 
@@ -246,8 +246,8 @@ async fn test_unstake_allows_at_most_three_unstake_accounts() {
     // unstake accounts again.
     context.update_stake_account_balance(vote_account).await;
 
-    let solido_after = context.get_solido().await;
-    let validator_after = &solido_after.validators.entries[0].entry;
+    let validators_after = context.get_validators().await;
+    let validator_after = &validators_after.entries[0].entry;
     assert_eq!(validator_after.unstake_seeds.begin, 3);
     assert_eq!(validator_after.unstake_seeds.end, 3);
 
@@ -260,8 +260,8 @@ async fn test_unstake_activating() {
     let mut context = Context::new_with_maintainer_and_validator().await;
     let unstake_lamports = Lamports(1_000_000_000);
 
-    let solido = context.get_solido().await;
-    let validator = &solido.validators.entries[0];
+    let validators = context.get_validators().await;
+    let validator = &validators.entries[0];
 
     context.deposit(Lamports(10_000_000_000)).await;
     context
@@ -293,3 +293,67 @@ async fn test_unstake_activating() {
     // Unstaking activating Sol will become inactive right away.
     assert_eq!(unstake_account.balance.inactive, unstake_lamports);
 }
+
+#[tokio::test]
+async fn test_emergency_unstake_validator_requires_manager() {
+    let mut context = new_unstake_context(&[STAKE_AMOUNT]).await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_emergency_unstake_validator(vote_account).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
+
+#[tokio::test]
+async fn test_emergency_unstake_validator() {
+    let mut context = new_unstake_context(&[STAKE_AMOUNT, STAKE_AMOUNT, STAKE_AMOUNT]).await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    // With one unstake account already in place, unstaking all three stake
+    // accounts would exceed `MAXIMUM_UNSTAKE_ACCOUNTS`, but the emergency
+    // unstake deactivates the stake accounts in place.
+    let unstake_amount = Lamports(1_000_000_000);
+    context.unstake(vote_account, unstake_amount).await;
+
+    context
+        .try_emergency_unstake_validator(vote_account)
+        .await
+        .unwrap();
+
+    let validator = &context.get_validators().await.entries[0];
+    assert!(!validator.entry.active);
+    assert_eq!(validator.entry.stake_seeds.begin, 0);
+    assert_eq!(validator.entry.stake_seeds.end, 3);
+    assert_eq!(validator.entry.unstake_seeds.begin, 0);
+    assert_eq!(validator.entry.unstake_seeds.end, 1);
+    for seed in 0..3 {
+        let stake_account = context.get_stake_account_from_seed(validator, seed).await;
+        assert_eq!(stake_account.balance.active, Lamports(0));
+        assert_eq!(stake_account.balance.activating, Lamports(0));
+        assert!(stake_account.balance.deactivating > Lamports(0));
+    }
+
+    // Once the stake is inactive, updating the balance moves all of it back
+    // into the reserve.
+    context.advance_to_normal_epoch(1);
+    context.update_exchange_rate().await;
+    let reserve_before = context.get_sol_balance(context.reserve_address).await;
+    context.update_stake_account_balance(vote_account).await;
+    let reserve_after = context.get_sol_balance(context.reserve_address).await;
+    assert!((reserve_after - reserve_before).unwrap() >= Lamports(3 * STAKE_AMOUNT.0));
+
+    let validator = &context.get_validators().await.entries[0];
+    assert_eq!(validator.entry.stake_seeds.begin, 3);
+    assert_eq!(validator.entry.unstake_seeds.begin, 1);
+    for seed in 0..3 {
+        let (stake_account, _) = validator.find_stake_account_address(
+            &solido_context::id(),
+            &context.solido.pubkey(),
+            seed,
+            StakeType::Stake,
+        );
+        assert!(context.try_get_account(stake_account).await.is_none());
+    }
+    assert_eq!(validator.entry.stake_accounts_balance, Lamports(0));
+    assert_eq!(validator.entry.unstake_accounts_balance, Lamports(0));
+}
@@ -98,4 +98,16 @@ async fn test_update_exchange_rate() {
     let (_, recipient) = context.deposit(Lamports(DEPOSIT_AMOUNT)).await;
     let received_st_sol = context.get_st_sol_balance(recipient).await;
     assert_eq!(received_st_sol, StLamports(DEPOSIT_AMOUNT / 2));
+
+    // Every update was also recorded in the history, most recent last.
+    let history = &solido.exchange_rate_history;
+    assert_eq!(history.iter().last(), Some(&solido.exchange_rate));
+    assert_eq!(
+        history.get(start_epoch + 1),
+        Some(&ExchangeRate {
+            computed_in_epoch: start_epoch + 1,
+            st_sol_supply: StLamports(DEPOSIT_AMOUNT),
+            sol_balance: Lamports(DEPOSIT_AMOUNT),
+        })
+    );
 }
@@ -15,13 +15,16 @@ async fn test_update_stake_account_balance() {
     // If we try to withdraw initially, that should work, but there is nothing to withdraw.
     // The 2nd time it runs, should succeed, but nothing should change
     let solido_before = context.get_solido().await;
+    let validators_before = context.get_validators().await;
     for _ in 0..2 {
         context
             .update_stake_account_balance(validator.vote_account)
             .await;
     }
     let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
     assert_eq!(solido_before, solido_after);
+    assert_eq!(validators_before, validators_after);
 
     // Deposit and stake the deposit with the validator. This creates one stake account.
     let initial_amount = Lamports(1_000_000_000);
@@ -33,11 +36,14 @@ async fn test_update_stake_account_balance() {
     // We should be able to withdraw the inactive stake. It should be a no-op,
     // because we already knew the current validator's balance.
     let solido_before = context.get_solido().await;
+    let validators_before = context.get_validators().await;
     context
         .update_stake_account_balance(validator.vote_account)
         .await;
     let solido_after = context.get_solido().await;
+    let validators_after = context.get_validators().await;
     assert_eq!(solido_before, solido_after);
+    assert_eq!(validators_before, validators_after);
 
     // Skip ahead a number of epochs.
     context.advance_to_normal_epoch(0);
@@ -92,11 +98,8 @@ async fn test_update_stake_account_balance() {
     let developer_before = context
         .get_st_sol_balance(context.developer_st_sol_account)
         .await;
-    let solido_before = context.get_solido().await;
-    let validator_before = solido_before
-        .validators
-        .get(&validator.vote_account)
-        .unwrap();
+    let validators_before = context.get_validators().await;
+    let validator_before = validators_before.get(&validator.vote_account).unwrap();
 
     let account = context.get_account(validator.vote_account).await;
     let vote_account_rent = Lamports(context.get_rent().await.minimum_balance(account.data.len()));
@@ -127,10 +130,8 @@ async fn test_update_stake_account_balance() {
         .get_st_sol_balance(context.developer_st_sol_account)
         .await;
     let solido_after = context.get_solido().await;
-    let validator_after = solido_after
-        .validators
-        .get(&validator.vote_account)
-        .unwrap();
+    let validators_after = context.get_validators().await;
+    let validator_after = validators_after.get(&validator.vote_account).unwrap();
 
     let rewards = (validator_after.entry.stake_accounts_balance
         - validator_before.entry.stake_accounts_balance)
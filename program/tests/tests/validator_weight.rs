@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use testlib::assert_solido_error;
+use testlib::solido_context::{Context, StakeDeposit};
+
+use lido::error::LidoError;
+use lido::state::DEFAULT_VALIDATOR_WEIGHT;
+use lido::token::Lamports;
+use solana_program_test::tokio;
+
+const STAKE_AMOUNT: Lamports = Lamports(1_000_000_000);
+
+#[tokio::test]
+async fn test_set_validator_weight() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    let validators = context.get_validators().await;
+    assert_eq!(validators.entries[0].entry.weight, DEFAULT_VALIDATOR_WEIGHT);
+
+    context
+        .try_set_validator_weight(vote_account, 3)
+        .await
+        .unwrap();
+
+    let validators = context.get_validators().await;
+    assert_eq!(validators.entries[0].entry.weight, 3);
+}
+
+#[tokio::test]
+async fn test_stake_deposit_fails_for_zero_weight_validator() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator = context.add_validator().await;
+    context
+        .try_set_validator_weight(validator.vote_account, 0)
+        .await
+        .unwrap();
+
+    context.deposit(Lamports(10_000_000_000)).await;
+    let result = context
+        .try_stake_deposit(validator.vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    assert_solido_error!(result, LidoError::StakeToInactiveValidator);
+}
+
+#[tokio::test]
+async fn test_stake_deposit_follows_validator_weight() {
+    let mut context = Context::new_with_maintainer().await;
+    let v1 = context.add_validator().await;
+    let v2 = context.add_validator().await;
+    context
+        .try_set_validator_weight(v1.vote_account, 1)
+        .await
+        .unwrap();
+    context
+        .try_set_validator_weight(v2.vote_account, 3)
+        .await
+        .unwrap();
+
+    context.deposit(Lamports(10_000_000_000)).await;
+
+    // Both validators have 0 stake, so every validator is as good as any.
+    context
+        .stake_deposit(v1.vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+
+    // v2 has three times the weight of v1, so it can receive three times as
+    // much stake before v1 is below its target.
+    for _ in 0..3 {
+        context
+            .stake_deposit(v2.vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+            .await;
+    }
+
+    // Now v2 has as much stake per weight as v1, so one more would put v1
+    // below its share.
+    let result = context
+        .try_stake_deposit(v2.vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+    assert_solido_error!(result, LidoError::ValidatorWithLessStakeExists);
+
+    context
+        .stake_deposit(v1.vote_account, StakeDeposit::Append, STAKE_AMOUNT)
+        .await;
+}
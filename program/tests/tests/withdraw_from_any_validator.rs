@@ -0,0 +1,121 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+use lido::token::{Lamports, StLamports};
+use testlib::solido_context::{Context, StakeDeposit};
+
+#[tokio::test]
+async fn test_withdraw_from_validator_below_target_pays_penalty() {
+    let mut context = Context::new_with_maintainer().await;
+    let validator_1 = context.add_validator().await;
+    let validator_2 = context.add_validator().await;
+
+    let (user, token_addr) = context.deposit(Lamports(100 * LAMPORTS_PER_SOL)).await;
+
+    // Both validators have a target of 50 SOL, validator 1 is above it.
+    let stake_account_1 = context
+        .stake_deposit(
+            validator_1.vote_account,
+            StakeDeposit::Append,
+            Lamports(60 * LAMPORTS_PER_SOL),
+        )
+        .await;
+    let stake_account_2 = context
+        .stake_deposit(
+            validator_2.vote_account,
+            StakeDeposit::Append,
+            Lamports(40 * LAMPORTS_PER_SOL),
+        )
+        .await;
+
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    // A regular `Withdraw` would refuse this, because validator 1 has more stake.
+    // Validator 2 goes from 10 to 20 SOL below its target, so all 10 SOL are
+    // penalized at the average shortfall of 15/50 of the maximum rate of 1%.
+    let split_stake_account = context
+        .try_withdraw_from_any_validator(
+            &user,
+            token_addr,
+            StLamports(10 * LAMPORTS_PER_SOL),
+            validator_2.vote_account,
+            stake_account_2,
+        )
+        .await
+        .unwrap();
+    let penalty = Lamports(30_000_000);
+    assert_eq!(
+        context.get_sol_balance(split_stake_account).await,
+        Lamports(10 * LAMPORTS_PER_SOL - penalty.0)
+    );
+
+    // The penalty stays behind in the validator's stake account.
+    let validators = context.get_validators().await;
+    assert_eq!(
+        validators
+            .get(&validator_2.vote_account)
+            .unwrap()
+            .entry
+            .stake_accounts_balance,
+        Lamports(30 * LAMPORTS_PER_SOL + penalty.0)
+    );
+
+    // Withdrawing from validator 1 keeps it above its target, which is free.
+    let split_stake_account = context
+        .try_withdraw_from_any_validator(
+            &user,
+            token_addr,
+            StLamports(5 * LAMPORTS_PER_SOL),
+            validator_1.vote_account,
+            stake_account_1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        context.get_sol_balance(split_stake_account).await,
+        Lamports(5 * LAMPORTS_PER_SOL)
+    );
+
+    let solido = context.get_solido().await;
+    assert_eq!(solido.metrics.withdraw_amount.count, 2);
+}
+
+#[tokio::test]
+async fn test_withdraw_from_any_validator_is_not_capped() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let vote_account = context.validator.as_ref().unwrap().vote_account;
+
+    let (user, token_addr) = context.deposit(Lamports(1000 * LAMPORTS_PER_SOL)).await;
+    let stake_account = context
+        .stake_deposit(
+            vote_account,
+            StakeDeposit::Append,
+            Lamports(1000 * LAMPORTS_PER_SOL),
+        )
+        .await;
+
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    // `Withdraw` allows at most 110 SOL here. Withdrawing half of the stake
+    // leaves the validator 50% below its target. On average the shortfall is
+    // 25%, so the penalty rate is 0.25%.
+    let split_stake_account = context
+        .try_withdraw_from_any_validator(
+            &user,
+            token_addr,
+            StLamports(500 * LAMPORTS_PER_SOL),
+            vote_account,
+            stake_account,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        context.get_sol_balance(split_stake_account).await,
+        Lamports(498_750_000_000)
+    );
+}
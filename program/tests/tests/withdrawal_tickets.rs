@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use lido::error::LidoError;
+use lido::state::WITHDRAWAL_TICKET_SIZE;
+use lido::token::{Lamports, StLamports};
+use testlib::assert_solido_error;
+use testlib::solido_context::Context;
+
+const TEST_DEPOSIT_AMOUNT: Lamports = Lamports(10_000_000_000);
+
+#[tokio::test]
+async fn test_request_and_claim_withdrawal() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    let amount = StLamports(2_000_000_000);
+    let ticket_address = context.request_withdrawal(&user, token_addr, amount).await;
+
+    let solido = context.get_solido().await;
+    let sol_owed = solido.exchange_rate.exchange_st_sol(amount).unwrap();
+    assert_eq!(solido.withdrawal_tickets_owed, sol_owed);
+    assert_eq!(
+        context.get_st_sol_balance(token_addr).await,
+        StLamports(8_000_000_000)
+    );
+
+    let ticket = context.get_withdrawal_ticket(ticket_address).await;
+    assert_eq!(ticket.solido, context.solido.pubkey());
+    assert_eq!(ticket.owner, user.pubkey());
+    assert_eq!(ticket.amount, sol_owed);
+
+    // The ticket cannot be claimed in the epoch that it was created.
+    let recipient = context.deterministic_keypair.new_keypair().pubkey();
+    assert_solido_error!(
+        context
+            .try_claim_withdrawal(&user, ticket_address, recipient)
+            .await,
+        LidoError::WithdrawalTicketNotClaimable
+    );
+
+    // The SOL owed to the ticket does not count towards the exchange rate, so
+    // the remaining stSOL is worth the same as before.
+    context.advance_to_normal_epoch(1);
+    context.update_exchange_rate().await;
+    let solido_after = context.get_solido().await;
+    assert_eq!(
+        solido_after.exchange_rate.sol_balance,
+        (TEST_DEPOSIT_AMOUNT - sol_owed).unwrap()
+    );
+    assert_eq!(
+        solido_after.exchange_rate.st_sol_supply,
+        StLamports(8_000_000_000)
+    );
+
+    let rent = context.get_rent().await;
+    let ticket_rent = Lamports(rent.minimum_balance(WITHDRAWAL_TICKET_SIZE));
+    context
+        .claim_withdrawal(&user, ticket_address, recipient)
+        .await;
+
+    assert_eq!(
+        context.get_sol_balance(recipient).await,
+        (sol_owed + ticket_rent).unwrap()
+    );
+    assert!(context.try_get_account(ticket_address).await.is_none());
+    assert_eq!(
+        context.get_solido().await.withdrawal_tickets_owed,
+        Lamports(0)
+    );
+}
+
+#[tokio::test]
+async fn test_claim_withdrawal_by_other_owner_fails() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    let (other_user, _) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    let ticket_address = context
+        .request_withdrawal(&user, token_addr, StLamports(1_000_000_000))
+        .await;
+    context.advance_to_normal_epoch(1);
+
+    let recipient = other_user.pubkey();
+    assert_solido_error!(
+        context
+            .try_claim_withdrawal(&other_user, ticket_address, recipient)
+            .await,
+        LidoError::InvalidWithdrawalTicket
+    );
+}
+
+#[tokio::test]
+async fn test_request_withdrawal_zero_fails() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    let (user, token_addr) = context.deposit(TEST_DEPOSIT_AMOUNT).await;
+    context.advance_to_normal_epoch(0);
+    context.update_exchange_rate().await;
+
+    assert_solido_error!(
+        context
+            .try_request_withdrawal(&user, token_addr, StLamports(0))
+            .await,
+        LidoError::InvalidAmount
+    );
+}
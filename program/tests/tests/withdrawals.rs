@@ -334,3 +334,51 @@ async fn test_withdraw_enforces_picking_most_stake_validator_in_presence_of_unst
         )
         .await;
 }
+
+#[tokio::test]
+async fn test_withdraw_pays_fee_to_treasury() {
+    let mut context = WithdrawContext::new(Lamports(LAMPORTS_PER_SOL * 100)).await;
+
+    // 0.25% fee.
+    context.context.try_set_withdrawal_fee(25).await.unwrap();
+    assert_eq!(context.context.get_solido().await.withdrawal_fee_bps, 25);
+
+    let treasury = context.context.treasury_st_sol_account;
+    let treasury_before = context.context.get_st_sol_balance(treasury).await;
+
+    let amount = StLamports(LAMPORTS_PER_SOL * 2);
+    let fee = StLamports(5_000_000);
+    let split_stake_account = context.try_withdraw(amount).await.unwrap();
+
+    // The fee goes to the treasury, only the remainder is exchanged for SOL.
+    assert_eq!(
+        context.context.get_st_sol_balance(treasury).await,
+        (treasury_before + fee).unwrap()
+    );
+    assert_eq!(
+        context.context.get_sol_balance(split_stake_account).await,
+        Lamports((amount - fee).unwrap().0)
+    );
+
+    let solido = context.context.get_solido().await;
+    assert_eq!(
+        solido.metrics.withdraw_amount.total_st_sol_amount,
+        (amount - fee).unwrap()
+    );
+    assert_eq!(solido.metrics.fee_withdrawal_st_sol_total, fee);
+}
+
+#[tokio::test]
+async fn test_set_withdrawal_fee() {
+    let mut context = Context::new_with_maintainer_and_validator().await;
+    assert_eq!(context.get_solido().await.withdrawal_fee_bps, 0);
+
+    context.try_set_withdrawal_fee(10_000).await.unwrap();
+    let result = context.try_set_withdrawal_fee(10_001).await;
+    assert_solido_error!(result, LidoError::WithdrawalFeeOutOfBounds);
+
+    // Only the fee admin can set the fee.
+    context.manager = context.deterministic_keypair.new_keypair();
+    let result = context.try_set_withdrawal_fee(0).await;
+    assert_solido_error!(result, LidoError::InvalidRoleAuthority);
+}
@@ -663,6 +663,59 @@ impl Context {
         .await
     }
 
+    pub async fn try_propose_new_manager(
+        &mut self,
+        manager: &Keypair,
+        new_manager: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.solido_context.context,
+            &[instruction::propose_new_manager(
+                &id(),
+                &instruction::ProposeNewManagerAccountsMeta {
+                    anker: self.anker,
+                    solido: self.solido_context.solido.pubkey(),
+                    manager: manager.pubkey(),
+                    new_manager,
+                },
+            )],
+            vec![manager],
+        )
+        .await
+    }
+
+    pub async fn try_accept_manager(&mut self, new_manager: &Keypair) -> transport::Result<()> {
+        send_transaction(
+            &mut self.solido_context.context,
+            &[instruction::accept_manager(
+                &id(),
+                &instruction::AcceptManagerAccountsMeta {
+                    anker: self.anker,
+                    solido: self.solido_context.solido.pubkey(),
+                    new_manager: new_manager.pubkey(),
+                },
+            )],
+            vec![new_manager],
+        )
+        .await
+    }
+
+    pub async fn try_migrate_state(&mut self, manager: &Keypair) -> transport::Result<()> {
+        send_transaction(
+            &mut self.solido_context.context,
+            &[instruction::migrate_state(
+                &id(),
+                &instruction::MigrateStateAccountsMeta {
+                    anker: self.anker,
+                    solido: self.solido_context.solido.pubkey(),
+                    manager: manager.pubkey(),
+                },
+            )],
+            vec![manager],
+        )
+        .await
+    }
+
     /// Return the `MicroUst` balance of the account in `address`.
     pub async fn try_fetch_pool_price(&mut self) -> transport::Result<()> {
         let (ust_address, st_sol_address) = self
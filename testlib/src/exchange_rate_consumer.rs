@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2021 Chorus One AG
+// SPDX-License-Identifier: GPL-3.0
+
+//! A minimal program that prices stSOL through a CPI into Solido, to test `lido::cpi`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Epoch,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use lido::token::{Lamports, StLamports};
+
+// Program id for the consumer program. Only used for tests.
+solana_program::declare_id!("Consumer11111111111111111111111111111111111");
+
+/// Instruction data for the consumer program.
+///
+/// The program queries the exchange rate from Solido, converts `amount` to
+/// SOL, and fails if the result does not match the expectations.
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct PriceStSol {
+    pub amount: StLamports,
+    pub expected_value: Lamports,
+    pub expected_computed_in_epoch: Epoch,
+}
+
+/// Expects the Solido program and the Solido instance as accounts.
+pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let solido_program = next_account_info(accounts_iter)?;
+    let solido = next_account_info(accounts_iter)?;
+    let args = PriceStSol::try_from_slice(input)?;
+
+    let exchange_rate = lido::cpi::get_exchange_rate(solido_program, solido)?;
+    let value = exchange_rate.exchange_st_sol(args.amount)?;
+    msg!(
+        "{} is worth {} as of epoch {}.",
+        args.amount,
+        value,
+        exchange_rate.computed_in_epoch
+    );
+
+    if value != args.expected_value
+        || exchange_rate.computed_in_epoch != args.expected_computed_in_epoch
+    {
+        msg!(
+            "Expected {} as of epoch {}.",
+            args.expected_value,
+            args.expected_computed_in_epoch
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
@@ -2,5 +2,6 @@
 // SPDX-License-Identifier: GPL-3.0
 
 pub mod anker_context;
+pub mod exchange_rate_consumer;
 pub mod solido_context;
 mod util;
@@ -3,6 +3,7 @@
 
 //! Holds a test context, which makes it easier to test with a Solido instance set up.
 
+use borsh::BorshSerialize;
 use num_traits::cast::FromPrimitive;
 use rand::prelude::StdRng;
 use rand::SeedableRng;
@@ -12,10 +13,13 @@ use solana_program::stake::state::Stake;
 use solana_program::system_instruction;
 use solana_program::system_program;
 use solana_program::{borsh::try_from_slice_unchecked, sysvar};
-use solana_program::{clock::Clock, instruction::Instruction};
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+};
 use solana_program::{instruction::InstructionError, stake_history::StakeHistory};
 use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
-use solana_sdk::account::{from_account, Account};
+use solana_sdk::account::{from_account, Account, AccountSharedData};
 use solana_sdk::account_info::AccountInfo;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
@@ -24,7 +28,7 @@ use solana_sdk::transaction::TransactionError;
 use solana_sdk::transport;
 use solana_sdk::transport::TransportError;
 use solana_vote_program::vote_instruction;
-use solana_vote_program::vote_state::{VoteInit, VoteState};
+use solana_vote_program::vote_state::{VoteInit, VoteState, VoteStateVersions};
 use std::sync::Once;
 
 use anker::error::AnkerError;
@@ -32,12 +36,18 @@ use lido::account_map::PubkeyAndEntry;
 use lido::processor::StakeType;
 use lido::stake_account::StakeAccount;
 use lido::token::{Lamports, StLamports};
+use lido::vote_state::EpochCredits;
 use lido::{error::LidoError, instruction, RESERVE_ACCOUNT, STAKE_AUTHORITY};
 use lido::{
-    state::{FeeRecipients, Lido, RewardDistribution, Validator},
+    state::{
+        FeeRecipient, Lido, ReserveBuffer, RewardDistribution, Role, Validator, Validators,
+        WithdrawalTicket,
+    },
     MINT_AUTHORITY,
 };
 
+use crate::exchange_rate_consumer::PriceStSol;
+
 static INIT: Once = Once::new();
 
 pub struct DeterministicKeypairGen {
@@ -81,6 +91,7 @@ pub struct Context {
 
     // Key pairs for the accounts in the Solido instance.
     pub solido: Keypair,
+    pub validator_list: Pubkey,
     pub manager: Keypair,
     pub st_sol_mint: Pubkey,
     pub maintainer: Option<Keypair>,
@@ -186,6 +197,42 @@ pub async fn send_transaction(
     result
 }
 
+/// Return the compute units that `program_id` consumed, from the
+/// "Program <id> consumed <n> of <m> compute units" line in `logs`.
+pub fn parse_compute_units(logs: &[String], program_id: &Pubkey) -> Option<u64> {
+    let prefix = format!("Program {} consumed ", program_id);
+    logs.iter()
+        .find_map(|line| line.strip_prefix(&prefix)?.split(' ').next()?.parse().ok())
+}
+
+/// Simulate a transaction, and return the compute units that Solido consumed.
+///
+/// The transaction is signed like in `send_transaction`, it must succeed. The
+/// runtime only meters BPF programs, so this returns `None` unless the tests
+/// run with `cargo test-bpf`.
+pub async fn simulate_compute_units(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    additional_signers: Vec<&Keypair>,
+) -> Option<u64> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&context.payer.pubkey()));
+    let mut signers = additional_signers;
+    signers.push(&context.payer);
+    transaction.sign(&signers, context.last_blockhash);
+
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .expect("Failed to simulate transaction.");
+    assert_eq!(
+        simulation.result,
+        Some(Ok(())),
+        "Simulated transaction failed."
+    );
+    parse_compute_units(&simulation.simulation_details?.logs, &id())
+}
+
 /// The different ways to stake some amount from the reserve.
 pub enum StakeDeposit {
     /// Stake into a new stake account, and delegate the new account.
@@ -208,12 +255,6 @@ impl Context {
         let manager = deterministic_keypair.new_keypair();
         let solido = deterministic_keypair.new_keypair();
 
-        let reward_distribution = RewardDistribution {
-            treasury_fee: 3,
-            developer_fee: 2,
-            st_sol_appreciation: 95,
-        };
-
         let (reserve_address, _) = Pubkey::find_program_address(
             &[&solido.pubkey().to_bytes()[..], RESERVE_ACCOUNT],
             &id(),
@@ -240,6 +281,11 @@ impl Context {
             crate::anker_context::id(),
             processor!(anker::processor::process),
         );
+        program_test.add_program(
+            "exchange_rate_consumer",
+            crate::exchange_rate_consumer::id(),
+            processor!(crate::exchange_rate_consumer::process),
+        );
 
         // Add the actual Orca token swap program, so we test against the real thing.
         // If we don't have it locally, download it from the chain.
@@ -259,12 +305,13 @@ impl Context {
             nonce: 0,
             manager,
             solido,
+            validator_list: Pubkey::default(),
             st_sol_mint: Pubkey::default(),
             maintainer: None,
             validator: None,
             treasury_st_sol_account: Pubkey::default(),
             developer_st_sol_account: Pubkey::default(),
-            reward_distribution,
+            reward_distribution: RewardDistribution::default(),
             reserve_address,
             stake_authority,
             mint_authority,
@@ -282,11 +329,30 @@ impl Context {
         result.developer_st_sol_account =
             result.create_st_sol_account(developer_owner.pubkey()).await;
 
+        result.reward_distribution = RewardDistribution {
+            fee_recipients: vec![
+                FeeRecipient {
+                    st_sol_account: result.treasury_st_sol_account,
+                    parts: 3,
+                },
+                FeeRecipient {
+                    st_sol_account: result.developer_st_sol_account,
+                    parts: 2,
+                },
+            ],
+            st_sol_appreciation: 95,
+        };
+
+        let validator_list = result.deterministic_keypair.new_keypair();
+        result.validator_list = validator_list.pubkey();
+
         let max_validators = 10_000;
         let max_maintainers = 1000;
-        let solido_size = Lido::calculate_size(max_validators, max_maintainers);
+        let solido_size = Lido::calculate_size(max_maintainers);
+        let validator_list_size = Validators::required_bytes(max_validators as usize);
         let rent = result.context.banks_client.get_rent().await.unwrap();
         let rent_solido = rent.minimum_balance(solido_size);
+        let rent_validator_list = rent.minimum_balance(validator_list_size);
 
         let rent_reserve = rent.minimum_balance(0);
         result
@@ -304,6 +370,13 @@ impl Context {
                     solido_size as u64,
                     &id(),
                 ),
+                system_instruction::create_account(
+                    &payer,
+                    &result.validator_list,
+                    rent_validator_list,
+                    validator_list_size as u64,
+                    &id(),
+                ),
                 instruction::initialize(
                     &id(),
                     result.reward_distribution.clone(),
@@ -312,15 +385,18 @@ impl Context {
                     result.max_commission_percentage,
                     &instruction::InitializeAccountsMeta {
                         lido: result.solido.pubkey(),
+                        validator_list: result.validator_list,
                         manager: result.manager.pubkey(),
                         st_sol_mint: result.st_sol_mint,
-                        treasury_account: result.treasury_st_sol_account,
-                        developer_account: result.developer_st_sol_account,
                         reserve_account: result.reserve_address,
+                        fee_recipients: vec![
+                            result.treasury_st_sol_account,
+                            result.developer_st_sol_account,
+                        ],
                     },
                 ),
             ],
-            vec![&result.solido],
+            vec![&result.solido, &validator_list],
         )
         .await
         .expect("Failed to initialize Solido instance.");
@@ -466,9 +542,25 @@ impl Context {
         &mut self,
         fund_amount: Lamports,
         authorized_staker_withdrawer: Pubkey,
+    ) -> Pubkey {
+        use solana_program::stake::state::Lockup;
+        self.create_stake_account_with_lockup(
+            fund_amount,
+            authorized_staker_withdrawer,
+            &Lockup::default(),
+        )
+        .await
+    }
+
+    /// Like `create_stake_account`, but with the given lockup.
+    pub async fn create_stake_account_with_lockup(
+        &mut self,
+        fund_amount: Lamports,
+        authorized_staker_withdrawer: Pubkey,
+        lockup: &solana_program::stake::state::Lockup,
     ) -> Pubkey {
         use solana_program::stake::instruction as stake;
-        use solana_program::stake::state::{Authorized, Lockup};
+        use solana_program::stake::state::Authorized;
 
         let keypair = self.deterministic_keypair.new_keypair();
 
@@ -479,7 +571,7 @@ impl Context {
                 staker: authorized_staker_withdrawer,
                 withdrawer: authorized_staker_withdrawer,
             },
-            &Lockup::default(),
+            lockup,
             fund_amount.0,
         );
         send_transaction(&mut self.context, &instructions[..], vec![&keypair])
@@ -704,6 +796,7 @@ impl Context {
                 &id(),
                 &lido::instruction::AddValidatorMetaV2 {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     manager: self.manager.pubkey(),
                     validator_vote_account: accounts.vote_account,
                 },
@@ -743,6 +836,7 @@ impl Context {
                 &id(),
                 &lido::instruction::DeactivateValidatorMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     manager: self.manager.pubkey(),
                     validator_vote_account_to_deactivate: vote_account,
                 },
@@ -760,6 +854,7 @@ impl Context {
                 &id(),
                 &lido::instruction::RemoveValidatorMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     validator_vote_account_to_remove: vote_account,
                 },
             )],
@@ -806,6 +901,68 @@ impl Context {
             .expect("Failed to call Deposit on Solido instance.")
     }
 
+    /// Like `try_deposit`, but direct the deposit to the given validator.
+    pub async fn try_deposit_directed(
+        &mut self,
+        amount: Lamports,
+        validator_vote_account: Pubkey,
+    ) -> transport::Result<(Keypair, Pubkey)> {
+        let user = self.deterministic_keypair.new_keypair();
+        let recipient = self.create_st_sol_account(user.pubkey()).await;
+        self.fund(user.pubkey(), amount).await;
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::deposit_directed(
+                &id(),
+                &instruction::DepositDirectedAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    user: user.pubkey(),
+                    recipient,
+                    st_sol_mint: self.st_sol_mint,
+                    reserve_account: self.reserve_address,
+                    mint_authority: self.mint_authority,
+                    validator_list: self.validator_list,
+                    validator_vote_account,
+                },
+                amount,
+            )],
+            vec![&user],
+        )
+        .await?;
+
+        Ok((user, recipient))
+    }
+
+    /// Build the instruction to withdraw from the given validator and stake
+    /// account into `destination_stake_account`.
+    pub fn withdraw_instruction(
+        &self,
+        user: Pubkey,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+        validator_vote_account: Pubkey,
+        source_stake_account: Pubkey,
+        destination_stake_account: Pubkey,
+    ) -> Instruction {
+        instruction::withdraw(
+            &id(),
+            &instruction::WithdrawAccountsMeta {
+                lido: self.solido.pubkey(),
+                validator_list: self.validator_list,
+                st_sol_mint: self.st_sol_mint,
+                st_sol_account_owner: user,
+                st_sol_account,
+                treasury_st_sol_account: self.treasury_st_sol_account,
+                validator_vote_account,
+                source_stake_account,
+                destination_stake_account,
+                stake_authority: self.stake_authority,
+            },
+            amount,
+        )
+    }
+
     /// Withdraw from the given validator and stake account.
     pub async fn try_withdraw(
         &mut self,
@@ -818,19 +975,47 @@ impl Context {
         // Where the new stake will live.
         let new_stake = self.deterministic_keypair.new_keypair();
 
+        let instruction = self.withdraw_instruction(
+            user.pubkey(),
+            st_sol_account,
+            amount,
+            validator_vote_account,
+            source_stake_account,
+            new_stake.pubkey(),
+        );
+        send_transaction(&mut self.context, &[instruction], vec![user, &new_stake]).await?;
+        Ok(new_stake.pubkey())
+    }
+
+    /// Withdraw from any validator, paying the imbalance penalty if there is one.
+    /// Returns the address of the new stake account.
+    pub async fn try_withdraw_from_any_validator(
+        &mut self,
+        user: &Keypair,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+        validator_vote_account: Pubkey,
+        source_stake_account: Pubkey,
+    ) -> transport::Result<Pubkey> {
+        // Where the new stake will live.
+        let new_stake = self.deterministic_keypair.new_keypair();
+
         send_transaction(
             &mut self.context,
-            &[instruction::withdraw(
+            &[instruction::withdraw_from_any_validator(
                 &id(),
-                &instruction::WithdrawAccountsMeta {
+                &instruction::WithdrawFromAnyValidatorAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     st_sol_mint: self.st_sol_mint,
                     st_sol_account_owner: user.pubkey(),
                     st_sol_account,
+                    treasury_st_sol_account: self.treasury_st_sol_account,
                     validator_vote_account,
                     source_stake_account,
                     destination_stake_account: new_stake.pubkey(),
                     stake_authority: self.stake_authority,
+                    reserve: self.reserve_address,
                 },
                 amount,
             )],
@@ -840,6 +1025,124 @@ impl Context {
         Ok(new_stake.pubkey())
     }
 
+    pub async fn try_instant_unstake(
+        &mut self,
+        user: &Keypair,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+        recipient: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[instruction::instant_unstake(
+                &id(),
+                &instruction::InstantUnstakeAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    st_sol_account_owner: user.pubkey(),
+                    st_sol_account,
+                    st_sol_mint: self.st_sol_mint,
+                    treasury_st_sol_account: self.treasury_st_sol_account,
+                    reserve_account: self.reserve_address,
+                    recipient,
+                },
+                amount,
+            )],
+            vec![user],
+        )
+        .await
+    }
+
+    /// Instantly unstake from the reserve, paying out SOL to `recipient`.
+    pub async fn instant_unstake(
+        &mut self,
+        user: &Keypair,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+        recipient: Pubkey,
+    ) {
+        self.try_instant_unstake(user, st_sol_account, amount, recipient)
+            .await
+            .expect("Failed to call InstantUnstake on Solido instance.");
+    }
+
+    /// Request a withdrawal, return the address of the withdrawal ticket.
+    pub async fn try_request_withdrawal(
+        &mut self,
+        user: &Keypair,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+    ) -> transport::Result<Pubkey> {
+        let ticket = self.deterministic_keypair.new_keypair();
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::request_withdrawal(
+                &id(),
+                &instruction::RequestWithdrawalAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    st_sol_account_owner: user.pubkey(),
+                    st_sol_account,
+                    st_sol_mint: self.st_sol_mint,
+                    withdrawal_ticket: ticket.pubkey(),
+                },
+                amount,
+            )],
+            vec![user, &ticket],
+        )
+        .await?;
+        Ok(ticket.pubkey())
+    }
+
+    pub async fn request_withdrawal(
+        &mut self,
+        user: &Keypair,
+        st_sol_account: Pubkey,
+        amount: StLamports,
+    ) -> Pubkey {
+        self.try_request_withdrawal(user, st_sol_account, amount)
+            .await
+            .expect("Failed to call RequestWithdrawal on Solido instance.")
+    }
+
+    pub async fn try_claim_withdrawal(
+        &mut self,
+        user: &Keypair,
+        withdrawal_ticket: Pubkey,
+        recipient: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[instruction::claim_withdrawal(
+                &id(),
+                &instruction::ClaimWithdrawalAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    ticket_owner: user.pubkey(),
+                    withdrawal_ticket,
+                    reserve_account: self.reserve_address,
+                    recipient,
+                },
+            )],
+            vec![user],
+        )
+        .await
+    }
+
+    pub async fn claim_withdrawal(
+        &mut self,
+        user: &Keypair,
+        withdrawal_ticket: Pubkey,
+        recipient: Pubkey,
+    ) {
+        self.try_claim_withdrawal(user, withdrawal_ticket, recipient)
+            .await
+            .expect("Failed to call ClaimWithdrawal on Solido instance.");
+    }
+
+    pub async fn get_withdrawal_ticket(&mut self, address: Pubkey) -> WithdrawalTicket {
+        let account = self.get_account(address).await;
+        try_from_slice_unchecked::<WithdrawalTicket>(account.data.as_slice()).unwrap()
+    }
+
     /// Withdraw from the given validator and vote account.
     pub async fn withdraw(
         &mut self,
@@ -860,17 +1163,17 @@ impl Context {
         .expect("Failed to call Withdraw on Solido instance.")
     }
 
-    /// Stake the given amount to the given validator, return the resulting stake account.
-    pub async fn try_stake_deposit(
+    /// Build the instruction to stake the given amount to the given validator,
+    /// return it together with the resulting stake account.
+    pub async fn stake_deposit_instruction(
         &mut self,
         validator_vote_account: Pubkey,
         approach: StakeDeposit,
         amount: Lamports,
-    ) -> transport::Result<Pubkey> {
-        let solido = self.get_solido().await;
+    ) -> (Instruction, Pubkey) {
+        let validators = self.get_validators().await;
 
-        let validator_entry = solido
-            .validators
+        let validator_entry = validators
             .get(&validator_vote_account)
             .expect("Trying to stake with a non-member validator.");
 
@@ -899,22 +1202,37 @@ impl Context {
             .as_ref()
             .expect("Must have maintainer to call StakeDeposit.");
 
+        let instruction = instruction::stake_deposit(
+            &id(),
+            &instruction::StakeDepositAccountsMeta {
+                lido: self.solido.pubkey(),
+                validator_list: self.validator_list,
+                maintainer: maintainer.pubkey(),
+                validator_vote_account,
+                reserve: self.reserve_address,
+                stake_account_merge_into,
+                stake_account_end,
+                stake_authority: self.stake_authority,
+            },
+            amount,
+        );
+        (instruction, stake_account_end)
+    }
+
+    /// Stake the given amount to the given validator, return the resulting stake account.
+    pub async fn try_stake_deposit(
+        &mut self,
+        validator_vote_account: Pubkey,
+        approach: StakeDeposit,
+        amount: Lamports,
+    ) -> transport::Result<Pubkey> {
+        let (instruction, stake_account_end) = self
+            .stake_deposit_instruction(validator_vote_account, approach, amount)
+            .await;
         send_transaction(
             &mut self.context,
-            &[instruction::stake_deposit(
-                &id(),
-                &instruction::StakeDepositAccountsMeta {
-                    lido: self.solido.pubkey(),
-                    maintainer: maintainer.pubkey(),
-                    validator_vote_account,
-                    reserve: self.reserve_address,
-                    stake_account_merge_into,
-                    stake_account_end,
-                    stake_authority: self.stake_authority,
-                },
-                amount,
-            )],
-            vec![maintainer],
+            &[instruction],
+            vec![self.maintainer.as_ref().unwrap()],
         )
         .await?;
 
@@ -933,6 +1251,63 @@ impl Context {
             .expect("Failed to call StakeDeposit on Solido instance.")
     }
 
+    /// Deposit a stake account of `owner`, who is its staker and withdrawer.
+    ///
+    /// Creates a new stSOL account for the owner, and returns it.
+    pub async fn try_deposit_stake(
+        &mut self,
+        validator_vote_account: Pubkey,
+        approach: StakeDeposit,
+        user_stake_account: Pubkey,
+        owner: &Keypair,
+    ) -> transport::Result<Pubkey> {
+        let recipient = self.create_st_sol_account(owner.pubkey()).await;
+        let validators = self.get_validators().await;
+        let validator_entry = validators
+            .get(&validator_vote_account)
+            .expect("Trying to deposit stake with a non-member validator.");
+
+        let (stake_account_end, _) = validator_entry.find_stake_account_address(
+            &id(),
+            &self.solido.pubkey(),
+            validator_entry.entry.stake_seeds.end,
+            StakeType::Stake,
+        );
+        let (stake_account_merge_into, _) = validator_entry.find_stake_account_address(
+            &id(),
+            &self.solido.pubkey(),
+            match approach {
+                StakeDeposit::Append => validator_entry.entry.stake_seeds.end,
+                StakeDeposit::Merge => validator_entry.entry.stake_seeds.end.wrapping_sub(1),
+            },
+            StakeType::Stake,
+        );
+
+        send_transaction(
+            &mut self.context,
+            &[instruction::deposit_stake(
+                &id(),
+                &instruction::DepositStakeAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
+                    validator_vote_account,
+                    user_stake_account,
+                    user_stake_authority: owner.pubkey(),
+                    recipient,
+                    st_sol_mint: self.st_sol_mint,
+                    stake_account_merge_into,
+                    stake_account_end,
+                    stake_authority: self.stake_authority,
+                    mint_authority: self.mint_authority,
+                },
+            )],
+            vec![owner],
+        )
+        .await?;
+
+        Ok(recipient)
+    }
+
     /// Try to unstake from the validator.
     pub async fn try_unstake(
         &mut self,
@@ -940,8 +1315,8 @@ impl Context {
         amount: Lamports,
     ) -> transport::Result<()> {
         // Where the new stake will live.
-        let solido = self.get_solido().await;
-        let validator = solido.validators.get(&validator_vote_account).unwrap();
+        let validators = self.get_validators().await;
+        let validator = validators.get(&validator_vote_account).unwrap();
 
         let (source_stake_account, _) = validator.find_stake_account_address(
             &id(),
@@ -962,6 +1337,7 @@ impl Context {
                 &id(),
                 &instruction::UnstakeAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     validator_vote_account,
                     source_stake_account,
                     destination_unstake_account,
@@ -987,7 +1363,6 @@ impl Context {
     pub async fn try_change_reward_distribution(
         &mut self,
         new_reward_distribution: &RewardDistribution,
-        new_fee_recipients: &FeeRecipients,
     ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
@@ -997,8 +1372,11 @@ impl Context {
                 &instruction::ChangeRewardDistributionMeta {
                     lido: self.solido.pubkey(),
                     manager: self.manager.pubkey(),
-                    treasury_account: new_fee_recipients.treasury_account,
-                    developer_account: new_fee_recipients.developer_account,
+                    fee_recipients: new_reward_distribution
+                        .fee_recipients
+                        .iter()
+                        .map(|recipient| recipient.st_sol_account)
+                        .collect(),
                 },
             )],
             vec![&self.manager],
@@ -1006,13 +1384,35 @@ impl Context {
         .await
     }
 
-    pub async fn try_update_exchange_rate(&mut self) -> transport::Result<()> {
+    /// Replace the stSOL accounts of the fee recipients, in the order of the
+    /// recipients in the current reward distribution.
+    pub async fn try_change_fee_recipients(
+        &mut self,
+        new_fee_recipients: &[Pubkey],
+    ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
-            &[instruction::update_exchange_rate(
+            &[instruction::change_fee_recipients(
                 &id(),
-                &instruction::UpdateExchangeRateAccountsMeta {
+                &instruction::ChangeFeeRecipientsMeta {
                     lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    fee_recipients: new_fee_recipients.to_vec(),
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_update_exchange_rate(&mut self) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[instruction::update_exchange_rate(
+                &id(),
+                &instruction::UpdateExchangeRateAccountsMeta {
+                    lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     reserve: self.reserve_address,
                     st_sol_mint: self.st_sol_mint,
                 },
@@ -1028,6 +1428,28 @@ impl Context {
             .expect("Failed to update exchange rate.");
     }
 
+    /// Have the consumer program price `args.amount` through a CPI into `GetExchangeRate`.
+    ///
+    /// The consumer program fails if the result does not match the expected values.
+    pub async fn try_price_st_sol_through_cpi(
+        &mut self,
+        args: PriceStSol,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[Instruction {
+                program_id: crate::exchange_rate_consumer::id(),
+                accounts: vec![
+                    AccountMeta::new_readonly(id(), false),
+                    AccountMeta::new_readonly(self.solido.pubkey(), false),
+                ],
+                data: args.try_to_vec().unwrap(),
+            }],
+            vec![],
+        )
+        .await
+    }
+
     /// Merge two accounts of a given validator.
     ///
     /// Returns the address that stake was merged into.
@@ -1057,6 +1479,7 @@ impl Context {
                 &id(),
                 &instruction::MergeStakeMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     validator_vote_account: validator.pubkey,
                     stake_authority: self.stake_authority,
                     from_stake: from_stake_account,
@@ -1082,42 +1505,85 @@ impl Context {
             .expect("Failed to call MergeStake on Solido instance.")
     }
 
-    /// Observe the new validator balance and write it to the state,
-    /// distribute any rewards received.
-    pub async fn try_update_stake_account_balance(
+    /// Build the instruction to update the balance of the given validator.
+    pub async fn update_stake_account_balance_instruction(
         &mut self,
         validator_vote_account: Pubkey,
-    ) -> transport::Result<()> {
-        let solido = self.get_solido().await;
-        let validator = solido.validators.get(&validator_vote_account).unwrap();
+    ) -> Instruction {
+        let validators = self.get_validators().await;
+        let validator = validators.get(&validator_vote_account).unwrap();
 
-        let mut stake_account_addrs: Vec<Pubkey> = Vec::new();
-
-        stake_account_addrs.extend(validator.entry.stake_seeds.into_iter().map(|seed| {
+        // The fee recipients go first, followed by the stake and unstake accounts.
+        let solido = self.get_solido().await;
+        let mut accounts: Vec<Pubkey> = solido
+            .reward_distribution
+            .fee_recipients
+            .iter()
+            .map(|recipient| recipient.st_sol_account)
+            .collect();
+
+        accounts.extend(validator.entry.stake_seeds.into_iter().map(|seed| {
             validator
                 .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Stake)
                 .0
         }));
-        stake_account_addrs.extend(validator.entry.unstake_seeds.into_iter().map(|seed| {
+        accounts.extend(validator.entry.unstake_seeds.into_iter().map(|seed| {
             validator
                 .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Unstake)
                 .0
         }));
 
+        instruction::update_stake_account_balance(
+            &id(),
+            &instruction::UpdateStakeAccountBalanceMeta {
+                lido: self.solido.pubkey(),
+                validator_list: self.validator_list,
+                validator_vote_account,
+                fee_recipients_and_stake_accounts: accounts,
+                reserve: self.reserve_address,
+                stake_authority: self.stake_authority,
+                st_sol_mint: self.st_sol_mint,
+                mint_authority: self.mint_authority,
+            },
+        )
+    }
+
+    /// Observe the new validator balance and write it to the state,
+    /// distribute any rewards received.
+    pub async fn try_update_stake_account_balance(
+        &mut self,
+        validator_vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        let instruction = self
+            .update_stake_account_balance_instruction(validator_vote_account)
+            .await;
+        send_transaction(&mut self.context, &[instruction], vec![]).await
+    }
+
+    pub async fn update_stake_account_balance(&mut self, validator_vote_account: Pubkey) {
+        self.try_update_stake_account_balance(validator_vote_account)
+            .await
+            .expect("Failed to withdraw inactive stake.");
+    }
+
+    /// Run `CheckInvariants` for the validator, with the given stake and
+    /// unstake accounts.
+    pub async fn try_check_invariants_with_accounts(
+        &mut self,
+        validator_vote_account: Pubkey,
+        stake_accounts: Vec<Pubkey>,
+    ) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
-            &[instruction::update_stake_account_balance(
+            &[instruction::check_invariants(
                 &id(),
-                &instruction::UpdateStakeAccountBalanceMeta {
+                &instruction::CheckInvariantsAccountsMeta {
                     lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
                     validator_vote_account,
-                    stake_accounts: stake_account_addrs,
                     reserve: self.reserve_address,
-                    stake_authority: self.stake_authority,
                     st_sol_mint: self.st_sol_mint,
-                    mint_authority: self.mint_authority,
-                    treasury_st_sol_account: self.treasury_st_sol_account,
-                    developer_st_sol_account: self.developer_st_sol_account,
+                    stake_accounts,
                 },
             )],
             vec![],
@@ -1125,10 +1591,46 @@ impl Context {
         .await
     }
 
-    pub async fn update_stake_account_balance(&mut self, validator_vote_account: Pubkey) {
-        self.try_update_stake_account_balance(validator_vote_account)
+    /// Return the stake accounts followed by the unstake accounts of the validator.
+    pub async fn get_stake_and_unstake_account_addresses(
+        &mut self,
+        validator_vote_account: Pubkey,
+    ) -> Vec<Pubkey> {
+        let validators = self.get_validators().await;
+        let validator = validators.get(&validator_vote_account).unwrap();
+
+        let mut accounts: Vec<Pubkey> = validator
+            .entry
+            .stake_seeds
+            .into_iter()
+            .map(|seed| {
+                validator
+                    .find_stake_account_address(
+                        &id(),
+                        &self.solido.pubkey(),
+                        seed,
+                        StakeType::Stake,
+                    )
+                    .0
+            })
+            .collect();
+        accounts.extend(validator.entry.unstake_seeds.into_iter().map(|seed| {
+            validator
+                .find_stake_account_address(&id(), &self.solido.pubkey(), seed, StakeType::Unstake)
+                .0
+        }));
+        accounts
+    }
+
+    pub async fn try_check_invariants(
+        &mut self,
+        validator_vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        let accounts = self
+            .get_stake_and_unstake_account_addresses(validator_vote_account)
+            .await;
+        self.try_check_invariants_with_accounts(validator_vote_account, accounts)
             .await
-            .expect("Failed to withdraw inactive stake.");
     }
 
     pub async fn try_get_account(&mut self, address: Pubkey) -> Option<Account> {
@@ -1202,6 +1704,11 @@ impl Context {
         try_from_slice_unchecked::<Lido>(lido_account.data.as_slice()).unwrap()
     }
 
+    pub async fn get_validators(&mut self) -> Validators {
+        let validator_list_account = self.get_account(self.validator_list).await;
+        try_from_slice_unchecked::<Validators>(validator_list_account.data.as_slice()).unwrap()
+    }
+
     pub async fn get_rent(&mut self) -> Rent {
         self.context
             .banks_client
@@ -1286,6 +1793,26 @@ impl Context {
         VoteState::deserialize(&vote_acc.data)
     }
 
+    /// Overwrite the credits history of a vote account, as if the validator
+    /// had been voting.
+    pub async fn set_vote_account_epoch_credits(
+        &mut self,
+        vote_account: Pubkey,
+        epoch_credits: Vec<EpochCredits>,
+    ) {
+        let mut account = self.get_account(vote_account).await;
+        let mut vote_state =
+            VoteState::deserialize(&account.data).expect("Failed to read vote account.");
+        vote_state.epoch_credits = epoch_credits;
+        VoteState::serialize(
+            &VoteStateVersions::Current(Box::new(vote_state)),
+            &mut account.data,
+        )
+        .expect("Failed to write vote account.");
+        self.context
+            .set_account(&vote_account, &AccountSharedData::from(account));
+    }
+
     pub async fn try_set_max_commission_percentage(&mut self, fee: u8) -> transport::Result<()> {
         send_transaction(
             &mut self.context,
@@ -1302,6 +1829,47 @@ impl Context {
         .await
     }
 
+    pub async fn try_set_instant_unstake_fee(
+        &mut self,
+        instant_unstake_fee_bps: u64,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_instant_unstake_fee(
+                &id(),
+                &lido::instruction::SetInstantUnstakeFeeMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                instant_unstake_fee_bps,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_validator_weight(
+        &mut self,
+        vote_account: Pubkey,
+        weight: u32,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_validator_weight(
+                &id(),
+                &lido::instruction::SetValidatorWeightMeta {
+                    lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
+                    manager: self.manager.pubkey(),
+                    validator_vote_account: vote_account,
+                },
+                weight,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
     pub async fn try_deactivate_validator_if_commission_exceeds_max(
         &mut self,
         vote_account: Pubkey,
@@ -1313,6 +1881,7 @@ impl Context {
                     &id(),
                     &lido::instruction::DeactivateValidatorIfCommissionExceedsMaxMeta {
                         lido: self.solido.pubkey(),
+                        validator_list: self.validator_list,
                         validator_vote_account_to_deactivate: vote_account,
                     },
                 ),
@@ -1321,6 +1890,300 @@ impl Context {
         )
         .await
     }
+
+    pub async fn try_set_min_vote_credits(
+        &mut self,
+        min_vote_credits: u64,
+        vote_credits_epochs: u64,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_min_vote_credits(
+                &id(),
+                &lido::instruction::SetMinVoteCreditsMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                min_vote_credits,
+                vote_credits_epochs,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_propose_new_manager(&mut self, new_manager: Pubkey) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::propose_new_manager(
+                &id(),
+                &lido::instruction::ProposeNewManagerMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    new_manager,
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_accept_manager(&mut self, new_manager: &Keypair) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::accept_manager(
+                &id(),
+                &lido::instruction::AcceptManagerMeta {
+                    lido: self.solido.pubkey(),
+                    new_manager: new_manager.pubkey(),
+                },
+            )],
+            vec![new_manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_paused_operations(
+        &mut self,
+        paused_operations: u8,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_paused_operations(
+                &id(),
+                &lido::instruction::SetPausedOperationsMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                paused_operations,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_deposit_limits(
+        &mut self,
+        max_total_sol: Lamports,
+        min_deposit: Lamports,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_deposit_limits(
+                &id(),
+                &lido::instruction::SetDepositLimitsMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                max_total_sol,
+                min_deposit,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_max_directed_stake(
+        &mut self,
+        max_directed_stake_bps: u64,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_max_directed_stake(
+                &id(),
+                &lido::instruction::SetMaxDirectedStakeMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                max_directed_stake_bps,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_reserve_buffer(
+        &mut self,
+        reserve_buffer: ReserveBuffer,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_reserve_buffer(
+                &id(),
+                &lido::instruction::SetReserveBufferMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                reserve_buffer,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_withdrawal_fee(
+        &mut self,
+        withdrawal_fee_bps: u64,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_withdrawal_fee(
+                &id(),
+                &lido::instruction::SetWithdrawalFeeMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                },
+                withdrawal_fee_bps,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_role(
+        &mut self,
+        role: Role,
+        new_authority: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_role(
+                &id(),
+                &lido::instruction::SetRoleMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    new_authority,
+                },
+                role,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_set_maintainer_permissions(
+        &mut self,
+        maintainer: Pubkey,
+        permissions: u8,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::set_maintainer_permissions(
+                &id(),
+                &lido::instruction::SetMaintainerPermissionsMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    maintainer,
+                },
+                permissions,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    /// Grow the validator list to hold `max_validators`, the payer funds the rent.
+    pub async fn try_resize_validator_list(
+        &mut self,
+        max_validators: u32,
+    ) -> transport::Result<()> {
+        let funder = self.context.payer.pubkey();
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::resize_validator_list(
+                &id(),
+                &lido::instruction::ResizeValidatorListMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    validator_list: self.validator_list,
+                    funder,
+                },
+                max_validators,
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    /// Migrate the Solido state from version 1, moving the validators to
+    /// `validator_list`. The payer funds the rent if the account needs to grow.
+    pub async fn try_migrate_state(&mut self, validator_list: Pubkey) -> transport::Result<()> {
+        let funder = self.context.payer.pubkey();
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::migrate_state(
+                &id(),
+                &lido::instruction::MigrateStateMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    validator_list,
+                    funder,
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
+
+    pub async fn try_deactivate_validator_if_delinquent(
+        &mut self,
+        vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::deactivate_validator_if_delinquent(
+                &id(),
+                &lido::instruction::DeactivateValidatorIfDelinquentMeta {
+                    lido: self.solido.pubkey(),
+                    validator_list: self.validator_list,
+                    validator_vote_account_to_deactivate: vote_account,
+                },
+            )],
+            vec![],
+        )
+        .await
+    }
+
+    pub async fn try_emergency_unstake_validator(
+        &mut self,
+        vote_account: Pubkey,
+    ) -> transport::Result<()> {
+        let validators = self.get_validators().await;
+        let validator = validators.get(&vote_account).unwrap();
+
+        let stake_accounts: Vec<Pubkey> = validator
+            .entry
+            .stake_seeds
+            .into_iter()
+            .map(|seed| {
+                validator
+                    .find_stake_account_address(
+                        &id(),
+                        &self.solido.pubkey(),
+                        seed,
+                        StakeType::Stake,
+                    )
+                    .0
+            })
+            .collect();
+
+        send_transaction(
+            &mut self.context,
+            &[lido::instruction::emergency_unstake_validator(
+                &id(),
+                &lido::instruction::EmergencyUnstakeValidatorMeta {
+                    lido: self.solido.pubkey(),
+                    manager: self.manager.pubkey(),
+                    validator_list: self.validator_list,
+                    validator_vote_account: vote_account,
+                    stake_authority: self.stake_authority,
+                    stake_accounts,
+                },
+            )],
+            vec![&self.manager],
+        )
+        .await
+    }
 }
 
 /// Return an `AccountInfo` for the given account, with `is_signer` and `is_writable` set to false.
@@ -208,8 +208,10 @@ assert solido_instance['solido']['exchange_rate'] == {
     'sol_balance': 0,
 }
 assert solido_instance['solido']['reward_distribution'] == {
-    'treasury_fee': 5,
-    'developer_fee': 2,
+    'fee_recipients': [
+        {'st_sol_account': treasury_account, 'parts': 5},
+        {'st_sol_account': developer_account, 'parts': 2},
+    ],
     'st_sol_appreciation': 93,
 }
 
@@ -307,7 +309,7 @@ solido_instance = solido(
     solido_address,
 )
 
-assert solido_instance['solido']['validators']['entries'][0] == {
+assert solido_instance['validators']['entries'][0] == {
     'pubkey': validator.vote_account.pubkey,
     'entry': {
         'stake_seeds': {
@@ -624,7 +626,7 @@ solido_instance = solido(
     '--solido-address',
     solido_address,
 )
-assert not solido_instance['solido']['validators']['entries'][0]['entry'][
+assert not solido_instance['validators']['entries'][0]['entry'][
     'active'
 ], 'Validator should be inactive after deactivation.'
 print('> Validator is inactive as expected.')
@@ -652,7 +654,7 @@ solido_instance = solido(
     solido_address,
 )
 # Should have bumped the validator's `stake_seeds` and `unstake_seeds`.
-val = solido_instance['solido']['validators']['entries'][0]['entry']
+val = solido_instance['validators']['entries'][0]['entry']
 assert val['stake_seeds'] == {'begin': 1, 'end': 1}
 assert val['unstake_seeds'] == {'begin': 1, 'end': 2}
 
@@ -694,7 +696,7 @@ solido_instance = solido(
     '--solido-address',
     solido_address,
 )
-number_validators = len(solido_instance['solido']['validators']['entries'])
+number_validators = len(solido_instance['validators']['entries'])
 assert (
     number_validators == 1
 ), f'\nExpected no validators\nGot: {number_validators} validators'